@@ -0,0 +1,116 @@
+//! The `client_error` module defines the errors an `RpcClient` request can fail with
+
+use reqwest;
+use solana_sdk::transaction::TransactionError;
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// A local I/O failure, or a request that gave up after too many retries
+    Io(io::Error),
+
+    /// The request couldn't be delivered or its response couldn't be read
+    Reqwest(reqwest::Error),
+
+    /// The node answered with a JSON-RPC error object
+    RpcError { code: i64, message: String },
+
+    /// The node's response wasn't what the request expected
+    ParseError(String),
+
+    /// The node processed the transaction, but it failed
+    TransactionError(TransactionError),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+impl ClientError {
+    /// Return true if repeating the request may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::Reqwest(_) => true,
+            ClientError::TransactionError(err) => err.is_retryable(),
+            ClientError::Io(_) | ClientError::RpcError { .. } | ClientError::ParseError(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(err) => write!(f, "{}", err),
+            ClientError::Reqwest(err) => write!(f, "{}", err),
+            ClientError::RpcError { code, message } => {
+                write!(f, "RPC error {}: {}", code, message)
+            }
+            ClientError::ParseError(message) => write!(f, "unexpected response: {}", message),
+            ClientError::TransactionError(err) => write!(f, "transaction failed: {}", err),
+        }
+    }
+}
+
+impl error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Reqwest(err)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(err: serde_json::Error) -> Self {
+        ClientError::ParseError(err.to_string())
+    }
+}
+
+impl From<TransactionError> for ClientError {
+    fn from(err: TransactionError) -> Self {
+        ClientError::TransactionError(err)
+    }
+}
+
+impl From<ClientError> for io::Error {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::Other, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(ClientError::TransactionError(TransactionError::AccountInUse).is_retryable());
+        assert!(
+            !ClientError::TransactionError(TransactionError::InsufficientFundsForFee)
+                .is_retryable()
+        );
+        assert!(!ClientError::ParseError("null".to_string()).is_retryable());
+        assert!(!ClientError::RpcError {
+            code: -32600,
+            message: "Invalid request".to_string(),
+        }
+        .is_retryable());
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let err: io::Error =
+            ClientError::Io(io::Error::new(io::ErrorKind::TimedOut, "slow")).into();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        let err: io::Error = ClientError::ParseError("null".to_string()).into();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert_eq!(err.to_string(), "unexpected response: null");
+    }
+}
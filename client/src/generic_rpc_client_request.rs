@@ -1,10 +1,11 @@
+use crate::client_error;
 use crate::rpc_request::RpcRequest;
 
-pub(crate) trait GenericRpcClientRequest {
+pub trait GenericRpcClientRequest {
     fn send(
         &self,
         request: &RpcRequest,
         params: Option<serde_json::Value>,
         retries: usize,
-    ) -> Result<serde_json::Value, Box<dyn std::error::Error>>;
+    ) -> client_error::Result<serde_json::Value>;
 }
@@ -1,10 +1,12 @@
-mod generic_rpc_client_request;
+pub mod client_error;
+pub mod generic_rpc_client_request;
 pub mod mock_rpc_client_request;
 pub mod rpc_client;
 pub mod rpc_client_request;
 pub mod rpc_request;
 pub mod rpc_signature_status;
 pub mod thin_client;
+pub mod tracing_rpc_client_request;
 
 #[macro_use]
 extern crate serde_derive;
@@ -1,19 +1,97 @@
+use crate::client_error;
 use crate::generic_rpc_client_request::GenericRpcClientRequest;
 use crate::rpc_request::RpcRequest;
 use serde_json::{Number, Value};
+use solana_sdk::account::Account;
+use solana_sdk::fee_calculator::FeeCalculator;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, KeypairUtil, SeedableKeypair, Signature};
+use solana_sdk::system_program;
+use solana_sdk::transaction::TransactionError;
+use solana_vote_api::vote_instruction::Vote;
+use solana_vote_api::vote_state::{self, VoteState, MAX_LOCKOUT_HISTORY};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub const PUBKEY: &str = "7RoSF9fUmdphVCpabEoefH81WwrW7orsWonXWqTXkKV8";
+pub const LAMPORTS_PER_SIGNATURE: u64 = 1;
+pub const MINIMUM_DELEGATION: u64 = 5;
+/// Lamports held by all accounts, of which `STAKED_SUPPLY` are in vote accounts
+pub const SUPPLY: u64 = 1000;
+pub const STAKED_SUPPLY: u64 = 300;
+/// The first slot a caught-up node reports, and the one the "stalled" node is stuck at
+pub const SLOT: u64 = 1000;
 pub const SIGNATURE: &str =
     "43yNSFC6fYTuPgTNFFhF4axw7AfWxB2BPdurme8yrsWEYwm8299xh8n6TAHjGymiSub1XtyxTNyd9GBfY2hxoBw8";
+/// Lamports the "balance_grows" account gains between consecutive balance readings
+pub const BALANCE_INCREMENT: u64 = 10;
+/// Seed of the validator identity that the "validator_exists" vote account is delegated to
+pub const VALIDATOR_IDENTITY_SEED: [u8; 32] = [7; 32];
 
 pub struct MockRpcClientRequest {
     url: String,
+    // Number of getTransactionCountAndSlot requests served, used to script increasing readings
+    sample_count: AtomicUsize,
+    // Number of getSignaturesForAddress requests served, used to script new account activity
+    poll_count: AtomicUsize,
+    // Number of getSlot requests served, used to script an advancing slot
+    slot_count: AtomicUsize,
+    // Number of getSignatureConfirmationCount requests served, used to script a deepening
+    // confirmation
+    confirmation_count: AtomicUsize,
+    // Number of getBalance requests served, used to script a growing balance
+    balance_count: AtomicUsize,
 }
 
 impl MockRpcClientRequest {
     pub fn new(url: String) -> Self {
-        Self { url }
+        Self {
+            url,
+            sample_count: AtomicUsize::new(0),
+            poll_count: AtomicUsize::new(0),
+            slot_count: AtomicUsize::new(0),
+            confirmation_count: AtomicUsize::new(0),
+            balance_count: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// The signature of the `n`th transaction the "monitor" account takes part in
+pub fn monitor_signature(n: usize) -> Signature {
+    Signature::new(&[n as u8; 64])
+}
+
+/// A vote account delegated to `delegate_id` that has voted on slots `0..num_votes`
+fn vote_account(delegate_id: &Pubkey, lamports: u64, num_votes: u64) -> Account {
+    let mut vote_state = VoteState::new(delegate_id);
+    for slot in 0..num_votes {
+        vote_state.process_vote(Vote::new(slot));
+    }
+    let mut account = vote_state::create_vote_account(lamports);
+    vote_state.serialize(&mut account.data).unwrap();
+    account
+}
+
+/// Vote accounts `[1; 32]` and `[2; 32]` are delegated to the node `PUBKEY`, while
+/// `[3; 32]` is delegated elsewhere. The epoch snapshot predates `[2; 32]`.
+fn vote_accounts(epoch_snapshot: bool) -> Value {
+    let node_id = Pubkey::from_str(PUBKEY).unwrap();
+    let mut accounts = vec![(
+        Pubkey::new(&[1; 32]),
+        vote_account(&node_id, 10, MAX_LOCKOUT_HISTORY as u64 + 1),
+    )];
+    if !epoch_snapshot {
+        accounts.push((Pubkey::new(&[2; 32]), vote_account(&node_id, 20, 0)));
     }
+    accounts.push((
+        Pubkey::new(&[3; 32]),
+        vote_account(&Pubkey::default(), 30, 1),
+    ));
+    let accounts: Vec<_> = accounts
+        .into_iter()
+        .map(|(pubkey, account)| (pubkey.to_string(), account))
+        .collect();
+    serde_json::to_value(accounts).unwrap()
 }
 
 impl GenericRpcClientRequest for MockRpcClientRequest {
@@ -22,7 +100,7 @@ impl GenericRpcClientRequest for MockRpcClientRequest {
         request: &RpcRequest,
         params: Option<serde_json::Value>,
         _retries: usize,
-    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    ) -> client_error::Result<serde_json::Value> {
         if self.url == "fails" {
             return Ok(Value::Null);
         }
@@ -38,22 +116,161 @@ impl GenericRpcClientRequest for MockRpcClientRequest {
                     Value::Null
                 }
             }
+            RpcRequest::GetAccountInfo if self.url == "validator_exists" => {
+                let identity_id = Keypair::from_seed(&VALIDATOR_IDENTITY_SEED).pubkey();
+                serde_json::to_value(vote_account(&identity_id, 50, 0)).unwrap()
+            }
+            RpcRequest::GetAccountInfo => {
+                let lamports = if self.url == "airdrop" { 0 } else { 50 };
+                serde_json::to_value(Account::new(lamports, 0, &Pubkey::default())).unwrap()
+            }
             RpcRequest::GetBalance => {
-                let n = if self.url == "airdrop" { 0 } else { 50 };
+                let n = self.balance_count.fetch_add(1, Ordering::Relaxed) as u64;
+                let n = if self.url == "airdrop" {
+                    0
+                } else if self.url == "balance_grows" {
+                    50 + BALANCE_INCREMENT * n
+                } else {
+                    50
+                };
                 Value::Number(Number::from(n))
             }
+            RpcRequest::GetEpochVoteAccounts => match params {
+                // Only epoch 0's stakes have been snapshotted
+                Some(Value::Array(ref param_array)) if param_array[0] == 0 => vote_accounts(true),
+                _ => Value::Null,
+            },
+            RpcRequest::GetFeeCalculator => {
+                serde_json::to_value(FeeCalculator::new(LAMPORTS_PER_SIGNATURE)).unwrap()
+            }
+            RpcRequest::GetGenesisHash => Value::String(PUBKEY.to_string()),
+            RpcRequest::GetLargestAccounts => {
+                let n = match params {
+                    Some(Value::Array(ref param_array)) => param_array[0].as_u64().unwrap_or(0),
+                    _ => 0,
+                };
+                let accounts: Vec<_> = vec![
+                    (PUBKEY.to_string(), 50),
+                    (solana_vote_api::id().to_string(), 20),
+                ]
+                .into_iter()
+                .take(n as usize)
+                .collect();
+                serde_json::to_value(accounts).unwrap()
+            }
+            RpcRequest::GetMinimumDelegation => Value::Number(Number::from(MINIMUM_DELEGATION)),
+            RpcRequest::GetProgramAccounts => match params {
+                Some(Value::Array(ref param_array))
+                    if param_array[0] == solana_vote_api::id().to_string() =>
+                {
+                    vote_accounts(false)
+                }
+                _ => serde_json::to_value(Vec::<(String, Account)>::new()).unwrap(),
+            },
             RpcRequest::GetRecentBlockhash => Value::String(PUBKEY.to_string()),
             RpcRequest::GetSignatureStatus => {
-                let str = if self.url == "account_in_use" {
+                let str = if self.url == "monitor" {
+                    // Only the most recent transaction is still pending
+                    let polls = self.poll_count.load(Ordering::Relaxed);
+                    let newest = monitor_signature(polls.saturating_sub(1)).to_string();
+                    match params {
+                        Some(Value::Array(ref param_array)) if param_array[0] == newest => {
+                            "SignatureNotFound"
+                        }
+                        _ => "Confirmed",
+                    }
+                } else if self.url == "account_in_use" {
                     "AccountInUse"
                 } else if self.url == "bad_sig_status" {
                     "SignatureNotFound"
+                } else if self.url == "program_not_executable" {
+                    "ProgramNotExecutable"
                 } else {
                     "Confirmed"
                 };
                 Value::String(str.to_string())
             }
+            RpcRequest::GetSignatureStatuses => {
+                let count = match params {
+                    Some(Value::Array(ref param_array)) => {
+                        param_array[0].as_array().map_or(0, Vec::len)
+                    }
+                    _ => 0,
+                };
+                let statuses: Vec<Option<Result<(), TransactionError>>> = (0..count)
+                    .map(|i| {
+                        if self.url == "bad_sig_status" {
+                            None
+                        } else if self.url == "account_in_use" {
+                            Some(Err(TransactionError::AccountInUse))
+                        } else if self.url == "program_not_executable" {
+                            Some(Err(TransactionError::InvalidProgramForExecution))
+                        } else if self.url == "mixed_sig_statuses" {
+                            // Cycle through confirmed, failed and unknown signatures
+                            match i % 3 {
+                                0 => Some(Ok(())),
+                                1 => Some(Err(TransactionError::InvalidProgramForExecution)),
+                                _ => None,
+                            }
+                        } else {
+                            Some(Ok(()))
+                        }
+                    })
+                    .collect();
+                serde_json::to_value(statuses).unwrap()
+            }
+            RpcRequest::GetSignatureConfirmationCount => {
+                let n = self.confirmation_count.fetch_add(1, Ordering::Relaxed) as u64;
+                if self.url == "bad_sig_status" || n == 0 {
+                    // Not seen yet
+                    Value::Null
+                } else {
+                    Value::Number(Number::from(n - 1))
+                }
+            }
+            RpcRequest::GetSignaturesForAddress => {
+                let signatures: Vec<_> = if self.url == "monitor" {
+                    // Each poll finds one more transaction
+                    let polls = self.poll_count.fetch_add(1, Ordering::Relaxed) + 1;
+                    (0..polls)
+                        .map(|n| monitor_signature(n).to_string())
+                        .collect()
+                } else {
+                    vec![]
+                };
+                serde_json::to_value(signatures).unwrap()
+            }
+            RpcRequest::GetSlot => {
+                let n = self.slot_count.fetch_add(1, Ordering::Relaxed) as u64;
+                let slot = if self.url == "stalled" {
+                    SLOT
+                } else if self.url == "lagging" {
+                    // Advancing, but far behind the other nodes
+                    n
+                } else {
+                    SLOT + n
+                };
+                Value::Number(Number::from(slot))
+            }
+            RpcRequest::GetSupply => {
+                let supply = vec![
+                    (system_program::id().to_string(), SUPPLY - STAKED_SUPPLY),
+                    (solana_vote_api::id().to_string(), STAKED_SUPPLY),
+                ];
+                serde_json::to_value(supply).unwrap()
+            }
             RpcRequest::GetTransactionCount => Value::Number(Number::from(1234)),
+            RpcRequest::GetTransactionCountAndSlot => {
+                let n = self.sample_count.fetch_add(1, Ordering::Relaxed) as u64;
+                let (transaction_count, slot) = if self.url == "transaction_count_reset" {
+                    // The counter restarts between readings, as if the node rebooted
+                    (1234 / (n + 1), n)
+                } else {
+                    (1234 + 100 * n, 10 * n)
+                };
+                serde_json::to_value((transaction_count, slot)).unwrap()
+            }
+            RpcRequest::RequestAirdrop => Value::String(SIGNATURE.to_string()),
             RpcRequest::SendTransaction => Value::String(SIGNATURE.to_string()),
             _ => Value::Null,
         };
@@ -1,39 +1,85 @@
+use crate::client_error::{self, ClientError};
 use crate::generic_rpc_client_request::GenericRpcClientRequest;
 use crate::mock_rpc_client_request::MockRpcClientRequest;
 use crate::rpc_client_request::RpcClientRequest;
 use crate::rpc_request::RpcRequest;
 use crate::rpc_signature_status::RpcSignatureStatus;
+use crate::tracing_rpc_client_request::TracingRpcClientRequest;
 use bincode::serialize;
 use bs58;
 use log::*;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use solana_sdk::account::Account;
+use solana_sdk::commitment::Commitment;
+use solana_sdk::fee_calculator::FeeCalculator;
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil, Signature};
 use solana_sdk::timing::{DEFAULT_TICKS_PER_SLOT, NUM_TICKS_PER_SECOND};
-use solana_sdk::transaction::Transaction;
-use std::error;
-use std::io;
+use solana_sdk::transaction::{Transaction, TransactionError};
 use std::net::SocketAddr;
+use std::rc::Rc;
 use std::str::FromStr;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
+use std::{fmt, io};
+
+/// The outcome of `RpcClient::health_check`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeHealth {
+    /// The node's slot is advancing and it isn't trailing the reference node
+    CaughtUp,
+    /// The node's slot didn't advance between two readings
+    Stalled { slot: u64 },
+    /// The node trails the reference node by more than the allowed number of slots
+    Behind { slot: u64, reference_slot: u64 },
+}
+
+impl fmt::Display for NodeHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeHealth::CaughtUp => write!(f, "node is caught up"),
+            NodeHealth::Stalled { slot } => write!(f, "node is stalled at slot {}", slot),
+            NodeHealth::Behind {
+                slot,
+                reference_slot,
+            } => write!(
+                f,
+                "node is at slot {}, {} slots behind the reference node",
+                slot,
+                reference_slot.saturating_sub(*slot)
+            ),
+        }
+    }
+}
 
 pub struct RpcClient {
     client: Box<GenericRpcClientRequest>,
+    trace: Option<Rc<Fn(&str)>>,
 }
 
 impl RpcClient {
     pub fn new(url: String) -> Self {
         Self {
             client: Box::new(RpcClientRequest::new(url)),
+            trace: None,
         }
     }
 
     pub fn new_mock(url: String) -> Self {
         Self {
             client: Box::new(MockRpcClientRequest::new(url)),
+            trace: None,
+        }
+    }
+
+    /// Create a client that sends its requests through `client`, such as one that answers
+    /// them without a network
+    pub fn new_with_client_request(client: Box<GenericRpcClientRequest>) -> Self {
+        Self {
+            client,
+            trace: None,
         }
     }
 
@@ -45,60 +91,154 @@ impl RpcClient {
         let url = get_rpc_request_str(addr, false);
         Self {
             client: Box::new(RpcClientRequest::new_with_timeout(url, timeout)),
+            trace: None,
         }
     }
 
-    pub fn send_transaction(
-        &self,
-        transaction: &Transaction,
-    ) -> Result<String, Box<dyn error::Error>> {
+    /// Pass a description of each request this client sends to `trace`: its method,
+    /// parameters, response or error, and latency, with signatures and key material redacted
+    pub fn with_trace<F>(self, trace: F) -> Self
+    where
+        F: Fn(&str) + 'static,
+    {
+        let trace: Rc<Fn(&str)> = Rc::new(trace);
+        Self {
+            client: Box::new(TracingRpcClientRequest::new(self.client, trace.clone())),
+            trace: Some(trace),
+        }
+    }
+
+    /// Log a description of each request this client sends at info level
+    pub fn with_request_logging(self) -> Self {
+        self.with_trace(|line| info!("{}", line))
+    }
+
+    /// Add `message` to the trace of a client created `with_trace`, otherwise do nothing
+    pub fn trace(&self, message: &str) {
+        if let Some(trace) = &self.trace {
+            trace(message);
+        }
+    }
+
+    pub fn send_transaction(&self, transaction: &Transaction) -> client_error::Result<String> {
         let serialized = serialize(transaction).unwrap();
         let params = json!([serialized]);
         let signature = self
             .client
             .send(&RpcRequest::SendTransaction, Some(params), 5)?;
-        if signature.as_str().is_none() {
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Received result of an unexpected type",
-            ))?;
+        match signature.as_str() {
+            Some(signature) => Ok(signature.to_string()),
+            None => Err(ClientError::ParseError(format!(
+                "SendTransaction invalid response: {:?}",
+                signature
+            ))),
         }
-        Ok(signature.as_str().unwrap().to_string())
+    }
+
+    /// Ask the node to airdrop `lamports` to `pubkey` from its drone, returning the
+    /// signature of the airdrop transaction
+    pub fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> client_error::Result<String> {
+        let params = json!([format!("{}", pubkey), lamports]);
+        let response = self
+            .client
+            .send(&RpcRequest::RequestAirdrop, Some(params), 0)?;
+        parse_response(response, "RequestAirdrop")
     }
 
     pub fn get_signature_status(
         &self,
         signature: &str,
-    ) -> Result<RpcSignatureStatus, Box<dyn error::Error>> {
+    ) -> client_error::Result<RpcSignatureStatus> {
         let params = json!([signature.to_string()]);
         let signature_status =
             self.client
                 .send(&RpcRequest::GetSignatureStatus, Some(params), 5)?;
-        if let Some(status) = signature_status.as_str() {
-            let rpc_status = RpcSignatureStatus::from_str(status).map_err(|err| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("Unable to parse signature status: {:?}", err),
-                )
-            })?;
-            Ok(rpc_status)
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Received result of an unexpected type",
-            ))?
-        }
+        signature_status
+            .as_str()
+            .and_then(|status| RpcSignatureStatus::from_str(status).ok())
+            .ok_or_else(|| {
+                ClientError::ParseError(format!(
+                    "GetSignatureStatus invalid response: {:?}",
+                    signature_status
+                ))
+            })
+    }
+
+    /// Return the status of each of `signatures`, in order: None if the node hasn't seen
+    /// the signature, otherwise the result of its transaction
+    pub fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> client_error::Result<Vec<Option<Result<(), TransactionError>>>> {
+        let signatures: Vec<_> = signatures.iter().map(ToString::to_string).collect();
+        let params = json!([signatures]);
+        let response = self
+            .client
+            .send(&RpcRequest::GetSignatureStatuses, Some(params), 0)?;
+        parse_response(response, "GetSignatureStatuses")
+    }
+
+    /// Return how many slots the node is past the one that processed `signature`, or None
+    /// if the node hasn't seen the signature
+    pub fn get_signature_confirmation_count(
+        &self,
+        signature: &Signature,
+    ) -> client_error::Result<Option<u64>> {
+        let params = json!([signature.to_string()]);
+        let response =
+            self.client
+                .send(&RpcRequest::GetSignatureConfirmationCount, Some(params), 0)?;
+        parse_response(response, "GetSignatureConfirmationCount")
     }
 
     pub fn send_and_confirm_transaction<T: KeypairUtil>(
         &self,
         transaction: &mut Transaction,
         signer: &T,
-    ) -> Result<String, Box<dyn error::Error>> {
+    ) -> client_error::Result<String> {
+        self.send_and_confirm_transaction_with_signers(transaction, &[signer])
+    }
+
+    /// Like `send_and_confirm_transaction`, for a transaction that needs every one of
+    /// `signers` to sign it again if it's resent with a new blockhash
+    pub fn send_and_confirm_transaction_with_signers<T: KeypairUtil>(
+        &self,
+        transaction: &mut Transaction,
+        signers: &[&T],
+    ) -> client_error::Result<String> {
+        self.send_and_confirm_transaction_with_resign(transaction, |transaction| {
+            self.resign_transaction_with_signers(transaction, signers)
+        })
+    }
+
+    /// Like `send_and_confirm_transaction`, for a transaction signed elsewhere. Its signers
+    /// aren't at hand to sign it with a new blockhash, so it's resent as it is.
+    pub fn send_and_confirm_presigned_transaction(
+        &self,
+        transaction: &mut Transaction,
+    ) -> client_error::Result<String> {
+        self.send_and_confirm_transaction_with_resign(transaction, |_| Ok(()))
+    }
+
+    fn send_and_confirm_transaction_with_resign<F>(
+        &self,
+        transaction: &mut Transaction,
+        resign: F,
+    ) -> client_error::Result<String>
+    where
+        F: Fn(&mut Transaction) -> client_error::Result<()>,
+    {
         let mut send_retries = 5;
         loop {
             let mut status_retries = 4;
-            let signature_str = self.send_transaction(transaction)?;
+            let signature_str = match self.send_transaction(transaction) {
+                Ok(signature_str) => signature_str,
+                Err(ref err) if err.is_retryable() && send_retries > 1 => {
+                    send_retries -= 1;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
             let status = loop {
                 let status = self.get_signature_status(&signature_str)?;
                 if status == RpcSignatureStatus::SignatureNotFound {
@@ -116,33 +256,59 @@ impl RpcClient {
                     ));
                 }
             };
-            match status {
-                RpcSignatureStatus::AccountInUse | RpcSignatureStatus::SignatureNotFound => {
-                    // Fetch a new blockhash and re-sign the transaction before sending it again
-                    self.resign_transaction(transaction, signer)?;
-                    send_retries -= 1;
-                }
-                RpcSignatureStatus::Confirmed => {
-                    return Ok(signature_str);
-                }
-                _ => {
-                    send_retries = 0;
-                }
+            let err = match status {
+                RpcSignatureStatus::Confirmed => return Ok(signature_str),
+                RpcSignatureStatus::SignatureNotFound => io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("Transaction {} was not confirmed", signature_str),
+                )
+                .into(),
+                _ => self.get_transaction_error(&signature_str, status),
+            };
+            // A transaction that went unseen or hit a busy account may land if resent; any
+            // other failure would just repeat
+            let retryable = status == RpcSignatureStatus::SignatureNotFound || err.is_retryable();
+            send_retries -= 1;
+            if !retryable || send_retries == 0 {
+                return Err(err);
             }
-            if send_retries == 0 {
-                Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("Transaction {:?} failed: {:?}", signature_str, status),
-                ))?;
+            // Re-sign the transaction with a new blockhash, if its signers are at hand, before
+            // sending it again. If that fails, the transaction's own failure is the more
+            // useful error to report.
+            if let Err(resign_err) = resign(transaction) {
+                debug!("Unable to re-sign transaction: {:?}", resign_err);
+                return Err(err);
             }
         }
     }
 
+    /// Fetch the error the transaction behind `signature_str` failed with, given the
+    /// `status` it was reported with
+    fn get_transaction_error(
+        &self,
+        signature_str: &str,
+        status: RpcSignatureStatus,
+    ) -> ClientError {
+        let err = Signature::from_str(signature_str)
+            .ok()
+            .and_then(|signature| self.get_signature_statuses(&[signature]).ok())
+            .and_then(|mut statuses| statuses.pop())
+            .and_then(|status| status)
+            .and_then(Result::err);
+        match err {
+            Some(err) => ClientError::TransactionError(err),
+            None => ClientError::ParseError(format!(
+                "Transaction {} failed with {:?}, but no error was reported for it",
+                signature_str, status
+            )),
+        }
+    }
+
     pub fn send_and_confirm_transactions(
         &self,
         mut transactions: Vec<Transaction>,
         signer: &Keypair,
-    ) -> Result<(), Box<dyn error::Error>> {
+    ) -> client_error::Result<()> {
         let mut send_retries = 5;
         loop {
             let mut status_retries = 4;
@@ -190,7 +356,10 @@ impl RpcClient {
             }
 
             if send_retries == 0 {
-                Err(io::Error::new(io::ErrorKind::Other, "Transactions failed"))?;
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Transactions failed",
+                ))?;
             }
             send_retries -= 1;
 
@@ -211,9 +380,18 @@ impl RpcClient {
         &self,
         tx: &mut Transaction,
         signer_key: &T,
-    ) -> Result<(), Box<dyn error::Error>> {
+    ) -> client_error::Result<()> {
+        self.resign_transaction_with_signers(tx, &[signer_key])
+    }
+
+    /// Sign `tx` with a new blockhash, using every one of `signers`
+    pub fn resign_transaction_with_signers<T: KeypairUtil>(
+        &self,
+        tx: &mut Transaction,
+        signers: &[&T],
+    ) -> client_error::Result<()> {
         let blockhash = self.get_new_blockhash(&tx.recent_blockhash)?;
-        tx.sign(&[signer_key], blockhash);
+        tx.sign(signers, blockhash);
         Ok(())
     }
 
@@ -221,62 +399,133 @@ impl RpcClient {
         &self,
         pubkey: &Pubkey,
         retries: usize,
-    ) -> Result<Option<u64>, Box<dyn error::Error>> {
-        let params = json!([format!("{}", pubkey)]);
+    ) -> client_error::Result<Option<u64>> {
+        self.retry_get_balance_with_commitment(pubkey, retries, Commitment::default())
+    }
+
+    /// Like `retry_get_balance`, but read from the bank `commitment` selects
+    pub fn retry_get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        retries: usize,
+        commitment: Commitment,
+    ) -> client_error::Result<Option<u64>> {
+        let params = commitment_params(vec![json!(format!("{}", pubkey))], commitment);
         let res = self
             .client
-            .send(&RpcRequest::GetBalance, Some(params), retries)?
+            .send(&RpcRequest::GetBalance, params, retries)?
             .as_u64();
         Ok(res)
     }
 
-    pub fn get_account_data(&self, pubkey: &Pubkey) -> io::Result<Vec<u8>> {
+    pub fn get_account(&self, pubkey: &Pubkey) -> client_error::Result<Account> {
+        self.get_account_with_commitment(pubkey, Commitment::default())
+    }
+
+    /// Like `get_account`, but read from the bank `commitment` selects
+    pub fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: Commitment,
+    ) -> client_error::Result<Account> {
+        let params = commitment_params(vec![json!(format!("{}", pubkey))], commitment);
+        let response = self
+            .client
+            .send(&RpcRequest::GetAccountInfo, params, 0)
+            .map_err(|err| {
+                debug!("get_account failed: {:?}", err);
+                err
+            })?;
+        parse_response(response, "GetAccountInfo")
+    }
+
+    /// Request every account owned by `program_id`
+    pub fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+    ) -> client_error::Result<Vec<(Pubkey, Account)>> {
+        let params = json!([format!("{}", program_id)]);
+        let response = self
+            .client
+            .send(&RpcRequest::GetProgramAccounts, Some(params), 0)?;
+        parse_keyed(response, "GetProgramAccounts")
+    }
+
+    /// Request the vote accounts the node snapshotted for `epoch`'s leader schedule
+    pub fn get_epoch_vote_accounts(
+        &self,
+        epoch: u64,
+    ) -> client_error::Result<Vec<(Pubkey, Account)>> {
+        let params = json!([epoch]);
+        let response = self
+            .client
+            .send(&RpcRequest::GetEpochVoteAccounts, Some(params), 0)?;
+        parse_keyed(response, "GetEpochVoteAccounts")
+    }
+
+    /// Request the `n` largest balances, largest first with ties ordered by pubkey,
+    /// skipping accounts owned by any of `excluded_owners`
+    pub fn get_largest_accounts(
+        &self,
+        n: usize,
+        excluded_owners: &[Pubkey],
+    ) -> client_error::Result<Vec<(Pubkey, u64)>> {
+        let excluded_owners: Vec<_> = excluded_owners.iter().map(Pubkey::to_string).collect();
+        let params = json!([n, excluded_owners]);
+        let response = self
+            .client
+            .send(&RpcRequest::GetLargestAccounts, Some(params), 0)?;
+        parse_keyed(response, "GetLargestAccounts")
+    }
+
+    /// Request the lamports held by every account summed per owning program, largest
+    /// first
+    pub fn get_supply(&self) -> client_error::Result<Vec<(Pubkey, u64)>> {
+        let response = self.client.send(&RpcRequest::GetSupply, None, 0)?;
+        parse_keyed(response, "GetSupply")
+    }
+
+    /// Request the signatures of recent transactions that reference `pubkey`
+    pub fn get_signatures_for_address(&self, pubkey: &Pubkey) -> client_error::Result<Vec<String>> {
         let params = json!([format!("{}", pubkey)]);
         let response = self
             .client
-            .send(&RpcRequest::GetAccountInfo, Some(params), 0);
-        match response {
-            Ok(account_json) => {
-                let account: Account =
-                    serde_json::from_value(account_json).expect("deserialize account");
-                Ok(account.data)
-            }
-            Err(error) => {
-                debug!("get_account_data failed: {:?}", error);
-                Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "get_account_data failed",
-                ))
-            }
-        }
+            .send(&RpcRequest::GetSignaturesForAddress, Some(params), 0)?;
+        parse_response(response, "GetSignaturesForAddress")
+    }
+
+    pub fn get_account_data(&self, pubkey: &Pubkey) -> client_error::Result<Vec<u8>> {
+        self.get_account(pubkey).map(|account| account.data)
     }
 
     /// Request the balance of the user holding `pubkey`. This method blocks
     /// until the server sends a response. If the response packet is dropped
     /// by the network, this method will hang indefinitely.
-    pub fn get_balance(&self, pubkey: &Pubkey) -> io::Result<u64> {
-        let params = json!([format!("{}", pubkey)]);
-        let response = self
-            .client
-            .send(&RpcRequest::GetAccountInfo, Some(params), 0);
+    pub fn get_balance(&self, pubkey: &Pubkey) -> client_error::Result<u64> {
+        self.get_balance_with_commitment(pubkey, Commitment::default())
+    }
 
-        response
-            .and_then(|account_json| {
-                let account: Account =
-                    serde_json::from_value(account_json).expect("deserialize account");
+    /// Like `get_balance`, but read from the bank `commitment` selects
+    pub fn get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: Commitment,
+    ) -> client_error::Result<u64> {
+        self.get_account_with_commitment(pubkey, commitment)
+            .map(|account| {
                 trace!("Response account {:?} {:?}", pubkey, account);
                 trace!("get_balance {:?}", account.lamports);
-                Ok(account.lamports)
+                account.lamports
             })
-            .map_err(|error| {
-                debug!("Response account {}: None (error: {:?})", pubkey, error);
-                io::Error::new(io::ErrorKind::Other, "AccountNotFound")
+            .map_err(|err| {
+                debug!("Response account {}: None (error: {:?})", pubkey, err);
+                err
             })
     }
 
     /// Request the transaction count.  If the response packet is dropped by the network,
     /// this method will try again 5 times.
-    pub fn get_transaction_count(&self) -> Result<u64, Box<dyn error::Error>> {
+    pub fn get_transaction_count(&self) -> client_error::Result<u64> {
         debug!("get_transaction_count");
 
         let mut num_retries = 5;
@@ -302,10 +551,112 @@ impl RpcClient {
         ))?
     }
 
-    pub fn get_recent_blockhash(&self) -> io::Result<Hash> {
+    /// Request the fee schedule currently in effect
+    pub fn get_fee_calculator(&self) -> client_error::Result<FeeCalculator> {
+        let response = self.client.send(&RpcRequest::GetFeeCalculator, None, 0)?;
+        parse_response(response, "GetFeeCalculator")
+    }
+
+    /// Request the hash of the genesis block the node's cluster was started from
+    pub fn get_genesis_hash(&self) -> client_error::Result<Hash> {
+        let response = self.client.send(&RpcRequest::GetGenesisHash, None, 0)?;
+
+        let genesis_hash = response
+            .as_str()
+            .and_then(|genesis_hash| bs58::decode(genesis_hash).into_vec().ok())
+            .ok_or_else(|| {
+                ClientError::ParseError(format!("GetGenesisHash invalid response: {:?}", response))
+            })?;
+        Ok(Hash::new(&genesis_hash))
+    }
+
+    /// Request the fewest lamports a new staking account may be created with
+    pub fn get_minimum_delegation(&self) -> client_error::Result<u64> {
+        let response = self
+            .client
+            .send(&RpcRequest::GetMinimumDelegation, None, 0)?;
+        parse_response(response, "GetMinimumDelegation")
+    }
+
+    pub fn get_slot(&self) -> client_error::Result<u64> {
+        let response = self.client.send(&RpcRequest::GetSlot, None, 0)?;
+        parse_response(response, "GetSlot")
+    }
+
+    /// Read the node's slot twice, `interval` apart, to check that it's still advancing, then
+    /// check that it's no more than `max_slots_behind` slots behind `reference`, if given
+    pub fn health_check(
+        &self,
+        reference: Option<&RpcClient>,
+        max_slots_behind: u64,
+        interval: Duration,
+    ) -> client_error::Result<NodeHealth> {
+        let first_slot = self.get_slot()?;
+        sleep(interval);
+        let slot = self.get_slot()?;
+        if slot <= first_slot {
+            return Ok(NodeHealth::Stalled { slot });
+        }
+
+        if let Some(reference) = reference {
+            let reference_slot = reference.get_slot()?;
+            if reference_slot.saturating_sub(slot) > max_slots_behind {
+                return Ok(NodeHealth::Behind {
+                    slot,
+                    reference_slot,
+                });
+            }
+        }
+        Ok(NodeHealth::CaughtUp)
+    }
+
+    /// Request the transaction count and the slot it was read at, both taken from the same
+    /// bank.  If the response packet is dropped by the network, this method will try again
+    /// 5 times.
+    pub fn get_transaction_count_and_slot(&self) -> client_error::Result<(u64, u64)> {
+        debug!("get_transaction_count_and_slot");
+
+        let mut num_retries = 5;
+        while num_retries > 0 {
+            let response = self
+                .client
+                .send(&RpcRequest::GetTransactionCountAndSlot, None, 0);
+
+            match response {
+                Ok(value) => {
+                    debug!("transaction_count_and_slot response: {:?}", value);
+                    if let Ok(count_and_slot) = serde_json::from_value(value) {
+                        return Ok(count_and_slot);
+                    }
+                }
+                Err(err) => {
+                    debug!("transaction_count_and_slot failed: {:?}", err);
+                }
+            }
+            num_retries -= 1;
+        }
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Unable to get transaction count and slot, too many retries",
+        ))?
+    }
+
+    pub fn get_recent_blockhash(&self) -> client_error::Result<Hash> {
+        self.get_recent_blockhash_with_commitment(Commitment::default())
+    }
+
+    /// Like `get_recent_blockhash`, but read from the bank `commitment` selects
+    pub fn get_recent_blockhash_with_commitment(
+        &self,
+        commitment: Commitment,
+    ) -> client_error::Result<Hash> {
+        let params = commitment_params(vec![], commitment);
         let mut num_retries = 5;
         while num_retries > 0 {
-            match self.client.send(&RpcRequest::GetRecentBlockhash, None, 0) {
+            match self
+                .client
+                .send(&RpcRequest::GetRecentBlockhash, params.clone(), 0)
+            {
                 Ok(value) => {
                     if let Some(blockhash_str) = value.as_str() {
                         let blockhash_vec = bs58::decode(blockhash_str)
@@ -323,10 +674,10 @@ impl RpcClient {
         Err(io::Error::new(
             io::ErrorKind::Other,
             "Unable to get recent blockhash, too many retries",
-        ))
+        ))?
     }
 
-    pub fn get_new_blockhash(&self, blockhash: &Hash) -> io::Result<Hash> {
+    pub fn get_new_blockhash(&self, blockhash: &Hash) -> client_error::Result<Hash> {
         let mut num_retries = 5;
         while num_retries > 0 {
             if let Ok(new_blockhash) = self.get_recent_blockhash() {
@@ -345,7 +696,7 @@ impl RpcClient {
         Err(io::Error::new(
             io::ErrorKind::Other,
             "Unable to get next blockhash, too many retries",
-        ))
+        ))?
     }
 
     pub fn poll_balance_with_timeout(
@@ -353,7 +704,7 @@ impl RpcClient {
         pubkey: &Pubkey,
         polling_frequency: &Duration,
         timeout: &Duration,
-    ) -> io::Result<u64> {
+    ) -> client_error::Result<u64> {
         let now = Instant::now();
         loop {
             match self.get_balance(&pubkey) {
@@ -370,7 +721,7 @@ impl RpcClient {
         }
     }
 
-    pub fn poll_get_balance(&self, pubkey: &Pubkey) -> io::Result<u64> {
+    pub fn poll_get_balance(&self, pubkey: &Pubkey) -> client_error::Result<u64> {
         self.poll_balance_with_timeout(pubkey, &Duration::from_millis(100), &Duration::from_secs(1))
     }
 
@@ -398,12 +749,12 @@ impl RpcClient {
     }
 
     /// Poll the server to confirm a transaction.
-    pub fn poll_for_signature(&self, signature: &Signature) -> io::Result<()> {
+    pub fn poll_for_signature(&self, signature: &Signature) -> client_error::Result<()> {
         let now = Instant::now();
         while !self.check_signature(signature) {
             if now.elapsed().as_secs() > 15 {
                 // TODO: Return a better error.
-                return Err(io::Error::new(io::ErrorKind::Other, "signature not found"));
+                Err(io::Error::new(io::ErrorKind::Other, "signature not found"))?;
             }
             sleep(Duration::from_millis(250));
         }
@@ -438,22 +789,9 @@ impl RpcClient {
             };
         }
     }
-    pub fn fullnode_exit(&self) -> io::Result<bool> {
-        let response = self
-            .client
-            .send(&RpcRequest::FullnodeExit, None, 0)
-            .map_err(|err| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("FullnodeExit request failure: {:?}", err),
-                )
-            })?;
-        serde_json::from_value(response).map_err(|err| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("FullnodeExit parse failure: {:?}", err),
-            )
-        })
+    pub fn fullnode_exit(&self) -> client_error::Result<bool> {
+        let response = self.client.send(&RpcRequest::FullnodeExit, None, 0)?;
+        parse_response(response, "FullnodeExit")
     }
 
     // TODO: Remove
@@ -462,11 +800,51 @@ impl RpcClient {
         request: &RpcRequest,
         params: Option<Value>,
         retries: usize,
-    ) -> Result<Value, Box<dyn error::Error>> {
+    ) -> client_error::Result<Value> {
         self.client.send(request, params, retries)
     }
 }
 
+/// Deserialize the result of `request`
+fn parse_response<T: DeserializeOwned>(response: Value, request: &str) -> client_error::Result<T> {
+    serde_json::from_value(response.clone()).map_err(|err| {
+        ClientError::ParseError(format!(
+            "{} invalid response: {:?} {}",
+            request, response, err
+        ))
+    })
+}
+
+/// Parse a list of `(base58 pubkey, value)` pairs returned by `request`
+fn parse_keyed<T: DeserializeOwned>(
+    response: Value,
+    request: &str,
+) -> client_error::Result<Vec<(Pubkey, T)>> {
+    let keyed_values: Vec<(String, T)> = parse_response(response, request)?;
+    keyed_values
+        .into_iter()
+        .map(|(pubkey, value)| {
+            let pubkey = Pubkey::from_str(&pubkey).map_err(|err| {
+                ClientError::ParseError(format!("{} invalid pubkey {}: {:?}", request, pubkey, err))
+            })?;
+            Ok((pubkey, value))
+        })
+        .collect()
+}
+
+/// Append `commitment` to a request's positional `params`, leaving it off when it's the
+/// default so requests to nodes that predate the parameter are unchanged
+fn commitment_params(mut params: Vec<Value>, commitment: Commitment) -> Option<Value> {
+    if commitment != Commitment::default() {
+        params.push(json!(commitment));
+    }
+    if params.is_empty() {
+        None
+    } else {
+        Some(Value::Array(params))
+    }
+}
+
 pub fn get_rpc_request_str(rpc_addr: SocketAddr, tls: bool) -> String {
     if tls {
         format!("https://{}", rpc_addr)
@@ -478,7 +856,7 @@ pub fn get_rpc_request_str(rpc_addr: SocketAddr, tls: bool) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mock_rpc_client_request::{PUBKEY, SIGNATURE};
+    use crate::mock_rpc_client_request::{LAMPORTS_PER_SIGNATURE, PUBKEY, SIGNATURE, SLOT, SUPPLY};
     use jsonrpc_core::{Error, IoHandler, Params};
     use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
     use serde_json::Number;
@@ -542,7 +920,10 @@ mod tests {
             Some(json!("paramter")),
             0,
         );
-        assert_eq!(blockhash.is_err(), true);
+        match blockhash {
+            Err(ClientError::RpcError { code, .. }) => assert_eq!(code, -32600),
+            result => panic!("unexpected result: {:?}", result),
+        }
     }
 
     #[test]
@@ -616,6 +997,137 @@ mod tests {
         assert!(blockhash.is_err());
     }
 
+    #[test]
+    fn test_get_fee_calculator() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        assert_eq!(
+            rpc_client.get_fee_calculator().unwrap(),
+            FeeCalculator::new(LAMPORTS_PER_SIGNATURE)
+        );
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.get_fee_calculator().is_err());
+    }
+
+    #[test]
+    fn test_request_airdrop() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let pubkey = Keypair::new().pubkey();
+        assert_eq!(rpc_client.request_airdrop(&pubkey, 50).unwrap(), SIGNATURE);
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.request_airdrop(&pubkey, 50).is_err());
+    }
+
+    #[test]
+    fn test_get_genesis_hash() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let expected_hash = Hash::new(&bs58::decode(PUBKEY).into_vec().unwrap());
+        assert_eq!(rpc_client.get_genesis_hash().unwrap(), expected_hash);
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.get_genesis_hash().is_err());
+    }
+
+    #[test]
+    fn test_get_program_accounts() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let accounts = rpc_client
+            .get_program_accounts(&solana_vote_api::id())
+            .unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert!(accounts
+            .iter()
+            .all(|(_, account)| account.owner == solana_vote_api::id()));
+        assert!(rpc_client
+            .get_program_accounts(&Pubkey::default())
+            .unwrap()
+            .is_empty());
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client
+            .get_program_accounts(&solana_vote_api::id())
+            .is_err());
+    }
+
+    #[test]
+    fn test_get_epoch_vote_accounts() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        assert_eq!(rpc_client.get_epoch_vote_accounts(0).unwrap().len(), 2);
+        assert!(rpc_client.get_epoch_vote_accounts(1).is_err());
+    }
+
+    #[test]
+    fn test_get_largest_accounts_and_supply() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let largest = rpc_client
+            .get_largest_accounts(1, &[solana_vote_api::id()])
+            .unwrap();
+        assert_eq!(largest, vec![(Pubkey::from_str(PUBKEY).unwrap(), 50)]);
+        let supply = rpc_client.get_supply().unwrap();
+        assert_eq!(supply.len(), 2);
+        assert_eq!(
+            supply.iter().map(|(_, lamports)| lamports).sum::<u64>(),
+            SUPPLY
+        );
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.get_largest_accounts(1, &[]).is_err());
+        assert!(rpc_client.get_supply().is_err());
+    }
+
+    #[test]
+    fn test_get_transaction_count_and_slot() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        assert_eq!(
+            rpc_client.get_transaction_count_and_slot().unwrap(),
+            (1234, 0)
+        );
+        assert_eq!(
+            rpc_client.get_transaction_count_and_slot().unwrap(),
+            (1334, 10)
+        );
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.get_transaction_count_and_slot().is_err());
+    }
+
+    #[test]
+    fn test_health_check() {
+        let interval = Duration::from_millis(0);
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        assert_eq!(
+            rpc_client.health_check(None, 0, interval).unwrap(),
+            NodeHealth::CaughtUp
+        );
+
+        let stalled = RpcClient::new_mock("stalled".to_string());
+        assert_eq!(
+            stalled.health_check(None, 0, interval).unwrap(),
+            NodeHealth::Stalled { slot: SLOT }
+        );
+
+        // Only a node that's advancing is compared with the reference
+        let reference = RpcClient::new_mock("succeeds".to_string());
+        let lagging = RpcClient::new_mock("lagging".to_string());
+        assert_eq!(
+            lagging.health_check(Some(&reference), 0, interval).unwrap(),
+            NodeHealth::Behind {
+                slot: 1,
+                reference_slot: SLOT,
+            }
+        );
+        assert_eq!(
+            lagging
+                .health_check(Some(&reference), SLOT, interval)
+                .unwrap(),
+            NodeHealth::CaughtUp
+        );
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.health_check(None, 0, interval).is_err());
+    }
+
     #[test]
     fn test_get_signature_status() {
         let rpc_client = RpcClient::new_mock("succeeds".to_string());
@@ -634,6 +1146,59 @@ mod tests {
         assert!(status.is_err());
     }
 
+    #[test]
+    fn test_get_signature_statuses() {
+        let signatures = vec![Signature::new(&[1; 64]); 4];
+
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let statuses = rpc_client.get_signature_statuses(&signatures).unwrap();
+        assert_eq!(statuses, vec![Some(Ok(())); 4]);
+
+        let rpc_client = RpcClient::new_mock("mixed_sig_statuses".to_string());
+        let statuses = rpc_client.get_signature_statuses(&signatures).unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(Ok(())),
+                Some(Err(TransactionError::InvalidProgramForExecution)),
+                None,
+                Some(Ok(())),
+            ]
+        );
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.get_signature_statuses(&signatures).is_err());
+    }
+
+    #[test]
+    fn test_get_signature_confirmation_count() {
+        let signature = Signature::default();
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        // Unseen on the first poll, then one slot deeper on each poll after that
+        assert_eq!(
+            rpc_client
+                .get_signature_confirmation_count(&signature)
+                .unwrap(),
+            None
+        );
+        for depth in 0..3 {
+            assert_eq!(
+                rpc_client
+                    .get_signature_confirmation_count(&signature)
+                    .unwrap(),
+                Some(depth)
+            );
+        }
+
+        let rpc_client = RpcClient::new_mock("bad_sig_status".to_string());
+        assert_eq!(
+            rpc_client
+                .get_signature_confirmation_count(&signature)
+                .unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_send_and_confirm_transaction() {
         let rpc_client = RpcClient::new_mock("succeeds".to_string());
@@ -648,7 +1213,17 @@ mod tests {
 
         let rpc_client = RpcClient::new_mock("account_in_use".to_string());
         let result = rpc_client.send_and_confirm_transaction(&mut tx, &key);
-        assert!(result.is_err());
+        match result {
+            Err(ClientError::TransactionError(TransactionError::AccountInUse)) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let rpc_client = RpcClient::new_mock("program_not_executable".to_string());
+        let result = rpc_client.send_and_confirm_transaction(&mut tx, &key);
+        match result {
+            Err(ClientError::TransactionError(TransactionError::InvalidProgramForExecution)) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
 
         let rpc_client = RpcClient::new_mock("fails".to_string());
         let result = rpc_client.send_and_confirm_transaction(&mut tx, &key);
@@ -678,4 +1253,24 @@ mod tests {
         assert_eq!(prev_tx.instructions, tx.instructions);
     }
 
+    #[test]
+    fn test_commitment_params() {
+        let pubkey = json!("deadbeefXjn8o3yroDHxUtKsZZgoy4GPkPPXfouKNHhx");
+        assert_eq!(
+            commitment_params(vec![pubkey.clone()], Commitment::Recent),
+            Some(json!(["deadbeefXjn8o3yroDHxUtKsZZgoy4GPkPPXfouKNHhx"]))
+        );
+        assert_eq!(
+            commitment_params(vec![pubkey], Commitment::Frozen),
+            Some(json!([
+                "deadbeefXjn8o3yroDHxUtKsZZgoy4GPkPPXfouKNHhx",
+                "frozen"
+            ]))
+        );
+        assert_eq!(commitment_params(vec![], Commitment::Recent), None);
+        assert_eq!(
+            commitment_params(vec![], Commitment::Root),
+            Some(json!(["root"]))
+        );
+    }
 }
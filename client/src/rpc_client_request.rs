@@ -1,5 +1,6 @@
+use crate::client_error::{self, ClientError};
 use crate::generic_rpc_client_request::GenericRpcClientRequest;
-use crate::rpc_request::{RpcError, RpcRequest};
+use crate::rpc_request::RpcRequest;
 use log::*;
 use reqwest;
 use reqwest::header::CONTENT_TYPE;
@@ -36,7 +37,7 @@ impl GenericRpcClientRequest for RpcClientRequest {
         request: &RpcRequest,
         params: Option<serde_json::Value>,
         mut retries: usize,
-    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    ) -> client_error::Result<serde_json::Value> {
         // Concurrent requests are not supported so reuse the same request id for all requests
         let request_id = 1;
 
@@ -51,14 +52,7 @@ impl GenericRpcClientRequest for RpcClientRequest {
                 .send()
             {
                 Ok(mut response) => {
-                    let json: serde_json::Value = serde_json::from_str(&response.text()?)?;
-                    if json["error"].is_object() {
-                        Err(RpcError::RpcRequestError(format!(
-                            "RPC Error response: {}",
-                            serde_json::to_string(&json["error"]).unwrap()
-                        )))?
-                    }
-                    return Ok(json["result"].clone());
+                    return parse_response(&response.text()?);
                 }
                 Err(e) => {
                     info!(
@@ -66,7 +60,7 @@ impl GenericRpcClientRequest for RpcClientRequest {
                         retries, e
                     );
                     if retries == 0 {
-                        Err(e)?;
+                        return Err(ClientError::Reqwest(e));
                     }
                     retries -= 1;
 
@@ -79,3 +73,45 @@ impl GenericRpcClientRequest for RpcClientRequest {
         }
     }
 }
+
+/// Return the result of a JSON-RPC response body, or the error object the node sent instead
+fn parse_response(body: &str) -> client_error::Result<serde_json::Value> {
+    let json: serde_json::Value = serde_json::from_str(body)?;
+    let error = &json["error"];
+    if error.is_object() {
+        return Err(ClientError::RpcError {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or_default().to_string(),
+        });
+    }
+    Ok(json["result"].clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response(r#"{"jsonrpc":"2.0","result":50,"id":1}"#).unwrap(),
+            json!(50)
+        );
+
+        match parse_response(r#"{"jsonrpc":"2.0","result":"#) {
+            Err(ClientError::ParseError(_)) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let body =
+            r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":1}"#;
+        match parse_response(body) {
+            Err(ClientError::RpcError { code, message }) => {
+                assert_eq!(code, -32600);
+                assert_eq!(message, "Invalid request");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -1,14 +1,30 @@
 use serde_json::{json, Value};
-use std::{error, fmt};
+
+/// The most signatures a single getSignatureStatuses request may ask about
+pub const MAX_GET_SIGNATURE_STATUSES: usize = 256;
+/// The most accounts a single getLargestAccounts request may ask for
+pub const MAX_GET_LARGEST_ACCOUNTS: usize = 1000;
 
 #[derive(Debug, PartialEq)]
 pub enum RpcRequest {
     ConfirmTransaction,
     GetAccountInfo,
     GetBalance,
+    GetEpochVoteAccounts,
+    GetFeeCalculator,
+    GetGenesisHash,
+    GetLargestAccounts,
+    GetMinimumDelegation,
+    GetProgramAccounts,
     GetRecentBlockhash,
     GetSignatureStatus,
+    GetSignatureStatuses,
+    GetSignatureConfirmationCount,
+    GetSignaturesForAddress,
+    GetSlot,
+    GetSupply,
     GetTransactionCount,
+    GetTransactionCountAndSlot,
     RequestAirdrop,
     SendTransaction,
     RegisterNode,
@@ -21,15 +37,27 @@ pub enum RpcRequest {
 }
 
 impl RpcRequest {
-    pub(crate) fn build_request_json(&self, id: u64, params: Option<Value>) -> Value {
-        let jsonrpc = "2.0";
-        let method = match self {
+    /// The JSON RPC method name of this request
+    pub fn method(&self) -> &'static str {
+        match self {
             RpcRequest::ConfirmTransaction => "confirmTransaction",
             RpcRequest::GetAccountInfo => "getAccountInfo",
             RpcRequest::GetBalance => "getBalance",
+            RpcRequest::GetEpochVoteAccounts => "getEpochVoteAccounts",
+            RpcRequest::GetFeeCalculator => "getFeeCalculator",
+            RpcRequest::GetGenesisHash => "getGenesisHash",
+            RpcRequest::GetLargestAccounts => "getLargestAccounts",
+            RpcRequest::GetMinimumDelegation => "getMinimumDelegation",
+            RpcRequest::GetProgramAccounts => "getProgramAccounts",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetSignatureStatus => "getSignatureStatus",
+            RpcRequest::GetSignatureStatuses => "getSignatureStatuses",
+            RpcRequest::GetSignatureConfirmationCount => "getSignatureConfirmationCount",
+            RpcRequest::GetSignaturesForAddress => "getSignaturesForAddress",
+            RpcRequest::GetSlot => "getSlot",
+            RpcRequest::GetSupply => "getSupply",
             RpcRequest::GetTransactionCount => "getTransactionCount",
+            RpcRequest::GetTransactionCountAndSlot => "getTransactionCountAndSlot",
             RpcRequest::RequestAirdrop => "requestAirdrop",
             RpcRequest::SendTransaction => "sendTransaction",
             RpcRequest::RegisterNode => "registerNode",
@@ -39,7 +67,12 @@ impl RpcRequest {
             RpcRequest::GetStorageEntryHeight => "getStorageEntryHeight",
             RpcRequest::GetStoragePubkeysForEntryHeight => "getStoragePubkeysForEntryHeight",
             RpcRequest::FullnodeExit => "fullnodeExit",
-        };
+        }
+    }
+
+    pub(crate) fn build_request_json(&self, id: u64, params: Option<Value>) -> Value {
+        let jsonrpc = "2.0";
+        let method = self.method();
         let mut request = json!({
            "jsonrpc": jsonrpc,
            "id": id,
@@ -52,28 +85,6 @@ impl RpcRequest {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum RpcError {
-    RpcRequestError(String),
-}
-
-impl fmt::Display for RpcError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "invalid")
-    }
-}
-
-impl error::Error for RpcError {
-    fn description(&self) -> &str {
-        "invalid"
-    }
-
-    fn cause(&self) -> Option<&dyn error::Error> {
-        // Generic error, underlying cause isn't tracked.
-        None
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,6 +109,10 @@ mod tests {
         let request = test_request.build_request_json(1, None);
         assert_eq!(request["method"], "getTransactionCount");
 
+        let test_request = RpcRequest::GetTransactionCountAndSlot;
+        let request = test_request.build_request_json(1, None);
+        assert_eq!(request["method"], "getTransactionCountAndSlot");
+
         let test_request = RpcRequest::RequestAirdrop;
         let request = test_request.build_request_json(1, None);
         assert_eq!(request["method"], "requestAirdrop");
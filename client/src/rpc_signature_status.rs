@@ -9,6 +9,7 @@ pub enum RpcSignatureStatus {
     AccountLoadedTwice,
     Confirmed,
     GenericFailure,
+    ProgramNotExecutable,
     ProgramRuntimeError,
     SignatureNotFound,
 }
@@ -22,6 +23,7 @@ impl FromStr for RpcSignatureStatus {
             "AccountLoadedTwice" => Ok(RpcSignatureStatus::AccountLoadedTwice),
             "Confirmed" => Ok(RpcSignatureStatus::Confirmed),
             "GenericFailure" => Ok(RpcSignatureStatus::GenericFailure),
+            "ProgramNotExecutable" => Ok(RpcSignatureStatus::ProgramNotExecutable),
             "ProgramRuntimeError" => Ok(RpcSignatureStatus::ProgramRuntimeError),
             "SignatureNotFound" => Ok(RpcSignatureStatus::SignatureNotFound),
             _ => Err(Error::parse_error()),
@@ -100,23 +100,23 @@ impl ThinClient {
     }
 
     pub fn get_account_data(&self, pubkey: &Pubkey) -> io::Result<Vec<u8>> {
-        self.rpc_client.get_account_data(pubkey)
+        Ok(self.rpc_client.get_account_data(pubkey)?)
     }
 
     pub fn get_balance(&self, pubkey: &Pubkey) -> io::Result<u64> {
-        self.rpc_client.get_balance(pubkey)
+        Ok(self.rpc_client.get_balance(pubkey)?)
     }
 
     pub fn get_transaction_count(&self) -> Result<u64, Box<dyn error::Error>> {
-        self.rpc_client.get_transaction_count()
+        Ok(self.rpc_client.get_transaction_count()?)
     }
 
     pub fn get_recent_blockhash(&self) -> io::Result<Hash> {
-        self.rpc_client.get_recent_blockhash()
+        Ok(self.rpc_client.get_recent_blockhash()?)
     }
 
     pub fn get_new_blockhash(&self, blockhash: &Hash) -> io::Result<Hash> {
-        self.rpc_client.get_new_blockhash(blockhash)
+        Ok(self.rpc_client.get_new_blockhash(blockhash)?)
     }
 
     pub fn poll_balance_with_timeout(
@@ -125,12 +125,13 @@ impl ThinClient {
         polling_frequency: &Duration,
         timeout: &Duration,
     ) -> io::Result<u64> {
-        self.rpc_client
-            .poll_balance_with_timeout(pubkey, polling_frequency, timeout)
+        Ok(self
+            .rpc_client
+            .poll_balance_with_timeout(pubkey, polling_frequency, timeout)?)
     }
 
     pub fn poll_get_balance(&self, pubkey: &Pubkey) -> io::Result<u64> {
-        self.rpc_client.poll_get_balance(pubkey)
+        Ok(self.rpc_client.poll_get_balance(pubkey)?)
     }
 
     pub fn wait_for_balance(&self, pubkey: &Pubkey, expected_balance: Option<u64>) -> Option<u64> {
@@ -138,7 +139,7 @@ impl ThinClient {
     }
 
     pub fn poll_for_signature(&self, signature: &Signature) -> io::Result<()> {
-        self.rpc_client.poll_for_signature(signature)
+        Ok(self.rpc_client.poll_for_signature(signature)?)
     }
 
     pub fn check_signature(&self, signature: &Signature) -> bool {
@@ -146,7 +147,7 @@ impl ThinClient {
     }
 
     pub fn fullnode_exit(&self) -> io::Result<bool> {
-        self.rpc_client.fullnode_exit()
+        Ok(self.rpc_client.fullnode_exit()?)
     }
 }
 
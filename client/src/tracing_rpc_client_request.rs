@@ -0,0 +1,135 @@
+//! The `tracing_rpc_client_request` module wraps another RPC client request and reports
+//! each request it sends, with signatures and key material redacted.
+
+use crate::client_error;
+use crate::generic_rpc_client_request::GenericRpcClientRequest;
+use crate::rpc_request::RpcRequest;
+use serde_json::{json, Value};
+use solana_sdk::signature::Signature;
+use solana_sdk::timing::duration_as_us;
+use std::mem;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Characters of a signature left visible by `redact`
+pub const REDACTED_SIGNATURE_LEN: usize = 8;
+
+/// Byte arrays at least this long are summarized rather than printed, since they may hold a
+/// secret key, a signature or a signed transaction
+const REDACTED_BYTES_LEN: usize = 64;
+
+/// Return `value` with every base58 signature cut to its first `REDACTED_SIGNATURE_LEN`
+/// characters and every long byte array replaced by its length
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact_signature(s)),
+        Value::Array(values) => {
+            let is_bytes = values
+                .iter()
+                .all(|value| value.as_u64().map_or(false, |byte| byte <= 255));
+            if is_bytes && values.len() >= REDACTED_BYTES_LEN {
+                json!(format!("<{} bytes>", values.len()))
+            } else {
+                Value::Array(values.iter().map(redact).collect())
+            }
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), redact(value)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// Cut `s` to its first `REDACTED_SIGNATURE_LEN` characters if it's a base58 signature
+pub fn redact_signature(s: &str) -> String {
+    let is_signature = bs58::decode(s)
+        .into_vec()
+        .map(|bytes| bytes.len() == mem::size_of::<Signature>())
+        .unwrap_or(false);
+    if is_signature {
+        format!("{}...", &s[..REDACTED_SIGNATURE_LEN])
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) struct TracingRpcClientRequest {
+    client: Box<GenericRpcClientRequest>,
+    trace: Rc<Fn(&str)>,
+}
+
+impl TracingRpcClientRequest {
+    pub fn new(client: Box<GenericRpcClientRequest>, trace: Rc<Fn(&str)>) -> Self {
+        Self { client, trace }
+    }
+}
+
+impl GenericRpcClientRequest for TracingRpcClientRequest {
+    fn send(
+        &self,
+        request: &RpcRequest,
+        params: Option<Value>,
+        retries: usize,
+    ) -> client_error::Result<Value> {
+        let traced_params = params.as_ref().map(redact).unwrap_or(Value::Null);
+        let now = Instant::now();
+        let result = self.client.send(request, params, retries);
+        let elapsed = duration_as_us(&now.elapsed());
+        let outcome = match &result {
+            Ok(response) => serde_json::to_string_pretty(&redact(response)).unwrap(),
+            Err(err) => format!("error: {:?}", err),
+        };
+        (self.trace)(&format!(
+            "{} {} -> {} ({}us)",
+            request.method(),
+            serde_json::to_string_pretty(&traced_params).unwrap(),
+            outcome,
+            elapsed
+        ));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc_client_request::{MockRpcClientRequest, PUBKEY, SIGNATURE};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact(&json!(PUBKEY)), json!(PUBKEY));
+        assert_eq!(
+            redact(&json!([SIGNATURE])),
+            json!([format!("{}...", &SIGNATURE[..REDACTED_SIGNATURE_LEN])])
+        );
+        assert_eq!(redact(&json!([[1, 2, 3]])), json!([[1, 2, 3]]));
+        assert_eq!(
+            redact(&json!({ "key": vec![7; 64] })),
+            json!({ "key": "<64 bytes>" })
+        );
+    }
+
+    #[test]
+    fn test_tracing_rpc_client_request() {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let trace_lines = lines.clone();
+        let client = TracingRpcClientRequest::new(
+            Box::new(MockRpcClientRequest::new("succeeds".to_string())),
+            Rc::new(move |line| trace_lines.lock().unwrap().push(line.to_string())),
+        );
+
+        let response = client
+            .send(&RpcRequest::SendTransaction, Some(json!([vec![0; 200]])), 0)
+            .unwrap();
+        assert_eq!(response, json!(SIGNATURE));
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("sendTransaction"));
+        assert!(lines[0].contains("<200 bytes>"));
+        assert!(lines[0].contains(&SIGNATURE[..REDACTED_SIGNATURE_LEN]));
+        assert!(!lines[0].contains(SIGNATURE));
+    }
+}
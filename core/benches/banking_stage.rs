@@ -53,6 +53,8 @@ fn bench_banking_stage_multi_accounts(bencher: &mut Bencher) {
 
     let (verified_sender, verified_receiver) = channel();
     let bank = Arc::new(Bank::new(&genesis_block));
+    // The transactions below carry random signatures
+    bank.set_verify_signatures(false);
     let dummy = SystemTransaction::new_move(
         &mint_keypair,
         &mint_keypair.pubkey(),
@@ -90,13 +92,13 @@ fn bench_banking_stage_multi_accounts(bencher: &mut Bencher) {
         let res = bank.process_transaction(&tx);
         assert!(res.is_ok(), "sanity test transactions");
     });
-    bank.clear_signatures();
+    bank.clear_signatures(false);
     //sanity check, make sure all the transactions can execute in parallel
     let res = bank.process_transactions(&transactions);
     for r in res {
         assert!(r.is_ok(), "sanity parallel execution");
     }
-    bank.clear_signatures();
+    bank.clear_signatures(false);
     let verified: Vec<_> = to_packets_chunked(&transactions.clone(), 192)
         .into_iter()
         .map(|x| {
@@ -125,7 +127,7 @@ fn bench_banking_stage_multi_accounts(bencher: &mut Bencher) {
             verified_sender.send(v.to_vec()).unwrap();
         }
         check_txs(&signal_receiver, txes / 2);
-        bank.clear_signatures();
+        bank.clear_signatures(false);
         start += half_len;
         start %= verified.len();
     });
@@ -145,6 +147,8 @@ fn bench_banking_stage_multi_programs(bencher: &mut Bencher) {
 
     let (verified_sender, verified_receiver) = channel();
     let bank = Arc::new(Bank::new(&genesis_block));
+    // The transactions below carry random signatures
+    bank.set_verify_signatures(false);
     let dummy = SystemTransaction::new_move(
         &mint_keypair,
         &mint_keypair.pubkey(),
@@ -197,13 +201,13 @@ fn bench_banking_stage_multi_programs(bencher: &mut Bencher) {
         let res = bank.process_transaction(&tx);
         assert!(res.is_ok(), "sanity test transactions");
     });
-    bank.clear_signatures();
+    bank.clear_signatures(false);
     //sanity check, make sure all the transactions can execute in parallel
     let res = bank.process_transactions(&transactions);
     for r in res {
         assert!(r.is_ok(), "sanity parallel execution");
     }
-    bank.clear_signatures();
+    bank.clear_signatures(false);
     let verified: Vec<_> = to_packets_chunked(&transactions.clone(), 96)
         .into_iter()
         .map(|x| {
@@ -232,7 +236,7 @@ fn bench_banking_stage_multi_programs(bencher: &mut Bencher) {
             verified_sender.send(v.to_vec()).unwrap();
         }
         check_txs(&signal_receiver, txes / 2);
-        bank.clear_signatures();
+        bank.clear_signatures(false);
         start += half_len;
         start %= verified.len();
     });
@@ -243,11 +243,14 @@ impl BankingStage {
         Ok(())
     }
 
+    /// Process and record `txs`, returning how many of them, from the start, the bank had
+    /// room for. The rest failed with `WouldExceedBlockLimit` and are left to be buffered
+    /// for the next slot.
     pub fn process_and_record_transactions(
         bank: &Bank,
         txs: &[Transaction],
         poh: &Arc<Mutex<PohRecorder>>,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         let now = Instant::now();
         // Once accounts are locked, other threads cannot encode transactions that will modify the
         // same account state
@@ -259,9 +262,16 @@ impl BankingStage {
         // the likelihood of any single thread getting starved and processing old ids.
         // TODO: Banking stage threads should be prioritized to complete faster then this queue
         // expires.
-        let (loaded_accounts, results) =
-            bank.load_and_execute_transactions(txs, lock_results, MAX_RECENT_BLOCKHASHES / 2);
+        let (loaded_accounts, results) = bank.load_and_execute_transactions(
+            txs,
+            lock_results.clone(),
+            MAX_RECENT_BLOCKHASHES / 2,
+        );
         let load_execute_time = now.elapsed();
+        let fit = results
+            .iter()
+            .position(|r| *r == Err(TransactionError::WouldExceedBlockLimit))
+            .unwrap_or(txs.len());
 
         let record_time = {
             let now = Instant::now();
@@ -269,15 +279,13 @@ impl BankingStage {
             now.elapsed()
         };
 
-        let commit_time = {
-            let now = Instant::now();
-            bank.commit_transactions(txs, &loaded_accounts, &results);
-            now.elapsed()
-        };
+        let now = Instant::now();
+        let results = bank.commit_transactions(txs, &loaded_accounts, &results);
+        let commit_time = now.elapsed();
 
         let now = Instant::now();
         // Once the accounts are new transactions can enter the pipeline to process them
-        bank.unlock_accounts(&txs, &results);
+        bank.unlock_accounts(&txs, &lock_results, &results);
         let unlock_time = now.elapsed();
         debug!(
             "bank: {} lock: {}us load_execute: {}us record: {}us commit: {}us unlock: {}us txs_len: {}",
@@ -289,13 +297,14 @@ impl BankingStage {
             duration_as_us(&unlock_time),
             txs.len(),
         );
-        Ok(())
+        Ok(fit)
     }
 
     /// Sends transactions to the bank.
     ///
     /// Returns the number of transactions successfully processed by the bank, which may be less
-    /// than the total number if max PoH height was reached and the bank halted
+    /// than the total number if max PoH height was reached and the bank halted, or if the
+    /// slot filled up
     fn process_transactions(
         bank: &Bank,
         transactions: &[Transaction],
@@ -324,7 +333,16 @@ impl BankingStage {
                 );
                 break;
             }
-            result?;
+            let fit = result?;
+            if chunk_start + fit < chunk_end {
+                info!(
+                    "process transactions: slot {} is full after {} transactions",
+                    bank.slot(),
+                    bank.transaction_count_in_slot()
+                );
+                chunk_start += fit;
+                break;
+            }
             chunk_start = chunk_end;
         }
         Ok(chunk_start)
@@ -474,6 +492,7 @@ mod tests {
     use crate::packet::to_packets;
     use crate::poh_recorder::WorkingBank;
     use solana_sdk::genesis_block::GenesisBlock;
+    use solana_sdk::pubkey::Pubkey;
     use solana_sdk::signature::{Keypair, KeypairUtil};
     use solana_sdk::system_transaction::SystemTransaction;
     use solana_sdk::transaction::InstructionError;
@@ -797,4 +816,56 @@ mod tests {
 
         assert_eq!(bank.get_balance(&pubkey), 1);
     }
+
+    #[test]
+    fn test_bank_process_transactions_full_slot() {
+        solana_logger::setup();
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        genesis_block.max_transactions_per_slot = Some(2);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let payers: Vec<_> = (0..2).map(|_| Keypair::new()).collect();
+        for payer in &payers {
+            parent
+                .transfer(10, &mint_keypair, &payer.pubkey(), genesis_block.hash())
+                .unwrap();
+        }
+
+        // Give every transaction its own fee payer so none lose a lock race
+        let bank = Arc::new(Bank::new_from_parent(&parent, &Pubkey::default(), 1));
+        let pubkeys: Vec<_> = (0..3).map(|_| Keypair::new().pubkey()).collect();
+        let transactions: Vec<_> = [&payers[0], &payers[1], &mint_keypair]
+            .iter()
+            .zip(pubkeys.iter())
+            .map(|(payer, pubkey)| {
+                SystemTransaction::new_account(payer, pubkey, 1, genesis_block.hash(), 0)
+            })
+            .collect();
+
+        let working_bank = WorkingBank {
+            bank: bank.clone(),
+            min_tick_height: bank.tick_height(),
+            max_tick_height: bank.tick_height() + 1,
+        };
+        let (poh_recorder, _entry_receiver) = PohRecorder::new(
+            bank.tick_height(),
+            bank.last_blockhash(),
+            bank.slot(),
+            Some(4),
+            bank.ticks_per_slot(),
+        );
+        let poh_recorder = Arc::new(Mutex::new(poh_recorder));
+        poh_recorder.lock().unwrap().set_working_bank(working_bank);
+
+        // The transaction the slot has no room for is left to be buffered for the next one
+        assert_eq!(
+            BankingStage::process_transactions(&bank, &transactions, &poh_recorder).unwrap(),
+            2
+        );
+        assert_eq!(bank.get_balance(&pubkeys[1]), 1);
+        assert_eq!(bank.get_balance(&pubkeys[2]), 0);
+        assert_eq!(
+            bank.get_signature_status(&transactions[2].signatures[0]),
+            None
+        );
+    }
 }
@@ -1,13 +1,14 @@
-use crate::bank_forks::BankForks;
 use crate::blocktree::Blocktree;
 use crate::entry::{Entry, EntrySlice};
 use crate::leader_schedule_utils;
 use rayon::prelude::*;
 use solana_metrics::counter::Counter;
-use solana_runtime::bank::{Bank, Result};
+use solana_runtime::bank::{Bank, BankError, Result};
+use solana_runtime::bank_forks::BankForks;
 use solana_sdk::genesis_block::GenesisBlock;
 use solana_sdk::timing::duration_as_ms;
 use solana_sdk::timing::MAX_RECENT_BLOCKHASHES;
+use solana_sdk::transaction::TransactionError;
 use std::result;
 use std::sync::Arc;
 use std::time::Instant;
@@ -29,7 +30,7 @@ fn par_execute_entries(bank: &Bank, entries: &[(&Entry, Vec<Result<()>>)]) -> Re
                 lock_results.to_vec(),
                 MAX_RECENT_BLOCKHASHES,
             );
-            bank.unlock_accounts(&e.transactions, &results);
+            bank.unlock_accounts(&e.transactions, lock_results, &results);
             first_err(&results)
         })
         .collect();
@@ -42,14 +43,19 @@ fn par_execute_entries(bank: &Bank, entries: &[(&Entry, Vec<Result<()>>)]) -> Re
 /// 2. Process the locked group in parallel
 /// 3. Register the `Tick` if it's available
 /// 4. Update the leader scheduler, goto 1
-pub fn process_entries(bank: &Bank, entries: &[Entry]) -> Result<()> {
+pub fn process_entries(
+    bank: &Bank,
+    entries: &[Entry],
+) -> result::Result<(), BlocktreeProcessorError> {
     // accumulator for entries that can be processed in parallel
     let mut mt_group = vec![];
+    let mut tick_height = bank.tick_height();
     for entry in entries {
         if entry.is_tick() {
             // if its a tick, execute the group and register the tick
             par_execute_entries(bank, &mt_group)?;
-            bank.register_tick(&entry.hash);
+            tick_height += 1;
+            bank.register_tick_at(&entry.hash, tick_height)?;
             mt_group = vec![];
             continue;
         }
@@ -61,7 +67,7 @@ pub fn process_entries(bank: &Bank, entries: &[Entry]) -> Result<()> {
             par_execute_entries(bank, &mt_group)?;
             mt_group = vec![];
             //reset the lock and push the entry
-            bank.unlock_accounts(&entry.transactions, &lock_results);
+            bank.unlock_uncommitted_accounts(&entry.transactions, &lock_results);
             let lock_results = bank.lock_accounts(&entry.transactions);
             mt_group.push((entry, lock_results));
         } else {
@@ -79,9 +85,58 @@ pub struct BankForksInfo {
     pub entry_height: u64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum BlocktreeProcessorError {
     LedgerVerificationFailed,
+
+    /// A transaction in the slot failed to process
+    TransactionError(TransactionError),
+
+    /// The entries hold more ticks than remain in the bank's slot
+    TooManyTicks,
+
+    /// The entries end before the slot's final tick
+    IncompleteSlot,
+
+    /// A tick didn't directly follow the bank's tick height
+    BankError(BankError),
+}
+
+impl From<TransactionError> for BlocktreeProcessorError {
+    fn from(err: TransactionError) -> Self {
+        BlocktreeProcessorError::TransactionError(err)
+    }
+}
+
+impl From<BankError> for BlocktreeProcessorError {
+    fn from(err: BankError) -> Self {
+        BlocktreeProcessorError::BankError(err)
+    }
+}
+
+/// Replay a slot's worth of entries into `bank`, committing transactions and registering
+/// ticks in order. The entries must end on the slot's final tick; a slot missing ticks is
+/// left unfinished, and so isn't votable.
+pub fn replay_slot(bank: &Bank, entries: &[Entry]) -> result::Result<(), BlocktreeProcessorError> {
+    let max_tick_height = (bank.slot() + 1) * bank.ticks_per_slot() - 1;
+    let remaining_ticks = max_tick_height.saturating_sub(bank.tick_height());
+    let num_ticks = entries.iter().filter(|entry| entry.is_tick()).count() as u64;
+    if num_ticks > remaining_ticks {
+        warn!(
+            "slot {} has {} ticks remaining, but {} were replayed",
+            bank.slot(),
+            remaining_ticks,
+            num_ticks
+        );
+        return Err(BlocktreeProcessorError::TooManyTicks);
+    }
+
+    process_entries(bank, entries)?;
+
+    if bank.tick_height() < max_tick_height {
+        return Err(BlocktreeProcessorError::IncompleteSlot);
+    }
+    Ok(())
 }
 
 pub fn process_blocktree(
@@ -516,6 +571,44 @@ mod tests {
         assert_eq!(bank.tick_height(), 0);
     }
 
+    #[test]
+    fn test_replay_slot() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(1000);
+        let ticks_per_slot = genesis_block.ticks_per_slot;
+        let blockhash = genesis_block.hash();
+        let keypair = Keypair::new();
+        let tx = SystemTransaction::new_move(&mint_keypair, &keypair.pubkey(), 1, blockhash, 0);
+        let entry = next_entry(&blockhash, 1, vec![tx]);
+
+        // Slot 0 starts at tick height 0, so its last tick is at ticks_per_slot - 1
+        let mut entries = vec![entry.clone()];
+        entries.extend(create_ticks(ticks_per_slot - 1, entry.hash));
+
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(replay_slot(&bank, &entries), Ok(()));
+        assert_eq!(bank.get_balance(&keypair.pubkey()), 1);
+        assert!(bank.is_votable());
+
+        // Missing the final tick
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(
+            replay_slot(&bank, &entries[..entries.len() - 1]),
+            Err(BlocktreeProcessorError::IncompleteSlot)
+        );
+        assert_eq!(bank.get_balance(&keypair.pubkey()), 1);
+        assert!(!bank.is_votable());
+
+        // One tick too many; nothing is replayed
+        let bank = Bank::new(&genesis_block);
+        entries.extend(create_ticks(1, entries.last().unwrap().hash));
+        assert_eq!(
+            replay_slot(&bank, &entries),
+            Err(BlocktreeProcessorError::TooManyTicks)
+        );
+        assert_eq!(bank.tick_height(), 0);
+        assert_eq!(bank.get_balance(&keypair.pubkey()), 0);
+    }
+
     #[test]
     fn test_process_entries_tick() {
         let (genesis_block, _mint_keypair) = GenesisBlock::new(1000);
@@ -735,7 +828,9 @@ mod tests {
         let entry_3 = next_entry(&entry_2.hash, 1, vec![tx]);
         assert_eq!(
             process_entries(&bank, &[entry_3]),
-            Err(TransactionError::AccountNotFound)
+            Err(BlocktreeProcessorError::TransactionError(
+                TransactionError::AccountNotFound
+            ))
         );
     }
 }
@@ -1,11 +1,11 @@
 //! The `gossip_service` module implements the network control plane.
 
-use crate::bank_forks::BankForks;
 use crate::blocktree::Blocktree;
 use crate::cluster_info::ClusterInfo;
 use crate::contact_info::ContactInfo;
 use crate::service::Service;
 use crate::streamer;
+use solana_runtime::bank_forks::BankForks;
 use solana_sdk::signature::{Keypair, KeypairUtil};
 use std::net::SocketAddr;
 use std::net::UdpSocket;
@@ -137,7 +137,7 @@ mod tests {
 
             while bank.tick_height() != max_tick_height {
                 tick_hash = hash(&serialize(&tick_hash).unwrap());
-                bank.register_tick(&tick_hash);
+                bank.register_tick_with_timestamp(&tick_hash, timing::timestamp());
             }
 
             bank = Arc::new(Bank::new_from_parent(&bank, &Pubkey::default(), slot));
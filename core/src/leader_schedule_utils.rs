@@ -4,19 +4,26 @@ use solana_runtime::bank::Bank;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::timing::NUM_CONSECUTIVE_LEADER_SLOTS;
 
-/// Return the leader schedule for the given epoch.
-fn leader_schedule(epoch_height: u64, bank: &Bank) -> Option<LeaderSchedule> {
+/// Return the stake-weighted leader schedule for the given epoch, seeded by the epoch.
+/// The stakes may be in any order, so validators with the same stakes derive the same
+/// schedule.
+pub fn leader_schedule(
+    epoch_height: u64,
+    stakes: &[(Pubkey, u64)],
+    slots_in_epoch: u64,
+) -> LeaderSchedule {
+    let mut seed = [0u8; 32];
+    seed[0..8].copy_from_slice(&epoch_height.to_le_bytes());
+    let mut stakes = stakes.to_vec();
+    sort_stakes(&mut stakes);
+    LeaderSchedule::new(&stakes, seed, slots_in_epoch, NUM_CONSECUTIVE_LEADER_SLOTS)
+}
+
+/// Return the leader schedule for the given epoch from the bank's stakes.
+fn bank_leader_schedule(epoch_height: u64, bank: &Bank) -> Option<LeaderSchedule> {
     staking_utils::delegated_stakes_at_epoch(bank, epoch_height).map(|stakes| {
-        let mut seed = [0u8; 32];
-        seed[0..8].copy_from_slice(&epoch_height.to_le_bytes());
-        let mut stakes: Vec<_> = stakes.into_iter().collect();
-        sort_stakes(&mut stakes);
-        LeaderSchedule::new(
-            &stakes,
-            seed,
-            bank.get_slots_in_epoch(epoch_height),
-            NUM_CONSECUTIVE_LEADER_SLOTS,
-        )
+        let stakes: Vec<_> = stakes.into_iter().collect();
+        leader_schedule(epoch_height, &stakes, bank.get_slots_in_epoch(epoch_height))
     })
 }
 
@@ -40,14 +47,14 @@ fn sort_stakes(stakes: &mut Vec<(Pubkey, u64)>) {
 pub fn slot_leader_at(slot: u64, bank: &Bank) -> Option<Pubkey> {
     let (epoch, slot_index) = bank.get_epoch_and_slot_index(slot);
 
-    leader_schedule(epoch, bank).map(|leader_schedule| leader_schedule[slot_index])
+    bank_leader_schedule(epoch, bank).map(|leader_schedule| leader_schedule[slot_index])
 }
 
 /// Return the next slot after the given current_slot that the given node will be leader
 pub fn next_leader_slot(pubkey: &Pubkey, current_slot: u64, bank: &Bank) -> Option<u64> {
     let (epoch, slot_index) = bank.get_epoch_and_slot_index(current_slot + 1);
 
-    if let Some(leader_schedule) = leader_schedule(epoch, bank) {
+    if let Some(leader_schedule) = bank_leader_schedule(epoch, bank) {
         // clippy thinks I should do this:
         //  for (i, <item>) in leader_schedule
         //                           .iter()
@@ -141,6 +148,31 @@ mod tests {
         assert_eq!(leader_schedule[2], pubkey);
     }
 
+    #[test]
+    fn test_leader_schedule_reproducible() {
+        let pubkey0 = Keypair::new().pubkey();
+        let pubkey1 = Keypair::new().pubkey();
+        let pubkey2 = Keypair::new().pubkey();
+        let stakes = vec![(pubkey0, 3), (pubkey1, 2), (pubkey2, 2)];
+        let slots_in_epoch = 64;
+
+        let schedule = leader_schedule(1, &stakes, slots_in_epoch);
+        assert_eq!(schedule, leader_schedule(1, &stakes, slots_in_epoch));
+
+        // Ties in stake don't depend on the order the stakes were collected in
+        let reordered_stakes = vec![(pubkey2, 2), (pubkey0, 3), (pubkey1, 2)];
+        assert_eq!(
+            schedule,
+            leader_schedule(1, &reordered_stakes, slots_in_epoch)
+        );
+
+        for slot_index in 0..slots_in_epoch {
+            assert!(stakes
+                .iter()
+                .any(|(pubkey, _)| *pubkey == schedule[slot_index]));
+        }
+    }
+
     #[test]
     fn test_leader_scheduler1_basic() {
         let pubkey = Keypair::new().pubkey();
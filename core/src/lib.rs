@@ -5,7 +5,6 @@
 //! command-line tools to spin up fullnodes and a Rust library
 //!
 
-pub mod bank_forks;
 pub mod banking_stage;
 pub mod blob_fetch_stage;
 pub mod broadcast_stage;
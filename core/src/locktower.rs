@@ -1,7 +1,7 @@
-use crate::bank_forks::BankForks;
 use crate::staking_utils;
 use hashbrown::{HashMap, HashSet};
 use solana_runtime::bank::Bank;
+use solana_runtime::bank_forks::BankForks;
 use solana_sdk::account::Account;
 use solana_sdk::pubkey::Pubkey;
 use solana_vote_api::vote_instruction::Vote;
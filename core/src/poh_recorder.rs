@@ -15,6 +15,7 @@ use crate::poh::Poh;
 use crate::result::{Error, Result};
 use solana_runtime::bank::Bank;
 use solana_sdk::hash::Hash;
+use solana_sdk::timing;
 use solana_sdk::transaction::Transaction;
 use std::sync::mpsc::{channel, Receiver, Sender, SyncSender};
 use std::sync::Arc;
@@ -183,7 +184,13 @@ impl PohRecorder {
             );
             let cache = &self.tick_cache[..cnt];
             for t in cache {
-                working_bank.bank.register_tick(&t.0.hash);
+                if let Err(err) = working_bank.bank.register_tick_at_with_timestamp(
+                    &t.0.hash,
+                    t.1,
+                    timing::timestamp(),
+                ) {
+                    error!("flush_cache: failed to register tick: {:?}", err);
+                }
             }
             self.sender
                 .send((working_bank.bank.clone(), cache.to_vec()))
@@ -1,6 +1,5 @@
 //! The `replay_stage` replays transactions broadcast by the leader.
 
-use crate::bank_forks::BankForks;
 use crate::blocktree::Blocktree;
 use crate::blocktree_processor;
 use crate::cluster_info::ClusterInfo;
@@ -15,6 +14,7 @@ use crate::service::Service;
 use solana_metrics::counter::Counter;
 use solana_metrics::influxdb;
 use solana_runtime::bank::Bank;
+use solana_runtime::bank_forks::BankForks;
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::KeypairUtil;
@@ -1,6 +1,7 @@
 //! The `result` module exposes a Result type that propagates one of many different Error types.
 
 use crate::blocktree;
+use crate::blocktree_processor;
 use crate::cluster_info;
 #[cfg(feature = "erasure")]
 use crate::erasure;
@@ -30,6 +31,7 @@ pub enum Error {
     SendError,
     PohRecorderError(poh_recorder::PohRecorderError),
     BlocktreeError(blocktree::BlocktreeError),
+    BlocktreeProcessorError(blocktree_processor::BlocktreeProcessorError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -113,6 +115,11 @@ impl std::convert::From<blocktree::BlocktreeError> for Error {
         Error::BlocktreeError(e)
     }
 }
+impl std::convert::From<blocktree_processor::BlocktreeProcessorError> for Error {
+    fn from(e: blocktree_processor::BlocktreeProcessorError) -> Error {
+        Error::BlocktreeProcessorError(e)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -1,6 +1,5 @@
 //! The `retransmit_stage` retransmits blobs between validators
 
-use crate::bank_forks::BankForks;
 use crate::blocktree::Blocktree;
 use crate::cluster_info::{
     compute_retransmit_peers, ClusterInfo, DATA_PLANE_FANOUT, GROW_LAYER_CAPACITY,
@@ -15,6 +14,7 @@ use crate::streamer::BlobReceiver;
 use crate::window_service::WindowService;
 use solana_metrics::counter::Counter;
 use solana_metrics::{influxdb, submit};
+use solana_runtime::bank_forks::BankForks;
 use std::net::UdpSocket;
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::channel;
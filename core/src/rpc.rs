@@ -1,6 +1,5 @@
 //! The `rpc` module implements the Solana RPC interface.
 
-use crate::bank_forks::BankForks;
 use crate::cluster_info::ClusterInfo;
 use crate::packet::PACKET_DATA_SIZE;
 use crate::storage_stage::StorageState;
@@ -8,10 +7,15 @@ use bincode::{deserialize, serialize};
 use bs58;
 use jsonrpc_core::{Error, Metadata, Result};
 use jsonrpc_derive::rpc;
+use solana_client::rpc_request::{MAX_GET_LARGEST_ACCOUNTS, MAX_GET_SIGNATURE_STATUSES};
 use solana_client::rpc_signature_status::RpcSignatureStatus;
 use solana_drone::drone::request_airdrop_transaction;
-use solana_runtime::bank;
+use solana_runtime::bank::{self, AccountFilter, BankReadView};
+use solana_runtime::bank_forks::BankForks;
 use solana_sdk::account::Account;
+use solana_sdk::commitment::Commitment;
+use solana_sdk::fee_calculator::FeeCalculator;
+use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_sdk::transaction::{Transaction, TransactionError};
@@ -37,12 +41,44 @@ impl Default for JsonRpcConfig {
     }
 }
 
+/// Where an account query reads from: a frozen bank's read view, or the working bank
+/// itself while its slot is open
+enum ReadView {
+    Frozen(BankReadView),
+    Bank(Arc<bank::Bank>),
+}
+
+impl ReadView {
+    fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        match self {
+            ReadView::Frozen(view) => view.get_account(pubkey),
+            ReadView::Bank(bank) => bank.get_account(pubkey),
+        }
+    }
+
+    fn get_balance(&self, pubkey: &Pubkey) -> u64 {
+        match self {
+            ReadView::Frozen(view) => view.get_balance(pubkey),
+            ReadView::Bank(bank) => bank.get_balance(pubkey),
+        }
+    }
+
+    fn get_program_accounts(&self, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
+        match self {
+            ReadView::Frozen(view) => view.get_program_accounts(program_id),
+            ReadView::Bank(bank) => bank.get_program_accounts(program_id),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct JsonRpcRequestProcessor {
     bank_forks: Arc<RwLock<BankForks>>,
     storage_state: StorageState,
     config: JsonRpcConfig,
     fullnode_exit: Arc<AtomicBool>,
+    // The read view of the last frozen bank an account query read, keyed by its hash
+    read_view: Arc<RwLock<Option<(Hash, BankReadView)>>>,
 }
 
 impl JsonRpcRequestProcessor {
@@ -50,6 +86,34 @@ impl JsonRpcRequestProcessor {
         self.bank_forks.read().unwrap().working_bank()
     }
 
+    /// Return the bank `commitment` selects, the working bank if it's `None`
+    fn bank_with_commitment(&self, commitment: Option<Commitment>) -> Arc<bank::Bank> {
+        self.bank_forks
+            .read()
+            .unwrap()
+            .best_bank(commitment.unwrap_or_default())
+    }
+
+    /// Return a read view of the bank `commitment` selects, so account queries don't
+    /// contend with the banking stage. A frozen bank never changes, so its view is taken
+    /// once and reused until a query selects a different bank; a bank whose slot is still
+    /// open is read directly, since a view of it would miss the slot's later transactions.
+    fn read_view_with_commitment(&self, commitment: Option<Commitment>) -> ReadView {
+        let bank = self.bank_with_commitment(commitment);
+        if !bank.is_frozen() {
+            return ReadView::Bank(bank);
+        }
+        let hash = bank.hash();
+        if let Some((view_hash, view)) = self.read_view.read().unwrap().as_ref() {
+            if *view_hash == hash {
+                return ReadView::Frozen(view.clone());
+            }
+        }
+        let view = bank.freeze_read_view();
+        *self.read_view.write().unwrap() = Some((hash, view.clone()));
+        ReadView::Frozen(view)
+    }
+
     pub fn new(
         storage_state: StorageState,
         config: JsonRpcConfig,
@@ -61,32 +125,117 @@ impl JsonRpcRequestProcessor {
             storage_state,
             config,
             fullnode_exit: fullnode_exit.clone(),
+            read_view: Arc::new(RwLock::new(None)),
         }
     }
 
-    pub fn get_account_info(&self, pubkey: &Pubkey) -> Result<Account> {
-        self.bank()
+    pub fn get_account_info(
+        &self,
+        pubkey: &Pubkey,
+        commitment: Option<Commitment>,
+    ) -> Result<Account> {
+        self.read_view_with_commitment(commitment)
             .get_account(&pubkey)
             .ok_or_else(Error::invalid_request)
     }
 
-    pub fn get_balance(&self, pubkey: &Pubkey) -> u64 {
-        self.bank().get_balance(&pubkey)
+    pub fn get_balance(&self, pubkey: &Pubkey, commitment: Option<Commitment>) -> u64 {
+        self.read_view_with_commitment(commitment)
+            .get_balance(&pubkey)
+    }
+
+    pub fn get_program_accounts(&self, program_id: &Pubkey) -> Vec<(String, Account)> {
+        self.read_view_with_commitment(None)
+            .get_program_accounts(&program_id)
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey.to_string(), account))
+            .collect()
     }
 
-    fn get_recent_blockhash(&self) -> String {
-        let id = self.bank().last_blockhash();
+    /// Return the vote accounts the bank snapshotted for `epoch`'s leader schedule
+    pub fn get_epoch_vote_accounts(&self, epoch: u64) -> Result<Vec<(String, Account)>> {
+        self.bank()
+            .epoch_vote_accounts_iter(epoch)
+            .map(|accounts| {
+                accounts
+                    .map(|(pubkey, account)| (pubkey.to_string(), account.clone()))
+                    .collect()
+            })
+            .ok_or_else(Error::invalid_request)
+    }
+
+    fn get_recent_blockhash(&self, commitment: Option<Commitment>) -> String {
+        let id = self.bank_with_commitment(commitment).last_blockhash();
         bs58::encode(id).into_string()
     }
 
+    fn get_fee_calculator(&self) -> FeeCalculator {
+        self.bank().fee_calculator()
+    }
+
+    fn get_genesis_hash(&self) -> String {
+        bs58::encode(self.bank().genesis_hash()).into_string()
+    }
+
     pub fn get_signature_status(&self, signature: Signature) -> Option<bank::Result<()>> {
         self.bank().get_signature_status(&signature)
     }
 
+    pub fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Vec<Option<bank::Result<()>>> {
+        self.bank().get_signature_statuses(signatures)
+    }
+
+    /// Return how many slots the working bank is past the one that holds `signature`
+    pub fn get_signature_confirmation_count(&self, signature: Signature) -> Option<u64> {
+        let bank = self.bank();
+        bank.get_signature_status_with_slot(&signature)
+            .map(|(slot, _)| bank.slot() - slot)
+    }
+
     fn get_transaction_count(&self) -> Result<u64> {
         Ok(self.bank().transaction_count() as u64)
     }
 
+    fn get_slot(&self) -> Result<u64> {
+        Ok(self.bank().slot())
+    }
+
+    fn get_minimum_delegation(&self) -> Result<u64> {
+        Ok(self.bank().minimum_delegation())
+    }
+
+    /// Return the `n` largest balances that pass `filter`, largest first with ties
+    /// ordered by pubkey
+    pub fn get_largest_accounts(&self, n: usize, filter: AccountFilter) -> Vec<(String, u64)> {
+        self.bank()
+            .largest_accounts(n, filter)
+            .into_iter()
+            .map(|(pubkey, lamports)| (pubkey.to_string(), lamports))
+            .collect()
+    }
+
+    /// Return the lamports held by every account summed per owning program, largest
+    /// first with ties ordered by program id
+    pub fn get_supply(&self) -> Vec<(String, u64)> {
+        let mut supply: Vec<_> = self.bank().supply_breakdown().into_iter().collect();
+        supply.sort_by(|(a_owner, a_lamports), (b_owner, b_lamports)| {
+            b_lamports.cmp(a_lamports).then(a_owner.cmp(b_owner))
+        });
+        supply
+            .into_iter()
+            .map(|(owner, lamports)| (owner.to_string(), lamports))
+            .collect()
+    }
+
+    /// Read both values from the same bank so they're consistent with each other
+    fn get_transaction_count_and_slot(&self) -> Result<(u64, u64)> {
+        let bank = self.bank();
+        Ok((bank.transaction_count() as u64, bank.slot()))
+    }
+
     fn get_storage_blockhash(&self) -> Result<String> {
         let hash = self.storage_state.get_storage_blockhash();
         Ok(bs58::encode(hash).into_string())
@@ -167,20 +316,68 @@ pub trait RpcSol {
     fn confirm_transaction(&self, _: Self::Metadata, _: String) -> Result<bool>;
 
     #[rpc(meta, name = "getAccountInfo")]
-    fn get_account_info(&self, _: Self::Metadata, _: String) -> Result<Account>;
+    fn get_account_info(
+        &self,
+        _: Self::Metadata,
+        _: String,
+        _: Option<Commitment>,
+    ) -> Result<Account>;
 
     #[rpc(meta, name = "getBalance")]
-    fn get_balance(&self, _: Self::Metadata, _: String) -> Result<u64>;
+    fn get_balance(&self, _: Self::Metadata, _: String, _: Option<Commitment>) -> Result<u64>;
+
+    #[rpc(meta, name = "getProgramAccounts")]
+    fn get_program_accounts(&self, _: Self::Metadata, _: String) -> Result<Vec<(String, Account)>>;
+
+    #[rpc(meta, name = "getEpochVoteAccounts")]
+    fn get_epoch_vote_accounts(&self, _: Self::Metadata, _: u64) -> Result<Vec<(String, Account)>>;
 
     #[rpc(meta, name = "getRecentBlockhash")]
-    fn get_recent_blockhash(&self, _: Self::Metadata) -> Result<String>;
+    fn get_recent_blockhash(&self, _: Self::Metadata, _: Option<Commitment>) -> Result<String>;
+
+    #[rpc(meta, name = "getFeeCalculator")]
+    fn get_fee_calculator(&self, _: Self::Metadata) -> Result<FeeCalculator>;
+
+    #[rpc(meta, name = "getGenesisHash")]
+    fn get_genesis_hash(&self, _: Self::Metadata) -> Result<String>;
 
     #[rpc(meta, name = "getSignatureStatus")]
     fn get_signature_status(&self, _: Self::Metadata, _: String) -> Result<RpcSignatureStatus>;
 
+    #[rpc(meta, name = "getSignatureStatuses")]
+    fn get_signature_statuses(
+        &self,
+        _: Self::Metadata,
+        _: Vec<String>,
+    ) -> Result<Vec<Option<bank::Result<()>>>>;
+
+    #[rpc(meta, name = "getSignatureConfirmationCount")]
+    fn get_signature_confirmation_count(&self, _: Self::Metadata, _: String)
+        -> Result<Option<u64>>;
+
     #[rpc(meta, name = "getTransactionCount")]
     fn get_transaction_count(&self, _: Self::Metadata) -> Result<u64>;
 
+    #[rpc(meta, name = "getSlot")]
+    fn get_slot(&self, _: Self::Metadata) -> Result<u64>;
+
+    #[rpc(meta, name = "getMinimumDelegation")]
+    fn get_minimum_delegation(&self, _: Self::Metadata) -> Result<u64>;
+
+    #[rpc(meta, name = "getLargestAccounts")]
+    fn get_largest_accounts(
+        &self,
+        _: Self::Metadata,
+        _: usize,
+        _: Option<Vec<String>>,
+    ) -> Result<Vec<(String, u64)>>;
+
+    #[rpc(meta, name = "getSupply")]
+    fn get_supply(&self, _: Self::Metadata) -> Result<Vec<(String, u64)>>;
+
+    #[rpc(meta, name = "getTransactionCountAndSlot")]
+    fn get_transaction_count_and_slot(&self, _: Self::Metadata) -> Result<(u64, u64)>;
+
     #[rpc(meta, name = "requestAirdrop")]
     fn request_airdrop(&self, _: Self::Metadata, _: String, _: u64) -> Result<String>;
 
@@ -214,28 +411,82 @@ impl RpcSol for RpcSolImpl {
             .map(|status| status == RpcSignatureStatus::Confirmed)
     }
 
-    fn get_account_info(&self, meta: Self::Metadata, id: String) -> Result<Account> {
+    fn get_account_info(
+        &self,
+        meta: Self::Metadata,
+        id: String,
+        commitment: Option<Commitment>,
+    ) -> Result<Account> {
         info!("get_account_info rpc request received: {:?}", id);
         let pubkey = verify_pubkey(id)?;
         meta.request_processor
             .read()
             .unwrap()
-            .get_account_info(&pubkey)
+            .get_account_info(&pubkey, commitment)
     }
 
-    fn get_balance(&self, meta: Self::Metadata, id: String) -> Result<u64> {
+    fn get_balance(
+        &self,
+        meta: Self::Metadata,
+        id: String,
+        commitment: Option<Commitment>,
+    ) -> Result<u64> {
         info!("get_balance rpc request received: {:?}", id);
         let pubkey = verify_pubkey(id)?;
-        Ok(meta.request_processor.read().unwrap().get_balance(&pubkey))
+        Ok(meta
+            .request_processor
+            .read()
+            .unwrap()
+            .get_balance(&pubkey, commitment))
     }
 
-    fn get_recent_blockhash(&self, meta: Self::Metadata) -> Result<String> {
+    fn get_program_accounts(
+        &self,
+        meta: Self::Metadata,
+        id: String,
+    ) -> Result<Vec<(String, Account)>> {
+        info!("get_program_accounts rpc request received: {:?}", id);
+        let program_id = verify_pubkey(id)?;
+        Ok(meta
+            .request_processor
+            .read()
+            .unwrap()
+            .get_program_accounts(&program_id))
+    }
+
+    fn get_epoch_vote_accounts(
+        &self,
+        meta: Self::Metadata,
+        epoch: u64,
+    ) -> Result<Vec<(String, Account)>> {
+        info!("get_epoch_vote_accounts rpc request received: {}", epoch);
+        meta.request_processor
+            .read()
+            .unwrap()
+            .get_epoch_vote_accounts(epoch)
+    }
+
+    fn get_recent_blockhash(
+        &self,
+        meta: Self::Metadata,
+        commitment: Option<Commitment>,
+    ) -> Result<String> {
         info!("get_recent_blockhash rpc request received");
         Ok(meta
             .request_processor
             .read()
             .unwrap()
-            .get_recent_blockhash())
+            .get_recent_blockhash(commitment))
+    }
+
+    fn get_fee_calculator(&self, meta: Self::Metadata) -> Result<FeeCalculator> {
+        info!("get_fee_calculator rpc request received");
+        Ok(meta.request_processor.read().unwrap().get_fee_calculator())
+    }
+
+    fn get_genesis_hash(&self, meta: Self::Metadata) -> Result<String> {
+        info!("get_genesis_hash rpc request received");
+        Ok(meta.request_processor.read().unwrap().get_genesis_hash())
     }
 
     fn get_signature_status(&self, meta: Self::Metadata, id: String) -> Result<RpcSignatureStatus> {
@@ -260,6 +511,9 @@ impl RpcSol for RpcSolImpl {
                     Err(TransactionError::InstructionError(_, _)) => {
                         RpcSignatureStatus::ProgramRuntimeError
                     }
+                    Err(TransactionError::InvalidProgramForExecution) => {
+                        RpcSignatureStatus::ProgramNotExecutable
+                    }
                     Err(err) => {
                         trace!("mapping {:?} to GenericFailure", err);
                         RpcSignatureStatus::GenericFailure
@@ -271,6 +525,46 @@ impl RpcSol for RpcSolImpl {
         Ok(status)
     }
 
+    fn get_signature_statuses(
+        &self,
+        meta: Self::Metadata,
+        ids: Vec<String>,
+    ) -> Result<Vec<Option<bank::Result<()>>>> {
+        info!("get_signature_statuses rpc request received: {}", ids.len());
+        if ids.len() > MAX_GET_SIGNATURE_STATUSES {
+            return Err(Error::invalid_params(format!(
+                "at most {} signatures may be requested",
+                MAX_GET_SIGNATURE_STATUSES
+            )));
+        }
+        let signatures = ids
+            .iter()
+            .map(|id| verify_signature(id))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(meta
+            .request_processor
+            .read()
+            .unwrap()
+            .get_signature_statuses(&signatures))
+    }
+
+    fn get_signature_confirmation_count(
+        &self,
+        meta: Self::Metadata,
+        id: String,
+    ) -> Result<Option<u64>> {
+        info!(
+            "get_signature_confirmation_count rpc request received: {:?}",
+            id
+        );
+        let signature = verify_signature(&id)?;
+        Ok(meta
+            .request_processor
+            .read()
+            .unwrap()
+            .get_signature_confirmation_count(signature))
+    }
+
     fn get_transaction_count(&self, meta: Self::Metadata) -> Result<u64> {
         info!("get_transaction_count rpc request received");
         meta.request_processor
@@ -279,6 +573,54 @@ impl RpcSol for RpcSolImpl {
             .get_transaction_count()
     }
 
+    fn get_slot(&self, meta: Self::Metadata) -> Result<u64> {
+        meta.request_processor.read().unwrap().get_slot()
+    }
+
+    fn get_minimum_delegation(&self, meta: Self::Metadata) -> Result<u64> {
+        meta.request_processor
+            .read()
+            .unwrap()
+            .get_minimum_delegation()
+    }
+
+    fn get_largest_accounts(
+        &self,
+        meta: Self::Metadata,
+        n: usize,
+        excluded_owners: Option<Vec<String>>,
+    ) -> Result<Vec<(String, u64)>> {
+        info!("get_largest_accounts rpc request received: {}", n);
+        if n > MAX_GET_LARGEST_ACCOUNTS {
+            return Err(Error::invalid_params(format!(
+                "at most {} accounts may be requested",
+                MAX_GET_LARGEST_ACCOUNTS
+            )));
+        }
+        let excluded_owners = excluded_owners
+            .unwrap_or_default()
+            .into_iter()
+            .map(verify_pubkey)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(meta
+            .request_processor
+            .read()
+            .unwrap()
+            .get_largest_accounts(n, AccountFilter::excluding(&excluded_owners)))
+    }
+
+    fn get_supply(&self, meta: Self::Metadata) -> Result<Vec<(String, u64)>> {
+        info!("get_supply rpc request received");
+        Ok(meta.request_processor.read().unwrap().get_supply())
+    }
+
+    fn get_transaction_count_and_slot(&self, meta: Self::Metadata) -> Result<(u64, u64)> {
+        meta.request_processor
+            .read()
+            .unwrap()
+            .get_transaction_count_and_slot()
+    }
+
     fn request_airdrop(&self, meta: Self::Metadata, id: String, lamports: u64) -> Result<String> {
         trace!("request_airdrop id={} lamports={}", id, lamports);
 
@@ -301,7 +643,7 @@ impl RpcSol for RpcSolImpl {
             .map_err(|err| {
                 info!("request_airdrop_transaction failed: {:?}", err);
                 Error::internal_error()
-            })?;;
+            })?;
 
         let data = serialize(&transaction).map_err(|err| {
             info!("request_airdrop: serialize error: {:?}", err);
@@ -404,9 +746,11 @@ mod tests {
     use super::*;
     use crate::contact_info::ContactInfo;
     use jsonrpc_core::{MetaIoHandler, Response};
+    use serde_json::Value;
     use solana_sdk::genesis_block::GenesisBlock;
     use solana_sdk::hash::{hash, Hash};
     use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::system_program;
     use solana_sdk::system_transaction::SystemTransaction;
     use std::thread;
 
@@ -482,6 +826,94 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_balance_with_commitment() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let exit = Arc::new(AtomicBool::new(false));
+        let (bank_forks, alice) = new_bank_forks();
+        let bank0 = bank_forks.read().unwrap().working_bank();
+        let tx = SystemTransaction::new_move(&alice, &bob_pubkey, 20, bank0.last_blockhash(), 0);
+        bank0.process_transaction(&tx).unwrap();
+
+        // Slot 1 freezes slot 0 and becomes the working bank
+        let bank1 = bank::Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let tx = SystemTransaction::new_move(&alice, &bob_pubkey, 10, bank1.last_blockhash(), 0);
+        bank1.process_transaction(&tx).unwrap();
+        bank_forks.write().unwrap().insert(bank1);
+
+        let request_processor = JsonRpcRequestProcessor::new(
+            StorageState::default(),
+            JsonRpcConfig::default(),
+            bank_forks.clone(),
+            &exit,
+        );
+        assert_eq!(request_processor.get_balance(&bob_pubkey, None), 30);
+        assert_eq!(
+            request_processor.get_balance(&bob_pubkey, Some(Commitment::Recent)),
+            30
+        );
+        assert_eq!(
+            request_processor.get_balance(&bob_pubkey, Some(Commitment::Frozen)),
+            20
+        );
+        assert_eq!(
+            request_processor.get_balance(&bob_pubkey, Some(Commitment::Root)),
+            20
+        );
+
+        bank_forks.write().unwrap().set_root(1);
+        assert_eq!(
+            request_processor.get_balance(&bob_pubkey, Some(Commitment::Root)),
+            30
+        );
+    }
+
+    #[test]
+    fn test_rpc_read_view_with_commitment() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let exit = Arc::new(AtomicBool::new(false));
+        let (bank_forks, alice) = new_bank_forks();
+        let bank0 = bank_forks.read().unwrap().working_bank();
+        let tx = SystemTransaction::new_move(&alice, &bob_pubkey, 20, bank0.last_blockhash(), 0);
+        bank0.process_transaction(&tx).unwrap();
+        let bank1 = bank::Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let bank1 = bank_forks.read().unwrap().working_bank();
+
+        let request_processor = JsonRpcRequestProcessor::new(
+            StorageState::default(),
+            JsonRpcConfig::default(),
+            bank_forks.clone(),
+            &exit,
+        );
+
+        // The frozen parent is read through a view, taken once
+        assert_eq!(
+            request_processor.get_balance(&bob_pubkey, Some(Commitment::Frozen)),
+            20
+        );
+        let cached_hash = |processor: &JsonRpcRequestProcessor| {
+            processor
+                .read_view
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|(hash, _)| *hash)
+        };
+        assert_eq!(cached_hash(&request_processor), Some(bank0.hash()));
+
+        // The open working bank is read directly, so its new transactions show up at once
+        let tx = SystemTransaction::new_move(&alice, &bob_pubkey, 10, bank1.last_blockhash(), 0);
+        bank1.process_transaction(&tx).unwrap();
+        assert_eq!(request_processor.get_balance(&bob_pubkey, None), 30);
+        assert_eq!(cached_hash(&request_processor), Some(bank0.hash()));
+
+        // Once frozen, the working bank gets a view of its own
+        bank1.freeze();
+        assert_eq!(request_processor.get_balance(&bob_pubkey, None), 30);
+        assert_eq!(cached_hash(&request_processor), Some(bank1.hash()));
+    }
+
     #[test]
     fn test_rpc_get_tx_count() {
         let bob_pubkey = Keypair::new().pubkey();
@@ -497,6 +929,45 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_slot() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, _blockhash, _alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getSlot"}}"#);
+        let res = io.handle_request_sync(&req, meta.clone());
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":0,"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+
+        let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getTransactionCountAndSlot"}}"#);
+        let res = io.handle_request_sync(&req, meta);
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":[1,0],"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_rpc_get_minimum_delegation() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, _blockhash, _alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getMinimumDelegation"}}"#);
+        let res = io.handle_request_sync(&req, meta);
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":0,"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_get_account_info() {
         let bob_pubkey = Keypair::new().pubkey();
@@ -524,6 +995,102 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_program_accounts() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, _blockhash, _alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        // Only the bootstrap leader's vote account is owned by the vote program
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccounts","params":["{}"]}}"#,
+            solana_vote_api::id()
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let accounts: Vec<(String, Account)> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].1.owner, solana_vote_api::id());
+    }
+
+    #[test]
+    fn test_rpc_get_largest_accounts_and_supply() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, _blockhash, alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getLargestAccounts","params":[2, ["{}"]]}}"#,
+            solana_vote_api::id()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let accounts: Vec<(String, u64)> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            accounts,
+            vec![
+                (alice.pubkey().to_string(), 10_000 - 20),
+                (bob_pubkey.to_string(), 20)
+            ]
+        );
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getLargestAccounts","params":[{}]}}"#,
+            MAX_GET_LARGEST_ACCOUNTS + 1
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["error"]["code"], -32602);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getSupply"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let supply: Vec<(String, u64)> = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(supply[0].0, system_program::id().to_string());
+        let capitalization = meta
+            .request_processor
+            .read()
+            .unwrap()
+            .bank()
+            .capitalization();
+        assert_eq!(
+            supply.iter().map(|(_, lamports)| lamports).sum::<u64>(),
+            capitalization
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_epoch_vote_accounts() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, _blockhash, _alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req =
+            format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getEpochVoteAccounts","params":[0]}}"#);
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let accounts: Vec<(String, Account)> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(accounts.len(), 1);
+
+        // No stakes have been snapshotted that far ahead
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getEpochVoteAccounts","params":[1000]}}"#
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let expected =
+            r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":1}"#;
+        let expected: Response =
+            serde_json::from_str(expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_confirm_tx() {
         let bob_pubkey = Keypair::new().pubkey();
@@ -576,6 +1143,70 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_signature_statuses() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, blockhash, alice) = start_rpc_handler_with_tx(&bob_pubkey);
+        let confirmed = SystemTransaction::new_move(&alice, &bob_pubkey, 20, blockhash, 0);
+        let unknown = SystemTransaction::new_move(&alice, &bob_pubkey, 10, blockhash, 0);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureStatuses","params":[["{}","{}"]]}}"#,
+            unknown.signatures[0], confirmed.signatures[0]
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":[null,{{"Ok":null}}],"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+
+        // Too many signatures
+        let signatures =
+            vec![format!("\"{}\"", confirmed.signatures[0]); MAX_GET_SIGNATURE_STATUSES + 1];
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureStatuses","params":[[{}]]}}"#,
+            signatures.join(",")
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result.get("error").is_some());
+    }
+
+    #[test]
+    fn test_rpc_get_signature_confirmation_count() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, blockhash, alice) = start_rpc_handler_with_tx(&bob_pubkey);
+        let confirmed = SystemTransaction::new_move(&alice, &bob_pubkey, 20, blockhash, 0);
+        let unknown = SystemTransaction::new_move(&alice, &bob_pubkey, 10, blockhash, 0);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureConfirmationCount","params":["{}"]}}"#,
+            confirmed.signatures[0]
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":0,"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSignatureConfirmationCount","params":["{}"]}}"#,
+            unknown.signatures[0]
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":null,"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_get_recent_blockhash() {
         let bob_pubkey = Keypair::new().pubkey();
@@ -591,6 +1222,38 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_fee_calculator() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, _blockhash, _alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getFeeCalculator"}}"#);
+        let res = io.handle_request_sync(&req, meta);
+        let expected =
+            format!(r#"{{"jsonrpc":"2.0","result":{{"lamports_per_signature":0}},"id":1}}"#);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_rpc_get_genesis_hash() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let (io, meta, blockhash, _alice) = start_rpc_handler_with_tx(&bob_pubkey);
+
+        // No ticks have been registered, so the last blockhash is still the genesis hash
+        let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getGenesisHash"}}"#);
+        let res = io.handle_request_sync(&req, meta);
+        let expected = format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, blockhash);
+        let expected: Response =
+            serde_json::from_str(&expected).expect("expected response deserialization");
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_fail_request_airdrop() {
         let bob_pubkey = Keypair::new().pubkey();
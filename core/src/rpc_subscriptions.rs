@@ -103,6 +103,9 @@ impl RpcSubscriptions {
             Err(TransactionError::InstructionError(_, _)) => {
                 RpcSignatureStatus::ProgramRuntimeError
             }
+            Err(TransactionError::InvalidProgramForExecution) => {
+                RpcSignatureStatus::ProgramNotExecutable
+            }
             Err(_) => RpcSignatureStatus::GenericFailure,
         };
 
@@ -71,7 +71,9 @@ fn main() -> Result<(), Box<error::Error>> {
     thread::spawn(move || loop {
         let time = drone1.lock().unwrap().time_slice;
         thread::sleep(time);
-        drone1.lock().unwrap().clear_request_count();
+        let mut drone = drone1.lock().unwrap();
+        drone.submit_info_metrics();
+        drone.clear_request_count();
     });
 
     let socket = TcpListener::bind(&drone_addr).unwrap();
@@ -52,6 +52,20 @@ pub enum DroneRequest {
         to: Pubkey,
         blockhash: Hash,
     },
+    GetInfo,
+}
+
+/// Drone counters returned in response to `DroneRequest::GetInfo`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DroneInfo {
+    /// Lamports granted since the drone started
+    pub dispensed_total: u64,
+    /// Lamports granted in the current time slice
+    pub dispensed_window: u64,
+    /// The most a single request may ask for
+    pub per_request_cap: u64,
+    /// Lamports still available in the current time slice
+    pub window_remaining: u64,
 }
 
 pub struct Drone {
@@ -60,6 +74,7 @@ pub struct Drone {
     pub time_slice: Duration,
     request_cap: u64,
     pub request_current: u64,
+    dispensed_total: u64,
 }
 
 impl Drone {
@@ -82,6 +97,7 @@ impl Drone {
             time_slice,
             request_cap,
             request_current: 0,
+            dispensed_total: 0,
         }
     }
 
@@ -89,6 +105,36 @@ impl Drone {
         (self.request_current + request_amount) <= self.request_cap
     }
 
+    pub fn info(&self) -> DroneInfo {
+        DroneInfo {
+            dispensed_total: self.dispensed_total,
+            dispensed_window: self.request_current,
+            per_request_cap: self.request_cap,
+            window_remaining: self.request_cap.saturating_sub(self.request_current),
+        }
+    }
+
+    pub fn submit_info_metrics(&self) {
+        let info = self.info();
+        solana_metrics::submit(
+            influxdb::Point::new("drone")
+                .add_tag("op", influxdb::Value::String("info".to_string()))
+                .add_field(
+                    "dispensed_total",
+                    influxdb::Value::Integer(info.dispensed_total as i64),
+                )
+                .add_field(
+                    "dispensed_window",
+                    influxdb::Value::Integer(info.dispensed_window as i64),
+                )
+                .add_field(
+                    "window_remaining",
+                    influxdb::Value::Integer(info.window_remaining as i64),
+                )
+                .to_owned(),
+        );
+    }
+
     pub fn clear_request_count(&mut self) {
         self.request_current = 0;
     }
@@ -114,6 +160,7 @@ impl Drone {
             } => {
                 if self.check_request_limit(lamports) {
                     self.request_current += lamports;
+                    self.dispensed_total += lamports;
                     solana_metrics::submit(
                         influxdb::Point::new("drone")
                             .add_tag("op", influxdb::Value::String("airdrop".to_string()))
@@ -147,6 +194,10 @@ impl Drone {
                     Err(Error::new(ErrorKind::Other, "token limit reached"))
                 }
             }
+            DroneRequest::GetInfo => Err(Error::new(
+                ErrorKind::Other,
+                "GetInfo doesn't build a transaction",
+            )),
         }
     }
     pub fn process_drone_request(&mut self, bytes: &BytesMut) -> Result<Bytes, io::Error> {
@@ -157,22 +208,17 @@ impl Drone {
             ))
         })?;
 
+        if let DroneRequest::GetInfo = req {
+            let info = self.info();
+            debug!("Drone info requested: {:?}", info);
+            return with_length_prefix(&info);
+        }
+
         info!("Airdrop transaction requested...{:?}", req);
         let res = self.build_airdrop_transaction(req);
         match res {
             Ok(tx) => {
-                let response_vec = bincode::serialize(&tx).or_else(|err| {
-                    Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!("deserialize packet in drone: {:?}", err),
-                    ))
-                })?;
-
-                let mut response_vec_with_length = vec![0; 2];
-                LittleEndian::write_u16(&mut response_vec_with_length, response_vec.len() as u16);
-                response_vec_with_length.extend_from_slice(&response_vec);
-
-                let response_bytes = Bytes::from(response_vec_with_length);
+                let response_bytes = with_length_prefix(&tx)?;
                 info!("Airdrop transaction granted");
                 Ok(response_bytes)
             }
@@ -184,6 +230,20 @@ impl Drone {
     }
 }
 
+fn with_length_prefix<T: serde::Serialize>(response: &T) -> Result<Bytes, io::Error> {
+    let response_vec = bincode::serialize(response).or_else(|err| {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("deserialize packet in drone: {:?}", err),
+        ))
+    })?;
+
+    let mut response_vec_with_length = vec![0; 2];
+    LittleEndian::write_u16(&mut response_vec_with_length, response_vec.len() as u16);
+    response_vec_with_length.extend_from_slice(&response_vec);
+    Ok(Bytes::from(response_vec_with_length))
+}
+
 impl Drop for Drone {
     fn drop(&mut self) {
         solana_metrics::flush();
@@ -200,55 +260,70 @@ pub fn request_airdrop_transaction(
         "request_airdrop_transaction: drone_addr={} id={} lamports={} blockhash={}",
         drone_addr, id, lamports, blockhash
     );
-    // TODO: make this async tokio client
-    let mut stream = TcpStream::connect_timeout(drone_addr, Duration::new(3, 0))?;
-    stream.set_read_timeout(Some(Duration::new(10, 0)))?;
     let req = DroneRequest::GetAirdrop {
         lamports,
         blockhash,
         to: *id,
     };
-    let req = serialize(&req).expect("serialize drone request");
+    let buffer = send_drone_request(drone_addr, &req)?;
+
+    let transaction: Transaction = deserialize(&buffer).or_else(|err| {
+        Err(Error::new(
+            ErrorKind::Other,
+            format!("request_airdrop_transaction deserialize failure: {:?}", err),
+        ))
+    })?;
+    Ok(transaction)
+}
+
+pub fn request_drone_info(drone_addr: &SocketAddr) -> Result<DroneInfo, Error> {
+    info!("request_drone_info: drone_addr={}", drone_addr);
+    let buffer = send_drone_request(drone_addr, &DroneRequest::GetInfo)?;
+    let drone_info: DroneInfo = deserialize(&buffer).or_else(|err| {
+        Err(Error::new(
+            ErrorKind::Other,
+            format!("request_drone_info deserialize failure: {:?}", err),
+        ))
+    })?;
+    Ok(drone_info)
+}
+
+/// Send `req` to the drone and return the body of its length-prefixed response
+fn send_drone_request(drone_addr: &SocketAddr, req: &DroneRequest) -> Result<Vec<u8>, Error> {
+    // TODO: make this async tokio client
+    let mut stream = TcpStream::connect_timeout(drone_addr, Duration::new(3, 0))?;
+    stream.set_read_timeout(Some(Duration::new(10, 0)))?;
+    let req = serialize(req).expect("serialize drone request");
     stream.write_all(&req)?;
 
-    // Read length of transaction
+    // Read length of response
     let mut buffer = [0; 2];
     stream.read_exact(&mut buffer).or_else(|err| {
         info!(
-            "request_airdrop_transaction: buffer length read_exact error: {:?}",
+            "send_drone_request: buffer length read_exact error: {:?}",
             err
         );
-        Err(Error::new(ErrorKind::Other, "Airdrop failed"))
+        Err(Error::new(ErrorKind::Other, "Drone request failed"))
     })?;
-    let transaction_length = LittleEndian::read_u16(&buffer) as usize;
-    if transaction_length >= PACKET_DATA_SIZE {
+    let response_length = LittleEndian::read_u16(&buffer) as usize;
+    if response_length >= PACKET_DATA_SIZE {
         Err(Error::new(
             ErrorKind::Other,
             format!(
-                "request_airdrop_transaction: invalid transaction_length from drone: {}",
-                transaction_length
+                "send_drone_request: invalid response_length from drone: {}",
+                response_length
             ),
         ))?;
     }
 
-    // Read the transaction
+    // Read the response
     let mut buffer = Vec::new();
-    buffer.resize(transaction_length, 0);
+    buffer.resize(response_length, 0);
     stream.read_exact(&mut buffer).or_else(|err| {
-        info!(
-            "request_airdrop_transaction: buffer read_exact error: {:?}",
-            err
-        );
-        Err(Error::new(ErrorKind::Other, "Airdrop failed"))
-    })?;
-
-    let transaction: Transaction = deserialize(&buffer).or_else(|err| {
-        Err(Error::new(
-            ErrorKind::Other,
-            format!("request_airdrop_transaction deserialize failure: {:?}", err),
-        ))
+        info!("send_drone_request: buffer read_exact error: {:?}", err);
+        Err(Error::new(ErrorKind::Other, "Drone request failed"))
     })?;
-    Ok(transaction)
+    Ok(buffer)
 }
 
 // For integration tests. Listens on random open port and reports port to Sender.
@@ -340,6 +415,50 @@ mod tests {
         assert!(drone.ip_cache.is_empty());
     }
 
+    #[test]
+    fn test_drone_info() {
+        let keypair = Keypair::new();
+        let mut drone = Drone::new(keypair, None, Some(10));
+        let to = Keypair::new().pubkey();
+        let request = DroneRequest::GetAirdrop {
+            lamports: 4,
+            to,
+            blockhash: Hash::default(),
+        };
+        drone.build_airdrop_transaction(request).unwrap();
+        assert_eq!(
+            drone.info(),
+            DroneInfo {
+                dispensed_total: 4,
+                dispensed_window: 4,
+                per_request_cap: 10,
+                window_remaining: 6,
+            }
+        );
+
+        // A new time slice resets the window but not the total
+        drone.clear_request_count();
+        drone.build_airdrop_transaction(request).unwrap();
+        assert_eq!(
+            drone.info(),
+            DroneInfo {
+                dispensed_total: 8,
+                dispensed_window: 4,
+                per_request_cap: 10,
+                window_remaining: 6,
+            }
+        );
+
+        // Rejected requests aren't counted
+        drone.build_airdrop_transaction(request).unwrap();
+        assert!(drone.build_airdrop_transaction(request).is_err());
+        assert_eq!(drone.info().dispensed_total, 12);
+        assert_eq!(drone.info().window_remaining, 2);
+        assert!(drone
+            .build_airdrop_transaction(DroneRequest::GetInfo)
+            .is_err());
+    }
+
     #[test]
     fn test_drone_default_init() {
         let keypair = Keypair::new();
@@ -427,6 +546,18 @@ mod tests {
         let response_vec = response.unwrap().to_vec();
         assert_eq!(expected_vec_with_length, response_vec);
 
+        let req = serialize(&DroneRequest::GetInfo).unwrap();
+        let mut bytes = BytesMut::with_capacity(req.len());
+        bytes.put(&req[..]);
+        let response_vec = drone.process_drone_request(&bytes).unwrap().to_vec();
+        assert_eq!(
+            LittleEndian::read_u16(&response_vec[..2]) as usize,
+            response_vec.len() - 2
+        );
+        let info: DroneInfo = deserialize(&response_vec[2..]).unwrap();
+        assert_eq!(info, drone.info());
+        assert_eq!(info.dispensed_total, lamports);
+
         let mut bad_bytes = BytesMut::with_capacity(9);
         bad_bytes.put("bad bytes");
         assert!(drone.process_drone_request(&bad_bytes).is_err());
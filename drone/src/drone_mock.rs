@@ -1,3 +1,4 @@
+use crate::drone::{DroneInfo, REQUEST_CAP};
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil};
@@ -21,3 +22,12 @@ pub fn request_airdrop_transaction(
     let tx = SystemTransaction::new_account(&key, &to, lamports, blockhash, 0);
     Ok(tx)
 }
+
+pub fn request_drone_info(_drone_addr: &SocketAddr) -> Result<DroneInfo, Error> {
+    Ok(DroneInfo {
+        dispensed_total: 0,
+        dispensed_window: 0,
+        per_request_cap: REQUEST_CAP,
+        window_remaining: REQUEST_CAP,
+    })
+}
@@ -1,4 +1,4 @@
-use solana_drone::drone::{request_airdrop_transaction, run_local_drone};
+use solana_drone::drone::{request_airdrop_transaction, request_drone_info, run_local_drone};
 use solana_sdk::hash::Hash;
 use solana_sdk::signature::{Keypair, KeypairUtil};
 use solana_sdk::system_instruction::SystemInstruction;
@@ -33,4 +33,9 @@ fn test_local_drone() {
 
     let result = request_airdrop_transaction(&drone_addr, &to, lamports, blockhash);
     assert_eq!(expected_tx, result.unwrap());
+
+    let info = request_drone_info(&drone_addr).unwrap();
+    assert_eq!(info.dispensed_total, lamports);
+    assert_eq!(info.dispensed_window, lamports);
+    assert_eq!(info.window_remaining, info.per_request_cap - lamports);
 }
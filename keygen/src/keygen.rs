@@ -1,6 +1,23 @@
 use clap::{crate_description, crate_name, crate_version, App, Arg};
-use solana_sdk::signature::gen_keypair_file;
+use solana_sdk::signature::{gen_keypair_file, gen_keypair_file_from_seed, seed_from_seed_phrase};
 use std::error;
+use std::io::{self, Write};
+
+/// Ask on the terminal for a seed phrase and passphrase and return the seed they derive
+fn prompt_seed_phrase() -> io::Result<[u8; 32]> {
+    let mut words = String::new();
+    eprint!("Seed phrase: ");
+    io::stderr().flush()?;
+    io::stdin().read_line(&mut words)?;
+
+    let mut passphrase = String::new();
+    eprint!("Passphrase (empty for none): ");
+    io::stderr().flush()?;
+    io::stdin().read_line(&mut passphrase)?;
+    let passphrase = passphrase.trim_end_matches(|c| c == '\n' || c == '\r');
+
+    Ok(seed_from_seed_phrase(&words, passphrase))
+}
 
 fn main() -> Result<(), Box<dyn error::Error>> {
     let matches = App::new(crate_name!())
@@ -14,6 +31,11 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .takes_value(true)
                 .help("Path to generated file"),
         )
+        .arg(
+            Arg::with_name("seed_phrase")
+                .long("seed-phrase")
+                .help("Prompt for a seed phrase and write the keypair it derives"),
+        )
         .get_matches();
 
     let mut path = dirs::home_dir().expect("home directory");
@@ -24,7 +46,11 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         path.to_str().unwrap()
     };
 
-    let serialized_keypair = gen_keypair_file(outfile.to_string())?;
+    let serialized_keypair = if matches.is_present("seed_phrase") {
+        gen_keypair_file_from_seed(&prompt_seed_phrase()?, outfile.to_string())?
+    } else {
+        gen_keypair_file(outfile.to_string())?
+    };
     if outfile == "-" {
         println!("{}", serialized_keypair);
     }
@@ -4,44 +4,124 @@ use chrono::prelude::{DateTime, Utc};
 use log::*;
 use solana_budget_api::budget_instruction::BudgetInstruction;
 use solana_budget_api::budget_state::{BudgetError, BudgetState};
-use solana_budget_api::payment_plan::Witness;
+use solana_budget_api::payment_plan::{Payment, Witness};
 use solana_sdk::account::KeyedAccount;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::transaction::InstructionError;
 
-/// Process a Witness Signature. Any payment plans waiting on this signature
-/// will progress one step.
-fn apply_signature(
+/// Find the signing account whose witness the budget is waiting on.
+fn find_witness(
+    budget_state: &BudgetState,
+    keyed_accounts: &[KeyedAccount],
+    witness: &Witness,
+) -> Option<usize> {
+    let mut pubkeys = budget_state
+        .pending_budget
+        .as_ref()
+        .map(|expr| expr.witness_pubkeys(witness))
+        .unwrap_or_default();
+    // A signature counts once, however many of the budget's conditions name its signer
+    if *witness == Witness::Signature {
+        pubkeys.retain(|pubkey| !budget_state.is_witnessed_by(pubkey));
+    }
+    keyed_accounts.iter().position(|keyed_account| {
+        keyed_account
+            .signer_key()
+            .map_or(false, |key| pubkeys.contains(&key))
+    })
+}
+
+/// Apply a witness from `keyed_accounts[witness_index]` and execute the payment if
+/// that was the last witness the budget needed.
+fn apply_witness(
     budget_state: &mut BudgetState,
     keyed_accounts: &mut [KeyedAccount],
+    witness_index: usize,
+    witness: &Witness,
 ) -> Result<(), BudgetError> {
     let mut final_payment = None;
     if let Some(ref mut expr) = budget_state.pending_budget {
-        let key = keyed_accounts[0].signer_key().unwrap();
-        expr.apply_witness(&Witness::Signature, key);
+        let from = *keyed_accounts[witness_index].unsigned_key();
+        if let Some(payment) = expr.release_tranches(witness, &from) {
+            let fully_vested = expr.is_fully_vested();
+            pay_tranches(keyed_accounts, &payment)?;
+            if fully_vested {
+                budget_state.pending_budget = None;
+            }
+            return Ok(());
+        }
+        expr.apply_witness(witness, &from);
         final_payment = expr.final_payment();
     }
 
     if let Some(payment) = final_payment {
-        if let Some(key) = keyed_accounts[0].signer_key() {
-            if &payment.to == key {
-                budget_state.pending_budget = None;
-                keyed_accounts[1].account.lamports -= payment.lamports;
-                keyed_accounts[0].account.lamports += payment.lamports;
-                return Ok(());
+        // A payment back to the signing witness is a refund, which the witness may
+        // redirect to the instruction's third account
+        let is_refund = *witness == Witness::Signature
+            && keyed_accounts[witness_index].signer_key() == Some(&payment.to);
+        // The contract is always the second account; the destination may be anywhere else
+        let destination = if is_refund && keyed_accounts.len() > 2 {
+            Some(2)
+        } else {
+            (0..keyed_accounts.len())
+                .find(|&i| i != 1 && keyed_accounts[i].unsigned_key() == &payment.to)
+        };
+        let destination = match destination {
+            Some(destination) => destination,
+            None => {
+                trace!("destination missing");
+                return Err(BudgetError::DestinationMissing);
             }
-        }
-        if &payment.to != keyed_accounts[2].unsigned_key() {
-            trace!("destination missing");
-            return Err(BudgetError::DestinationMissing);
-        }
+        };
         budget_state.pending_budget = None;
         keyed_accounts[1].account.lamports -= payment.lamports;
-        keyed_accounts[2].account.lamports += payment.lamports;
+        keyed_accounts[destination].account.lamports += payment.lamports;
     }
     Ok(())
 }
 
+/// Move the lamports of released vesting tranches from the contract to their recipient
+fn pay_tranches(keyed_accounts: &mut [KeyedAccount], payment: &Payment) -> Result<(), BudgetError> {
+    let destination = (0..keyed_accounts.len())
+        .find(|&i| i != 1 && keyed_accounts[i].unsigned_key() == &payment.to)
+        .ok_or_else(|| {
+            trace!("destination missing");
+            BudgetError::DestinationMissing
+        })?;
+    keyed_accounts[1].account.lamports -= payment.lamports;
+    keyed_accounts[destination].account.lamports += payment.lamports;
+    Ok(())
+}
+
+/// Process a Witness Signature. Any payment plans waiting on this signature
+/// will progress one step.
+fn apply_signature(
+    budget_state: &mut BudgetState,
+    keyed_accounts: &mut [KeyedAccount],
+) -> Result<(), BudgetError> {
+    let witness = Witness::Signature;
+    let witness_index = match find_witness(budget_state, keyed_accounts, &witness) {
+        Some(witness_index) => witness_index,
+        None if keyed_accounts.iter().any(|keyed_account| {
+            keyed_account
+                .signer_key()
+                .map_or(false, |key| budget_state.is_witnessed_by(key))
+        }) =>
+        {
+            trace!("signature already applied to the contract");
+            return Err(BudgetError::SignatureAlreadyApplied);
+        }
+        None => {
+            trace!("signature not from a key the contract is waiting on");
+            return Err(BudgetError::UnauthorizedSignature);
+        }
+    };
+    let signer = *keyed_accounts[witness_index].unsigned_key();
+    apply_witness(budget_state, keyed_accounts, witness_index, &witness)?;
+    budget_state.witnessed_signers.insert(signer);
+    Ok(())
+}
+
 /// Process a Witness Timestamp. Any payment plans waiting on this timestamp
 /// will progress one step.
 fn apply_timestamp(
@@ -49,32 +129,35 @@ fn apply_timestamp(
     keyed_accounts: &mut [KeyedAccount],
     dt: DateTime<Utc>,
 ) -> Result<(), BudgetError> {
-    // Check to see if any timelocked transactions can be completed.
-    let mut final_payment = None;
-
-    if let Some(ref mut expr) = budget_state.pending_budget {
-        let key = keyed_accounts[0].signer_key().unwrap();
-        expr.apply_witness(&Witness::Timestamp(dt), key);
-        final_payment = expr.final_payment();
-    }
+    let witness = Witness::Timestamp(dt);
+    let witness_index = find_witness(budget_state, keyed_accounts, &witness).ok_or_else(|| {
+        trace!("timestamp not from a key the contract accepts timestamps from");
+        BudgetError::UnauthorizedTimestamp
+    })?;
+    apply_witness(budget_state, keyed_accounts, witness_index, &witness)
+}
 
-    if let Some(payment) = final_payment {
-        if &payment.to != keyed_accounts[2].unsigned_key() {
-            trace!("destination missing");
-            return Err(BudgetError::DestinationMissing);
-        }
-        budget_state.pending_budget = None;
-        keyed_accounts[1].account.lamports -= payment.lamports;
-        keyed_accounts[2].account.lamports += payment.lamports;
-    }
-    Ok(())
+/// Process the bank's tick height. Any payment plans waiting on that block height
+/// will progress one step.
+fn apply_block_height(
+    budget_state: &mut BudgetState,
+    keyed_accounts: &mut [KeyedAccount],
+    tick_height: u64,
+) -> Result<(), BudgetError> {
+    // Block height conditions don't name a witness, so the sender's key is irrelevant
+    apply_witness(
+        budget_state,
+        keyed_accounts,
+        0,
+        &Witness::BlockHeight(tick_height),
+    )
 }
 
 pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &mut [KeyedAccount],
     data: &[u8],
-    _tick_height: u64,
+    tick_height: u64,
 ) -> Result<(), InstructionError> {
     let instruction = deserialize(data).map_err(|err| {
         info!("Invalid transaction data: {:?} {:?}", data, err);
@@ -85,21 +168,37 @@ pub fn process_instruction(
 
     match instruction {
         BudgetInstruction::InitializeAccount(expr) => {
-            let expr = expr.clone();
-            if let Some(payment) = expr.final_payment() {
-                keyed_accounts[1].account.lamports = 0;
-                keyed_accounts[0].account.lamports += payment.lamports;
-                return Ok(());
+            if !expr.is_within_limits() {
+                trace!("budget expression too large");
+                return Err(InstructionError::CustomError(
+                    serialize(&BudgetError::ExpressionTooLarge).unwrap(),
+                ));
             }
-            let existing = BudgetState::deserialize(&keyed_accounts[0].account.data).ok();
+            let mut expr = expr.clone();
+            // Block height conditions are checked against the bank rather than a witness
+            expr.apply_witness(&Witness::BlockHeight(tick_height), &Pubkey::default());
+            let contract_index = keyed_accounts.len() - 1;
+            let existing =
+                BudgetState::deserialize(&keyed_accounts[contract_index].account.data).ok();
             if Some(true) == existing.map(|x| x.initialized) {
                 trace!("contract already exists");
                 return Err(InstructionError::AccountAlreadyInitialized);
             }
+            if let Some(payment) = expr.final_payment() {
+                if contract_index == 0 || keyed_accounts[0].unsigned_key() != &payment.to {
+                    trace!("destination missing");
+                    return Err(InstructionError::CustomError(
+                        serialize(&BudgetError::DestinationMissing).unwrap(),
+                    ));
+                }
+                keyed_accounts[1].account.lamports = 0;
+                keyed_accounts[0].account.lamports += payment.lamports;
+                return Ok(());
+            }
             let mut budget_state = BudgetState::default();
             budget_state.pending_budget = Some(expr);
             budget_state.initialized = true;
-            budget_state.serialize(&mut keyed_accounts[0].account.data)
+            budget_state.serialize(&mut keyed_accounts[contract_index].account.data)
         }
         BudgetInstruction::ApplyTimestamp(dt) => {
             let mut budget_state = BudgetState::deserialize(&keyed_accounts[1].account.data)?;
@@ -110,7 +209,7 @@ pub fn process_instruction(
                 trace!("contract is uninitialized");
                 return Err(InstructionError::UninitializedAccount);
             }
-            if keyed_accounts[0].signer_key().is_none() {
+            if keyed_accounts.iter().all(|ka| ka.signer_key().is_none()) {
                 return Err(InstructionError::MissingRequiredSignature);
             }
             trace!("apply timestamp");
@@ -128,7 +227,7 @@ pub fn process_instruction(
                 trace!("contract is uninitialized");
                 return Err(InstructionError::UninitializedAccount);
             }
-            if keyed_accounts[0].signer_key().is_none() {
+            if keyed_accounts.iter().all(|ka| ka.signer_key().is_none()) {
                 return Err(InstructionError::MissingRequiredSignature);
             }
             trace!("apply signature");
@@ -137,17 +236,58 @@ pub fn process_instruction(
             trace!("apply signature committed");
             budget_state.serialize(&mut keyed_accounts[1].account.data)
         }
+        BudgetInstruction::ApplyBlockHeight => {
+            let mut budget_state = BudgetState::deserialize(&keyed_accounts[1].account.data)?;
+            if !budget_state.is_pending() {
+                return Ok(()); // Nothing to do here.
+            }
+            if !budget_state.initialized {
+                trace!("contract is uninitialized");
+                return Err(InstructionError::UninitializedAccount);
+            }
+            trace!("apply block height");
+            apply_block_height(&mut budget_state, keyed_accounts, tick_height)
+                .map_err(|e| InstructionError::CustomError(serialize(&e).unwrap()))?;
+            trace!("apply block height committed");
+            budget_state.serialize(&mut keyed_accounts[1].account.data)
+        }
+        BudgetInstruction::AddFunds(lamports) => {
+            // A paid or cancelled contract has no data left to deserialize
+            let mut budget_state =
+                BudgetState::deserialize(&keyed_accounts[1].account.data).unwrap_or_default();
+            if !budget_state.is_pending() {
+                trace!("contract is not pending");
+                return Err(InstructionError::CustomError(
+                    serialize(&BudgetError::ContractNotPending).unwrap(),
+                ));
+            }
+            if keyed_accounts[0].signer_key().is_none() {
+                return Err(InstructionError::MissingRequiredSignature);
+            }
+            let expr = budget_state.pending_budget.as_mut().unwrap();
+            expr.add_lamports(lamports);
+            // The lamports must have been moved into the contract beforehand
+            if !expr.verify(keyed_accounts[1].account.lamports) {
+                trace!("contract balance doesn't cover the added funds");
+                return Err(InstructionError::InvalidArgument);
+            }
+            trace!("add funds committed");
+            budget_state.serialize(&mut keyed_accounts[1].account.data)
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use chrono::Duration;
+    use solana_budget_api::budget_expr::{BudgetExpr, Condition, MAX_BUDGET_EXPR_DEPTH};
     use solana_budget_api::budget_instruction::BudgetInstruction;
     use solana_budget_api::budget_script::BudgetScript;
     use solana_budget_api::id;
     use solana_runtime::bank::Bank;
     use solana_runtime::bank_client::BankClient;
+    use solana_sdk::account::Account;
     use solana_sdk::genesis_block::GenesisBlock;
     use solana_sdk::signature::{Keypair, KeypairUtil};
     use solana_sdk::transaction::{InstructionError, Transaction, TransactionError};
@@ -259,6 +399,57 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_unauthorized_timestamp() {
+        let (bank, mint_keypair) = create_bank(10_000);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+
+        // Initialize BudgetState
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let dt = Utc::now();
+        let script = BudgetScript::pay_on_date(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            dt,
+            &alice_pubkey,
+            None,
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+
+        // Attack! Sign a timestamp with a key that isn't the contract's timestamp authority.
+        let mallory_client = BankClient::new(&bank, Keypair::new());
+        let mallory_pubkey = mallory_client.pubkey();
+        alice_client.transfer(1, &mallory_pubkey).unwrap();
+        let instruction = BudgetInstruction::new_apply_timestamp(
+            &mallory_pubkey,
+            &budget_pubkey,
+            &bob_pubkey,
+            dt,
+        );
+        assert_eq!(
+            mallory_client.process_instruction(instruction).unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::CustomError(
+                    serialize(&BudgetError::UnauthorizedTimestamp).unwrap()
+                )
+            )
+        );
+        assert_eq!(bank.get_balance(&budget_pubkey), 1);
+        assert_eq!(bank.get_balance(&bob_pubkey), 0);
+
+        // The authority's timestamp completes the payment
+        let instruction =
+            BudgetInstruction::new_apply_timestamp(&alice_pubkey, &budget_pubkey, &bob_pubkey, dt);
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&budget_pubkey), 0);
+        assert_eq!(bank.get_balance(&bob_pubkey), 1);
+    }
+
     #[test]
     fn test_pay_on_date() {
         let (bank, mint_keypair) = create_bank(2);
@@ -318,6 +509,212 @@ mod test {
         assert_eq!(bank.get_account(&budget_pubkey), None);
     }
 
+    #[test]
+    fn test_pay_on_block_height() {
+        let (bank, mint_keypair) = create_bank(3);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let tick_height = bank.tick_height() + 1;
+        let script = BudgetScript::pay_on_block_height(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            tick_height,
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+        assert_eq!(bank.get_balance(&budget_pubkey), 1);
+        assert_eq!(bank.get_balance(&bob_pubkey), 0);
+
+        // The contract stays locked until the bank reaches the block height. Anyone
+        // may ask the contract to check.
+        let carol_keypair = Keypair::new();
+        let carol_pubkey = carol_keypair.pubkey();
+        alice_client.transfer(1, &carol_pubkey).unwrap();
+        let instruction =
+            BudgetInstruction::new_apply_block_height(&carol_pubkey, &budget_pubkey, &bob_pubkey);
+        alice_client
+            .send_instruction(&carol_keypair, instruction)
+            .unwrap();
+        assert_eq!(bank.get_balance(&budget_pubkey), 1);
+        assert_eq!(bank.get_balance(&bob_pubkey), 0);
+        let contract_account = bank.get_account(&budget_pubkey).unwrap();
+        let budget_state = BudgetState::deserialize(&contract_account.data).unwrap();
+        assert!(budget_state.is_pending());
+
+        alice_client.advance_slot();
+        assert!(bank.tick_height() >= tick_height);
+        let instruction =
+            BudgetInstruction::new_apply_block_height(&alice_pubkey, &budget_pubkey, &bob_pubkey);
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&alice_pubkey), 1);
+        assert_eq!(bank.get_balance(&bob_pubkey), 1);
+        assert_eq!(bank.get_account(&budget_pubkey), None);
+    }
+
+    #[test]
+    fn test_pay_on_past_block_height() {
+        let (bank, mint_keypair) = create_bank(2);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+
+        // A block height that has already passed releases the payment immediately
+        let script = BudgetScript::pay_on_block_height(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            bank.tick_height(),
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+        assert_eq!(bank.get_balance(&alice_pubkey), 1);
+        assert_eq!(bank.get_balance(&budget_pubkey), 0);
+        assert_eq!(bank.get_balance(&bob_pubkey), 1);
+    }
+
+    #[test]
+    fn test_pay_on_past_block_height_checks_accounts() {
+        let bob_pubkey = Keypair::new().pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let expr = BudgetExpr::new_block_height_payment(0, 1, &bob_pubkey);
+        let data = serialize(&BudgetInstruction::InitializeAccount(expr.clone())).unwrap();
+
+        // The released payment may only go to its destination
+        let mallory_pubkey = Keypair::new().pubkey();
+        let mut mallory_account = Account::new(0, 0, &Pubkey::default());
+        let mut budget_account = Account::new(1, BudgetState::size_of(&expr) as usize, &id());
+        let mut keyed_accounts = [
+            KeyedAccount::new(&mallory_pubkey, false, &mut mallory_account),
+            KeyedAccount::new(&budget_pubkey, false, &mut budget_account),
+        ];
+        assert_eq!(
+            process_instruction(&id(), &mut keyed_accounts, &data, 0),
+            Err(InstructionError::CustomError(
+                serialize(&BudgetError::DestinationMissing).unwrap()
+            ))
+        );
+        assert_eq!(mallory_account.lamports, 0);
+        assert_eq!(budget_account.lamports, 1);
+
+        // An existing contract keeps its lamports
+        BudgetState::new(expr.clone())
+            .serialize(&mut budget_account.data)
+            .unwrap();
+        let mut bob_account = Account::new(0, 0, &Pubkey::default());
+        let mut keyed_accounts = [
+            KeyedAccount::new(&bob_pubkey, false, &mut bob_account),
+            KeyedAccount::new(&budget_pubkey, false, &mut budget_account),
+        ];
+        assert_eq!(
+            process_instruction(&id(), &mut keyed_accounts, &data, 0),
+            Err(InstructionError::AccountAlreadyInitialized)
+        );
+        assert_eq!(bob_account.lamports, 0);
+        assert_eq!(budget_account.lamports, 1);
+    }
+
+    #[test]
+    fn test_payment_unless_event_refunds() {
+        let (bank, mint_keypair) = create_bank(2);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let event_dt = Utc::now();
+        let deadline = event_dt + Duration::days(1);
+
+        // Pay bob at the deadline unless alice witnesses the event first
+        let expr = BudgetExpr::new_payment_unless(
+            Condition::Timestamp(event_dt, alice_pubkey),
+            Condition::Timestamp(deadline, alice_pubkey),
+            1,
+            &bob_pubkey,
+            &alice_pubkey,
+        );
+        let script = BudgetScript::new_account(&alice_pubkey, &budget_pubkey, 1, expr);
+        alice_client.process_script(script).unwrap();
+        assert_eq!(bank.get_balance(&alice_pubkey), 1);
+        assert_eq!(bank.get_balance(&budget_pubkey), 1);
+
+        // The event comes first, so the lamports go back to alice
+        let instruction = BudgetInstruction::new_apply_timestamp(
+            &alice_pubkey,
+            &budget_pubkey,
+            &alice_pubkey,
+            event_dt,
+        );
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&alice_pubkey), 2);
+        assert_eq!(bank.get_balance(&budget_pubkey), 0);
+        assert_eq!(bank.get_balance(&bob_pubkey), 0);
+        assert_eq!(bank.get_account(&budget_pubkey), None);
+    }
+
+    #[test]
+    fn test_pay_on_schedule() {
+        let (bank, mint_keypair) = create_bank(10_000);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let start = Utc::now();
+        let interval = Duration::days(30);
+
+        // Alice is the timestamp authority for a 3-tranche schedule
+        let script = BudgetScript::pay_on_schedule(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            start,
+            interval,
+            3,
+            &alice_pubkey,
+            100,
+        );
+        alice_client.process_script(script).unwrap();
+        assert_eq!(bank.get_balance(&budget_pubkey), 100);
+
+        // A timestamp between the second and third unlock releases two tranches at once
+        let instruction = BudgetInstruction::new_apply_timestamp(
+            &alice_pubkey,
+            &budget_pubkey,
+            &bob_pubkey,
+            start + interval + Duration::days(1),
+        );
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&bob_pubkey), 66);
+        assert_eq!(bank.get_balance(&budget_pubkey), 34);
+
+        let account = bank.get_account(&budget_pubkey).unwrap();
+        let budget_state = BudgetState::deserialize(&account.data).unwrap();
+        assert!(budget_state.is_pending());
+
+        // Replaying an earlier timestamp releases nothing more
+        let instruction = BudgetInstruction::new_apply_timestamp(
+            &alice_pubkey,
+            &budget_pubkey,
+            &bob_pubkey,
+            start,
+        );
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&bob_pubkey), 66);
+
+        // The last tranche completes the contract
+        let instruction = BudgetInstruction::new_apply_timestamp(
+            &alice_pubkey,
+            &budget_pubkey,
+            &bob_pubkey,
+            start + interval * 2,
+        );
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&bob_pubkey), 100);
+        assert_eq!(bank.get_balance(&budget_pubkey), 0);
+    }
+
     #[test]
     fn test_cancel_payment() {
         let (bank, mint_keypair) = create_bank(3);
@@ -352,8 +749,16 @@ mod test {
 
         let instruction =
             BudgetInstruction::new_apply_signature(&mallory_pubkey, &budget_pubkey, &bob_pubkey);
-        mallory_client.process_instruction(instruction).unwrap();
-        // nothing should be changed because apply witness didn't finalize a payment
+        assert_eq!(
+            mallory_client.process_instruction(instruction).unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::CustomError(
+                    serialize(&BudgetError::UnauthorizedSignature).unwrap()
+                )
+            )
+        );
+        // nothing should be changed because mallory isn't a witness
         assert_eq!(bank.get_balance(&alice_pubkey), 1);
         assert_eq!(bank.get_balance(&budget_pubkey), 1);
         assert_eq!(bank.get_account(&bob_pubkey), None);
@@ -366,4 +771,236 @@ mod test {
         assert_eq!(bank.get_account(&budget_pubkey), None);
         assert_eq!(bank.get_account(&bob_pubkey), None);
     }
+
+    #[test]
+    fn test_signature_applied_once() {
+        let (bank, mint_keypair) = create_bank(10_000);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let witness_client = BankClient::new(&bank, Keypair::new());
+        let witness_pubkey = witness_client.pubkey();
+        alice_client.transfer(1, &witness_pubkey).unwrap();
+
+        // Both signatures the contract waits on are from the same signer
+        let expr =
+            BudgetExpr::new_2_2_multisig_payment(&witness_pubkey, &witness_pubkey, 1, &bob_pubkey);
+        let script = BudgetScript::new_account(&alice_pubkey, &budget_pubkey, 1, expr);
+        alice_client.process_script(script).unwrap();
+
+        let instruction =
+            BudgetInstruction::new_apply_signature(&witness_pubkey, &budget_pubkey, &bob_pubkey);
+        witness_client.process_instruction(instruction).unwrap();
+        let contract_account = bank.get_account(&budget_pubkey).unwrap();
+        let budget_state = BudgetState::deserialize(&contract_account.data).unwrap();
+        assert_eq!(budget_state.witnessed_signers.len(), 1);
+        assert!(budget_state.is_witnessed_by(&witness_pubkey));
+        assert!(budget_state.is_pending());
+
+        // The second witness lands in the same slot, and must still see the first
+        let instruction =
+            BudgetInstruction::new_apply_signature(&witness_pubkey, &budget_pubkey, &alice_pubkey);
+        assert_eq!(
+            witness_client.process_instruction(instruction).unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::CustomError(
+                    serialize(&BudgetError::SignatureAlreadyApplied).unwrap()
+                )
+            )
+        );
+        let contract_account = bank.get_account(&budget_pubkey).unwrap();
+        let budget_state = BudgetState::deserialize(&contract_account.data).unwrap();
+        assert_eq!(budget_state.witnessed_signers.len(), 1);
+        assert!(budget_state.is_pending());
+        assert_eq!(bank.get_balance(&budget_pubkey), 1);
+        assert_eq!(bank.get_account(&bob_pubkey), None);
+    }
+
+    #[test]
+    fn test_add_funds() {
+        let (bank, mint_keypair) = create_bank(4);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let dt = Utc::now();
+        let script = BudgetScript::pay_on_date(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            dt,
+            &alice_pubkey,
+            None,
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+
+        // Top up the pending contract
+        let script = BudgetScript::add_funds(&alice_pubkey, &budget_pubkey, 2);
+        alice_client.process_script(script).unwrap();
+        assert_eq!(bank.get_balance(&alice_pubkey), 1);
+        assert_eq!(bank.get_balance(&budget_pubkey), 3);
+        let contract_account = bank.get_account(&budget_pubkey).unwrap();
+        let budget_state = BudgetState::deserialize(&contract_account.data).unwrap();
+        assert!(budget_state.pending_budget.unwrap().verify(3));
+
+        // The payment includes the added funds
+        let instruction =
+            BudgetInstruction::new_apply_timestamp(&alice_pubkey, &budget_pubkey, &bob_pubkey, dt);
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&budget_pubkey), 0);
+        assert_eq!(bank.get_balance(&bob_pubkey), 3);
+    }
+
+    #[test]
+    fn test_add_funds_without_lamports() {
+        let (bank, mint_keypair) = create_bank(2);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let script = BudgetScript::pay_on_signature(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            &alice_pubkey,
+            None,
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+
+        // Raising the payment without moving lamports into the contract is rejected
+        let instruction = BudgetInstruction::new_add_funds(&alice_pubkey, &budget_pubkey, 1);
+        assert_eq!(
+            alice_client.process_instruction(instruction).unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        );
+        let contract_account = bank.get_account(&budget_pubkey).unwrap();
+        let budget_state = BudgetState::deserialize(&contract_account.data).unwrap();
+        assert!(budget_state.pending_budget.unwrap().verify(1));
+    }
+
+    #[test]
+    fn test_add_funds_to_completed_contract() {
+        let (bank, mint_keypair) = create_bank(3);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let script = BudgetScript::pay_on_signature(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            &alice_pubkey,
+            None,
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+        let instruction =
+            BudgetInstruction::new_apply_signature(&alice_pubkey, &budget_pubkey, &bob_pubkey);
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&bob_pubkey), 1);
+
+        let script = BudgetScript::add_funds(&alice_pubkey, &budget_pubkey, 1);
+        assert_eq!(
+            alice_client.process_script(script),
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::CustomError(serialize(&BudgetError::ContractNotPending).unwrap())
+            ))
+        );
+        assert_eq!(bank.get_balance(&alice_pubkey), 2);
+        assert_eq!(bank.get_balance(&budget_pubkey), 0);
+        assert_eq!(bank.get_balance(&bob_pubkey), 1);
+    }
+
+    #[test]
+    fn test_redirect_refund() {
+        let (bank, mint_keypair) = create_bank(3);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let carol_pubkey = Keypair::new().pubkey();
+        let script = BudgetScript::pay_on_date(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            Utc::now(),
+            &alice_pubkey,
+            Some(alice_pubkey),
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+
+        // The cancel authority sends the refund to carol instead of itself
+        let instruction =
+            BudgetInstruction::new_apply_signature(&alice_pubkey, &budget_pubkey, &carol_pubkey);
+        alice_client.process_instruction(instruction).unwrap();
+        assert_eq!(bank.get_balance(&alice_pubkey), 2);
+        assert_eq!(bank.get_balance(&carol_pubkey), 1);
+        assert_eq!(bank.get_account(&budget_pubkey), None);
+        assert_eq!(bank.get_account(&bob_pubkey), None);
+    }
+
+    #[test]
+    fn test_redirect_payment_to_other() {
+        let (bank, mint_keypair) = create_bank(3);
+        let alice_client = BankClient::new(&bank, mint_keypair);
+        let alice_pubkey = alice_client.pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let carol_pubkey = Keypair::new().pubkey();
+        let script = BudgetScript::pay_on_signature(
+            &alice_pubkey,
+            &bob_pubkey,
+            &budget_pubkey,
+            &alice_pubkey,
+            None,
+            1,
+        );
+        alice_client.process_script(script).unwrap();
+
+        // A witness releasing a payment to someone else can't redirect it
+        let instruction =
+            BudgetInstruction::new_apply_signature(&alice_pubkey, &budget_pubkey, &carol_pubkey);
+        assert_eq!(
+            alice_client.process_instruction(instruction).unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::CustomError(serialize(&BudgetError::DestinationMissing).unwrap())
+            )
+        );
+        assert_eq!(bank.get_balance(&budget_pubkey), 1);
+        assert_eq!(bank.get_account(&carol_pubkey), None);
+    }
+
+    #[test]
+    fn test_initialize_oversized_expression() {
+        // An expression this deep won't fit in a packet, so call the
+        // processor directly rather than going through a transaction.
+        let alice_pubkey = Keypair::new().pubkey();
+        let budget_pubkey = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let expr = (0..MAX_BUDGET_EXPR_DEPTH)
+            .fold(BudgetExpr::new_payment(1, &bob_pubkey), |expr, _| {
+                BudgetExpr::After(Condition::Signature(alice_pubkey), Box::new(expr))
+            });
+        let mut alice_account = Account::new(1, 0, &Pubkey::default());
+        let mut budget_account = Account::new(1, BudgetState::size_of(&expr) as usize, &id());
+        let mut keyed_accounts = [
+            KeyedAccount::new(&alice_pubkey, true, &mut alice_account),
+            KeyedAccount::new(&budget_pubkey, false, &mut budget_account),
+        ];
+        let data = serialize(&BudgetInstruction::InitializeAccount(expr)).unwrap();
+        assert_eq!(
+            process_instruction(&id(), &mut keyed_accounts, &data, 0),
+            Err(InstructionError::CustomError(
+                serialize(&BudgetError::ExpressionTooLarge).unwrap()
+            ))
+        );
+        assert_eq!(budget_account.data, vec![0; budget_account.data.len()]);
+    }
 }
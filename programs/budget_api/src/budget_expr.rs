@@ -4,10 +4,18 @@
 //! `Payment`, the payment is executed.
 
 use crate::payment_plan::{Payment, Witness};
+use bincode;
 use chrono::prelude::*;
+use chrono::Duration;
 use serde_derive::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
-use std::mem;
+use std::{cmp, mem};
+
+/// The deepest nesting of conditions a contract's budget may have
+pub const MAX_BUDGET_EXPR_DEPTH: usize = 8;
+
+/// The most bytes a contract's serialized budget may occupy
+pub const MAX_BUDGET_EXPR_SIZE: u64 = 1024;
 
 /// A data type representing a `Witness` that the payment plan is waiting on.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -17,6 +25,10 @@ pub enum Condition {
 
     /// Wait for a `Signature` `Witness` from `Pubkey`.
     Signature(Pubkey),
+
+    /// Wait for the bank to reach the given tick height. Unlike `Timestamp`, this
+    /// doesn't depend on a trusted witness.
+    BlockHeight(u64),
 }
 
 impl Condition {
@@ -27,9 +39,38 @@ impl Condition {
             (Condition::Timestamp(dt, pubkey), Witness::Timestamp(last_time)) => {
                 pubkey == from && dt <= last_time
             }
+            (Condition::BlockHeight(tick_height), Witness::BlockHeight(current_tick_height)) => {
+                tick_height <= current_tick_height
+            }
             _ => false,
         }
     }
+
+    /// Return the pubkey this Condition accepts the given kind of Witness from, if any.
+    pub fn witness_pubkey(&self, witness: &Witness) -> Option<&Pubkey> {
+        match (self, witness) {
+            (Condition::Signature(pubkey), Witness::Signature) => Some(pubkey),
+            (Condition::Timestamp(_, pubkey), Witness::Timestamp(_)) => Some(pubkey),
+            _ => None,
+        }
+    }
+
+    /// Return true if the Condition is met at a known point in time, so that a Witness can
+    /// confirm it has passed.
+    pub fn is_deadline(&self) -> bool {
+        match self {
+            Condition::Timestamp(..) | Condition::BlockHeight(_) => true,
+            Condition::Signature(_) => false,
+        }
+    }
+}
+
+/// One installment of a vesting budget
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Tranche {
+    /// The earliest timestamp that releases the installment
+    pub unlock: DateTime<Utc>,
+    pub lamports: u64,
 }
 
 /// A data type representing a payment plan.
@@ -48,6 +89,16 @@ pub enum BudgetExpr {
 
     /// Make a payment after both of two conditions are satisfied
     And(Condition, Condition, Box<BudgetExpr>),
+
+    /// Pay each tranche to the second `Pubkey` once the first witnesses a timestamp at or
+    /// after the tranche's unlock time. Unlike the other budgets, this makes many payments.
+    Vesting(Pubkey, Pubkey, Vec<Tranche>),
+
+    /// Invert an `And(event, deadline, expr)`: make the payment once the deadline is
+    /// satisfied, unless the event was satisfied first, in which case make the second
+    /// payment instead. Only a child whose second condition is a deadline may be negated,
+    /// so that the budget always resolves.
+    Not(Box<BudgetExpr>, Box<BudgetExpr>),
 }
 
 impl BudgetExpr {
@@ -133,6 +184,56 @@ impl BudgetExpr {
         )
     }
 
+    /// Create a budget that pays `lamports` to `to` once `deadline` is satisfied unless
+    /// `event` is satisfied first, in which case it refunds them to `from`.
+    pub fn new_payment_unless(
+        event: Condition,
+        deadline: Condition,
+        lamports: u64,
+        to: &Pubkey,
+        from: &Pubkey,
+    ) -> Self {
+        BudgetExpr::Not(
+            Box::new(BudgetExpr::And(
+                event,
+                deadline,
+                Box::new(Self::new_payment(lamports, to)),
+            )),
+            Box::new(Self::new_payment(lamports, from)),
+        )
+    }
+
+    /// Create a budget that pays `lamports` to `to` in `count` tranches, the `k`th unlocking
+    /// at `start + k * interval` as witnessed by `dt_pubkey`. Lamports that don't divide
+    /// evenly go to the last tranche.
+    pub fn new_vesting_payment(
+        start: DateTime<Utc>,
+        interval: Duration,
+        count: u32,
+        dt_pubkey: &Pubkey,
+        lamports: u64,
+        to: &Pubkey,
+    ) -> Self {
+        assert!(count > 0, "a vesting budget needs at least one tranche");
+        let share = lamports / u64::from(count);
+        let mut tranches: Vec<_> = (0..count)
+            .map(|k| Tranche {
+                unlock: start + interval * k as i32,
+                lamports: share,
+            })
+            .collect();
+        tranches.last_mut().unwrap().lamports += lamports % u64::from(count);
+        BudgetExpr::Vesting(*dt_pubkey, *to, tranches)
+    }
+
+    /// Create a budget that pays `lamports` to `to` once the bank reaches `tick_height`.
+    pub fn new_block_height_payment(tick_height: u64, lamports: u64, to: &Pubkey) -> Self {
+        BudgetExpr::After(
+            Condition::BlockHeight(tick_height),
+            Box::new(Self::new_payment(lamports, to)),
+        )
+    }
+
     /// Return Payment if the budget requires no additional Witnesses.
     pub fn final_payment(&self) -> Option<Payment> {
         match self {
@@ -141,6 +242,17 @@ impl BudgetExpr {
         }
     }
 
+    /// Return the Payment if the budget only waits on the block height.
+    pub fn block_height_payment(&self) -> Option<&Payment> {
+        match self {
+            BudgetExpr::After(Condition::BlockHeight(_), sub_expr) => match sub_expr.as_ref() {
+                BudgetExpr::Pay(payment) => Some(payment),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Return true if the budget spends exactly `spendable_lamports`.
     pub fn verify(&self, spendable_lamports: u64) -> bool {
         match self {
@@ -151,9 +263,114 @@ impl BudgetExpr {
             BudgetExpr::Or(a, b) => {
                 a.1.verify(spendable_lamports) && b.1.verify(spendable_lamports)
             }
+            BudgetExpr::Vesting(_, _, tranches) => {
+                !tranches.is_empty()
+                    && tranches
+                        .iter()
+                        .try_fold(0u64, |sum, tranche| sum.checked_add(tranche.lamports))
+                        == Some(spendable_lamports)
+            }
+            BudgetExpr::Not(sub_expr, refund) => match sub_expr.as_ref() {
+                BudgetExpr::And(_, deadline, sub_expr) if deadline.is_deadline() => {
+                    sub_expr.verify(spendable_lamports) && refund.verify(spendable_lamports)
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// Return the number of bytes the budget occupies when serialized.
+    pub fn serialized_size(&self) -> u64 {
+        bincode::serialized_size(self).unwrap()
+    }
+
+    /// Return the number of nested expressions down to the budget's deepest payment.
+    pub fn depth(&self) -> usize {
+        match self {
+            BudgetExpr::Pay(_) | BudgetExpr::Vesting(..) => 1,
+            BudgetExpr::After(_, sub_expr) | BudgetExpr::And(_, _, sub_expr) => {
+                1 + sub_expr.depth()
+            }
+            BudgetExpr::Or(a, b) => 1 + cmp::max(a.1.depth(), b.1.depth()),
+            BudgetExpr::Not(sub_expr, refund) => 1 + cmp::max(sub_expr.depth(), refund.depth()),
+        }
+    }
+
+    /// Return the number of signature conditions anywhere in the budget, which bounds how
+    /// many distinct signers may witness it.
+    pub fn signature_conditions(&self) -> usize {
+        let count = |cond: &Condition| match cond {
+            Condition::Signature(_) => 1,
+            _ => 0,
+        };
+        match self {
+            BudgetExpr::Pay(_) | BudgetExpr::Vesting(..) => 0,
+            BudgetExpr::After(cond, sub_expr) => count(cond) + sub_expr.signature_conditions(),
+            BudgetExpr::Or((cond0, sub_expr0), (cond1, sub_expr1)) => {
+                count(cond0)
+                    + count(cond1)
+                    + sub_expr0.signature_conditions()
+                    + sub_expr1.signature_conditions()
+            }
+            BudgetExpr::And(cond0, cond1, sub_expr) => {
+                count(cond0) + count(cond1) + sub_expr.signature_conditions()
+            }
+            BudgetExpr::Not(sub_expr, refund) => {
+                sub_expr.signature_conditions() + refund.signature_conditions()
+            }
         }
     }
 
+    /// Return true if a contract may hold the budget.
+    pub fn is_within_limits(&self) -> bool {
+        self.depth() <= MAX_BUDGET_EXPR_DEPTH && self.serialized_size() <= MAX_BUDGET_EXPR_SIZE
+    }
+
+    /// Add `lamports` to every payment the budget may make.
+    pub fn add_lamports(&mut self, lamports: u64) {
+        match self {
+            BudgetExpr::Pay(payment) => payment.lamports += lamports,
+            BudgetExpr::After(_, sub_expr) | BudgetExpr::And(_, _, sub_expr) => {
+                sub_expr.add_lamports(lamports)
+            }
+            BudgetExpr::Or(a, b) => {
+                a.1.add_lamports(lamports);
+                b.1.add_lamports(lamports);
+            }
+            BudgetExpr::Not(sub_expr, refund) => {
+                sub_expr.add_lamports(lamports);
+                refund.add_lamports(lamports);
+            }
+            BudgetExpr::Vesting(_, _, tranches) => {
+                if let Some(tranche) = tranches.last_mut() {
+                    tranche.lamports += lamports;
+                }
+            }
+        }
+    }
+
+    /// Return the pubkeys the budget accepts the given kind of Witness from.
+    pub fn witness_pubkeys(&self, witness: &Witness) -> Vec<&Pubkey> {
+        let conditions = match self {
+            BudgetExpr::Pay(_) => vec![],
+            BudgetExpr::After(cond, _) => vec![cond],
+            BudgetExpr::Or((cond0, _), (cond1, _)) | BudgetExpr::And(cond0, cond1, _) => {
+                vec![cond0, cond1]
+            }
+            BudgetExpr::Vesting(dt_pubkey, _, _) => {
+                return match witness {
+                    Witness::Timestamp(_) => vec![dt_pubkey],
+                    _ => vec![],
+                };
+            }
+            BudgetExpr::Not(sub_expr, _) => return sub_expr.witness_pubkeys(witness),
+        };
+        conditions
+            .into_iter()
+            .filter_map(|cond| cond.witness_pubkey(witness))
+            .collect()
+    }
+
     /// Apply a witness to the budget to see if the budget can be reduced.
     /// If so, modify the budget in-place.
     pub fn apply_witness(&mut self, witness: &Witness, from: &Pubkey) {
@@ -176,12 +393,52 @@ impl BudgetExpr {
                     None
                 }
             }
+            BudgetExpr::Not(sub_expr, refund) => match sub_expr.as_ref() {
+                // The event beat the deadline, so the refund is made instead
+                BudgetExpr::And(event, _, _) if event.is_satisfied(witness, from) => {
+                    Some(refund.clone())
+                }
+                BudgetExpr::And(_, deadline, sub_expr) if deadline.is_satisfied(witness, from) => {
+                    Some(sub_expr.clone())
+                }
+                _ => None,
+            },
             _ => None,
         };
         if let Some(expr) = new_expr {
             mem::replace(self, *expr);
         }
     }
+
+    /// Remove the tranches of a vesting budget that the given Witness unlocks and return
+    /// one Payment for all of them.
+    pub fn release_tranches(&mut self, witness: &Witness, from: &Pubkey) -> Option<Payment> {
+        match self {
+            BudgetExpr::Vesting(dt_pubkey, to, tranches) => {
+                let (due, pending): (Vec<_>, Vec<_>) = tranches.drain(..).partition(|tranche| {
+                    Condition::Timestamp(tranche.unlock, *dt_pubkey).is_satisfied(witness, from)
+                });
+                *tranches = pending;
+                if due.is_empty() {
+                    None
+                } else {
+                    Some(Payment {
+                        lamports: due.iter().map(|tranche| tranche.lamports).sum(),
+                        to: *to,
+                    })
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Return true if the budget is a vesting budget with no tranches left to release.
+    pub fn is_fully_vested(&self) -> bool {
+        match self {
+            BudgetExpr::Vesting(_, _, tranches) => tranches.is_empty(),
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -205,6 +462,69 @@ mod tests {
         assert!(!Condition::Timestamp(dt2, from).is_satisfied(&Witness::Timestamp(dt1), &from));
     }
 
+    #[test]
+    fn test_block_height_satisfied() {
+        let from = Pubkey::default();
+        let cond = Condition::BlockHeight(5);
+        assert!(!cond.is_satisfied(&Witness::BlockHeight(4), &from));
+        assert!(cond.is_satisfied(&Witness::BlockHeight(5), &from));
+        assert!(cond.is_satisfied(&Witness::BlockHeight(6), &from));
+        assert!(!cond.is_satisfied(&Witness::Signature, &from));
+        assert_eq!(cond.witness_pubkey(&Witness::BlockHeight(5)), None);
+    }
+
+    #[test]
+    fn test_block_height_payment() {
+        let from = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+
+        let mut expr = BudgetExpr::new_block_height_payment(5, 42, &to);
+        assert!(expr.verify(42));
+        expr.apply_witness(&Witness::BlockHeight(4), &from);
+        assert_eq!(expr, BudgetExpr::new_block_height_payment(5, 42, &to));
+        expr.apply_witness(&Witness::BlockHeight(5), &from);
+        assert_eq!(expr, BudgetExpr::new_payment(42, &to));
+    }
+
+    #[test]
+    fn test_add_lamports() {
+        let dt = Utc.ymd(2014, 11, 14).and_hms(8, 9, 10);
+        let from = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+
+        let mut expr = BudgetExpr::new_cancelable_future_payment(dt, &from, 42, &to, &from);
+        expr.add_lamports(8);
+        assert_eq!(
+            expr,
+            BudgetExpr::new_cancelable_future_payment(dt, &from, 50, &to, &from)
+        );
+        assert!(expr.verify(50));
+    }
+
+    #[test]
+    fn test_limits() {
+        let witness = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let nest = |depth| {
+            (1..depth).fold(BudgetExpr::new_payment(1, &to), |expr, _| {
+                BudgetExpr::After(Condition::Signature(witness), Box::new(expr))
+            })
+        };
+
+        let expr = nest(MAX_BUDGET_EXPR_DEPTH);
+        assert_eq!(expr.depth(), MAX_BUDGET_EXPR_DEPTH);
+        assert!(expr.is_within_limits());
+        assert!(!nest(MAX_BUDGET_EXPR_DEPTH + 1).is_within_limits());
+
+        let expr =
+            BudgetExpr::new_cancelable_future_payment(Utc::now(), &witness, 1, &to, &witness);
+        assert_eq!(expr.depth(), 2);
+        assert_eq!(
+            expr.serialized_size(),
+            bincode::serialize(&expr).unwrap().len() as u64
+        );
+    }
+
     #[test]
     fn test_verify() {
         let dt = Utc.ymd(2014, 11, 14).and_hms(8, 9, 10);
@@ -216,6 +536,48 @@ mod tests {
         assert!(BudgetExpr::new_cancelable_future_payment(dt, &from, 42, &to, &from).verify(42));
     }
 
+    #[test]
+    fn test_vesting_payment() {
+        let start = Utc.ymd(2014, 11, 14).and_hms(8, 9, 10);
+        let interval = Duration::days(30);
+        let from = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+
+        let mut expr = BudgetExpr::new_vesting_payment(start, interval, 3, &from, 100, &to);
+        match &expr {
+            BudgetExpr::Vesting(_, _, tranches) => {
+                let lamports: Vec<_> = tranches.iter().map(|tranche| tranche.lamports).collect();
+                assert_eq!(lamports, vec![33, 33, 34]);
+                assert_eq!(tranches[2].unlock, start + Duration::days(60));
+            }
+            _ => panic!("not a vesting budget"),
+        }
+        assert!(expr.verify(100));
+        assert!(!expr.verify(99));
+        assert_eq!(
+            expr.witness_pubkeys(&Witness::Timestamp(start)),
+            vec![&from]
+        );
+
+        // Only the timestamp authority releases tranches, and only the ones that are due
+        let witness = Witness::Timestamp(start + Duration::days(31));
+        assert_eq!(expr.release_tranches(&witness, &to), None);
+        assert_eq!(
+            expr.release_tranches(&witness, &from),
+            Some(Payment { lamports: 66, to })
+        );
+        assert!(!expr.is_fully_vested());
+        assert!(expr.verify(34));
+        assert_eq!(expr.release_tranches(&witness, &from), None);
+
+        let witness = Witness::Timestamp(start + interval * 2);
+        assert_eq!(
+            expr.release_tranches(&witness, &from),
+            Some(Payment { lamports: 34, to })
+        );
+        assert!(expr.is_fully_vested());
+    }
+
     #[test]
     fn test_authorized_payment() {
         let from = Pubkey::default();
@@ -251,6 +613,31 @@ mod tests {
         assert_eq!(expr, orig_expr);
     }
 
+    #[test]
+    fn test_witness_pubkeys() {
+        let dt = Utc.ymd(2014, 11, 14).and_hms(8, 9, 10);
+        let from = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let dt_pubkey = Keypair::new().pubkey();
+
+        let expr = BudgetExpr::new_cancelable_future_payment(dt, &dt_pubkey, 42, &to, &from);
+        assert_eq!(
+            expr.witness_pubkeys(&Witness::Timestamp(dt)),
+            vec![&dt_pubkey]
+        );
+        assert_eq!(expr.witness_pubkeys(&Witness::Signature), vec![&from]);
+
+        let expr = BudgetExpr::new_2_2_multisig_payment(&from, &dt_pubkey, 42, &to);
+        assert_eq!(
+            expr.witness_pubkeys(&Witness::Signature),
+            vec![&from, &dt_pubkey]
+        );
+        assert!(expr.witness_pubkeys(&Witness::Timestamp(dt)).is_empty());
+
+        let expr = BudgetExpr::new_payment(42, &to);
+        assert!(expr.witness_pubkeys(&Witness::Signature).is_empty());
+    }
+
     #[test]
     fn test_cancelable_future_payment() {
         let dt = Utc.ymd(2014, 11, 14).and_hms(8, 9, 10);
@@ -265,6 +652,75 @@ mod tests {
         expr.apply_witness(&Witness::Signature, &from);
         assert_eq!(expr, BudgetExpr::new_payment(42, &from));
     }
+    #[test]
+    fn test_payment_unless_timestamp() {
+        let event_dt = Utc.ymd(2014, 11, 14).and_hms(8, 9, 10);
+        let deadline = Utc.ymd(2014, 11, 15).and_hms(8, 9, 10);
+        let oracle = Keypair::new().pubkey();
+        let dt_pubkey = Keypair::new().pubkey();
+        let from = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+
+        let new_expr = || {
+            BudgetExpr::new_payment_unless(
+                Condition::Timestamp(event_dt, oracle),
+                Condition::Timestamp(deadline, dt_pubkey),
+                42,
+                &to,
+                &from,
+            )
+        };
+
+        // Nothing resolves before the deadline
+        let mut expr = new_expr();
+        assert!(expr.verify(42));
+        expr.apply_witness(&Witness::Timestamp(event_dt), &dt_pubkey);
+        assert_eq!(expr, new_expr());
+
+        // The deadline confirms the event didn't happen
+        expr.apply_witness(&Witness::Timestamp(deadline), &dt_pubkey);
+        assert_eq!(expr, BudgetExpr::new_payment(42, &to));
+
+        // The event beat the deadline, so the lamports go back to `from`
+        let mut expr = new_expr();
+        expr.apply_witness(&Witness::Timestamp(event_dt), &oracle);
+        assert_eq!(expr, BudgetExpr::new_payment(42, &from));
+        expr.apply_witness(&Witness::Timestamp(deadline), &dt_pubkey);
+        assert_eq!(expr, BudgetExpr::new_payment(42, &from));
+    }
+
+    #[test]
+    fn test_payment_unless_requires_deadline() {
+        let from = Keypair::new().pubkey();
+        let witness = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+
+        let expr = BudgetExpr::new_payment_unless(
+            Condition::Signature(from),
+            Condition::BlockHeight(5),
+            42,
+            &to,
+            &from,
+        );
+        assert!(expr.verify(42));
+        assert_eq!(expr.witness_pubkeys(&Witness::Signature), vec![&from]);
+
+        let expr = BudgetExpr::new_payment_unless(
+            Condition::Signature(from),
+            Condition::Signature(witness),
+            42,
+            &to,
+            &from,
+        );
+        assert!(!expr.verify(42));
+
+        let expr = BudgetExpr::Not(
+            Box::new(BudgetExpr::new_authorized_payment(&from, 42, &to)),
+            Box::new(BudgetExpr::new_payment(42, &from)),
+        );
+        assert!(!expr.verify(42));
+    }
+
     #[test]
     fn test_2_2_multisig_payment() {
         let from0 = Keypair::new().pubkey();
@@ -276,6 +732,22 @@ mod tests {
         assert_eq!(expr, BudgetExpr::new_authorized_payment(&from1, 42, &to));
     }
 
+    #[test]
+    fn test_signature_conditions() {
+        let from0 = Keypair::new().pubkey();
+        let from1 = Keypair::new().pubkey();
+        let dt = Utc.ymd(2014, 11, 11).and_hms(7, 7, 7);
+        let to = Pubkey::default();
+
+        assert_eq!(BudgetExpr::new_payment(42, &to).signature_conditions(), 0);
+        let expr = BudgetExpr::new_2_2_multisig_payment(&from0, &from1, 42, &to);
+        assert_eq!(expr.signature_conditions(), 2);
+        let expr = BudgetExpr::After(Condition::Timestamp(dt, from0), Box::new(expr));
+        assert_eq!(expr.signature_conditions(), 2);
+        let expr = BudgetExpr::new_cancelable_future_payment(dt, &from0, 42, &to, &from1);
+        assert_eq!(expr.signature_conditions(), 1);
+    }
+
     #[test]
     fn test_multisig_after_sig() {
         let from0 = Keypair::new().pubkey();
@@ -25,6 +25,14 @@ pub enum BudgetInstruction {
     /// Tell the budget that the `InitializeAccount` with `Signature` has been
     /// signed by the containing transaction's `Pubkey`.
     ApplySignature,
+
+    /// Tell the budget to check its block height conditions against the bank's
+    /// current tick height. Anyone may send this instruction.
+    ApplyBlockHeight,
+
+    /// Add the given number of lamports to each payment of a pending contract.
+    /// The contract account must already hold the additional lamports.
+    AddFunds(u64),
 }
 
 impl BudgetInstruction {
@@ -32,6 +40,9 @@ impl BudgetInstruction {
         let mut keys = vec![];
         if let BudgetExpr::Pay(payment) = &expr {
             keys.push((payment.to, false));
+        } else if let Some(payment) = expr.block_height_payment() {
+            // The condition may already be satisfied by the time the account is initialized
+            keys.push((payment.to, false));
         }
         keys.push((*contract, false));
         Instruction::new(id(), &BudgetInstruction::InitializeAccount(expr), keys)
@@ -57,4 +68,17 @@ impl BudgetInstruction {
         }
         Instruction::new(id(), &BudgetInstruction::ApplySignature, keys)
     }
+
+    pub fn new_apply_block_height(from: &Pubkey, contract: &Pubkey, to: &Pubkey) -> Instruction {
+        let mut keys = vec![(*from, true), (*contract, false)];
+        if from != to {
+            keys.push((*to, false));
+        }
+        Instruction::new(id(), &BudgetInstruction::ApplyBlockHeight, keys)
+    }
+
+    pub fn new_add_funds(from: &Pubkey, contract: &Pubkey, lamports: u64) -> Instruction {
+        let keys = vec![(*from, true), (*contract, false)];
+        Instruction::new(id(), &BudgetInstruction::AddFunds(lamports), keys)
+    }
 }
@@ -2,8 +2,8 @@ use crate::budget_expr::BudgetExpr;
 use crate::budget_instruction::BudgetInstruction;
 use crate::budget_state::BudgetState;
 use crate::id;
-use bincode::serialized_size;
 use chrono::prelude::{DateTime, Utc};
+use chrono::Duration;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::script::Script;
 use solana_sdk::signature::{Keypair, KeypairUtil};
@@ -21,7 +21,7 @@ impl BudgetScript {
         if !expr.verify(lamports) {
             panic!("invalid budget expression");
         }
-        let space = serialized_size(&BudgetState::new(expr.clone())).unwrap();
+        let space = BudgetState::size_of(&expr);
         let instructions = vec![
             SystemInstruction::new_program_account(&from, contract, lamports, space, &id()),
             BudgetInstruction::new_initialize_account(contract, expr),
@@ -55,6 +55,18 @@ impl BudgetScript {
         Self::new_account(from, contract, lamports, expr)
     }
 
+    /// Create a script that pays `to` once the bank reaches `tick_height`.
+    pub fn pay_on_block_height(
+        from: &Pubkey,
+        to: &Pubkey,
+        contract: &Pubkey,
+        tick_height: u64,
+        lamports: u64,
+    ) -> Script {
+        let expr = BudgetExpr::new_block_height_payment(tick_height, lamports, to);
+        Self::new_account(from, contract, lamports, expr)
+    }
+
     /// Create a multisig payment script.
     pub fn pay_on_signature(
         from: &Pubkey,
@@ -72,6 +84,33 @@ impl BudgetScript {
 
         Self::new_account(from, contract, lamports, expr)
     }
+
+    /// Create a script that pays `to` in `count` tranches, one every `interval` from
+    /// `start`, as witnessed by `dt_pubkey`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_on_schedule(
+        from: &Pubkey,
+        to: &Pubkey,
+        contract: &Pubkey,
+        start: DateTime<Utc>,
+        interval: Duration,
+        count: u32,
+        dt_pubkey: &Pubkey,
+        lamports: u64,
+    ) -> Script {
+        let expr = BudgetExpr::new_vesting_payment(start, interval, count, dt_pubkey, lamports, to);
+        Self::new_account(from, contract, lamports, expr)
+    }
+
+    /// Create a script that moves `lamports` into a pending contract and adds
+    /// them to its payment.
+    pub fn add_funds(from: &Pubkey, contract: &Pubkey, lamports: u64) -> Script {
+        let instructions = vec![
+            SystemInstruction::new_move(from, contract, lamports),
+            BudgetInstruction::new_add_funds(from, contract, lamports),
+        ];
+        Script::new(instructions)
+    }
 }
 
 #[cfg(test)]
@@ -1,18 +1,38 @@
 //! budget state
 use crate::budget_expr::BudgetExpr;
-use bincode::{self, deserialize, serialize_into};
+use bincode::{self, deserialize, serialize, serialize_into};
 use serde_derive::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::transaction::InstructionError;
+use std::collections::BTreeSet;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum BudgetError {
     DestinationMissing,
+    /// The timestamp wasn't signed by a key the contract accepts timestamps from
+    UnauthorizedTimestamp,
+    /// The signature wasn't from a key the contract is waiting on
+    UnauthorizedSignature,
+    /// The contract was already paid or cancelled
+    ContractNotPending,
+    /// The budget is nested too deeply or too large for a contract to hold
+    ExpressionTooLarge,
+    /// The contract's state was written in a format this program doesn't read
+    UnsupportedStateVersion(u8),
+    /// The signer already witnessed the contract in an earlier transaction
+    SignatureAlreadyApplied,
 }
 
+/// The leading byte of every serialized `BudgetState`. Unversioned states began with
+/// their `initialized` flag, so versions 0 and 1 are never written.
+const BUDGET_STATE_VERSION: u8 = 3;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct BudgetState {
     pub initialized: bool,
     pub pending_budget: Option<BudgetExpr>,
+    /// The signers whose `ApplySignature` witnesses the contract already counted
+    pub witnessed_signers: BTreeSet<Pubkey>,
 }
 
 impl BudgetState {
@@ -20,6 +40,7 @@ impl BudgetState {
         Self {
             initialized: true,
             pending_budget: Some(budget_expr),
+            witnessed_signers: BTreeSet::new(),
         }
     }
 
@@ -27,20 +48,50 @@ impl BudgetState {
         self.pending_budget.is_some()
     }
 
+    /// Return the account space needed to hold a contract for `budget_expr`, including
+    /// room for every signer it may be witnessed by.
+    pub fn size_of(budget_expr: &BudgetExpr) -> u64 {
+        // The version byte, the `initialized` flag, the `Option` tag and the signer
+        // set's length
+        let signers_size = 32 * budget_expr.signature_conditions() as u64;
+        11 + budget_expr.serialized_size() + signers_size
+    }
+
+    /// Return true if `signer`'s signature was already counted toward the contract
+    pub fn is_witnessed_by(&self, signer: &Pubkey) -> bool {
+        self.witnessed_signers.contains(signer)
+    }
+
     pub fn serialize(&self, output: &mut [u8]) -> Result<(), InstructionError> {
-        serialize_into(output, self).map_err(|_| InstructionError::AccountDataTooSmall)
+        if output.is_empty() {
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        output[0] = BUDGET_STATE_VERSION;
+        serialize_into(&mut output[1..], self).map_err(|_| InstructionError::AccountDataTooSmall)
     }
 
     pub fn deserialize(input: &[u8]) -> Result<Self, InstructionError> {
-        deserialize(input).map_err(|_| InstructionError::InvalidAccountData)
+        match input.first() {
+            None => Err(InstructionError::InvalidAccountData),
+            // Zeroed data belongs to an account that was never initialized
+            Some(0) => Ok(Self::default()),
+            Some(&BUDGET_STATE_VERSION) => {
+                deserialize(&input[1..]).map_err(|_| InstructionError::InvalidAccountData)
+            }
+            Some(version) => Err(InstructionError::CustomError(
+                serialize(&BudgetError::UnsupportedStateVersion(*version)).unwrap(),
+            )),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::budget_expr::{Condition, MAX_BUDGET_EXPR_DEPTH, MAX_BUDGET_EXPR_SIZE};
     use crate::id;
-    use solana_sdk::account::Account;
+    use solana_sdk::account::{Account, MAX_PERMITTED_DATA_LENGTH};
+    use solana_sdk::signature::{Keypair, KeypairUtil};
 
     #[test]
     fn test_serializer() {
@@ -51,6 +102,61 @@ mod test {
         assert_eq!(b, c);
     }
 
+    #[test]
+    fn test_serializer_max_depth() {
+        let witness = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let expr = (1..MAX_BUDGET_EXPR_DEPTH).fold(BudgetExpr::new_payment(1, &to), |expr, _| {
+            BudgetExpr::After(Condition::Signature(witness), Box::new(expr))
+        });
+        assert!(expr.is_within_limits());
+        let mut a = Account::new(1, BudgetState::size_of(&expr) as usize, &id());
+        let b = BudgetState::new(expr);
+        b.serialize(&mut a.data).unwrap();
+        assert_eq!(BudgetState::deserialize(&a.data), Ok(b));
+    }
+
+    #[test]
+    fn test_largest_state_fits_data_cap() {
+        // Every signature condition serializes to more than the 32 bytes its signer adds
+        assert!(11 + 2 * MAX_BUDGET_EXPR_SIZE <= MAX_PERMITTED_DATA_LENGTH as u64);
+    }
+
+    #[test]
+    fn test_serializer_witnessed_signers() {
+        let from0 = Keypair::new().pubkey();
+        let from1 = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let expr = BudgetExpr::new_2_2_multisig_payment(&from0, &from1, 1, &to);
+        let mut a = Account::new(1, BudgetState::size_of(&expr) as usize, &id());
+        let mut b = BudgetState::new(expr);
+        b.witnessed_signers.insert(from0);
+        b.witnessed_signers.insert(from1);
+        b.serialize(&mut a.data).unwrap();
+        let c = BudgetState::deserialize(&a.data).unwrap();
+        assert!(c.is_witnessed_by(&from0));
+        assert!(c.is_witnessed_by(&from1));
+        assert!(!c.is_witnessed_by(&to));
+        assert_eq!(b, c);
+    }
+
+    #[test]
+    fn test_deserialize_unversioned() {
+        let to = Keypair::new().pubkey();
+        let b = BudgetState::new(BudgetExpr::new_payment(1, &to));
+        let data = bincode::serialize(&b).unwrap();
+        assert_eq!(
+            BudgetState::deserialize(&data),
+            Err(InstructionError::CustomError(
+                serialize(&BudgetError::UnsupportedStateVersion(1)).unwrap()
+            ))
+        );
+        assert_eq!(
+            BudgetState::deserialize(&[]),
+            Err(InstructionError::InvalidAccountData)
+        );
+    }
+
     #[test]
     fn test_serializer_data_too_small() {
         let mut a = Account::new(0, 1, &id());
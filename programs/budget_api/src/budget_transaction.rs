@@ -3,6 +3,7 @@
 use crate::budget_instruction::BudgetInstruction;
 use crate::budget_script::BudgetScript;
 use chrono::prelude::*;
+use chrono::Duration;
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::script::Script;
@@ -88,6 +89,32 @@ impl BudgetTransaction {
         Self::new_signed(from_keypair, script, recent_blockhash, 0)
     }
 
+    /// Create and sign a Transaction that pays `to` in `count` tranches on a schedule.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_on_schedule(
+        from_keypair: &Keypair,
+        to: &Pubkey,
+        contract: &Pubkey,
+        start: DateTime<Utc>,
+        interval: Duration,
+        count: u32,
+        dt_pubkey: &Pubkey,
+        lamports: u64,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        let script = BudgetScript::pay_on_schedule(
+            &from_keypair.pubkey(),
+            to,
+            contract,
+            start,
+            interval,
+            count,
+            dt_pubkey,
+            lamports,
+        );
+        Self::new_signed(from_keypair, script, recent_blockhash, 0)
+    }
+
     /// Create and sign a multisig Transaction.
     pub fn new_when_signed(
         from_keypair: &Keypair,
@@ -108,4 +135,15 @@ impl BudgetTransaction {
         );
         Self::new_signed(from_keypair, script, recent_blockhash, 0)
     }
+
+    /// Create and sign a Transaction that tops up a pending contract.
+    pub fn new_add_funds(
+        from_keypair: &Keypair,
+        contract: &Pubkey,
+        lamports: u64,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        let script = BudgetScript::add_funds(&from_keypair.pubkey(), contract, lamports);
+        Self::new_signed(from_keypair, script, recent_blockhash, 0)
+    }
 }
@@ -15,6 +15,9 @@ pub enum Witness {
 
     /// A signature from Pubkey.
     Signature,
+
+    /// The bank's current tick height.
+    BlockHeight(u64),
 }
 
 /// Some amount of lamports that should be sent to the `to` `Pubkey`.
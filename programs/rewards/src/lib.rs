@@ -1,9 +1,10 @@
 //! Rewards program
 //! Exchanges validation and storage proofs for lamports
 
-use bincode::deserialize;
+use bincode::{deserialize, serialize};
 use log::*;
 use solana_rewards_api::rewards_instruction::RewardsInstruction;
+use solana_rewards_api::rewards_state::{RewardsError, RewardsState};
 use solana_sdk::account::KeyedAccount;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::solana_entrypoint;
@@ -54,7 +55,17 @@ fn redeem_vote_credits(keyed_accounts: &mut [KeyedAccount]) -> Result<(), Instru
         Err(InstructionError::InvalidArgument)?;
     }
 
+    if !solana_rewards_api::check_id(&keyed_accounts[1].account.owner) {
+        error!("account[1] is not assigned to the REWARDS_PROGRAM");
+        Err(InstructionError::InvalidArgument)?;
+    }
+    RewardsState::deserialize(&keyed_accounts[1].account.data)?;
+
     let lamports = calc_vote_reward(vote_state.credits(), stake)?;
+    if keyed_accounts[1].account.lamports < lamports {
+        error!("rewards pool can't cover {} lamports", lamports);
+        Err(rewards_error(RewardsError::PoolExhausted))?;
+    }
 
     // Transfer rewards from the rewards pool to the staking account.
     keyed_accounts[1].account.lamports -= lamports;
@@ -63,6 +74,39 @@ fn redeem_vote_credits(keyed_accounts: &mut [KeyedAccount]) -> Result<(), Instru
     Ok(())
 }
 
+fn replenish(keyed_accounts: &mut [KeyedAccount], lamports: u64) -> Result<(), InstructionError> {
+    let authority_id = match keyed_accounts[0].signer_key() {
+        Some(authority_id) => *authority_id,
+        None => {
+            error!("account[0] is unsigned");
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+    };
+
+    if !solana_rewards_api::check_id(&keyed_accounts[1].account.owner) {
+        error!("account[1] is not assigned to the REWARDS_PROGRAM");
+        Err(InstructionError::InvalidArgument)?;
+    }
+    let rewards_state = RewardsState::deserialize(&keyed_accounts[1].account.data)?;
+    if rewards_state.authority_id != authority_id {
+        error!("account[0] is not the rewards pool's authority");
+        Err(rewards_error(RewardsError::UnauthorizedReplenish))?;
+    }
+
+    if keyed_accounts[0].account.lamports < lamports {
+        error!("authority can't cover {} lamports", lamports);
+        Err(rewards_error(RewardsError::InsufficientFunds))?;
+    }
+    keyed_accounts[0].account.lamports -= lamports;
+    keyed_accounts[1].account.lamports += lamports;
+
+    Ok(())
+}
+
+fn rewards_error(err: RewardsError) -> InstructionError {
+    InstructionError::CustomError(serialize(&err).unwrap())
+}
+
 solana_entrypoint!(entrypoint);
 fn entrypoint(
     _program_id: &Pubkey,
@@ -77,6 +121,7 @@ fn entrypoint(
 
     match deserialize(data).map_err(|_| InstructionError::InvalidInstructionData)? {
         RewardsInstruction::RedeemVoteCredits => redeem_vote_credits(keyed_accounts),
+        RewardsInstruction::Replenish(lamports) => replenish(keyed_accounts, lamports),
     }
 }
 
@@ -84,7 +129,6 @@ fn entrypoint(
 mod tests {
     use super::*;
     use solana_rewards_api;
-    use solana_rewards_api::rewards_state::RewardsState;
     use solana_sdk::account::Account;
     use solana_sdk::signature::{Keypair, KeypairUtil};
     use solana_vote_api::vote_instruction::Vote;
@@ -108,24 +152,42 @@ mod tests {
         redeem_vote_credits(&mut keyed_accounts)
     }
 
-    #[test]
-    fn test_redeem_vote_credits_via_program() {
-        let vote_id = Keypair::new().pubkey();
+    fn replenish_(
+        authority_id: &Pubkey,
+        authority_account: &mut Account,
+        rewards_id: &Pubkey,
+        rewards_account: &mut Account,
+        lamports: u64,
+    ) -> Result<(), InstructionError> {
+        let mut keyed_accounts = [
+            KeyedAccount::new(authority_id, true, authority_account),
+            KeyedAccount::new(rewards_id, false, rewards_account),
+        ];
+        replenish(&mut keyed_accounts, lamports)
+    }
+
+    fn create_vote_account_with_credit(vote_id: &Pubkey) -> Account {
         let mut vote_account = vote_state::create_vote_account(100);
-        vote_state::initialize_and_deserialize(&vote_id, &mut vote_account).unwrap();
+        vote_state::initialize_and_deserialize(vote_id, &mut vote_account).unwrap();
 
         for i in 0..vote_state::MAX_LOCKOUT_HISTORY {
             let vote = Vote::new(i as u64);
             let vote_state =
-                vote_state::vote_and_deserialize(&vote_id, &mut vote_account, vote.clone())
-                    .unwrap();
+                vote_state::vote_and_deserialize(vote_id, &mut vote_account, vote.clone()).unwrap();
             assert_eq!(vote_state.credits(), 0);
         }
 
         let vote = Vote::new(vote_state::MAX_LOCKOUT_HISTORY as u64 + 1);
         let vote_state =
-            vote_state::vote_and_deserialize(&vote_id, &mut vote_account, vote.clone()).unwrap();
+            vote_state::vote_and_deserialize(vote_id, &mut vote_account, vote.clone()).unwrap();
         assert_eq!(vote_state.credits(), 1);
+        vote_account
+    }
+
+    #[test]
+    fn test_redeem_vote_credits_via_program() {
+        let vote_id = Keypair::new().pubkey();
+        let mut vote_account = create_vote_account_with_credit(&vote_id);
 
         let rewards_id = Keypair::new().pubkey();
         let mut rewards_account = create_rewards_account(100);
@@ -141,4 +203,72 @@ mod tests {
         .unwrap();
         assert!(vote_account.lamports > lamports_before);
     }
+
+    #[test]
+    fn test_redeem_vote_credits_pool_exhausted() {
+        let vote_id = Keypair::new().pubkey();
+        let mut vote_account = create_vote_account_with_credit(&vote_id);
+
+        let rewards_id = Keypair::new().pubkey();
+        let mut rewards_account = create_rewards_account(0);
+
+        let lamports_before = vote_account.lamports;
+        assert_eq!(
+            redeem_vote_credits_(
+                &rewards_id,
+                &mut rewards_account,
+                &vote_id,
+                &mut vote_account,
+            ),
+            Err(rewards_error(RewardsError::PoolExhausted))
+        );
+        assert_eq!(vote_account.lamports, lamports_before);
+    }
+
+    #[test]
+    fn test_replenish() {
+        let authority_id = Keypair::new().pubkey();
+        let mut authority_account = Account::new(10, 0, &solana_rewards_api::id());
+
+        let rewards_id = Keypair::new().pubkey();
+        let mut rewards_account = create_rewards_account(0);
+        RewardsState::new(&authority_id)
+            .serialize(&mut rewards_account.data)
+            .unwrap();
+
+        let impostor_id = Keypair::new().pubkey();
+        let mut impostor_account = Account::new(10, 0, &solana_rewards_api::id());
+        assert_eq!(
+            replenish_(
+                &impostor_id,
+                &mut impostor_account,
+                &rewards_id,
+                &mut rewards_account,
+                5
+            ),
+            Err(rewards_error(RewardsError::UnauthorizedReplenish))
+        );
+
+        assert_eq!(
+            replenish_(
+                &authority_id,
+                &mut authority_account,
+                &rewards_id,
+                &mut rewards_account,
+                11
+            ),
+            Err(rewards_error(RewardsError::InsufficientFunds))
+        );
+
+        replenish_(
+            &authority_id,
+            &mut authority_account,
+            &rewards_id,
+            &mut rewards_account,
+            5,
+        )
+        .unwrap();
+        assert_eq!(authority_account.lamports, 5);
+        assert_eq!(rewards_account.lamports, 5);
+    }
 }
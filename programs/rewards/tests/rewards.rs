@@ -1,103 +1,62 @@
-use solana_rewards_api::rewards_transaction::RewardsTransaction;
-use solana_runtime::bank::{Bank, Result};
-use solana_sdk::genesis_block::GenesisBlock;
-use solana_sdk::hash::hash;
-use solana_sdk::pubkey::Pubkey;
+use bincode::serialize;
+use solana_rewards_api::rewards_instruction::RewardsInstruction;
+use solana_rewards_api::rewards_state::RewardsError;
+use solana_runtime::bank::Bank;
+use solana_runtime::bank_client::BankClient;
+use solana_sdk::genesis_block::{GenesisBlock, RewardsPool};
 use solana_sdk::signature::{Keypair, KeypairUtil};
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::transaction::{InstructionError, Transaction, TransactionError};
+use solana_vote_api::vote_instruction::{Vote, VoteInstruction};
 use solana_vote_api::vote_state::{self, VoteState};
-use solana_vote_api::vote_transaction::VoteTransaction;
-
-struct RewardsBank<'a> {
-    bank: &'a Bank,
-}
-
-impl<'a> RewardsBank<'a> {
-    fn new(bank: &'a Bank) -> Self {
-        bank.add_native_program("solana_rewards_program", &solana_rewards_api::id());
-        Self { bank }
-    }
-
-    fn create_rewards_account(
-        &self,
-        from_keypair: &Keypair,
-        rewards_id: &Pubkey,
-        lamports: u64,
-    ) -> Result<()> {
-        let blockhash = self.bank.last_blockhash();
-        let tx = RewardsTransaction::new_account(from_keypair, rewards_id, blockhash, lamports, 0);
-        self.bank.process_transaction(&tx)
-    }
-
-    fn create_vote_account(
-        &self,
-        from_keypair: &Keypair,
-        vote_id: &Pubkey,
-        lamports: u64,
-    ) -> Result<()> {
-        let blockhash = self.bank.last_blockhash();
-        let tx = VoteTransaction::new_account(from_keypair, vote_id, blockhash, lamports, 0);
-        self.bank.process_transaction(&tx)
-    }
-
-    fn submit_vote(
-        &self,
-        staking_account: &Pubkey,
-        vote_keypair: &Keypair,
-        tick_height: u64,
-    ) -> Result<VoteState> {
-        let blockhash = self.bank.last_blockhash();
-        let tx =
-            VoteTransaction::new_vote(staking_account, vote_keypair, tick_height, blockhash, 0);
-        self.bank.process_transaction(&tx)?;
-        self.bank.register_tick(&hash(blockhash.as_ref()));
-
-        let vote_account = self.bank.get_account(&vote_keypair.pubkey()).unwrap();
-        Ok(VoteState::deserialize(&vote_account.data).unwrap())
-    }
-
-    fn redeem_credits(&self, rewards_id: &Pubkey, vote_keypair: &Keypair) -> Result<VoteState> {
-        let blockhash = self.bank.last_blockhash();
-        let tx = RewardsTransaction::new_redeem_credits(&vote_keypair, rewards_id, blockhash, 0);
-        self.bank.process_transaction(&tx)?;
-        let vote_account = self.bank.get_account(&vote_keypair.pubkey()).unwrap();
-        Ok(VoteState::deserialize(&vote_account.data).unwrap())
-    }
-}
 
 #[test]
 fn test_redeem_vote_credits_via_bank() {
-    let (genesis_block, from_keypair) = GenesisBlock::new(10_000);
+    // The rewards pool starts out with less than the credits below are worth.
+    let (mut genesis_block, from_keypair) = GenesisBlock::new(10_000);
+    let rewards_id = Keypair::new().pubkey();
+    let authority_keypair = Keypair::new();
+    let authority_id = authority_keypair.pubkey();
+    genesis_block.rewards_pool = Some(RewardsPool {
+        id: rewards_id,
+        lamports: 5,
+        authority_id,
+    });
     let bank = Bank::new(&genesis_block);
-    let rewards_bank = RewardsBank::new(&bank);
-
-    // Create a rewards account to hold all rewards pool lamports.
-    let rewards_keypair = Keypair::new();
-    let rewards_id = rewards_keypair.pubkey();
-    rewards_bank
-        .create_rewards_account(&from_keypair, &rewards_id, 100)
-        .unwrap();
+    bank.add_native_program("solana_rewards_program", &solana_rewards_api::id());
+    let from_client = BankClient::new(&bank, from_keypair);
+    let from_id = from_client.pubkey();
+    assert_eq!(bank.get_balance(&rewards_id), 5);
 
     // A staker create a vote account account and delegates a validator to vote on its behalf.
     let vote_keypair = Keypair::new();
     let vote_id = vote_keypair.pubkey();
-    rewards_bank
-        .create_vote_account(&from_keypair, &vote_id, 100)
+    let create_ix = SystemInstruction::new_program_account(
+        &from_id,
+        &vote_id,
+        1_000,
+        VoteState::max_size() as u64,
+        &solana_vote_api::id(),
+    );
+    let init_ix = VoteInstruction::new_initialize_account(&vote_id);
+    from_client
+        .process_instructions(vec![create_ix, init_ix])
         .unwrap();
 
     // The validator submits votes to accumulate credits.
-    for i in 0..vote_state::MAX_LOCKOUT_HISTORY {
-        let vote_state = rewards_bank
-            .submit_vote(&vote_id, &vote_keypair, i as u64)
+    let submit_vote = |slot: u64| {
+        let instruction = VoteInstruction::new_vote(&vote_id, Vote::new(slot));
+        from_client
+            .send_instruction(&vote_keypair, instruction)
             .unwrap();
+        from_client.advance_slot();
+        from_client.get_account_data::<VoteState>(&vote_id).unwrap()
+    };
+    for i in 0..vote_state::MAX_LOCKOUT_HISTORY {
+        let vote_state = submit_vote(i as u64);
         assert_eq!(vote_state.credits(), 0);
     }
-    let vote_state = rewards_bank
-        .submit_vote(
-            &vote_id,
-            &vote_keypair,
-            vote_state::MAX_LOCKOUT_HISTORY as u64 + 1,
-        )
-        .unwrap();
+    let vote_state = submit_vote(vote_state::MAX_LOCKOUT_HISTORY as u64 + 1);
     assert_eq!(vote_state.credits(), 1);
 
     // TODO: Add VoteInstruction::RegisterStakerId so that we don't need to point the "to"
@@ -107,9 +66,46 @@ fn test_redeem_vote_credits_via_bank() {
 
     // Periodically, the staker sumbits its vote account to the rewards pool
     // to exchange its credits for lamports.
-    let vote_state = rewards_bank
-        .redeem_credits(&rewards_id, &vote_keypair)
+    let redeem_credits = || {
+        let redeem_ix = RewardsInstruction::new_redeem_vote_credits(&vote_id, &rewards_id);
+        let clear_ix = VoteInstruction::new_clear_credits(&vote_id);
+        from_client.send_transaction(
+            &[&vote_keypair],
+            Transaction::new(vec![redeem_ix, clear_ix]),
+        )
+    };
+
+    // The pool can't cover the 10 lamports the credit is worth.
+    assert_eq!(
+        redeem_credits(),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::CustomError(serialize(&RewardsError::PoolExhausted).unwrap())
+        ))
+    );
+    assert_eq!(bank.get_balance(&to_id), to_lamports);
+    assert_eq!(bank.get_balance(&rewards_id), 5);
+    from_client.advance_slot();
+
+    // The authority funds an account only the rewards program can spend and replenishes
+    // the pool from it.
+    let create_ix = SystemInstruction::new_program_account(
+        &from_id,
+        &authority_id,
+        100,
+        0,
+        &solana_rewards_api::id(),
+    );
+    from_client.process_instruction(create_ix).unwrap();
+    let replenish_ix = RewardsInstruction::new_replenish(&authority_id, &rewards_id, 100);
+    from_client
+        .send_instruction(&authority_keypair, replenish_ix)
         .unwrap();
-    assert!(bank.get_balance(&to_id) > to_lamports);
+    assert_eq!(bank.get_balance(&rewards_id), 105);
+
+    redeem_credits().unwrap();
+    let vote_state = from_client.get_account_data::<VoteState>(&vote_id).unwrap();
+    assert_eq!(bank.get_balance(&to_id), to_lamports + 10);
+    assert_eq!(bank.get_balance(&rewards_id), 95);
     assert_eq!(vote_state.credits(), 0);
 }
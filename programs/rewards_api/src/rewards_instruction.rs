@@ -6,6 +6,12 @@ use solana_sdk::transaction::Instruction;
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum RewardsInstruction {
     RedeemVoteCredits,
+
+    /// Move lamports from the pool's authority into the pool. The authority's account must
+    /// be owned by the rewards program, so that only this instruction can spend it.
+    /// * Transaction::keys[0] - the authority, a signer
+    /// * Transaction::keys[1] - the rewards pool
+    Replenish(u64),
 }
 
 impl RewardsInstruction {
@@ -16,4 +22,12 @@ impl RewardsInstruction {
             vec![(*vote_id, true), (*rewards_id, false)],
         )
     }
+
+    pub fn new_replenish(authority_id: &Pubkey, rewards_id: &Pubkey, lamports: u64) -> Instruction {
+        Instruction::new(
+            id(),
+            &RewardsInstruction::Replenish(lamports),
+            vec![(*authority_id, true), (*rewards_id, false)],
+        )
+    }
 }
@@ -1,13 +1,45 @@
-use bincode::serialized_size;
+use bincode::{deserialize, serialize_into, serialized_size, ErrorKind};
 use serde_derive::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::InstructionError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RewardsError {
+    /// The pool holds fewer lamports than the redeemed credits are worth
+    PoolExhausted,
+    /// The signer isn't the pool's replenishing authority
+    UnauthorizedReplenish,
+    /// The authority's account holds fewer lamports than the replenishment
+    InsufficientFunds,
+}
 
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
-pub struct RewardsState {}
+pub struct RewardsState {
+    /// The only account allowed to replenish the pool
+    pub authority_id: Pubkey,
+}
 
 impl RewardsState {
+    pub fn new(authority_id: &Pubkey) -> Self {
+        Self {
+            authority_id: *authority_id,
+        }
+    }
+
     /// Upper limit on the serialized size of RewardsState.
     pub fn max_size() -> usize {
         let rewards_state = RewardsState::default();
         serialized_size(&rewards_state).unwrap() as usize
     }
+
+    pub fn deserialize(input: &[u8]) -> Result<Self, InstructionError> {
+        deserialize(input).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    pub fn serialize(&self, output: &mut [u8]) -> Result<(), InstructionError> {
+        serialize_into(output, self).map_err(|err| match *err {
+            ErrorKind::SizeLimit => InstructionError::AccountDataTooSmall,
+            _ => InstructionError::GenericError,
+        })
+    }
 }
@@ -30,14 +30,17 @@ fn entrypoint(
         VoteInstruction::AuthorizeVoter(voter_id) => {
             vote_state::authorize_voter(keyed_accounts, &voter_id)
         }
-        VoteInstruction::Vote(vote) => {
-            debug!("{:?} by {}", vote, keyed_accounts[0].signer_key().unwrap());
+        VoteInstruction::Vote(votes) => {
+            debug!("{:?} by {}", votes, keyed_accounts[0].signer_key().unwrap());
             solana_metrics::submit(
                 solana_metrics::influxdb::Point::new("vote-native")
-                    .add_field("count", solana_metrics::influxdb::Value::Integer(1))
+                    .add_field(
+                        "count",
+                        solana_metrics::influxdb::Value::Integer(votes.len() as i64),
+                    )
                     .to_owned(),
             );
-            vote_state::process_vote(keyed_accounts, vote)
+            vote_state::process_votes(keyed_accounts, &votes)
         }
         VoteInstruction::ClearCredits => vote_state::clear_credits(keyed_accounts),
     }
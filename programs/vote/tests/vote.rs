@@ -82,6 +82,45 @@ fn test_vote_bank_basic() {
     assert_eq!(vote_state.votes.len(), 1);
 }
 
+#[test]
+fn test_vote_bank_catch_up_votes() {
+    let (genesis_block, from_keypair) = GenesisBlock::new(10_000);
+    let bank = Bank::new(&genesis_block);
+    let vote_bank = VoteBank::new(&bank);
+
+    let sequential_keypair = Keypair::new();
+    let sequential_id = sequential_keypair.pubkey();
+    vote_bank
+        .create_vote_account(&from_keypair, &sequential_id, 100)
+        .unwrap();
+    let batched_keypair = Keypair::new();
+    let batched_id = batched_keypair.pubkey();
+    vote_bank
+        .create_vote_account(&from_keypair, &batched_id, 100)
+        .unwrap();
+
+    let slots = [1, 2, 3, 4, 5];
+    let mut sequential_state = None;
+    for slot in &slots {
+        sequential_state = Some(
+            vote_bank
+                .submit_vote(&sequential_id, &sequential_keypair, *slot)
+                .unwrap(),
+        );
+    }
+
+    let blockhash = bank.last_blockhash();
+    let tx = VoteTransaction::new_votes(&batched_id, &batched_keypair, &slots, blockhash, 0);
+    bank.process_transaction(&tx).unwrap();
+    let vote_account = bank.get_account(&batched_id).unwrap();
+    let mut batched_state = VoteState::deserialize(&vote_account.data).unwrap();
+
+    // Only the identities differ
+    batched_state.delegate_id = sequential_id;
+    batched_state.authorized_voter_id = sequential_id;
+    assert_eq!(Some(batched_state), sequential_state);
+}
+
 #[test]
 fn test_vote_bank_delegate() {
     let (genesis_block, from_keypair) = GenesisBlock::new(10_000);
@@ -111,7 +150,7 @@ fn test_vote_via_bank_with_no_signature() {
     let blockhash = bank.last_blockhash();
     let vote_ix = Instruction::new(
         solana_vote_api::id(),
-        &VoteInstruction::Vote(Vote::new(0)),
+        &VoteInstruction::Vote(vec![Vote::new(0)]),
         vec![(vote_id, false)], // <--- attack!! No signature.
     );
 
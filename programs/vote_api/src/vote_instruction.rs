@@ -25,7 +25,8 @@ pub enum VoteInstruction {
     DelegateStake(Pubkey),
     /// Authorize a voter to send signed votes.
     AuthorizeVoter(Pubkey),
-    Vote(Vote),
+    /// Vote for one or more slots, processed in order, e.g. to catch up after an outage
+    Vote(Vec<Vote>),
     /// Clear the credits in the vote account
     /// * Transaction::keys[0] - the "vote account"
     ClearCredits,
@@ -57,6 +58,9 @@ impl VoteInstruction {
         )
     }
     pub fn new_vote(vote_id: &Pubkey, vote: Vote) -> Instruction {
-        Instruction::new(id(), &VoteInstruction::Vote(vote), vec![(*vote_id, true)])
+        Self::new_votes(vote_id, vec![vote])
+    }
+    pub fn new_votes(vote_id: &Pubkey, votes: Vec<Vote>) -> Instruction {
+        Instruction::new(id(), &VoteInstruction::Vote(votes), vec![(*vote_id, true)])
     }
 }
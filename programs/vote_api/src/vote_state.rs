@@ -6,15 +6,26 @@ use crate::{check_id, id};
 use bincode::{deserialize, serialize_into, serialized_size, ErrorKind};
 use log::*;
 use serde_derive::{Deserialize, Serialize};
-use solana_sdk::account::{Account, KeyedAccount};
+use solana_sdk::account::{Account, KeyedAccount, MAX_PERMITTED_DATA_LENGTH};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::transaction::InstructionError;
 use std::collections::VecDeque;
+use std::mem::size_of;
 
 // Maximum number of votes to keep around
 pub const MAX_LOCKOUT_HISTORY: usize = 31;
 pub const INITIAL_LOCKOUT: usize = 2;
 
+/// An upper bound on `VoteState::max_size()`: no field serializes to more bytes than it
+/// occupies in memory
+const VOTE_STATE_SIZE_BOUND: usize =
+    size_of::<VoteState>() + MAX_LOCKOUT_HISTORY * size_of::<Lockout>();
+
+// Vote accounts must fit under the accounts store's data cap
+#[allow(dead_code)]
+const VOTE_STATE_FITS_DATA_CAP: [(); 0] =
+    [(); 0 - !(VOTE_STATE_SIZE_BOUND <= MAX_PERMITTED_DATA_LENGTH) as usize];
+
 #[derive(Serialize, Default, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Lockout {
     pub slot: u64,
@@ -113,6 +124,13 @@ impl VoteState {
         self.double_lockouts();
     }
 
+    /// Process `votes` in order, as if each had arrived in its own instruction
+    pub fn process_votes(&mut self, votes: &[Vote]) {
+        for vote in votes {
+            self.process_vote(vote.clone());
+        }
+    }
+
     pub fn nth_recent_vote(&self, position: usize) -> Option<&Lockout> {
         if position < self.votes.len() {
             let pos = self.votes.len() - 1 - position;
@@ -242,6 +260,13 @@ pub fn initialize_account(keyed_accounts: &mut [KeyedAccount]) -> Result<(), Ins
 pub fn process_vote(
     keyed_accounts: &mut [KeyedAccount],
     vote: Vote,
+) -> Result<(), InstructionError> {
+    process_votes(keyed_accounts, &[vote])
+}
+
+pub fn process_votes(
+    keyed_accounts: &mut [KeyedAccount],
+    votes: &[Vote],
 ) -> Result<(), InstructionError> {
     if !check_id(&keyed_accounts[0].account.owner) {
         error!("account[0] is not assigned to the VOTE_PROGRAM");
@@ -266,7 +291,7 @@ pub fn process_vote(
         Err(InstructionError::InvalidArgument)?;
     }
 
-    vote_state.process_vote(vote);
+    vote_state.process_votes(votes);
     vote_state.serialize(&mut keyed_accounts[0].account.data)?;
     Ok(())
 }
@@ -349,6 +374,11 @@ mod tests {
         assert_eq!(VoteState::deserialize(&buffer).unwrap(), vote_state);
     }
 
+    #[test]
+    fn test_vote_state_size_bound() {
+        assert!(VoteState::max_size() <= VOTE_STATE_SIZE_BOUND);
+    }
+
     #[test]
     fn test_voter_registration() {
         let vote_id = Keypair::new().pubkey();
@@ -498,6 +528,27 @@ mod tests {
         assert!(vote_state.nth_recent_vote(MAX_LOCKOUT_HISTORY).is_none());
     }
 
+    #[test]
+    fn test_process_votes_matches_sequential_votes() {
+        let voter_id = Keypair::new().pubkey();
+        // Includes expirations, a stale slot, and enough votes to root the oldest
+        let slots: Vec<u64> = (0..MAX_LOCKOUT_HISTORY as u64 + 2)
+            .chain(vec![3, 40, 41, 100])
+            .collect();
+        let votes: Vec<Vote> = slots.iter().map(|slot| Vote::new(*slot)).collect();
+
+        let mut sequential = VoteState::new(&voter_id);
+        for vote in &votes {
+            sequential.process_vote(vote.clone());
+        }
+        let mut batched = VoteState::new(&voter_id);
+        batched.process_votes(&votes);
+
+        assert_eq!(batched, sequential);
+        assert_eq!(batched.root_slot, sequential.root_slot);
+        assert!(batched.credits() > 0);
+    }
+
     fn check_lockouts(vote_state: &VoteState) {
         for (i, vote) in vote_state.votes.iter().enumerate() {
             let num_lockouts = vote_state.votes.len() - i;
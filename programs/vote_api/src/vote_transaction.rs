@@ -20,8 +20,25 @@ impl VoteTransaction {
         recent_blockhash: Hash,
         fee: u64,
     ) -> Transaction {
-        let vote = Vote { slot };
-        let ix = VoteInstruction::new_vote(staking_account, vote);
+        Self::new_votes(
+            staking_account,
+            authorized_voter_keypair,
+            &[slot],
+            recent_blockhash,
+            fee,
+        )
+    }
+
+    /// Vote for several slots in a single instruction, oldest first
+    pub fn new_votes<T: KeypairUtil>(
+        staking_account: &Pubkey,
+        authorized_voter_keypair: &T,
+        slots: &[u64],
+        recent_blockhash: Hash,
+        fee: u64,
+    ) -> Transaction {
+        let votes = slots.iter().map(|slot| Vote::new(*slot)).collect();
+        let ix = VoteInstruction::new_votes(staking_account, votes);
         let mut tx = Transaction::new(vec![ix]);
         tx.fee = fee;
         tx.sign(&[authorized_voter_keypair], recent_blockhash);
@@ -97,21 +114,24 @@ impl VoteTransaction {
         tx
     }
 
-    fn get_vote(tx: &Transaction, ix_index: usize) -> Option<(Pubkey, Vote, Hash)> {
+    fn get_instruction_votes(tx: &Transaction, ix_index: usize) -> Vec<(Pubkey, Vote, Hash)> {
         if !check_id(&tx.program_id(ix_index)) {
-            return None;
+            return vec![];
         }
         let instruction = deserialize(&tx.data(ix_index)).unwrap();
-        if let VoteInstruction::Vote(vote) = instruction {
-            Some((tx.account_keys[0], vote, tx.recent_blockhash))
+        if let VoteInstruction::Vote(votes) = instruction {
+            votes
+                .into_iter()
+                .map(|vote| (tx.account_keys[0], vote, tx.recent_blockhash))
+                .collect()
         } else {
-            None
+            vec![]
         }
     }
 
     pub fn get_votes(tx: &Transaction) -> Vec<(Pubkey, Vote, Hash)> {
         (0..tx.instructions.len())
-            .filter_map(|i| Self::get_vote(tx, i))
+            .flat_map(|i| Self::get_instruction_votes(tx, i))
             .collect()
     }
 }
@@ -131,5 +151,15 @@ mod tests {
             VoteTransaction::get_votes(&transaction),
             vec![(keypair.pubkey(), Vote::new(slot), recent_blockhash)]
         );
+
+        let transaction =
+            VoteTransaction::new_votes(&keypair.pubkey(), &keypair, &[1, 2], recent_blockhash, 0);
+        assert_eq!(
+            VoteTransaction::get_votes(&transaction),
+            vec![
+                (keypair.pubkey(), Vote::new(1), recent_blockhash),
+                (keypair.pubkey(), Vote::new(2), recent_blockhash)
+            ]
+        );
     }
 }
@@ -3,11 +3,16 @@
 extern crate test;
 
 use solana_runtime::bank::*;
+use solana_runtime::bank_utils::create_funded_keypairs;
+use solana_runtime::verified_transaction::VerifiedTransaction;
 use solana_sdk::genesis_block::GenesisBlock;
 use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil};
 use solana_sdk::system_transaction::SystemTransaction;
 use solana_sdk::timing::{DEFAULT_TICKS_PER_SLOT, MAX_RECENT_BLOCKHASHES};
+use solana_sdk::transaction::TransactionError;
+use std::sync::Arc;
 use test::Bencher;
 
 #[bench]
@@ -23,7 +28,7 @@ fn bench_process_transaction(bencher: &mut Bencher) {
             let rando0 = Keypair::new();
             let tx = SystemTransaction::new_move(
                 &mint_keypair,
-                rando0.pubkey(),
+                &rando0.pubkey(),
                 10_000,
                 bank.last_blockhash(),
                 0,
@@ -33,11 +38,11 @@ fn bench_process_transaction(bencher: &mut Bencher) {
             // Seed the 'to' account and a cell for its signature.
             let rando1 = Keypair::new();
             let tx =
-                SystemTransaction::new_move(&rando0, rando1.pubkey(), 1, bank.last_blockhash(), 0);
+                SystemTransaction::new_move(&rando0, &rando1.pubkey(), 1, bank.last_blockhash(), 0);
             assert_eq!(bank.process_transaction(&tx), Ok(()));
 
             // Finally, return the transaction to the benchmark.
-            tx
+            VerifiedTransaction::verify(tx).unwrap()
         })
         .collect();
 
@@ -50,8 +55,126 @@ fn bench_process_transaction(bencher: &mut Bencher) {
 
     bencher.iter(|| {
         // Since benchmarker runs this multiple times, we need to clear the signatures.
-        bank.clear_signatures();
-        let results = bank.process_transactions(&transactions);
+        bank.clear_signatures(false);
+        let results = bank.process_verified_transactions(&transactions);
         assert!(results.iter().all(Result::is_ok));
     })
 }
+
+#[bench]
+fn bench_bank_new_from_parent(bencher: &mut Bencher) {
+    let (genesis_block, _) = GenesisBlock::new(100);
+    let mut parent = Arc::new(Bank::new(&genesis_block));
+    let mut id = parent.last_blockhash();
+    for _ in 0..(MAX_RECENT_BLOCKHASHES * DEFAULT_TICKS_PER_SLOT as usize) {
+        parent.register_tick(&id);
+        id = hash(&id.as_ref())
+    }
+
+    // Consecutive slots in the same epoch, as a leader would create them
+    bencher.iter(|| {
+        let slot = parent.slot() + 1;
+        parent = Arc::new(Bank::new_from_parent(&parent, &Pubkey::default(), slot));
+    })
+}
+
+const NUM_TRANSACTIONS: usize = 4096;
+
+/// Register enough ticks that the bank's blockhash queue is full, as it would be on a
+/// long-running cluster.
+fn fill_blockhash_queue(bank: &Bank) {
+    let mut id = bank.last_blockhash();
+    for _ in 0..(MAX_RECENT_BLOCKHASHES * DEFAULT_TICKS_PER_SLOT as usize) {
+        bank.register_tick(&id);
+        id = hash(&id.as_ref())
+    }
+}
+
+/// Create one transaction per payer, each moving a lamport to a new account.
+fn create_transactions(bank: &Bank, payers: &[Keypair]) -> Vec<VerifiedTransaction> {
+    payers
+        .iter()
+        .map(|payer| {
+            let to = Keypair::new().pubkey();
+            let tx = SystemTransaction::new_move(payer, &to, 1, bank.last_blockhash(), 0);
+            VerifiedTransaction::verify(tx).unwrap()
+        })
+        .collect()
+}
+
+#[bench]
+fn bench_process_transactions_disjoint_accounts(bencher: &mut Bencher) {
+    let (genesis_block, _mint_keypair) = GenesisBlock::new(1);
+    let bank = Bank::new(&genesis_block);
+    let payers = create_funded_keypairs(&bank, NUM_TRANSACTIONS, 1_000_000);
+    let transactions = create_transactions(&bank, &payers);
+    fill_blockhash_queue(&bank);
+
+    bencher.iter(|| {
+        bank.clear_signatures(false);
+        let results = bank.process_verified_transactions(&transactions);
+        assert!(results.iter().all(Result::is_ok));
+    })
+}
+
+#[bench]
+fn bench_process_transactions_conflicting_accounts(bencher: &mut Bencher) {
+    let (genesis_block, _mint_keypair) = GenesisBlock::new(1);
+    let bank = Bank::new(&genesis_block);
+
+    // Every payer signs two transactions, so half the batch can't lock its accounts
+    let payers = create_funded_keypairs(&bank, NUM_TRANSACTIONS / 2, 1_000_000);
+    let mut transactions = create_transactions(&bank, &payers);
+    transactions.extend(create_transactions(&bank, &payers));
+    fill_blockhash_queue(&bank);
+
+    bencher.iter(|| {
+        bank.clear_signatures(false);
+        let results = bank.process_verified_transactions(&transactions);
+        let num_conflicts = results
+            .iter()
+            .filter(|result| **result == Err(TransactionError::AccountInUse))
+            .count();
+        assert_eq!(num_conflicts, NUM_TRANSACTIONS / 2);
+    })
+}
+
+#[bench]
+fn bench_process_transactions_duplicate_signatures(bencher: &mut Bencher) {
+    let (genesis_block, _mint_keypair) = GenesisBlock::new(1);
+    let bank = Bank::new(&genesis_block);
+    let payers = create_funded_keypairs(&bank, NUM_TRANSACTIONS, 1_000_000);
+    fill_blockhash_queue(&bank);
+    let transactions = create_transactions(&bank, &payers);
+    let results = bank.process_verified_transactions(&transactions);
+    assert!(results.iter().all(Result::is_ok));
+
+    // Every transaction has already been processed
+    bencher.iter(|| {
+        let results = bank.process_verified_transactions(&transactions);
+        assert!(results
+            .iter()
+            .all(|result| *result == Err(TransactionError::DuplicateSignature)));
+    })
+}
+
+#[bench]
+fn bench_get_signature_status_deep_parents(bencher: &mut Bencher) {
+    let (genesis_block, _mint_keypair) = GenesisBlock::new(1);
+    let mut bank = Arc::new(Bank::new(&genesis_block));
+    let payers = create_funded_keypairs(&bank, NUM_TRANSACTIONS, 1_000_000);
+    let transactions = create_transactions(&bank, &payers);
+    let results = bank.process_verified_transactions(&transactions);
+    assert!(results.iter().all(Result::is_ok));
+
+    // The signatures all live in the status cache of the oldest ancestor
+    for slot in 1..=MAX_RECENT_BLOCKHASHES as u64 {
+        bank = Arc::new(Bank::new_from_parent(&bank, &Pubkey::default(), slot));
+    }
+
+    bencher.iter(|| {
+        for tx in &transactions {
+            assert_eq!(bank.get_signature_status(&tx.signatures[0]), Some(Ok(())));
+        }
+    })
+}
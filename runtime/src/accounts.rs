@@ -6,14 +6,17 @@ use hashbrown::{HashMap, HashSet};
 use log::*;
 use rand::{thread_rng, Rng};
 use solana_metrics::counter::Counter;
-use solana_sdk::account::Account;
+use solana_sdk::account::{Account, MAX_PERMITTED_DATA_LENGTH};
+use solana_sdk::fee_calculator::FeeCalculator;
 use solana_sdk::hash::{hash, Hash};
 use solana_sdk::native_loader;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil};
 use solana_sdk::transaction::{Transaction, TransactionError};
 use solana_vote_api;
-use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
 use std::env;
 use std::fs::{create_dir_all, remove_dir_all};
 use std::path::Path;
@@ -35,6 +38,75 @@ pub struct ErrorCounters {
     pub duplicate_signature: usize,
     pub call_chain_too_deep: usize,
     pub missing_signature_for_fee: usize,
+    pub invalid_program_for_execution: usize,
+}
+
+#[derive(Default)]
+struct LoaderCacheEntries {
+    chains: HashMap<Pubkey, Vec<(Pubkey, Account)>>,
+    /// Bumped by every invalidation, so a chain loaded before a program was redeployed
+    /// isn't cached after it
+    generation: u64,
+}
+
+/// The loader chains of the programs a fork has executed, keyed by program id. Program
+/// accounts rarely change, so they're looked up once rather than for every transaction.
+#[derive(Default)]
+pub struct LoaderCache {
+    entries: RwLock<LoaderCacheEntries>,
+}
+
+impl LoaderCache {
+    /// Start a child fork's cache from its frozen parent's chains
+    pub fn new_from_parent(parent: &LoaderCache) -> Self {
+        let chains = parent.entries.read().unwrap().chains.clone();
+        Self {
+            entries: RwLock::new(LoaderCacheEntries {
+                chains,
+                generation: 0,
+            }),
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.entries.read().unwrap().generation
+    }
+
+    fn get(&self, program_id: &Pubkey) -> Option<Vec<(Pubkey, Account)>> {
+        self.entries.read().unwrap().chains.get(program_id).cloned()
+    }
+
+    /// Cache the chain of `program_id`, unless an account was invalidated since
+    /// `generation` was read and the chain may be stale
+    fn insert(&self, program_id: Pubkey, chain: Vec<(Pubkey, Account)>, generation: u64) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.generation == generation {
+            entries.chains.insert(program_id, chain);
+        }
+    }
+
+    pub fn contains(&self, program_id: &Pubkey) -> bool {
+        self.entries.read().unwrap().chains.contains_key(program_id)
+    }
+
+    /// Forget every chain `pubkey` is part of, now that `account` was stored under it
+    pub fn invalidate(&self, pubkey: &Pubkey, account: &Account) {
+        let in_chain = |entries: &LoaderCacheEntries| {
+            entries
+                .chains
+                .values()
+                .any(|chain| chain.iter().any(|(id, _)| id == pubkey))
+        };
+        // Only executable accounts can be in a chain being loaded concurrently
+        if !account.executable && !in_chain(&self.entries.read().unwrap()) {
+            return;
+        }
+        let mut entries = self.entries.write().unwrap();
+        entries.generation += 1;
+        entries
+            .chains
+            .retain(|_, chain| chain.iter().all(|(id, _)| id != pubkey));
+    }
 }
 
 //
@@ -111,8 +183,9 @@ struct AccountInfo {
     is_vote_account: bool,
 }
 
-// in a given a Fork, which AppendVecId and offset
-type AccountMap = RwLock<HashMap<Pubkey, AccountInfo>>;
+// in a given a Fork, which AppendVecId and offset. Read views share the map and keep the
+// version they captured; a store copies it first if a view still holds it.
+type AccountMap = RwLock<Arc<HashMap<Pubkey, AccountInfo>>>;
 
 /// information about where Accounts are stored
 /// keying hierarchy is:
@@ -173,6 +246,24 @@ impl AccountStorageEntry {
         self.count.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Add an account unless the storage is empty, and so free to be reset. Return true if
+    /// it was added.
+    fn add_account_if_not_empty(&self) -> bool {
+        let mut count = self.count.load(Ordering::Relaxed);
+        while count != 0 {
+            match self.count.compare_exchange_weak(
+                count,
+                count + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(current) => count = current,
+            }
+        }
+        false
+    }
+
     fn remove_account(&self) {
         if self.count.fetch_sub(1, Ordering::Relaxed) == 1 {
             self.accounts.write().unwrap().reset();
@@ -233,6 +324,25 @@ pub struct Accounts {
     own_paths: bool,
 }
 
+/// Hash one account the way it's folded into a fork's accounts delta hash, so a single
+/// account can be checked without the rest of the fork
+pub fn hash_account(pubkey: &Pubkey, account: &Account) -> Hash {
+    hash(&serialize(&(pubkey, account)).unwrap())
+}
+
+/// Fail with `InvalidAccountDataLength` if any of `accounts` holds more data than an
+/// account may
+fn check_data_lengths<'a>(accounts: impl IntoIterator<Item = &'a Account>) -> Result<()> {
+    if accounts
+        .into_iter()
+        .any(|account| account.data.len() > MAX_PERMITTED_DATA_LENGTH)
+    {
+        Err(TransactionError::InvalidAccountDataLength)
+    } else {
+        Ok(())
+    }
+}
+
 fn get_paths_vec(paths: &str) -> Vec<String> {
     paths.split(',').map(|s| s.to_string()).collect()
 }
@@ -294,7 +404,7 @@ impl AccountsDB {
             }
         }
         let mut account_maps = self.account_index.account_maps.write().unwrap();
-        account_maps.insert(fork, RwLock::new(HashMap::new()));
+        account_maps.insert(fork, RwLock::new(Arc::new(HashMap::new())));
     }
 
     fn new_storage_entry(&self, path: &str) -> AccountStorageEntry {
@@ -367,23 +477,21 @@ impl AccountsDB {
     pub fn hash_internal_state(&self, fork: Fork) -> Option<Hash> {
         let account_maps = self.account_index.account_maps.read().unwrap();
         let account_map = account_maps.get(&fork).unwrap();
-        let ordered_accounts: BTreeMap<_, _> = account_map
+        let ordered_hashes: BTreeMap<_, _> = account_map
             .read()
             .unwrap()
             .iter()
             .map(|(pubkey, account_info)| {
-                (
-                    *pubkey,
-                    self.get_account(account_info.id, account_info.offset),
-                )
+                let account = self.get_account(account_info.id, account_info.offset);
+                (*pubkey, hash_account(pubkey, &account))
             })
             .collect();
 
-        if ordered_accounts.is_empty() {
+        if ordered_hashes.is_empty() {
             return None;
         }
 
-        Some(hash(&serialize(&ordered_accounts).unwrap()))
+        Some(hash(&serialize(&ordered_hashes).unwrap()))
     }
 
     fn get_account(&self, id: AppendVecId, offset: u64) -> Account {
@@ -392,11 +500,27 @@ impl AccountsDB {
         av.get_account(offset).unwrap()
     }
 
+    fn get_account_owner(&self, id: AppendVecId, offset: u64) -> Pubkey {
+        let accounts = &self.storage.read().unwrap()[id].accounts;
+        let av = accounts.read().unwrap();
+        av.get_account_owner(offset).unwrap()
+    }
+
     fn load(&self, fork: Fork, pubkey: &Pubkey, walk_back: bool) -> Option<Account> {
+        self.load_with(fork, pubkey, walk_back, |account_info| {
+            self.get_account(account_info.id, account_info.offset)
+        })
+    }
+
+    /// Find the most recent entry for `pubkey` as of `fork` and read it with `read`
+    fn load_with<T, F>(&self, fork: Fork, pubkey: &Pubkey, walk_back: bool, read: F) -> Option<T>
+    where
+        F: Fn(&AccountInfo) -> T,
+    {
         let account_maps = self.account_index.account_maps.read().unwrap();
         let account_map = account_maps.get(&fork).unwrap().read().unwrap();
         if let Some(account_info) = account_map.get(&pubkey) {
-            return Some(self.get_account(account_info.id, account_info.offset));
+            return Some(read(account_info));
         }
         if !walk_back {
             return None;
@@ -408,7 +532,7 @@ impl AccountsDB {
                 if let Some(account_map) = account_maps.get(&parent_fork) {
                     let account_map = account_map.read().unwrap();
                     if let Some(account_info) = account_map.get(&pubkey) {
-                        return Some(self.get_account(account_info.id, account_info.offset));
+                        return Some(read(account_info));
                     }
                 }
             }
@@ -416,6 +540,26 @@ impl AccountsDB {
         None
     }
 
+    /// Return every account stored in `fork` itself, ignoring its parents
+    fn load_fork_accounts(&self, fork: Fork) -> Vec<(Pubkey, Account)> {
+        self.account_index
+            .account_maps
+            .read()
+            .unwrap()
+            .get(&fork)
+            .unwrap()
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(pubkey, account_info)| {
+                (
+                    *pubkey,
+                    self.get_account(account_info.id, account_info.offset),
+                )
+            })
+            .collect()
+    }
+
     fn load_program_accounts(&self, fork: Fork, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
         self.account_index
             .account_maps
@@ -525,7 +669,8 @@ impl AccountsDB {
     fn remove_account_entries(&self, fork: Fork, pubkey: &Pubkey) -> bool {
         let account_maps = self.account_index.account_maps.read().unwrap();
         let mut account_map = account_maps.get(&fork).unwrap().write().unwrap();
-        if let Some(account_info) = account_map.remove(&pubkey) {
+        if account_map.contains_key(pubkey) {
+            let account_info = Arc::make_mut(&mut account_map).remove(&pubkey).unwrap();
             let stores = self.storage.read().unwrap();
             stores[account_info.id].remove_account();
         }
@@ -560,34 +705,161 @@ impl AccountsDB {
                 lamports: account.lamports,
                 is_vote_account: solana_vote_api::check_id(&account.owner),
             };
-            self.insert_account_entry(&pubkey, &account_info, &mut account_map);
+            self.insert_account_entry(&pubkey, &account_info, Arc::make_mut(&mut account_map));
         }
     }
 
-    pub fn store_accounts(
+    /// Store many account updates, taking the fork's index lock once for all of them
+    fn store_many(&self, fork: Fork, accounts: &[(Pubkey, Account)]) {
+        let squashed = self.is_squashed(fork);
+        let (purged, stored): (Vec<_>, Vec<_>) = accounts
+            .iter()
+            .partition(|(_, account)| account.lamports == 0 && squashed);
+        for (pubkey, _) in purged {
+            // purge if balance is 0 and no checkpoints
+            self.remove_account_entries(fork, pubkey);
+        }
+
+        let account_infos: Vec<_> = stored
+            .into_iter()
+            .map(|(pubkey, account)| {
+                let (id, offset) = self.append_account(account);
+                let account_info = AccountInfo {
+                    id,
+                    offset,
+                    lamports: account.lamports,
+                    is_vote_account: solana_vote_api::check_id(&account.owner),
+                };
+                (pubkey, account_info)
+            })
+            .collect();
+        let account_maps = self.account_index.account_maps.read().unwrap();
+        let mut account_map = account_maps.get(&fork).unwrap().write().unwrap();
+        let account_map = Arc::make_mut(&mut account_map);
+        for (pubkey, account_info) in account_infos {
+            self.insert_account_entry(pubkey, &account_info, account_map);
+        }
+    }
+
+    /// Share the index maps visible from `fork`, newest first, and pin every storage entry
+    /// that holds accounts so none is reset while the maps are alive. Return the maps and
+    /// the ids of the pinned storage, which `unpin_storage` releases.
+    fn pin_index(&self, fork: Fork) -> (Vec<Arc<HashMap<Pubkey, AccountInfo>>>, Vec<usize>) {
+        let account_maps = self.account_index.account_maps.read().unwrap();
+        let mut forks = vec![fork];
+        if let Some(fork_info) = self.fork_infos.read().unwrap().get(&fork) {
+            forks.extend_from_slice(&fork_info.parents);
+        }
+        // Hold every map's read lock until the storage is pinned, so no store can replace
+        // an entry and release its storage in between
+        let maps: Vec<_> = forks
+            .iter()
+            .filter_map(|fork| account_maps.get(fork))
+            .map(|account_map| account_map.read().unwrap())
+            .collect();
+        let stores = self.storage.read().unwrap();
+        let pinned = (0..stores.len())
+            .filter(|id| stores[*id].add_account_if_not_empty())
+            .collect();
+        let maps = maps
+            .iter()
+            .map(|account_map| Arc::clone(account_map))
+            .collect();
+        (maps, pinned)
+    }
+
+    fn unpin_storage(&self, pinned: &[usize]) {
+        let stores = self.storage.read().unwrap();
+        for id in pinned {
+            stores[*id].remove_account();
+        }
+    }
+
+    /// Call `scan` with the newest index entry of every account visible from `fork` that
+    /// holds lamports. The index read locks are held throughout, so `scan` may read the
+    /// entry's storage but must not store.
+    fn scan_index<F>(&self, fork: Fork, mut scan: F)
+    where
+        F: FnMut(&Pubkey, &AccountInfo),
+    {
+        let account_maps = self.account_index.account_maps.read().unwrap();
+        let mut forks = vec![fork];
+        if let Some(fork_info) = self.fork_infos.read().unwrap().get(&fork) {
+            forks.extend_from_slice(&fork_info.parents);
+        }
+        let mut seen = HashSet::new();
+        for account_map in forks.iter().filter_map(|fork| account_maps.get(fork)) {
+            for (pubkey, account_info) in account_map.read().unwrap().iter() {
+                if seen.insert(*pubkey) && account_info.lamports != 0 {
+                    scan(pubkey, account_info);
+                }
+            }
+        }
+    }
+
+    /// Remove `fork`'s zero-lamport entry for `pubkey`, unless an ancestor fork holds the
+    /// account with lamports, in which case the entry is what hides it and must stay until
+    /// the fork is squashed
+    fn purge_zero_lamport_account(&self, fork: Fork, pubkey: &Pubkey) {
+        {
+            let account_maps = self.account_index.account_maps.read().unwrap();
+            let account_map = account_maps.get(&fork).unwrap().read().unwrap();
+            if account_map.get(pubkey).map(|info| info.lamports) != Some(0) {
+                return;
+            }
+            let fork_infos = self.fork_infos.read().unwrap();
+            if let Some(fork_info) = fork_infos.get(&fork) {
+                let parent_lamports = fork_info.parents.iter().find_map(|parent_fork| {
+                    let parent_map = account_maps.get(parent_fork)?.read().unwrap();
+                    parent_map.get(pubkey).map(|info| info.lamports)
+                });
+                if parent_lamports.unwrap_or(0) != 0 {
+                    return;
+                }
+            }
+        }
+        self.remove_account_entries(fork, pubkey);
+    }
+
+    /// Store the accounts of each successful transaction, failing with
+    /// `InvalidAccountDataLength` and storing none of a transaction's accounts if any
+    /// holds more than `MAX_PERMITTED_DATA_LENGTH` bytes of data
+    pub fn store_accounts<T>(
         &self,
         fork: Fork,
-        txs: &[Transaction],
+        txs: &[T],
         res: &[Result<()>],
         loaded: &[Result<(InstructionAccounts, InstructionLoaders)>],
-    ) {
-        for (i, raccs) in loaded.iter().enumerate() {
-            if res[i].is_err() || raccs.is_err() {
-                continue;
-            }
+        loader_cache: &LoaderCache,
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        loaded
+            .iter()
+            .enumerate()
+            .map(|(i, raccs)| {
+                if res[i].is_err() || raccs.is_err() {
+                    return res[i].clone();
+                }
 
-            let tx = &txs[i];
-            let acc = raccs.as_ref().unwrap();
-            for (key, account) in tx.account_keys.iter().zip(acc.0.iter()) {
-                self.store(fork, key, account);
-            }
-        }
+                let tx = txs[i].borrow();
+                let acc = raccs.as_ref().unwrap();
+                check_data_lengths(acc.0.iter())?;
+                for (key, account) in tx.account_keys.iter().zip(acc.0.iter()) {
+                    self.store(fork, key, account);
+                    loader_cache.invalidate(key, account);
+                }
+                Ok(())
+            })
+            .collect()
     }
 
     fn load_tx_accounts(
         &self,
         fork: Fork,
         tx: &Transaction,
+        fee: u64,
         error_counters: &mut ErrorCounters,
     ) -> Result<Vec<Account>> {
         // Copy all the accounts
@@ -609,11 +881,11 @@ impl AccountsDB {
             if called_accounts.is_empty() || called_accounts[0].lamports == 0 {
                 error_counters.account_not_found += 1;
                 Err(TransactionError::AccountNotFound)
-            } else if called_accounts[0].lamports < tx.fee {
+            } else if called_accounts[0].lamports < fee {
                 error_counters.insufficient_funds += 1;
                 Err(TransactionError::InsufficientFundsForFee)
             } else {
-                called_accounts[0].lamports -= tx.fee;
+                called_accounts[0].lamports -= fee;
                 Ok(called_accounts)
             }
         }
@@ -647,7 +919,11 @@ impl AccountsDB {
                     return Err(TransactionError::AccountNotFound);
                 }
             };
-            if !program.executable || program.owner == Pubkey::default() {
+            if !program.executable {
+                error_counters.invalid_program_for_execution += 1;
+                return Err(TransactionError::InvalidProgramForExecution);
+            }
+            if program.owner == Pubkey::default() {
                 error_counters.account_not_found += 1;
                 return Err(TransactionError::AccountNotFound);
             }
@@ -660,11 +936,13 @@ impl AccountsDB {
         Ok(accounts)
     }
 
-    /// For each program_id in the transaction, load its loaders.
+    /// For each program_id in the transaction, load its loaders, from `loader_cache` if
+    /// the program executed in this fork before
     fn load_loaders(
         &self,
         fork: Fork,
         tx: &Transaction,
+        loader_cache: &LoaderCache,
         error_counters: &mut ErrorCounters,
     ) -> Result<Vec<Vec<(Pubkey, Account)>>> {
         tx.instructions
@@ -675,24 +953,37 @@ impl AccountsDB {
                     return Err(TransactionError::AccountNotFound);
                 }
                 let program_id = tx.program_ids[ix.program_ids_index as usize];
-                self.load_executable_accounts(fork, &program_id, error_counters)
+                if let Some(chain) = loader_cache.get(&program_id) {
+                    return Ok(chain);
+                }
+                let generation = loader_cache.generation();
+                let chain = self.load_executable_accounts(fork, &program_id, error_counters)?;
+                loader_cache.insert(program_id, chain.clone(), generation);
+                Ok(chain)
             })
             .collect()
     }
 
-    fn load_accounts(
+    fn load_accounts<T>(
         &self,
         fork: Fork,
-        txs: &[Transaction],
+        txs: &[T],
         lock_results: Vec<Result<()>>,
+        fee_calculator: &FeeCalculator,
+        loader_cache: &LoaderCache,
         error_counters: &mut ErrorCounters,
-    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>> {
+    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>>
+    where
+        T: Borrow<Transaction>,
+    {
         txs.iter()
             .zip(lock_results.into_iter())
             .map(|etx| match etx {
                 (tx, Ok(())) => {
-                    let accounts = self.load_tx_accounts(fork, tx, error_counters)?;
-                    let loaders = self.load_loaders(fork, tx, error_counters)?;
+                    let tx = tx.borrow();
+                    let fee = fee_calculator.calculate_fee(tx);
+                    let accounts = self.load_tx_accounts(fork, tx, fee, error_counters)?;
+                    let loaders = self.load_loaders(fork, tx, loader_cache, error_counters)?;
                     Ok((accounts, loaders))
                 }
                 (_, Err(e)) => Err(e),
@@ -736,11 +1027,12 @@ impl AccountsDB {
 
         let account_maps = self.account_index.account_maps.read().unwrap();
         let mut account_map = account_maps.get(&fork).unwrap().write().unwrap();
+        let account_map = Arc::make_mut(&mut account_map);
         for parent_fork in parents.iter() {
             let parent_map = account_maps.get(&parent_fork).unwrap().read().unwrap();
             for (pubkey, account_info) in parent_map.iter() {
                 if account_map.get(pubkey).is_none() {
-                    self.insert_account_entry(&pubkey, &account_info, &mut account_map);
+                    self.insert_account_entry(&pubkey, &account_info, account_map);
                 }
             }
         }
@@ -802,6 +1094,28 @@ impl Accounts {
             .filter(|acc| acc.lamports != 0)
     }
 
+    /// Load the owner of each of `pubkeys` as of `fork`, or None if it has no account,
+    /// without reading any account data
+    pub fn load_owners_slow(&self, fork: Fork, pubkeys: &[Pubkey]) -> Vec<Option<Pubkey>> {
+        pubkeys
+            .iter()
+            .map(|pubkey| {
+                self.accounts_db
+                    .load_with(fork, pubkey, true, |account_info| {
+                        if account_info.lamports == 0 {
+                            None
+                        } else {
+                            Some(
+                                self.accounts_db
+                                    .get_account_owner(account_info.id, account_info.offset),
+                            )
+                        }
+                    })
+                    .and_then(|owner| owner)
+            })
+            .collect()
+    }
+
     /// Slow because lock is held for 1 operation instead of many
     pub fn load_slow_no_parent(&self, fork: Fork, pubkey: &Pubkey) -> Option<Account> {
         self.accounts_db
@@ -809,6 +1123,23 @@ impl Accounts {
             .filter(|acc| acc.lamports != 0)
     }
 
+    /// Load every account owned by `program_id` as of `fork`, including those last
+    /// stored in a parent fork. Each account is reloaded so a newer store in a descendant,
+    /// e.g. one that reassigned or drained it, takes precedence.
+    pub fn load_by_program_slow(&self, fork: Fork, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
+        let mut pubkeys = HashSet::new();
+        self.accounts_db
+            .load_by_program(fork, program_id, true)
+            .into_iter()
+            .filter(|(pubkey, _)| pubkeys.insert(*pubkey))
+            .filter_map(|(pubkey, _)| {
+                self.load_slow(fork, &pubkey)
+                    .filter(|account| account.owner == *program_id)
+                    .map(|account| (pubkey, account))
+            })
+            .collect()
+    }
+
     /// Slow because lock is held for 1 operation instead of many
     pub fn load_by_program_slow_no_parent(
         &self,
@@ -822,9 +1153,103 @@ impl Accounts {
             .collect()
     }
 
-    /// Slow because lock is held for 1 operation instead of many
-    pub fn store_slow(&self, fork: Fork, pubkey: &Pubkey, account: &Account) {
+    /// Load every account stored in `fork` itself, ordered by pubkey. Unlike the other
+    /// loaders, accounts drained to zero lamports are included, since storing them was a
+    /// write the fork made.
+    pub fn load_fork_slow_no_parent(&self, fork: Fork) -> Vec<(Pubkey, Account)> {
+        let mut accounts = self.accounts_db.load_fork_accounts(fork);
+        accounts.sort_by_key(|(pubkey, _)| *pubkey);
+        accounts
+    }
+
+    /// Return the `n` largest balances visible from `fork`, largest first with ties
+    /// ordered by pubkey, skipping accounts owned by any of `excluded_owners`. Balances
+    /// come from the index; an account's owner is only read when there are owners to skip.
+    pub fn load_largest_slow(
+        &self,
+        fork: Fork,
+        n: usize,
+        excluded_owners: &[Pubkey],
+    ) -> Vec<(Pubkey, u64)> {
+        if n == 0 {
+            return vec![];
+        }
+        // Min-heap of the largest balances seen so far, so the smallest is evicted first
+        let mut largest = BinaryHeap::new();
+        self.accounts_db.scan_index(fork, |pubkey, account_info| {
+            if !excluded_owners.is_empty() {
+                let owner = self
+                    .accounts_db
+                    .get_account_owner(account_info.id, account_info.offset);
+                if excluded_owners.contains(&owner) {
+                    return;
+                }
+            }
+            largest.push(Reverse((account_info.lamports, Reverse(*pubkey))));
+            if largest.len() > n {
+                largest.pop();
+            }
+        });
+        largest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((lamports, Reverse(pubkey)))| (pubkey, lamports))
+            .collect()
+    }
+
+    /// Return the lamports visible from `fork` summed per owning program
+    pub fn load_lamports_by_owner_slow(&self, fork: Fork) -> HashMap<Pubkey, u64> {
+        let mut totals = HashMap::new();
+        self.accounts_db.scan_index(fork, |_, account_info| {
+            let owner = self
+                .accounts_db
+                .get_account_owner(account_info.id, account_info.offset);
+            *totals.entry(owner).or_insert(0) += account_info.lamports;
+        });
+        totals
+    }
+
+    /// Return the lamports of every account visible from `fork`, summed from the index
+    /// without reading any account
+    pub fn total_lamports(&self, fork: Fork) -> u64 {
+        let mut total = 0;
+        self.accounts_db
+            .scan_index(fork, |_, account_info| total += account_info.lamports);
+        total
+    }
+
+    /// Slow because lock is held for 1 operation instead of many. Fails with
+    /// `InvalidAccountDataLength` if the account holds more than `MAX_PERMITTED_DATA_LENGTH`
+    /// bytes of data.
+    pub fn store_slow(&self, fork: Fork, pubkey: &Pubkey, account: &Account) -> Result<()> {
+        check_data_lengths(Some(account))?;
         self.accounts_db.store(fork, pubkey, account);
+        Ok(())
+    }
+
+    /// Store all of `accounts` in one pass, or none of them if any holds more than
+    /// `MAX_PERMITTED_DATA_LENGTH` bytes of data
+    pub fn store_many(&self, fork: Fork, accounts: &[(Pubkey, Account)]) -> Result<()> {
+        check_data_lengths(accounts.iter().map(|(_, account)| account))?;
+        self.accounts_db.store_many(fork, accounts);
+        Ok(())
+    }
+
+    /// Drop `fork`'s entry for `pubkey` if it holds zero lamports and no ancestor fork
+    /// holds the account with lamports
+    pub fn purge_zero_lamport_account(&self, fork: Fork, pubkey: &Pubkey) {
+        self.accounts_db.purge_zero_lamport_account(fork, pubkey);
+    }
+
+    /// Capture the accounts visible from `fork` as they are now. Later stores to the fork
+    /// or its parents don't show through the view.
+    pub fn read_view(accounts: &Arc<Accounts>, fork: Fork) -> AccountsReadView {
+        let (index, pinned_storage) = accounts.accounts_db.pin_index(fork);
+        AccountsReadView {
+            index,
+            pinned_storage,
+            accounts: accounts.clone(),
+        }
     }
 
     fn lock_account(
@@ -853,17 +1278,15 @@ impl Accounts {
         result: &Result<()>,
         account_locks: &mut HashMap<Fork, HashSet<Pubkey>>,
     ) {
-        match result {
-            Err(TransactionError::AccountInUse) => (),
-            _ => {
-                if let Some(locks) = account_locks.get_mut(&fork) {
-                    for k in &tx.account_keys {
-                        locks.remove(k);
-                    }
-                    if locks.is_empty() {
-                        account_locks.remove(&fork);
-                    }
-                }
+        if result.is_err() {
+            return;
+        }
+        if let Some(locks) = account_locks.get_mut(&fork) {
+            for k in &tx.account_keys {
+                locks.remove(k);
+            }
+            if locks.is_empty() {
+                account_locks.remove(&fork);
             }
         }
     }
@@ -873,18 +1296,30 @@ impl Accounts {
     }
 
     /// This function will prevent multiple threads from modifying the same account state at the
-    /// same time
+    /// same time. Transactions whose entry in `results` is already an error are not locked.
+    /// Locks are taken in the order of `txs`, so of two transactions in the batch that share
+    /// an account, the earlier one always gets it and the later fails with `AccountInUse`.
     #[must_use]
-    pub fn lock_accounts(&self, fork: Fork, txs: &[Transaction]) -> Vec<Result<()>> {
+    pub fn lock_accounts<T>(
+        &self,
+        fork: Fork,
+        txs: &[T],
+        results: Vec<Result<()>>,
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
         let mut account_locks = self.account_locks.lock().unwrap();
         let mut error_counters = ErrorCounters::default();
         let rv = txs
             .iter()
-            .map(|tx| {
+            .zip(results.into_iter())
+            .map(|(tx, result)| {
+                result?;
                 Self::lock_account(
                     fork,
                     &mut account_locks,
-                    &tx.account_keys,
+                    &tx.borrow().account_keys,
                     &mut error_counters,
                 )
             })
@@ -898,39 +1333,62 @@ impl Accounts {
         rv
     }
 
-    /// Once accounts are unlocked, new transactions that modify that state can enter the pipeline
-    pub fn unlock_accounts(&self, fork: Fork, txs: &[Transaction], results: &[Result<()>]) {
+    /// Once accounts are unlocked, new transactions that modify that state can enter the pipeline.
+    /// `results` are the ones `lock_accounts` returned for `txs`, so only the transactions
+    /// it locked are unlocked.
+    pub fn unlock_accounts<T>(&self, fork: Fork, txs: &[T], results: &[Result<()>])
+    where
+        T: Borrow<Transaction>,
+    {
         let mut account_locks = self.account_locks.lock().unwrap();
         debug!("bank unlock accounts");
-        txs.iter()
-            .zip(results.iter())
-            .for_each(|(tx, result)| Self::unlock_account(fork, tx, result, &mut account_locks));
+        txs.iter().zip(results.iter()).for_each(|(tx, result)| {
+            Self::unlock_account(fork, tx.borrow(), result, &mut account_locks)
+        });
     }
 
     pub fn has_accounts(&self, fork: Fork) -> bool {
         self.accounts_db.has_accounts(fork)
     }
 
-    pub fn load_accounts(
+    pub fn load_accounts<T>(
         &self,
         fork: Fork,
-        txs: &[Transaction],
+        txs: &[T],
         results: Vec<Result<()>>,
+        fee_calculator: &FeeCalculator,
+        loader_cache: &LoaderCache,
         error_counters: &mut ErrorCounters,
-    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>> {
-        self.accounts_db
-            .load_accounts(fork, txs, results, error_counters)
+    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>>
+    where
+        T: Borrow<Transaction>,
+    {
+        self.accounts_db.load_accounts(
+            fork,
+            txs,
+            results,
+            fee_calculator,
+            loader_cache,
+            error_counters,
+        )
     }
 
-    /// Store the accounts into the DB
-    pub fn store_accounts(
+    /// Store the accounts into the DB, returning each transaction's result updated with
+    /// any failure to store its accounts. Cached loader chains that include a stored
+    /// account are dropped from `loader_cache`.
+    pub fn store_accounts<T>(
         &self,
         fork: Fork,
-        txs: &[Transaction],
+        txs: &[T],
         res: &[Result<()>],
         loaded: &[Result<(InstructionAccounts, InstructionLoaders)>],
-    ) {
-        self.accounts_db.store_accounts(fork, txs, res, loaded)
+        loader_cache: &LoaderCache,
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        self.accounts_db
+            .store_accounts(fork, txs, res, loaded, loader_cache)
     }
 
     pub fn increment_transaction_count(&self, fork: Fork, tx_count: usize) {
@@ -956,6 +1414,59 @@ impl Accounts {
     }
 }
 
+/// A point-in-time view of the account index visible from one fork. It shares the fork's
+/// index maps rather than copying them, and reads go straight to storage, which the view
+/// pins, so they never wait on the index locks that stores take.
+pub struct AccountsReadView {
+    /// The index maps of the fork and its parents, newest first
+    index: Vec<Arc<HashMap<Pubkey, AccountInfo>>>,
+    pinned_storage: Vec<usize>,
+    accounts: Arc<Accounts>,
+}
+
+impl AccountsReadView {
+    pub fn load(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.index
+            .iter()
+            .find_map(|account_map| account_map.get(pubkey))
+            .filter(|account_info| account_info.lamports != 0)
+            .map(|account_info| {
+                self.accounts
+                    .accounts_db
+                    .get_account(account_info.id, account_info.offset)
+            })
+    }
+
+    pub fn load_by_program(&self, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
+        let mut seen = HashSet::new();
+        self.index
+            .iter()
+            .flat_map(|account_map| account_map.iter())
+            .filter(|(pubkey, _)| seen.insert(**pubkey))
+            .filter(|(_, account_info)| account_info.lamports != 0)
+            .filter_map(|(pubkey, account_info)| {
+                let account = self
+                    .accounts
+                    .accounts_db
+                    .get_account(account_info.id, account_info.offset);
+                if account.owner == *program_id {
+                    Some((*pubkey, account))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for AccountsReadView {
+    fn drop(&mut self) {
+        self.accounts
+            .accounts_db
+            .unpin_storage(&self.pinned_storage);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // TODO: all the bank tests are bank specific, issue: 2194
@@ -976,20 +1487,37 @@ mod tests {
         });
     }
 
-    fn load_accounts(
+    fn load_accounts_with_fee(
         tx: Transaction,
         ka: &Vec<(Pubkey, Account)>,
+        fee_calculator: &FeeCalculator,
         error_counters: &mut ErrorCounters,
     ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>> {
         let accounts = Accounts::new(0, None);
         for ka in ka.iter() {
-            accounts.store_slow(0, &ka.0, &ka.1);
+            accounts.store_slow(0, &ka.0, &ka.1).unwrap();
         }
 
-        let res = accounts.load_accounts(0, &[tx], vec![Ok(())], error_counters);
+        let res = accounts.load_accounts(
+            0,
+            &[tx],
+            vec![Ok(())],
+            fee_calculator,
+            &LoaderCache::default(),
+            error_counters,
+        );
         res
     }
 
+    fn load_accounts(
+        tx: Transaction,
+        ka: &Vec<(Pubkey, Account)>,
+        error_counters: &mut ErrorCounters,
+    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>> {
+        let fee_calculator = FeeCalculator::default();
+        load_accounts_with_fee(tx, ka, &fee_calculator, error_counters)
+    }
+
     #[test]
     fn test_load_accounts_no_key() {
         let accounts: Vec<(Pubkey, Account)> = Vec::new();
@@ -1089,7 +1617,11 @@ mod tests {
             instructions,
         );
 
-        let loaded_accounts = load_accounts(tx, &accounts, &mut error_counters);
+        let fee_calculator = FeeCalculator::new(10);
+        assert_eq!(fee_calculator.calculate_fee(&tx), 10);
+
+        let loaded_accounts =
+            load_accounts_with_fee(tx, &accounts, &fee_calculator, &mut error_counters);
 
         assert_eq!(error_counters.insufficient_funds, 1);
         assert_eq!(loaded_accounts.len(), 1);
@@ -1265,9 +1797,12 @@ mod tests {
 
         let loaded_accounts = load_accounts(tx, &accounts, &mut error_counters);
 
-        assert_eq!(error_counters.account_not_found, 1);
+        assert_eq!(error_counters.invalid_program_for_execution, 1);
         assert_eq!(loaded_accounts.len(), 1);
-        assert_eq!(loaded_accounts[0], Err(TransactionError::AccountNotFound));
+        assert_eq!(
+            loaded_accounts[0],
+            Err(TransactionError::InvalidProgramForExecution)
+        );
     }
 
     #[test]
@@ -1707,7 +2242,7 @@ mod tests {
         let accounts = Accounts::new(0, None);
         let mut vote_account = Account::new(1, 0, &solana_vote_api::id());
         let key = Keypair::new().pubkey();
-        accounts.store_slow(0, &key, &vote_account);
+        accounts.store_slow(0, &key, &vote_account).unwrap();
 
         accounts.new_from_parent(1, 0);
 
@@ -1715,14 +2250,14 @@ mod tests {
         assert_eq!(vote_accounts.len(), 1);
 
         vote_account.lamports = 0;
-        accounts.store_slow(1, &key, &vote_account);
+        accounts.store_slow(1, &key, &vote_account).unwrap();
 
         vote_accounts = accounts.get_vote_accounts(1).collect();
         assert_eq!(vote_accounts.len(), 0);
 
         let mut vote_account1 = Account::new(2, 0, &solana_vote_api::id());
         let key1 = Keypair::new().pubkey();
-        accounts.store_slow(1, &key1, &vote_account1);
+        accounts.store_slow(1, &key1, &vote_account1).unwrap();
 
         accounts.squash(1);
         vote_accounts = accounts.get_vote_accounts(0).collect();
@@ -1731,13 +2266,192 @@ mod tests {
         assert_eq!(vote_accounts.len(), 1);
 
         vote_account1.lamports = 0;
-        accounts.store_slow(1, &key1, &vote_account1);
-        accounts.store_slow(0, &key, &vote_account);
+        accounts.store_slow(1, &key1, &vote_account1).unwrap();
+        accounts.store_slow(0, &key, &vote_account).unwrap();
 
         vote_accounts = accounts.get_vote_accounts(1).collect();
         assert_eq!(vote_accounts.len(), 0);
     }
 
+    #[test]
+    fn test_accounts_load_largest_slow() {
+        let accounts = Accounts::new(0, None);
+        let owner = Pubkey::new(&[2; 32]);
+        let key0 = Pubkey::new(&[10; 32]);
+        let key1 = Pubkey::new(&[11; 32]);
+        let key2 = Pubkey::new(&[12; 32]);
+        accounts
+            .store_slow(0, &key0, &Account::new(5, 0, &Pubkey::default()))
+            .unwrap();
+        accounts
+            .store_slow(0, &key1, &Account::new(7, 0, &owner))
+            .unwrap();
+        accounts
+            .store_slow(0, &key2, &Account::new(3, 0, &Pubkey::default()))
+            .unwrap();
+
+        // A child's stores shadow its parent's, including one that drains an account
+        accounts.new_from_parent(1, 0);
+        accounts
+            .store_slow(1, &key1, &Account::new(0, 0, &owner))
+            .unwrap();
+        accounts
+            .store_slow(1, &key2, &Account::new(6, 0, &owner))
+            .unwrap();
+
+        assert_eq!(
+            accounts.load_largest_slow(0, 2, &[]),
+            vec![(key1, 7), (key0, 5)]
+        );
+        assert_eq!(
+            accounts.load_largest_slow(1, 10, &[]),
+            vec![(key2, 6), (key0, 5)]
+        );
+        assert_eq!(accounts.load_largest_slow(1, 10, &[owner]), vec![(key0, 5)]);
+        assert_eq!(accounts.load_largest_slow(1, 0, &[]), vec![]);
+
+        let totals = accounts.load_lamports_by_owner_slow(1);
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[&Pubkey::default()], 5);
+        assert_eq!(totals[&owner], 6);
+        assert_eq!(accounts.total_lamports(0), 15);
+        assert_eq!(accounts.total_lamports(1), 11);
+    }
+
+    #[test]
+    fn test_accounts_store_slow_oversized() {
+        let accounts = Accounts::new(0, None);
+        let key = Keypair::new().pubkey();
+        let account = Account::new(1, MAX_PERMITTED_DATA_LENGTH, &Pubkey::default());
+        assert_eq!(accounts.store_slow(0, &key, &account), Ok(()));
+
+        let oversized_account = Account::new(2, MAX_PERMITTED_DATA_LENGTH + 1, &Pubkey::default());
+        assert_eq!(
+            accounts.store_slow(0, &key, &oversized_account),
+            Err(TransactionError::InvalidAccountDataLength)
+        );
+        assert_eq!(accounts.load_slow(0, &key).unwrap().lamports, 1);
+    }
+
+    #[test]
+    fn test_accounts_store_accounts_oversized() {
+        let accounts = Accounts::new(0, None);
+        let keypair = Keypair::new();
+        let key0 = keypair.pubkey();
+        let key1 = Keypair::new().pubkey();
+        let tx = Transaction::new_with_compiled_instructions(
+            &[&keypair],
+            &[key1],
+            Hash::default(),
+            0,
+            vec![native_loader::id()],
+            vec![CompiledInstruction::new(1, &(), vec![0, 1])],
+        );
+        let payer = Account::new(1, 0, &Pubkey::default());
+        let oversized_account = Account::new(1, MAX_PERMITTED_DATA_LENGTH + 1, &Pubkey::default());
+        let loaded = vec![Ok((vec![payer, oversized_account], vec![]))];
+
+        // None of the transaction's accounts are stored
+        let results =
+            accounts.store_accounts(0, &[tx], &[Ok(())], &loaded, &LoaderCache::default());
+        assert_eq!(
+            results,
+            vec![Err(TransactionError::InvalidAccountDataLength)]
+        );
+        assert!(accounts.load_slow(0, &key0).is_none());
+        assert!(accounts.load_slow(0, &key1).is_none());
+    }
+
+    #[test]
+    fn test_accounts_loader_cache() {
+        let accounts = Accounts::new(0, None);
+        let loader_cache = LoaderCache::default();
+        let fee_calculator = FeeCalculator::default();
+        let mut error_counters = ErrorCounters::default();
+
+        let keypair = Keypair::new();
+        let key0 = keypair.pubkey();
+        let program_id = Keypair::new().pubkey();
+        accounts
+            .store_slow(0, &key0, &Account::new(1, 0, &Pubkey::default()))
+            .unwrap();
+        let mut program = Account::new(40, 1, &native_loader::id());
+        program.executable = true;
+        accounts.store_slow(0, &program_id, &program).unwrap();
+
+        let tx = Transaction::new_with_compiled_instructions(
+            &[&keypair],
+            &[],
+            Hash::default(),
+            0,
+            vec![program_id],
+            vec![CompiledInstruction::new(0, &(), vec![0])],
+        );
+        let load = |loader_cache: &LoaderCache, error_counters: &mut ErrorCounters| {
+            let loaded = accounts.load_accounts(
+                0,
+                &[tx.clone()],
+                vec![Ok(())],
+                &fee_calculator,
+                loader_cache,
+                error_counters,
+            );
+            loaded[0].clone().unwrap().1
+        };
+
+        // The first load caches the program's chain
+        assert_eq!(
+            load(&loader_cache, &mut error_counters),
+            vec![vec![(program_id, program.clone())]]
+        );
+        assert!(loader_cache.contains(&program_id));
+
+        // A transaction storing a user account leaves the cache alone
+        let loaded = vec![Ok((vec![Account::new(2, 0, &Pubkey::default())], vec![]))];
+        accounts.store_accounts(0, &[tx.clone()], &[Ok(())], &loaded, &loader_cache);
+        assert!(loader_cache.contains(&program_id));
+
+        // Redeploying the program, even in a later fork, drops its chain
+        accounts.new_from_parent(1, 0);
+        let owner = Keypair::new();
+        let redeploy_tx = Transaction::new_with_compiled_instructions(
+            &[&owner],
+            &[program_id],
+            Hash::default(),
+            0,
+            vec![native_loader::id()],
+            vec![CompiledInstruction::new(1, &(), vec![0, 1])],
+        );
+        let mut redeployed = program.clone();
+        redeployed.data = vec![7];
+        let loaded = vec![Ok((
+            vec![Account::new(1, 0, &Pubkey::default()), redeployed.clone()],
+            vec![],
+        ))];
+        accounts.store_accounts(1, &[redeploy_tx], &[Ok(())], &loaded, &loader_cache);
+        assert!(!loader_cache.contains(&program_id));
+
+        let loaded = accounts.load_accounts(
+            1,
+            &[tx],
+            vec![Ok(())],
+            &fee_calculator,
+            &loader_cache,
+            &mut error_counters,
+        );
+        assert_eq!(
+            loaded[0].clone().unwrap().1,
+            vec![vec![(program_id, redeployed.clone())]]
+        );
+        assert!(loader_cache.contains(&program_id));
+
+        // A chain loaded before an invalidation isn't cached after it
+        let generation = loader_cache.generation();
+        loader_cache.invalidate(&program_id, &redeployed);
+        loader_cache.insert(program_id, vec![(program_id, program)], generation);
+        assert!(!loader_cache.contains(&program_id));
+    }
+
     #[test]
     fn test_account_vote() {
         let paths = get_tmp_accounts_path!();
@@ -1796,6 +2510,30 @@ mod tests {
         assert_eq!(accounts.hash_internal_state(0), None);
     }
 
+    #[test]
+    fn test_accounts_hash_internal_state_from_account_hashes() {
+        let paths = get_tmp_accounts_path!();
+        let accounts = AccountsDB::new(0, &paths.paths);
+        let key0 = Keypair::new().pubkey();
+        let key1 = Keypair::new().pubkey();
+        let account0 = Account::new(1, 0, &key0);
+        let account1 = Account::new(2, 3, &key1);
+        accounts.store(0, &key0, &account0);
+        accounts.store(0, &key1, &account1);
+
+        let mut ordered_hashes = BTreeMap::new();
+        ordered_hashes.insert(key0, hash_account(&key0, &account0));
+        ordered_hashes.insert(key1, hash_account(&key1, &account1));
+        assert_eq!(
+            accounts.hash_internal_state(0),
+            Some(hash(&serialize(&ordered_hashes).unwrap()))
+        );
+        assert_ne!(
+            hash_account(&key0, &account0),
+            hash_account(&key1, &account0)
+        );
+    }
+
     #[test]
     #[should_panic]
     fn test_accountsdb_duplicate_fork_should_panic() {
@@ -103,6 +103,26 @@ pub fn deserialize_account(
     })
 }
 
+/// Read just the owner of the account serialized at `index`, skipping over its data
+pub fn deserialize_account_owner(
+    src_slice: &[u8],
+    index: usize,
+    current_offset: usize,
+) -> Result<Pubkey> {
+    let mut at = index;
+
+    let size = read_u64(&mut at, &src_slice);
+    let len = size as usize;
+    assert!(current_offset >= at + len);
+
+    // Skip the lamports and the data
+    at += mem::size_of::<u64>() + len - get_account_size_static();
+
+    let mut pubkey = vec![0; mem::size_of::<Pubkey>()];
+    read_bytes(&mut at, &mut pubkey, &src_slice, mem::size_of::<Pubkey>());
+    Ok(Pubkey::new(&pubkey))
+}
+
 impl<T> AppendVec<T>
 where
     T: Default,
@@ -199,6 +219,15 @@ where
         )
     }
 
+    pub fn get_account_owner(&self, index: u64) -> Result<Pubkey> {
+        let index = index as usize;
+        deserialize_account_owner(
+            &self.mmap[..],
+            index,
+            self.current_offset.load(Ordering::Relaxed),
+        )
+    }
+
     pub fn append_account(&self, account: &Account) -> Option<u64> {
         let mut mmap_mut = self.mmap_mut.lock().unwrap();
         let data_at = align_up!(
@@ -291,6 +320,19 @@ pub mod tests {
         std::fs::remove_file(path).unwrap();
     }
 
+    #[test]
+    fn test_append_vec_account_owner() {
+        let path = Path::new("append_vec_account_owner");
+        let av: AppendVec<Account> = AppendVec::new(path, true, START_SIZE, INC_SIZE);
+        let owner1 = Pubkey::new(&[1; 32]);
+        let owner2 = Pubkey::new(&[2; 32]);
+        let index1 = av.append_account(&Account::new(1, 0, &owner1)).unwrap();
+        let index2 = av.append_account(&Account::new(1, 100, &owner2)).unwrap();
+        assert_eq!(av.get_account_owner(index1).unwrap(), owner1);
+        assert_eq!(av.get_account_owner(index2).unwrap(), owner2);
+        std::fs::remove_file(path).unwrap();
+    }
+
     #[test]
     fn test_grow_append_vec() {
         let path = Path::new("grow");
@@ -3,29 +3,58 @@
 //! on behalf of the caller, and a low-level API for when they have
 //! already been signed and verified.
 
-use crate::accounts::{Accounts, ErrorCounters, InstructionAccounts, InstructionLoaders};
+use crate::accounts::{
+    self, Accounts, AccountsReadView, ErrorCounters, InstructionAccounts, InstructionLoaders,
+    LoaderCache,
+};
 use crate::blockhash_queue::BlockhashQueue;
-use crate::runtime::{ProcessInstruction, Runtime};
-use crate::status_cache::StatusCache;
+use crate::runtime::{merge_program_timings, sanitize_transaction, ProcessInstruction, Runtime};
+pub use crate::runtime::{ProgramTiming, ProgramTimings};
+use crate::status_cache::{StatusCache, StatusCacheConfig, StatusCacheStats};
+use crate::verified_transaction::VerifiedTransaction;
 use bincode::serialize;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use log::*;
+use rayon::prelude::*;
+use rayon::ThreadPool;
 use solana_metrics::counter::Counter;
+use solana_metrics::influxdb;
+use solana_rewards_api::rewards_state::RewardsState;
 use solana_sdk::account::Account;
-use solana_sdk::genesis_block::GenesisBlock;
+use solana_sdk::fee_calculator::FeeCalculator;
+use solana_sdk::genesis_block::{GenesisBlock, RewardsPool};
 use solana_sdk::hash::{extend_and_hash, Hash};
+use solana_sdk::inflation_pool;
 use solana_sdk::native_loader;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signature::{Keypair, KeypairUtil, Signature};
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
 use solana_sdk::system_transaction::SystemTransaction;
+use solana_sdk::sysvar::clock::{self, Clock};
+use solana_sdk::sysvar::slot_hashes::{self, SlotHashes};
 use solana_sdk::timing::{duration_as_us, MAX_RECENT_BLOCKHASHES, NUM_TICKS_PER_SECOND};
-use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::transaction::{Transaction, TransactionError, MAX_INSTRUCTION_DATA_LEN};
 use solana_vote_api::vote_instruction::Vote;
 use solana_vote_api::vote_state::{Lockout, VoteState};
+use std::borrow::Borrow;
 use std::result;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Length of a year in seconds, accounting for leap years
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Reasons the bank might reject a tick or a request about its history.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BankError {
+    /// The tick's height doesn't directly follow the bank's current tick height
+    TickHeightMismatch { expected: u64, actual: u64 },
+
+    /// The bank isn't the root slot-0 bank a genesis block creates
+    NotGenesisBank { slot: u64 },
+}
 
 /// Reasons a transaction might be rejected.
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
@@ -71,16 +100,12 @@ impl EpochSchedule {
     }
 
     /// get the epoch for which the given slot should save off
-    ///  information about stakers
+    ///  information about stakers: the epoch stakers_slot_offset slots later
     pub fn get_stakers_epoch(&self, slot: u64) -> u64 {
-        if slot < self.first_normal_slot {
-            // until we get to normal slots, behave as if stakers_slot_offset == slots_per_epoch
-
-            self.get_epoch_and_slot_index(slot).0 + 1
-        } else {
-            self.first_normal_epoch
-                + (slot - self.first_normal_slot + self.stakers_slot_offset) / self.slots_per_epoch
-        }
+        // during warmup the offset may span several of the short warmup epochs, so
+        //  walk it through the schedule instead of dividing by slots_per_epoch
+        self.get_epoch_and_slot_index(slot.saturating_add(self.stakers_slot_offset))
+            .0
     }
 
     /// get epoch and offset into the epoch for the given slot
@@ -104,6 +129,56 @@ impl EpochSchedule {
     }
 }
 
+/// A consistent snapshot of where a bank sits in the epoch schedule.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EpochInfo {
+    /// The epoch the bank's slot falls in
+    pub epoch: u64,
+
+    /// The offset of the bank's slot into its epoch
+    pub slot_index: u64,
+
+    /// The number of slots in the bank's epoch, a power of two during warmup
+    pub slots_in_epoch: u64,
+
+    /// The bank's slot
+    pub absolute_slot: u64,
+
+    /// The number of ticks since genesis
+    pub block_height: u64,
+}
+
+/// Whether transactions may still use a blockhash, as reported by `Bank::blockhash_status`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BlockhashStatus {
+    /// The blockhash is accepted; `age` hashes have been registered since it
+    Valid { age: u64 },
+
+    /// The blockhash was registered but has aged out, so a transaction using it needs a
+    /// newer one before it's resent
+    Expired,
+
+    /// The blockhash was never registered, or aged out too long ago to be remembered
+    Unknown,
+}
+
+/// Which accounts `Bank::largest_accounts` ranks. The default ranks every account.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct AccountFilter {
+    /// Accounts owned by any of these programs are skipped
+    pub excluded_owners: Vec<Pubkey>,
+}
+
+impl AccountFilter {
+    /// Skip accounts owned by any of `owners`, e.g. vote accounts when only circulating
+    /// balances are wanted
+    pub fn excluding(owners: &[Pubkey]) -> Self {
+        Self {
+            excluded_owners: owners.to_vec(),
+        }
+    }
+}
+
 pub type Result<T> = result::Result<T, TransactionError>;
 
 type BankStatusCache = StatusCache<TransactionError>;
@@ -120,8 +195,9 @@ pub struct Bank {
     /// A cache of signature statuses
     status_cache: RwLock<BankStatusCache>,
 
-    /// FIFO queue of `recent_blockhash` items
-    blockhash_queue: RwLock<BlockhashQueue>,
+    /// FIFO queue of `recent_blockhash` items, shared with the parent until either registers
+    /// a new hash
+    blockhash_queue: RwLock<Arc<BlockhashQueue>>,
 
     /// Previous checkpoint of this bank
     parent: RwLock<Option<Arc<Bank>>>,
@@ -132,31 +208,73 @@ pub struct Bank {
     /// Hash of this Bank's parent's state
     parent_hash: Hash,
 
+    /// Hash of the genesis block this Bank descends from
+    genesis_hash: Hash,
+
+    /// The account the genesis block funded as its mint, only recorded by the genesis bank
+    mint_id: Pubkey,
+
     /// Bank tick height
     tick_height: AtomicUsize, // TODO: Use AtomicU64 if/when available
 
     /// The number of ticks in each slot.
     ticks_per_slot: u64,
 
+    /// The maximum number of transactions committed in a slot, if any
+    max_transactions_per_slot: Option<usize>,
+
+    /// Transactions committed or in flight in this slot
+    transaction_count_in_slot: AtomicUsize,
+
+    /// The fewest lamports a new staking account may be created with
+    minimum_delegation: u64,
+
+    /// The fewest lamports a withdrawal may leave in an account without closing it
+    minimum_balance: u64,
+
+    /// Whether fee-less transactions that only move lamports to their source are rejected
+    reject_self_transfers: bool,
+
+    /// The fee schedule currently in effect
+    fee_calculator: FeeCalculator,
+
     /// Bank fork (i.e. slot, i.e. block)
     slot: u64,
 
     /// The pubkey to send transactions fees to.
     collector_id: Pubkey,
 
+    /// Fees deposited to the collector by this bank's own commits
+    collected_fees: AtomicUsize, // TODO: Use AtomicU64 if/when available
+
     /// initialized from genesis
     epoch_schedule: EpochSchedule,
 
     /// staked nodes on epoch boundaries, saved off when a bank.slot() is at
-    ///   a leader schedule boundary
-    epoch_vote_accounts: HashMap<u64, HashMap<Pubkey, Account>>,
+    ///   a leader schedule boundary; shared with the parent until a new epoch is added
+    epoch_vote_accounts: Arc<HashMap<u64, HashMap<Pubkey, Account>>>,
+
+    /// vote accounts as of this bank's frozen state, keyed by the stakers epoch a child
+    ///   entering it would save them off for; every such child copies the same snapshot
+    frozen_epoch_vote_accounts: RwLock<HashMap<u64, HashMap<Pubkey, Account>>>,
 
     /// A boolean reflecting whether any entries were recorded into the PoH
     /// stream for the slot == self.slot
     is_delta: AtomicBool,
 
+    /// Whether signature verification is skipped, only ever set by test and bench
+    /// harnesses. Stored inverted so that a default bank verifies.
+    skip_signature_verification: AtomicBool,
+
     /// The runtime executation environment
     runtime: Runtime,
+
+    /// Execution timings of the instructions this bank executed, by program id
+    program_timings: RwLock<ProgramTimings>,
+
+    /// Loader chains of the programs executed in this fork, dropped when a store touches
+    /// one of their accounts
+    loader_cache: LoaderCache,
 }
 
 impl Default for BlockhashQueue {
@@ -175,31 +293,60 @@ impl Bank {
         bank.accounts = Arc::new(Accounts::new(bank.slot, paths));
         bank.process_genesis_block(genesis_block);
 
-        // genesis needs stakes for all epochs up to the epoch implied by
-        //  slot = 0 and genesis configuration
+        // genesis needs stakes for every epoch from its own through the epoch implied by
+        //  slot = 0 and genesis configuration; later banks add each epoch as they cross
+        //  into it, since get_stakers_epoch never skips one
         let vote_accounts: HashMap<_, _> = bank.vote_accounts().collect();
-        for i in 0..=bank.get_stakers_epoch(bank.slot) {
-            bank.epoch_vote_accounts.insert(i, vote_accounts.clone());
+        let (epoch, _) = bank.get_epoch_and_slot_index(bank.slot);
+        let stakers_epoch = bank.get_stakers_epoch(bank.slot);
+        let epoch_vote_accounts = Arc::make_mut(&mut bank.epoch_vote_accounts);
+        for i in epoch..=stakers_epoch {
+            epoch_vote_accounts.insert(i, vote_accounts.clone());
         }
 
         bank
     }
 
+    /// Create a bank whose signature caches, and those of its descendants, are bounded by
+    /// `config`
+    pub fn new_with_status_cache_config(
+        genesis_block: &GenesisBlock,
+        config: StatusCacheConfig,
+    ) -> Self {
+        let bank = Self::new(genesis_block);
+        *bank.status_cache.write().unwrap() =
+            BankStatusCache::new_with_config(&Hash::default(), config);
+        bank
+    }
+
     /// Create a new bank that points to an immutable checkpoint of another bank.
     pub fn new_from_parent(parent: &Arc<Bank>, collector_id: &Pubkey, slot: u64) -> Self {
         parent.freeze();
         assert_ne!(slot, parent.slot());
 
         let mut bank = Self::default();
+        bank.status_cache = RwLock::new(BankStatusCache::new_with_config(
+            &Hash::default(),
+            parent.status_cache.read().unwrap().config(),
+        ));
         bank.blockhash_queue = RwLock::new(parent.blockhash_queue.read().unwrap().clone());
         bank.tick_height
             .store(parent.tick_height.load(Ordering::SeqCst), Ordering::SeqCst);
         bank.ticks_per_slot = parent.ticks_per_slot;
+        bank.max_transactions_per_slot = parent.max_transactions_per_slot;
+        bank.minimum_delegation = parent.minimum_delegation;
+        bank.minimum_balance = parent.minimum_balance;
+        bank.reject_self_transfers = parent.reject_self_transfers;
+        bank.fee_calculator = parent.fee_calculator.clone();
         bank.epoch_schedule = parent.epoch_schedule;
+        bank.skip_signature_verification =
+            AtomicBool::new(parent.skip_signature_verification.load(Ordering::Relaxed));
+        bank.loader_cache = LoaderCache::new_from_parent(&parent.loader_cache);
 
         bank.slot = slot;
         bank.parent = RwLock::new(Some(parent.clone()));
         bank.parent_hash = parent.hash();
+        bank.genesis_hash = parent.genesis_hash;
         bank.collector_id = *collector_id;
 
         // Accounts needs a unique id
@@ -209,29 +356,63 @@ impl Bank {
         bank.accounts
             .new_from_parent(bank.accounts_id, parent.accounts_id);
 
-        bank.epoch_vote_accounts = {
-            let mut epoch_vote_accounts = parent.epoch_vote_accounts.clone();
-            let epoch = bank.get_stakers_epoch(bank.slot);
-            // update epoch_vote_states cache
-            //  if my parent didn't populate for this epoch, we've
-            //  crossed a boundary
-            if epoch_vote_accounts.get(&epoch).is_none() {
-                epoch_vote_accounts.insert(epoch, bank.vote_accounts().collect());
-            }
-            epoch_vote_accounts
-        };
+        bank.epoch_vote_accounts = parent.epoch_vote_accounts.clone();
+        let epoch = bank.get_stakers_epoch(bank.slot);
+        // update epoch_vote_states cache
+        //  if my parent didn't populate for this epoch, we've
+        //  crossed a boundary
+        if bank.epoch_vote_accounts.get(&epoch).is_none() {
+            let vote_accounts = parent.frozen_epoch_vote_accounts(epoch);
+            Arc::make_mut(&mut bank.epoch_vote_accounts).insert(epoch, vote_accounts);
+        }
+
+        bank.update_slot_hashes(parent.slot(), parent.last_blockhash());
+        bank.update_clock();
 
         bank
     }
 
+    /// Write this bank's slot, epoch and tick-derived timestamp to the clock sysvar
+    fn update_clock(&self) {
+        let (epoch, _) = self.get_epoch_and_slot_index(self.slot);
+        let clock = Clock {
+            slot: self.slot,
+            epoch,
+            timestamp: self.tick_height() / NUM_TICKS_PER_SECOND,
+        };
+        self.store_bounded_account(&clock::id(), &clock::create_account(1, &clock));
+    }
+
+    /// Record the last blockhash `slot` registered in the slot_hashes sysvar
+    fn update_slot_hashes(&self, slot: u64, hash: Hash) {
+        let mut account = self
+            .get_account(&slot_hashes::id())
+            .unwrap_or_else(|| slot_hashes::create_account(1));
+        let mut hashes = SlotHashes::from(&account).unwrap_or_default();
+        hashes.add(slot, hash);
+        hashes.to(&mut account).unwrap();
+        self.store_bounded_account(&slot_hashes::id(), &account);
+    }
+
     pub fn collector_id(&self) -> Pubkey {
         self.collector_id
     }
 
+    /// Return the fees collected by this bank, excluding those collected by its parents
+    pub fn collected_fees(&self) -> u64 {
+        self.collected_fees.load(Ordering::SeqCst) as u64
+    }
+
     pub fn slot(&self) -> u64 {
         self.slot
     }
 
+    /// Return the id of this bank's fork in the shared accounts store. The root bank
+    /// is 0; every bank created by `new_from_parent` gets a fresh id.
+    pub fn accounts_id(&self) -> u64 {
+        self.accounts_id
+    }
+
     pub fn hash(&self) -> Hash {
         *self.hash.read().unwrap()
     }
@@ -246,7 +427,54 @@ impl Bank {
         if *hash == Hash::default() {
             //  freeze is a one-way trip, idempotent
             *hash = self.hash_internal_state();
+
+            // the next slot starts a new stakers epoch, snapshot the stakes it will save off
+            let epoch = self.get_stakers_epoch(self.slot + 1);
+            if self.epoch_vote_accounts.get(&epoch).is_none() {
+                self.frozen_epoch_vote_accounts(epoch);
+            }
+
+            self.submit_program_timings();
+        }
+    }
+
+    fn submit_program_timings(&self) {
+        for (program_id, timing) in self.program_timings.read().unwrap().iter() {
+            solana_metrics::submit(
+                influxdb::Point::new("bank-program_timings")
+                    .add_tag(
+                        "program_id",
+                        influxdb::Value::String(program_id.to_string()),
+                    )
+                    .add_field("slot", influxdb::Value::Integer(self.slot as i64))
+                    .add_field("count", influxdb::Value::Integer(timing.count as i64))
+                    .add_field("total_us", influxdb::Value::Integer(timing.total_us as i64))
+                    .add_field("errors", influxdb::Value::Integer(timing.errors as i64))
+                    .to_owned(),
+            );
+        }
+    }
+
+    /// Return how many instructions each program executed in this bank, how long they took
+    /// and how many failed. Banks don't inherit their parent's timings.
+    pub fn program_timings(&self) -> ProgramTimings {
+        self.program_timings.read().unwrap().clone()
+    }
+
+    /// Return the vote accounts a child entering stakers epoch `epoch` saves off. The
+    /// first call snapshots this bank's vote accounts; later calls return that snapshot, so
+    /// siblings agree however the forks below them diverge.
+    fn frozen_epoch_vote_accounts(&self, epoch: u64) -> HashMap<Pubkey, Account> {
+        if let Some(vote_accounts) = self.frozen_epoch_vote_accounts.read().unwrap().get(&epoch) {
+            return vote_accounts.clone();
         }
+        let vote_accounts = self.vote_accounts().collect();
+        self.frozen_epoch_vote_accounts
+            .write()
+            .unwrap()
+            .entry(epoch)
+            .or_insert(vote_accounts)
+            .clone()
     }
 
     /// squash the parent's state up into this Bank,
@@ -280,17 +508,16 @@ impl Bank {
 
         // Bootstrap leader collects fees until `new_from_parent` is called.
         self.collector_id = genesis_block.bootstrap_leader_id;
+        self.mint_id = genesis_block.mint_id;
 
         let mint_lamports = genesis_block.lamports - genesis_block.bootstrap_leader_lamports;
-        self.deposit(&genesis_block.mint_id, mint_lamports);
-
         let bootstrap_leader_lamports = 1;
         let bootstrap_leader_stake =
             genesis_block.bootstrap_leader_lamports - bootstrap_leader_lamports;
-        self.deposit(
-            &genesis_block.bootstrap_leader_id,
-            bootstrap_leader_lamports,
-        );
+        self.deposit_many(&[
+            (genesis_block.mint_id, mint_lamports),
+            (genesis_block.bootstrap_leader_id, bootstrap_leader_lamports),
+        ]);
 
         // Construct a vote account for the bootstrap_leader such that the leader_scheduler
         // will be forced to select it as the leader for height 0
@@ -307,18 +534,36 @@ impl Bank {
             .serialize(&mut bootstrap_leader_vote_account.data)
             .unwrap();
 
-        self.accounts.store_slow(
-            self.accounts_id,
+        self.store_bounded_account(
             &genesis_block.bootstrap_leader_vote_account_id,
             &bootstrap_leader_vote_account,
         );
 
-        self.blockhash_queue
-            .write()
-            .unwrap()
-            .genesis_hash(&genesis_block.hash());
+        self.genesis_hash = genesis_block.hash();
+        Arc::make_mut(&mut self.blockhash_queue.write().unwrap()).genesis_hash(&self.genesis_hash);
 
         self.ticks_per_slot = genesis_block.ticks_per_slot;
+        self.max_transactions_per_slot = genesis_block
+            .max_transactions_per_slot
+            .map(|max| max as usize);
+        self.minimum_delegation = genesis_block.minimum_delegation.unwrap_or(0);
+        self.minimum_balance = genesis_block.minimum_balance.unwrap_or(0);
+        self.reject_self_transfers = genesis_block.reject_self_transfers;
+        self.fee_calculator = genesis_block.fee_calculator.clone().unwrap_or_default();
+        if let Some(lamports) = genesis_block.inflation_pool_lamports {
+            self.deposit(&inflation_pool::id(), lamports);
+        }
+        if let Some(rewards_pool) = &genesis_block.rewards_pool {
+            let mut rewards_pool_account = Account::new(
+                rewards_pool.lamports,
+                RewardsState::max_size(),
+                &solana_rewards_api::id(),
+            );
+            RewardsState::new(&rewards_pool.authority_id)
+                .serialize(&mut rewards_pool_account.data)
+                .unwrap();
+            self.store_bounded_account(&rewards_pool.id, &rewards_pool_account);
+        }
 
         self.epoch_schedule = EpochSchedule::new(
             genesis_block.slots_per_epoch,
@@ -326,10 +571,14 @@ impl Bank {
             genesis_block.epoch_warmup,
         );
 
+        self.update_clock();
+        self.store_bounded_account(&slot_hashes::id(), &slot_hashes::create_account(1));
+
         // Add native programs mandatory for the runtime to function
         self.add_native_program("solana_system_program", &solana_sdk::system_program::id());
         self.add_native_program("solana_bpf_loader", &solana_sdk::bpf_loader::id());
         self.add_native_program("solana_vote_program", &solana_vote_api::id());
+        self.add_native_program("solana_memo_program", &solana_sdk::memo_program::id());
 
         // Add additional native programs specified in the genesis block
         for (name, program_id) in &genesis_block.native_programs {
@@ -337,11 +586,109 @@ impl Bank {
         }
     }
 
+    /// Reconstruct the genesis block this bank was created from, using its current balances
+    /// and configuration, so a test can start another cluster from the same conditions.
+    /// Only the genesis bank itself, at slot 0 and without a parent, can be exported.
+    pub fn export_genesis(&self) -> result::Result<GenesisBlock, BankError> {
+        if self.slot != 0 || self.parent().is_some() {
+            return Err(BankError::NotGenesisBank { slot: self.slot });
+        }
+
+        let bootstrap_leader_id = self.collector_id;
+        let (bootstrap_leader_vote_account_id, bootstrap_leader_stake) = self
+            .vote_accounts()
+            .find(|(_, account)| {
+                VoteState::deserialize(&account.data)
+                    .map(|vote_state| vote_state.delegate_id == bootstrap_leader_id)
+                    .unwrap_or(false)
+            })
+            .map(|(pubkey, account)| (pubkey, account.lamports))
+            .expect("genesis bank has a bootstrap leader vote account");
+        let bootstrap_leader_lamports =
+            self.get_balance(&bootstrap_leader_id) + bootstrap_leader_stake;
+
+        let mandatory_programs = [
+            system_program::id(),
+            solana_sdk::bpf_loader::id(),
+            solana_vote_api::id(),
+            solana_sdk::memo_program::id(),
+        ];
+        let mut native_programs: Vec<_> = self
+            .get_program_accounts(&native_loader::id())
+            .into_iter()
+            .filter(|(program_id, _)| !mandatory_programs.contains(program_id))
+            .map(|(program_id, account)| {
+                let name = String::from_utf8_lossy(&account.data).to_string();
+                (name, program_id)
+            })
+            .collect();
+        native_programs.sort();
+
+        let rewards_pool = self
+            .get_program_accounts(&solana_rewards_api::id())
+            .into_iter()
+            .find_map(|(id, account)| {
+                let rewards_state = RewardsState::deserialize(&account.data).ok()?;
+                Some(RewardsPool {
+                    id,
+                    lamports: account.lamports,
+                    authority_id: rewards_state.authority_id,
+                })
+            });
+
+        let slots_per_epoch = self.epoch_schedule.slots_per_epoch;
+        let stakers_slot_offset = self.epoch_schedule.stakers_slot_offset;
+        let epoch_warmup =
+            EpochSchedule::new(slots_per_epoch, stakers_slot_offset, true) == self.epoch_schedule;
+
+        Ok(GenesisBlock {
+            bootstrap_leader_id,
+            bootstrap_leader_lamports,
+            bootstrap_leader_vote_account_id,
+            mint_id: self.mint_id,
+            lamports: self.get_balance(&self.mint_id) + bootstrap_leader_lamports,
+            ticks_per_slot: self.ticks_per_slot,
+            slots_per_epoch,
+            stakers_slot_offset,
+            epoch_warmup,
+            native_programs,
+            max_transactions_per_slot: self.max_transactions_per_slot.map(|max| max as u64),
+            minimum_delegation: Some(self.minimum_delegation).filter(|min| *min > 0),
+            minimum_balance: Some(self.minimum_balance).filter(|min| *min > 0),
+            fee_calculator: Some(self.fee_calculator.clone())
+                .filter(|fee_calculator| *fee_calculator != FeeCalculator::default()),
+            reject_self_transfers: self.reject_self_transfers,
+            inflation_pool_lamports: self
+                .get_account(&inflation_pool::id())
+                .map(|account| account.lamports),
+            rewards_pool,
+        })
+    }
+
     pub fn add_native_program(&self, name: &str, program_id: &Pubkey) {
         debug!("Adding native program {} under {:?}", name, program_id);
         let account = native_loader::create_program_account(name);
+        // The name comes from the genesis block, which can't be used if it's malformed
+        if self
+            .accounts
+            .store_slow(self.accounts_id, program_id, &account)
+            .is_err()
+        {
+            panic!(
+                "native program {} has a name longer than an account may hold",
+                program_id
+            );
+        }
+        self.loader_cache.invalidate(program_id, &account);
+    }
+
+    /// Store an account whose data length is bounded by construction, so the
+    /// `MAX_PERMITTED_DATA_LENGTH` cap can't reject it
+    fn store_bounded_account(&self, pubkey: &Pubkey, account: &Account) {
         self.accounts
-            .store_slow(self.accounts_id, program_id, &account);
+            .store_slow(self.accounts_id, pubkey, account)
+            .expect("account data exceeds MAX_PERMITTED_DATA_LENGTH");
+        self.loader_cache.invalidate(pubkey, account);
     }
 
     /// Return the last block hash registered.
@@ -349,14 +696,81 @@ impl Bank {
         self.blockhash_queue.read().unwrap().last_hash()
     }
 
-    /// Forget all signatures. Useful for benchmarking.
-    pub fn clear_signatures(&self) {
-        self.status_cache.write().unwrap().clear();
+    /// Return the hash of the genesis block this bank descends from
+    pub fn genesis_hash(&self) -> Hash {
+        self.genesis_hash
     }
 
-    fn update_transaction_statuses(&self, txs: &[Transaction], res: &[Result<()>]) {
+    /// Return the fee schedule currently in effect
+    pub fn fee_calculator(&self) -> FeeCalculator {
+        self.fee_calculator.clone()
+    }
+
+    /// Return the fee this bank charges for `tx`, as determined by its signatures
+    /// rather than the fee the transaction declares
+    pub fn get_fee_for_message(&self, tx: &Transaction) -> u64 {
+        self.fee_calculator.calculate_fee(tx)
+    }
+
+    /// Return the fee this bank charges a transaction carrying `num_signatures`, so a client
+    /// can price a transaction before building it
+    pub fn fee_for_signatures(&self, num_signatures: usize) -> u64 {
+        self.fee_calculator
+            .calculate_fee_for_signatures(num_signatures)
+    }
+
+    /// Return the last block hash registered, paired with the fee schedule currently in
+    /// effect, which may differ from the one in effect when the hash was registered
+    pub fn last_blockhash_with_fee_calculator(&self) -> (Hash, FeeCalculator) {
+        (self.last_blockhash(), self.fee_calculator())
+    }
+
+    /// Size of the signature caches consulted by this bank, including its parents'
+    pub fn status_cache_stats(&self) -> StatusCacheStats {
+        let parents = self.parents();
+        let mut caches = vec![self.status_cache.read().unwrap()];
+        caches.extend(parents.iter().map(|b| b.status_cache.read().unwrap()));
+        caches
+            .iter()
+            .fold(StatusCacheStats::default(), |stats, cache| {
+                StatusCacheStats {
+                    num_caches: stats.num_caches + cache.num_caches(),
+                    num_entries: stats.num_entries + cache.len(),
+                }
+            })
+    }
+
+    /// Forget all signatures, or only those of successful transactions if
+    /// `preserve_failures` is set so failure statuses remain queryable. Useful for
+    /// benchmarking.
+    pub fn clear_signatures(&self, preserve_failures: bool) {
+        let mut status_cache = self.status_cache.write().unwrap();
+        if preserve_failures {
+            status_cache.clear_successes();
+        } else {
+            status_cache.clear();
+        }
+    }
+
+    /// Forget signatures this bank's cache has held for more than `slots` slots, rounded
+    /// up to whole cache generations. More recent signatures are still rejected as
+    /// duplicates.
+    pub fn clear_signatures_older_than(&self, slots: u64) {
+        let ticks = slots * self.ticks_per_slot;
+        let generations = (ticks + NUM_TICKS_PER_SECOND - 1) / NUM_TICKS_PER_SECOND;
+        self.status_cache
+            .write()
+            .unwrap()
+            .clear_older_than(generations as usize);
+    }
+
+    fn update_transaction_statuses<T>(&self, txs: &[T], res: &[Result<()>])
+    where
+        T: Borrow<Transaction>,
+    {
         let mut status_cache = self.status_cache.write().unwrap();
         for (i, tx) in txs.iter().enumerate() {
+            let tx = tx.borrow();
             match &res[i] {
                 Ok(_) => {
                     if !tx.signatures.is_empty() {
@@ -364,8 +778,14 @@ impl Bank {
                     }
                 }
                 Err(TransactionError::BlockhashNotFound) => (),
+                // Lost the lock race without paying a fee; leave it free to be retried
+                Err(TransactionError::AccountInUse) => (),
                 Err(TransactionError::DuplicateSignature) => (),
                 Err(TransactionError::AccountNotFound) => (),
+                Err(TransactionError::WouldExceedBlockLimit) => (),
+                Err(TransactionError::SelfTransferNotAllowed) => (),
+                // Refused before it ran, without charging a fee
+                Err(TransactionError::SanitizeFailure(_)) => (),
                 Err(e) => {
                     if !tx.signatures.is_empty() {
                         status_cache.add(&tx.signatures[0]);
@@ -411,6 +831,61 @@ impl Bank {
     /// the oldest ones once its internal cache is full. Once boot, the
     /// bank will reject transactions using that `hash`.
     pub fn register_tick(&self, hash: &Hash) {
+        let current_tick_height = self.tick_height.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+        self.register_tick_and_timestamp(hash, current_tick_height, None);
+    }
+
+    /// Like `register_tick`, but also records the wall-clock `timestamp` (in ms)
+    /// at which the tick was produced, for use by `get_confirmation_timestamp`
+    pub fn register_tick_with_timestamp(&self, hash: &Hash, timestamp: u64) {
+        let current_tick_height = self.tick_height.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+        self.register_tick_and_timestamp(hash, current_tick_height, Some(timestamp));
+    }
+
+    /// Like `register_tick`, but rejects the tick unless `tick_height` directly
+    /// follows the bank's current tick height.
+    pub fn register_tick_at(&self, hash: &Hash, tick_height: u64) -> result::Result<(), BankError> {
+        self.advance_tick_height(tick_height)?;
+        self.register_tick_and_timestamp(hash, tick_height, None);
+        Ok(())
+    }
+
+    /// Like `register_tick_at`, but also records the wall-clock `timestamp` (in ms)
+    /// at which the tick was produced
+    pub fn register_tick_at_with_timestamp(
+        &self,
+        hash: &Hash,
+        tick_height: u64,
+        timestamp: u64,
+    ) -> result::Result<(), BankError> {
+        self.advance_tick_height(tick_height)?;
+        self.register_tick_and_timestamp(hash, tick_height, Some(timestamp));
+        Ok(())
+    }
+
+    /// Advance the tick height to `tick_height`, but only if it's still the one directly
+    /// preceding it
+    fn advance_tick_height(&self, tick_height: u64) -> result::Result<(), BankError> {
+        self.tick_height
+            .compare_exchange(
+                tick_height.wrapping_sub(1) as usize,
+                tick_height as usize,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .map(|_| ())
+            .map_err(|previous| BankError::TickHeightMismatch {
+                expected: previous as u64 + 1,
+                actual: tick_height,
+            })
+    }
+
+    fn register_tick_and_timestamp(
+        &self,
+        hash: &Hash,
+        current_tick_height: u64,
+        timestamp: Option<u64>,
+    ) {
         if self.is_frozen() {
             warn!("=========== FIXME: register_tick() working on a frozen bank! ================");
         }
@@ -418,16 +893,12 @@ impl Bank {
         // TODO: put this assert back in
         // assert!(!self.is_frozen());
 
-        let current_tick_height = {
-            self.tick_height.fetch_add(1, Ordering::SeqCst);
-            self.tick_height.load(Ordering::SeqCst) as u64
-        };
         inc_new_counter_info!("bank-register_tick-registered", 1);
 
         // Register a new block hash if at the last tick in the slot
         if current_tick_height % self.ticks_per_slot == self.ticks_per_slot - 1 {
             let mut blockhash_queue = self.blockhash_queue.write().unwrap();
-            blockhash_queue.register_hash(hash);
+            Arc::make_mut(&mut blockhash_queue).register_hash(hash, timestamp);
         }
 
         if current_tick_height % NUM_TICKS_PER_SECOND == 0 {
@@ -435,6 +906,45 @@ impl Bank {
         }
     }
 
+    /// Register a blockhash observed in the ledger as though a slot had just ended, without
+    /// advancing the tick height. Lets replay seed the queue when some of the parent's ticks
+    /// are missing. Ignored once the bank is frozen.
+    pub fn register_recent_blockhash(&self, hash: &Hash) {
+        if self.is_frozen() {
+            warn!("register_recent_blockhash() ignored on a frozen bank");
+            return;
+        }
+        let mut blockhash_queue = self.blockhash_queue.write().unwrap();
+        Arc::make_mut(&mut blockhash_queue).register_hash(hash, None);
+    }
+
+    /// Return true if `hash` is in the queue of recent blockhashes
+    pub fn has_blockhash(&self, hash: &Hash) -> bool {
+        self.blockhash_queue.read().unwrap().check_hash(*hash)
+    }
+
+    /// Return the number of blockhashes registered after `blockhash`, 0 for the newest, or
+    /// None if it is not one of the last `MAX_RECENT_BLOCKHASHES`. Clients holding on to a
+    /// transaction can pick the blockhash with the most validity left.
+    pub fn blockhash_age(&self, blockhash: &Hash) -> Option<usize> {
+        self.blockhash_queue
+            .read()
+            .unwrap()
+            .get_hash_age(blockhash)
+            .map(|age| age as usize)
+    }
+
+    /// Tell apart the reasons a transaction's blockhash may be rejected with
+    /// `BlockhashNotFound`: it expired, or it was never valid
+    pub fn blockhash_status(&self, hash: &Hash) -> BlockhashStatus {
+        let blockhash_queue = self.blockhash_queue.read().unwrap();
+        match blockhash_queue.hash_age(hash) {
+            Some(age) if age <= MAX_RECENT_BLOCKHASHES as u64 => BlockhashStatus::Valid { age },
+            _ if blockhash_queue.was_registered(hash) => BlockhashStatus::Expired,
+            _ => BlockhashStatus::Unknown,
+        }
+    }
+
     /// Process a Transaction. This is used for unit tests and simply calls the vector Bank::process_transactions method.
     pub fn process_transaction(&self, tx: &Transaction) -> Result<()> {
         let txs = vec![tx.clone()];
@@ -444,41 +954,197 @@ impl Bank {
             .map_or(Ok(()), |sig| self.get_signature_status(sig).unwrap())
     }
 
-    pub fn lock_accounts(&self, txs: &[Transaction]) -> Vec<Result<()>> {
+    /// Like `process_transaction`, but also returns how long loading and executing
+    /// the transaction took. A transaction that fails to load isn't executed.
+    pub fn process_transaction_timed(&self, tx: &Transaction) -> (Result<()>, Duration, Duration) {
+        if !self.check_signature(tx) {
+            return (
+                Err(TransactionError::SignatureFailure),
+                Duration::default(),
+                Duration::default(),
+            );
+        }
+        let txs = vec![tx.clone()];
+        let lock_results = self.lock_accounts(&txs);
+        let (loaded_accounts, executed, _, load_elapsed, execution_elapsed) = self
+            .load_and_execute_transactions_timed(
+                &txs,
+                lock_results.clone(),
+                MAX_RECENT_BLOCKHASHES,
+                None,
+                false,
+            );
+        let results = self.commit_transactions(&txs, &loaded_accounts, &executed);
+        self.unlock_accounts(&txs, &lock_results, &results);
+        let result = results[0].clone().and_then(|_| {
+            tx.signatures
+                .get(0)
+                .map_or(Ok(()), |sig| self.get_signature_status(sig).unwrap())
+        });
+        (result, load_elapsed, execution_elapsed)
+    }
+
+    /// Lock the accounts of `txs` in input order; see `Accounts::lock_accounts`
+    pub fn lock_accounts<T>(&self, txs: &[T]) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
         if self.is_frozen() {
             warn!("=========== FIXME: lock_accounts() working on a frozen bank! ================");
         }
         // TODO: put this assert back in
         // assert!(!self.is_frozen());
-        self.accounts.lock_accounts(self.accounts_id, txs)
+        let dedup_results = Self::check_batch_duplicates(txs);
+        let capacity_results = self.reserve_transaction_capacity(dedup_results);
+        let lock_results = self
+            .accounts
+            .lock_accounts(self.accounts_id, txs, capacity_results);
+        let in_use = lock_results
+            .iter()
+            .filter(|r| **r == Err(TransactionError::AccountInUse))
+            .count();
+        self.release_transaction_capacity(in_use);
+        lock_results
+    }
+
+    /// Mark every transaction whose first signature already appeared earlier in the batch
+    /// as a `DuplicateSignature`, so only the first copy is ever locked and executed
+    fn check_batch_duplicates<T>(txs: &[T]) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        let mut signatures = HashSet::with_capacity(txs.len());
+        txs.iter()
+            .map(|tx| match tx.borrow().signatures.get(0) {
+                Some(signature) if !signatures.insert(signature) => {
+                    Err(TransactionError::DuplicateSignature)
+                }
+                _ => Ok(()),
+            })
+            .collect()
+    }
+
+    /// Reserve room in this slot for each transaction that hasn't already failed.
+    /// Reservations that would exceed `max_transactions_per_slot` fail with
+    /// `WouldExceedBlockLimit`.
+    fn reserve_transaction_capacity(&self, results: Vec<Result<()>>) -> Vec<Result<()>> {
+        let max = match self.max_transactions_per_slot {
+            Some(max) => max,
+            None => return results,
+        };
+        results
+            .into_iter()
+            .map(|result| {
+                if result.is_err() {
+                    result
+                } else if self
+                    .transaction_count_in_slot
+                    .fetch_add(1, Ordering::SeqCst)
+                    < max
+                {
+                    Ok(())
+                } else {
+                    self.transaction_count_in_slot
+                        .fetch_sub(1, Ordering::SeqCst);
+                    Err(TransactionError::WouldExceedBlockLimit)
+                }
+            })
+            .collect()
+    }
+
+    /// Return `count` reservations made by `reserve_transaction_capacity` for transactions
+    /// that will not be committed
+    fn release_transaction_capacity(&self, count: usize) {
+        if self.max_transactions_per_slot.is_some() && count > 0 {
+            self.transaction_count_in_slot
+                .fetch_sub(count, Ordering::SeqCst);
+        }
+    }
+
+    /// Return the fewest lamports a new staking account may be created with
+    pub fn minimum_delegation(&self) -> u64 {
+        self.minimum_delegation
+    }
+
+    /// Return the fewest lamports a withdrawal may leave in an account it doesn't close
+    pub fn minimum_balance(&self) -> u64 {
+        self.minimum_balance
+    }
+
+    /// Return true if fee-less transactions that only move lamports to their source are rejected
+    pub fn reject_self_transfers(&self) -> bool {
+        self.reject_self_transfers
+    }
+
+    /// Return the number of transactions committed, or locked and about to be committed,
+    /// in this slot
+    pub fn transaction_count_in_slot(&self) -> usize {
+        self.transaction_count_in_slot.load(Ordering::SeqCst)
+    }
+
+    /// Unlock the accounts `lock_accounts` locked for `txs`, given the `lock_results` it
+    /// returned, and return the capacity reserved in this slot by the locked transactions
+    /// that `results` shows weren't committed
+    pub fn unlock_accounts<T>(&self, txs: &[T], lock_results: &[Result<()>], results: &[Result<()>])
+    where
+        T: Borrow<Transaction>,
+    {
+        let uncommitted = lock_results
+            .iter()
+            .zip(results.iter())
+            .filter(|(lock_result, result)| match result {
+                Ok(()) | Err(TransactionError::InstructionError(_, _)) => false,
+                Err(_) => lock_result.is_ok(),
+            })
+            .count();
+        self.release_transaction_capacity(uncommitted);
+        self.accounts
+            .unlock_accounts(self.accounts_id, txs, lock_results)
     }
 
-    pub fn unlock_accounts(&self, txs: &[Transaction], results: &[Result<()>]) {
+    /// Unlock accounts that were locked by `lock_accounts` but will not be committed,
+    /// returning the capacity they reserved in this slot
+    pub fn unlock_uncommitted_accounts(&self, txs: &[Transaction], lock_results: &[Result<()>]) {
+        self.release_transaction_capacity(lock_results.iter().filter(|r| r.is_ok()).count());
         self.accounts
-            .unlock_accounts(self.accounts_id, txs, results)
+            .unlock_accounts(self.accounts_id, txs, lock_results)
     }
 
-    fn load_accounts(
+    fn load_accounts<T>(
         &self,
-        txs: &[Transaction],
+        txs: &[T],
         results: Vec<Result<()>>,
         error_counters: &mut ErrorCounters,
-    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>> {
-        self.accounts
-            .load_accounts(self.accounts_id, txs, results, error_counters)
+    ) -> Vec<Result<(InstructionAccounts, InstructionLoaders)>>
+    where
+        T: Borrow<Transaction>,
+    {
+        self.accounts.load_accounts(
+            self.accounts_id,
+            txs,
+            results,
+            &self.fee_calculator,
+            &self.loader_cache,
+            error_counters,
+        )
     }
-    fn check_age(
+    fn check_age<T>(
         &self,
-        txs: &[Transaction],
+        txs: &[T],
         lock_results: Vec<Result<()>>,
         max_age: usize,
         error_counters: &mut ErrorCounters,
-    ) -> Vec<Result<()>> {
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
         let hash_queue = self.blockhash_queue.read().unwrap();
         txs.iter()
             .zip(lock_results.into_iter())
             .map(|(tx, lock_res)| {
-                if lock_res.is_ok() && !hash_queue.check_hash_age(tx.recent_blockhash, max_age) {
+                if lock_res.is_ok()
+                    && !hash_queue.check_hash_age(tx.borrow().recent_blockhash, max_age)
+                {
                     error_counters.reserve_blockhash += 1;
                     Err(TransactionError::BlockhashNotFound)
                 } else {
@@ -487,18 +1153,22 @@ impl Bank {
             })
             .collect()
     }
-    fn check_signatures(
+    fn check_signatures<T>(
         &self,
-        txs: &[Transaction],
+        txs: &[T],
         lock_results: Vec<Result<()>>,
         error_counters: &mut ErrorCounters,
-    ) -> Vec<Result<()>> {
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
         let parents = self.parents();
         let mut caches = vec![self.status_cache.read().unwrap()];
         caches.extend(parents.iter().map(|b| b.status_cache.read().unwrap()));
         txs.iter()
             .zip(lock_results.into_iter())
             .map(|(tx, lock_res)| {
+                let tx = tx.borrow();
                 if tx.signatures.is_empty() {
                     return lock_res;
                 }
@@ -511,6 +1181,111 @@ impl Bank {
             })
             .collect()
     }
+    /// Reject malformed instructions before anything indexes into the transaction with them
+    fn check_sanitized<T>(txs: &[T], results: Vec<Result<()>>) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        txs.iter()
+            .zip(results.into_iter())
+            .map(|(tx, result)| result.and_then(|_| sanitize_transaction(tx.borrow())))
+            .collect()
+    }
+
+    /// Reject transactions that declare less than the fee schedule charges for them
+    fn check_fees<T>(&self, txs: &[T], results: Vec<Result<()>>) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        txs.iter()
+            .zip(results.into_iter())
+            .map(|(tx, result)| {
+                let tx = tx.borrow();
+                if result.is_ok() && tx.fee < self.get_fee_for_message(tx) {
+                    Err(TransactionError::InsufficientFee)
+                } else {
+                    result
+                }
+            })
+            .collect()
+    }
+
+    /// Reject transactions that create a staking account with less than the minimum delegation
+    fn check_delegations<T>(&self, txs: &[T], results: Vec<Result<()>>) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        if self.minimum_delegation == 0 {
+            return results;
+        }
+        txs.iter()
+            .zip(results.into_iter())
+            .map(|(tx, result)| {
+                if result.is_ok() && self.creates_underfunded_staking_account(tx.borrow()) {
+                    Err(TransactionError::DelegationBelowMinimum)
+                } else {
+                    result
+                }
+            })
+            .collect()
+    }
+    fn creates_underfunded_staking_account(&self, tx: &Transaction) -> bool {
+        tx.instructions.iter().enumerate().any(|(i, instruction)| {
+            if !system_program::check_id(tx.program_id(i)) {
+                return false;
+            }
+            match bincode::deserialize(&instruction.data) {
+                Ok(SystemInstruction::CreateAccount {
+                    lamports,
+                    program_id,
+                    ..
+                }) => solana_vote_api::check_id(&program_id) && lamports < self.minimum_delegation,
+                _ => false,
+            }
+        })
+    }
+    /// Reject pure self-transfers if the genesis block's policy says to
+    fn check_self_transfers<T>(&self, txs: &[T], results: Vec<Result<()>>) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
+        if !self.reject_self_transfers {
+            return results;
+        }
+        txs.iter()
+            .zip(results.into_iter())
+            .map(|(tx, result)| {
+                if result.is_ok() && Self::is_self_transfer(tx.borrow()) {
+                    Err(TransactionError::SelfTransferNotAllowed)
+                } else {
+                    result
+                }
+            })
+            .collect()
+    }
+    /// A transaction that pays a fee still moves lamports to the collector, so only
+    /// fee-less transactions made entirely of moves to the source account are no-ops.
+    /// Runs before sanitizing, so indexes are checked rather than trusted.
+    fn is_self_transfer(tx: &Transaction) -> bool {
+        tx.fee == 0
+            && !tx.instructions.is_empty()
+            && tx.instructions.iter().all(|instruction| {
+                let program_id = tx.program_ids.get(instruction.program_ids_index as usize);
+                if !program_id.map_or(false, system_program::check_id) {
+                    return false;
+                }
+                match bincode::deserialize(&instruction.data) {
+                    Ok(SystemInstruction::Move { .. }) => match instruction.accounts[..] {
+                        [from, to] => {
+                            let from = tx.account_keys.get(from as usize);
+                            from.is_some() && from == tx.account_keys.get(to as usize)
+                        }
+                        _ => false,
+                    },
+                    _ => false,
+                }
+            })
+    }
     #[allow(clippy::type_complexity)]
     pub fn load_and_execute_transactions(
         &self,
@@ -521,29 +1296,143 @@ impl Bank {
         Vec<Result<(InstructionAccounts, InstructionLoaders)>>,
         Vec<Result<()>>,
     ) {
-        debug!("processing transactions: {}", txs.len());
-        let mut error_counters = ErrorCounters::default();
-        let now = Instant::now();
-        let age_results = self.check_age(txs, lock_results, max_age, &mut error_counters);
-        let sig_results = self.check_signatures(txs, age_results, &mut error_counters);
-        let mut loaded_accounts = self.load_accounts(txs, sig_results, &mut error_counters);
-        let tick_height = self.tick_height();
+        let (loaded_accounts, executed, _, _, _) =
+            self.load_and_execute_transactions_timed(txs, lock_results, max_age, None, false);
+        (loaded_accounts, executed)
+    }
 
-        let load_elapsed = now.elapsed();
-        let now = Instant::now();
-        let executed: Vec<Result<()>> = loaded_accounts
-            .iter_mut()
-            .zip(txs.iter())
-            .map(|(accs, tx)| match accs {
-                Err(e) => Err(e.clone()),
-                Ok((ref mut accounts, ref mut loaders)) => {
-                    self.runtime
-                        .execute_transaction(tx, loaders, accounts, tick_height)
-                }
-            })
-            .collect();
+    /// Like `load_and_execute_transactions`, but also returns the write set of each
+    /// transaction that executed successfully: the accounts whose contents execution
+    /// changed, plus the fee payer if it was charged a fee. An account the transaction
+    /// loaded but left as it was isn't part of it. Failed transactions have no write set.
+    #[allow(clippy::type_complexity)]
+    pub fn load_and_execute_transactions_with_write_sets(
+        &self,
+        txs: &[Transaction],
+        lock_results: Vec<Result<()>>,
+        max_age: usize,
+    ) -> (
+        Vec<Result<(InstructionAccounts, InstructionLoaders)>>,
+        Vec<Result<()>>,
+        Vec<Option<HashSet<Pubkey>>>,
+    ) {
+        let (loaded_accounts, executed, write_sets, _, _) =
+            self.load_and_execute_transactions_timed(txs, lock_results, max_age, None, true);
+        (loaded_accounts, executed, write_sets)
+    }
 
-        let execution_elapsed = now.elapsed();
+    /// The keys of the accounts `tx` changed from `pre_accounts` to `post_accounts`, plus its
+    /// fee payer if it's charged a fee
+    fn write_set(
+        &self,
+        tx: &Transaction,
+        pre_accounts: &[Account],
+        post_accounts: &[Account],
+    ) -> HashSet<Pubkey> {
+        let charged_fee = self.fee_calculator.calculate_fee(tx) != 0;
+        tx.account_keys
+            .iter()
+            .zip(pre_accounts.iter().zip(post_accounts))
+            .enumerate()
+            .filter(|(i, (_, (pre, post)))| pre != post || (*i == 0 && charged_fee))
+            .map(|(_, (key, _))| *key)
+            .collect()
+    }
+
+    /// Like `load_and_execute_transactions`, but also returns how long loading and
+    /// executing the transactions took, and with `collect_write_sets`, each successful
+    /// transaction's write set. With a `pool`, the loaded transactions execute across its
+    /// threads; they hold disjoint account locks, so they can't observe each other, and
+    /// results stay in the order of `txs`.
+    #[allow(clippy::type_complexity)]
+    fn load_and_execute_transactions_timed<T>(
+        &self,
+        txs: &[T],
+        lock_results: Vec<Result<()>>,
+        max_age: usize,
+        pool: Option<&ThreadPool>,
+        collect_write_sets: bool,
+    ) -> (
+        Vec<Result<(InstructionAccounts, InstructionLoaders)>>,
+        Vec<Result<()>>,
+        Vec<Option<HashSet<Pubkey>>>,
+        Duration,
+        Duration,
+    )
+    where
+        T: Borrow<Transaction> + Sync,
+    {
+        debug!("processing transactions: {}", txs.len());
+        let mut error_counters = ErrorCounters::default();
+        let now = Instant::now();
+        let age_results = self.check_age(txs, lock_results, max_age, &mut error_counters);
+        let sig_results = self.check_signatures(txs, age_results, &mut error_counters);
+        let self_transfer_results = self.check_self_transfers(txs, sig_results);
+        let sanitize_results = Self::check_sanitized(txs, self_transfer_results);
+        let fee_results = self.check_fees(txs, sanitize_results);
+        let delegation_results = self.check_delegations(txs, fee_results);
+        let mut loaded_accounts = self.load_accounts(txs, delegation_results, &mut error_counters);
+        let tick_height = self.tick_height();
+
+        let load_elapsed = now.elapsed();
+        let now = Instant::now();
+        let execute = |accs: &mut Result<(InstructionAccounts, InstructionLoaders)>,
+                       tx: &Transaction,
+                       timings: &mut ProgramTimings| match accs {
+            Err(e) => (Err(e.clone()), None),
+            Ok((ref mut accounts, ref mut loaders)) => {
+                let pre_accounts = if collect_write_sets {
+                    Some(accounts.clone())
+                } else {
+                    None
+                };
+                let result =
+                    self.runtime
+                        .execute_transaction(tx, loaders, accounts, tick_height, timings);
+                let write_set = match pre_accounts {
+                    Some(pre_accounts) if result.is_ok() => {
+                        Some(self.write_set(tx, &pre_accounts, accounts))
+                    }
+                    _ => None,
+                };
+                (result, write_set)
+            }
+        };
+        // A fully-conflicting batch leaves at most one transaction to execute
+        let executable = loaded_accounts.iter().filter(|accs| accs.is_ok()).count();
+        let mut timings = ProgramTimings::new();
+        let (executed, write_sets): (Vec<Result<()>>, Vec<_>) = match pool {
+            Some(pool) if executable > 1 => {
+                let executed: Vec<_> = pool.install(|| {
+                    loaded_accounts
+                        .par_iter_mut()
+                        .zip(txs.par_iter())
+                        .map(|(accs, tx)| {
+                            let mut tx_timings = ProgramTimings::new();
+                            let executed = execute(accs, tx.borrow(), &mut tx_timings);
+                            (executed, tx_timings)
+                        })
+                        .collect()
+                });
+                executed
+                    .into_iter()
+                    .map(|(executed, tx_timings)| {
+                        merge_program_timings(&mut timings, &tx_timings);
+                        executed
+                    })
+                    .unzip()
+            }
+            _ => loaded_accounts
+                .iter_mut()
+                .zip(txs.iter())
+                .map(|(accs, tx)| execute(accs, tx.borrow(), &mut timings))
+                .unzip(),
+        };
+        if !timings.is_empty() {
+            merge_program_timings(&mut self.program_timings.write().unwrap(), &timings);
+        }
+
+        let execution_elapsed = now.elapsed();
 
         debug!(
             "load: {}us execute: {}us txs_len={}",
@@ -558,7 +1447,7 @@ impl Bank {
                 tx_count += 1;
             } else {
                 if err_count == 0 {
-                    info!("tx error: {:?} {:?}", r, tx);
+                    info!("tx error: {:?} {:?}", r, tx.borrow());
                 }
                 err_count += 1;
             }
@@ -606,42 +1495,64 @@ impl Bank {
                 error_counters.account_loaded_twice
             );
         }
-        (loaded_accounts, executed)
+        if 0 != error_counters.invalid_program_for_execution {
+            inc_new_counter_info!(
+                "bank-process_transactions-error-invalid_program_for_execution",
+                error_counters.invalid_program_for_execution
+            );
+        }
+        (
+            loaded_accounts,
+            executed,
+            write_sets,
+            load_elapsed,
+            execution_elapsed,
+        )
     }
 
-    fn filter_program_errors_and_collect_fee(
+    fn filter_program_errors_and_collect_fee<T>(
         &self,
-        txs: &[Transaction],
+        txs: &[T],
         executed: &[Result<()>],
-    ) -> Vec<Result<()>> {
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
         let mut fees = 0;
         let results = txs
             .iter()
+            .map(Borrow::borrow)
             .zip(executed.iter())
             .map(|(tx, res)| match *res {
                 Err(TransactionError::InstructionError(_, _)) => {
                     // Charge the transaction fee even in case of InstructionError
-                    self.withdraw(&tx.account_keys[0], tx.fee)?;
-                    fees += tx.fee;
+                    let fee = self.get_fee_for_message(tx);
+                    self.withdraw(&tx.account_keys[0], fee)?;
+                    fees += fee;
                     Ok(())
                 }
                 Ok(()) => {
-                    fees += tx.fee;
+                    fees += self.get_fee_for_message(tx);
                     Ok(())
                 }
                 _ => res.clone(),
             })
             .collect();
         self.deposit(&self.collector_id, fees);
+        self.collected_fees
+            .fetch_add(fees as usize, Ordering::SeqCst);
         results
     }
 
-    pub fn commit_transactions(
+    pub fn commit_transactions<T>(
         &self,
-        txs: &[Transaction],
+        txs: &[T],
         loaded_accounts: &[Result<(InstructionAccounts, InstructionLoaders)>],
         executed: &[Result<()>],
-    ) -> Vec<Result<()>> {
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction>,
+    {
         if self.is_frozen() {
             warn!("=========== FIXME: commit_transactions() working on a frozen bank! ================");
         }
@@ -651,18 +1562,24 @@ impl Bank {
         // TODO: put this assert back in
         // assert!(!self.is_frozen());
         let now = Instant::now();
-        self.accounts
-            .store_accounts(self.accounts_id, txs, executed, loaded_accounts);
+        let executed = self.accounts.store_accounts(
+            self.accounts_id,
+            txs,
+            executed,
+            loaded_accounts,
+            &self.loader_cache,
+        );
 
         // once committed there is no way to unroll
         let write_elapsed = now.elapsed();
+
         debug!(
             "store: {}us txs_len={}",
             duration_as_us(&write_elapsed),
             txs.len(),
         );
         self.update_transaction_statuses(txs, &executed);
-        self.filter_program_errors_and_collect_fee(txs, executed)
+        self.filter_program_errors_and_collect_fee(txs, &executed)
     }
 
     /// Process a batch of transactions.
@@ -673,21 +1590,177 @@ impl Bank {
         lock_results: Vec<Result<()>>,
         max_age: usize,
     ) -> Vec<Result<()>> {
-        let (loaded_accounts, executed) =
-            self.load_and_execute_transactions(txs, lock_results, max_age);
+        self.load_execute_and_commit_transactions_in(txs, lock_results, max_age, None)
+    }
+
+    fn load_execute_and_commit_transactions_in<T>(
+        &self,
+        txs: &[T],
+        lock_results: Vec<Result<()>>,
+        max_age: usize,
+        pool: Option<&ThreadPool>,
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction> + Sync,
+    {
+        let (loaded_accounts, executed, _, _, _) =
+            self.load_and_execute_transactions_timed(txs, lock_results, max_age, pool, false);
 
         self.commit_transactions(txs, &loaded_accounts, &executed)
     }
 
+    /// Return whether this bank, and the banks created from it, verify the signatures of the
+    /// transactions they process. True unless a harness turned it off.
+    pub fn verify_signatures(&self) -> bool {
+        !self.skip_signature_verification.load(Ordering::Relaxed)
+    }
+
+    /// Turn signature verification on or off for this bank and the banks created from it
+    /// afterwards. Only for test and bench harnesses whose transactions carry signatures
+    /// that won't verify; a validator must never turn it off.
+    pub fn set_verify_signatures(&self, verify_signatures: bool) {
+        self.skip_signature_verification
+            .store(!verify_signatures, Ordering::Relaxed);
+    }
+
+    fn check_signature(&self, tx: &Transaction) -> bool {
+        !self.verify_signatures() || tx.verify_signature()
+    }
+
+    /// Process `txs`, first verifying their signatures. Transactions that fail
+    /// verification fail with `SignatureFailure` without locking any accounts. Results are
+    /// in the order of `txs`, and when two transactions conflict on an account the
+    /// earlier-indexed one wins, so the same batch always has the same outcome.
     #[must_use]
     pub fn process_transactions(&self, txs: &[Transaction]) -> Vec<Result<()>> {
+        self.process_transactions_filtered(txs, |_| Ok(()))
+    }
+
+    /// Like `process_transactions`, but execute the transactions that lock their accounts
+    /// across the threads of `pool`. Locking, commits and results are still in the order
+    /// of `txs`, so the outcome matches `process_transactions` for the same batch.
+    #[must_use]
+    pub fn process_transactions_parallel(
+        &self,
+        txs: &[Transaction],
+        pool: &ThreadPool,
+    ) -> Vec<Result<()>> {
+        self.process_transactions_filtered_in(txs, |_| Ok(()), Some(pool))
+    }
+
+    /// Process transactions whose signatures were already verified
+    #[must_use]
+    pub fn process_verified_transactions(&self, txs: &[VerifiedTransaction]) -> Vec<Result<()>> {
+        self.process_transactions_unverified(txs, None)
+    }
+
+    fn process_transactions_unverified<T>(
+        &self,
+        txs: &[T],
+        pool: Option<&ThreadPool>,
+    ) -> Vec<Result<()>>
+    where
+        T: Borrow<Transaction> + Sync,
+    {
         let lock_results = self.lock_accounts(txs);
-        let results =
-            self.load_execute_and_commit_transactions(txs, lock_results, MAX_RECENT_BLOCKHASHES);
-        self.unlock_accounts(txs, &results);
+        let results = self.load_execute_and_commit_transactions_in(
+            txs,
+            lock_results.clone(),
+            MAX_RECENT_BLOCKHASHES,
+            pool,
+        );
+        self.unlock_accounts(txs, &lock_results, &results);
         results
     }
 
+    /// Process `txs`, then re-process the ones that failed with AccountInUse because another
+    /// transaction in the same round held their accounts, for at most `max_rounds` rounds in
+    /// all. Results are in the order of `txs`. Also return the number of rounds run, which
+    /// is at least one.
+    #[must_use]
+    pub fn process_transactions_with_retries(
+        &self,
+        txs: &[Transaction],
+        max_rounds: usize,
+    ) -> (Vec<Result<()>>, usize) {
+        let mut results = self.process_transactions(txs);
+        let mut rounds = 1;
+        while rounds < max_rounds {
+            let retry_indexes: Vec<_> = results
+                .iter()
+                .enumerate()
+                .filter(|(_, result)| **result == Err(TransactionError::AccountInUse))
+                .map(|(i, _)| i)
+                .collect();
+            if retry_indexes.is_empty() {
+                break;
+            }
+
+            // Transactions that lost a lock race already passed verification
+            let retry_txs: Vec<_> = retry_indexes.iter().map(|i| &txs[*i]).collect();
+            let retry_results = self.process_transactions_unverified(&retry_txs, None);
+            for (i, result) in retry_indexes.into_iter().zip(retry_results) {
+                results[i] = result;
+            }
+            rounds += 1;
+        }
+        (results, rounds)
+    }
+
+    /// Process the transactions `predicate` accepts and whose signatures verify. Rejected
+    /// transactions fail with the predicate's error, or `SignatureFailure`, without locking
+    /// any accounts.
+    #[must_use]
+    pub fn process_transactions_filtered<F>(
+        &self,
+        txs: &[Transaction],
+        predicate: F,
+    ) -> Vec<Result<()>>
+    where
+        F: Fn(&Transaction) -> Result<()>,
+    {
+        self.process_transactions_filtered_in(txs, predicate, None)
+    }
+
+    fn process_transactions_filtered_in<F>(
+        &self,
+        txs: &[Transaction],
+        predicate: F,
+        pool: Option<&ThreadPool>,
+    ) -> Vec<Result<()>>
+    where
+        F: Fn(&Transaction) -> Result<()>,
+    {
+        let filter_results: Vec<_> = txs
+            .iter()
+            .map(|tx| {
+                predicate(tx)?;
+                if self.check_signature(tx) {
+                    Ok(())
+                } else {
+                    Err(TransactionError::SignatureFailure)
+                }
+            })
+            .collect();
+        let accepted: Vec<_> = txs
+            .iter()
+            .zip(filter_results.iter())
+            .filter(|(_, result)| result.is_ok())
+            .map(|(tx, _)| tx)
+            .collect();
+        if accepted.is_empty() {
+            return filter_results;
+        }
+
+        let mut accepted_results = self
+            .process_transactions_unverified(&accepted, pool)
+            .into_iter();
+        filter_results
+            .into_iter()
+            .map(|result| result.and_then(|_| accepted_results.next().unwrap()))
+            .collect()
+    }
+
     /// Create, sign, and process a Transaction from `keypair` to `to` of
     /// `n` lamports where `blockhash` is the last Entry ID observed by the client.
     pub fn transfer(
@@ -697,6 +1770,9 @@ impl Bank {
         to: &Pubkey,
         blockhash: Hash,
     ) -> Result<Signature> {
+        if self.reject_self_transfers && *to == keypair.pubkey() {
+            return Err(TransactionError::SelfTransferNotAllowed);
+        }
         let tx = SystemTransaction::new_account(keypair, to, n, blockhash, 0);
         let signature = tx.signatures[0];
         self.process_transaction(&tx).map(|_| signature)
@@ -730,25 +1806,162 @@ impl Bank {
                 if lamports > account.lamports {
                     return Err(TransactionError::InsufficientFundsForFee);
                 }
+                let remaining = account.lamports - lamports;
+                if remaining != 0 && remaining < self.minimum_balance {
+                    return Err(TransactionError::WouldBecomeRentPaying);
+                }
 
                 account.lamports -= lamports;
-                self.accounts.store_slow(self.accounts_id, pubkey, &account);
+                self.accounts
+                    .store_slow(self.accounts_id, pubkey, &account)?;
+                self.loader_cache.invalidate(pubkey, &account);
                 Ok(())
             }
             None => Err(TransactionError::AccountNotFound),
         }
     }
 
+    /// Credit `lamports` to `pubkey`. Only the balance changes, and every stored account is
+    /// within `MAX_PERMITTED_DATA_LENGTH`, so storing the result can't fail.
     pub fn deposit(&self, pubkey: &Pubkey, lamports: u64) {
         let mut account = self.get_account(pubkey).unwrap_or_default();
         account.lamports += lamports;
-        self.accounts.store_slow(self.accounts_id, pubkey, &account);
+        self.store_bounded_account(pubkey, &account);
+    }
+
+    /// Credit every `(pubkey, lamports)` in `credits` in one pass over the accounts store.
+    /// A pubkey that appears more than once gets the sum of its credits. Panics, before
+    /// crediting any account, if a balance would overflow.
+    pub fn deposit_many(&self, credits: &[(Pubkey, u64)]) {
+        let mut indexes: HashMap<Pubkey, usize> = HashMap::new();
+        let mut totals: Vec<(Pubkey, u64)> = vec![];
+        for (pubkey, lamports) in credits {
+            match indexes.get(pubkey) {
+                Some(&i) => {
+                    let total = &mut totals[i].1;
+                    *total = total
+                        .checked_add(*lamports)
+                        .expect("deposits overflow an account's balance");
+                }
+                None => {
+                    indexes.insert(*pubkey, totals.len());
+                    totals.push((*pubkey, *lamports));
+                }
+            }
+        }
+
+        let accounts: Vec<_> = totals
+            .into_iter()
+            .filter(|(_, lamports)| *lamports != 0)
+            .map(|(pubkey, lamports)| {
+                let mut account = self.get_account(&pubkey).unwrap_or_default();
+                account.lamports = account
+                    .lamports
+                    .checked_add(lamports)
+                    .expect("deposits overflow an account's balance");
+                (pubkey, account)
+            })
+            .collect();
+        self.accounts
+            .store_many(self.accounts_id, &accounts)
+            .expect("account data exceeds MAX_PERMITTED_DATA_LENGTH");
+        for (pubkey, account) in &accounts {
+            self.loader_cache.invalidate(pubkey, account);
+        }
     }
 
     pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
         self.accounts.load_slow(self.accounts_id, pubkey)
     }
 
+    /// Close the account at `pubkey`, returning its balance, which is always zero. A
+    /// zero-lamport account drained in this bank is removed from the accounts store, rather
+    /// than lingering until the bank is squashed, unless it hides a funded account in a
+    /// parent. An account that doesn't exist is already closed. Fails with `AccountNotEmpty`
+    /// if the account still holds lamports.
+    pub fn close_account(&self, pubkey: &Pubkey) -> Result<u64> {
+        if self.get_account(pubkey).is_some() {
+            return Err(TransactionError::AccountNotEmpty);
+        }
+        self.accounts
+            .purge_zero_lamport_account(self.accounts_id, pubkey);
+        Ok(0)
+    }
+
+    /// Return the hash `account` contributes to the accounts delta hash when stored at `pubkey`
+    pub fn hash_account(pubkey: &Pubkey, account: &Account) -> Hash {
+        accounts::hash_account(pubkey, account)
+    }
+
+    /// Return true if the account at `pubkey` exists and hashes to `expected_hash`
+    pub fn verify_account(&self, pubkey: &Pubkey, expected_hash: &Hash) -> bool {
+        self.get_account(pubkey)
+            .map(|account| Self::hash_account(pubkey, &account) == *expected_hash)
+            .unwrap_or(false)
+    }
+
+    /// Return the lamports left in the inflation pool for paying rewards
+    pub fn inflation_pool_balance(&self) -> u64 {
+        self.get_balance(&inflation_pool::id())
+    }
+
+    /// Pay each of `rewards` out of the inflation pool, returning the lamports paid. If the
+    /// pool can't cover them all, every reward is scaled down by the same fraction and the
+    /// shortfall is logged; the pool is never overdrawn.
+    pub fn distribute_rewards(&self, rewards: &[(Pubkey, u64)]) -> u64 {
+        let mut pool = self.get_account(&inflation_pool::id()).unwrap_or_default();
+        let owed: u128 = rewards
+            .iter()
+            .map(|(_, lamports)| u128::from(*lamports))
+            .sum();
+        let available = u128::from(pool.lamports);
+        if owed > available {
+            warn!(
+                "inflation pool is {} lamports short of the {} owed in rewards",
+                owed - available,
+                owed
+            );
+        }
+
+        let credits: Vec<_> = rewards
+            .iter()
+            .map(|(pubkey, lamports)| {
+                let reward = if owed > available {
+                    (u128::from(*lamports) * available / owed) as u64
+                } else {
+                    *lamports
+                };
+                (*pubkey, reward)
+            })
+            .filter(|(_, reward)| *reward > 0)
+            .collect();
+        let paid = credits.iter().map(|(_, reward)| reward).sum();
+        self.deposit_many(&credits);
+
+        pool.lamports -= paid;
+        self.store_bounded_account(&inflation_pool::id(), &pool);
+        paid
+    }
+
+    /// Return the owner of each of `pubkeys`, or None for those without an account, without
+    /// loading any account data
+    pub fn get_account_owners(&self, pubkeys: &[Pubkey]) -> Vec<Option<Pubkey>> {
+        self.accounts.load_owners_slow(self.accounts_id, pubkeys)
+    }
+
+    /// Return true if the account exists and is marked executable
+    pub fn is_executable(&self, pubkey: &Pubkey) -> bool {
+        self.get_account(pubkey)
+            .map(|account| account.executable)
+            .unwrap_or(false)
+    }
+
+    /// Return every account owned by `program_id`, including those inherited from parents
+    pub fn get_program_accounts(&self, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
+        self.accounts
+            .load_by_program_slow(self.accounts_id, program_id)
+    }
+
     pub fn get_program_accounts_modified_since_parent(
         &self,
         program_id: &Pubkey,
@@ -761,10 +1974,61 @@ impl Bank {
         self.accounts.load_slow_no_parent(self.accounts_id, pubkey)
     }
 
+    /// Return every account this bank wrote, ignoring its parents, ordered by pubkey. This
+    /// is what an incremental snapshot of the bank on top of its parent needs to record,
+    /// including accounts drained to zero lamports.
+    pub fn get_accounts_delta(&self) -> Vec<(Pubkey, Account)> {
+        self.accounts.load_fork_slow_no_parent(self.accounts_id)
+    }
+
+    /// Return the `n` largest balances that pass `filter`, largest first with ties ordered
+    /// by pubkey. Only the accounts index is scanned, so no account is cloned.
+    pub fn largest_accounts(&self, n: usize, filter: AccountFilter) -> Vec<(Pubkey, u64)> {
+        self.accounts
+            .load_largest_slow(self.accounts_id, n, &filter.excluded_owners)
+    }
+
+    /// Return the lamports of every account summed per owning program, e.g. the system
+    /// program's total is what's held in ordinary wallets and the vote program's is what's
+    /// staked. The totals sum to `capitalization()`.
+    pub fn supply_breakdown(&self) -> HashMap<Pubkey, u64> {
+        self.accounts.load_lamports_by_owner_slow(self.accounts_id)
+    }
+
+    /// Return the lamports held by every account in the bank
+    pub fn capitalization(&self) -> u64 {
+        self.accounts.total_lamports(self.accounts_id)
+    }
+
     pub fn transaction_count(&self) -> u64 {
         self.accounts.transaction_count(self.accounts_id)
     }
 
+    /// Return a read-only handle on this bank's fork of the accounts store, which RPC
+    /// threads can read through without holding the bank or blocking its writers
+    pub fn snapshot_handle(&self) -> BankSnapshot {
+        BankSnapshot {
+            slot: self.slot,
+            hash: if self.is_frozen() {
+                Some(self.hash())
+            } else {
+                None
+            },
+            accounts: self.accounts.clone(),
+            accounts_id: self.accounts_id,
+        }
+    }
+
+    /// Capture this bank's accounts as they are now, for RPC threads to read without
+    /// contending with the banking stage. Unlike `snapshot_handle`, transactions the bank
+    /// commits after the view is taken never show through it.
+    pub fn freeze_read_view(&self) -> BankReadView {
+        BankReadView {
+            slot: self.slot,
+            accounts: Arc::new(Accounts::read_view(&self.accounts, self.accounts_id)),
+        }
+    }
+
     pub fn get_signature_status(&self, signature: &Signature) -> Option<Result<()>> {
         let parents = self.parents();
         let mut caches = vec![self.status_cache.read().unwrap()];
@@ -772,6 +2036,47 @@ impl Bank {
         StatusCache::get_signature_status_all(&caches, signature)
     }
 
+    /// Return the status of each of `signatures`, in order, reading every status cache
+    /// once for the whole batch
+    pub fn get_signature_statuses(&self, signatures: &[Signature]) -> Vec<Option<Result<()>>> {
+        let parents = self.parents();
+        let mut caches = vec![self.status_cache.read().unwrap()];
+        caches.extend(parents.iter().map(|b| b.status_cache.read().unwrap()));
+        signatures
+            .iter()
+            .map(|signature| StatusCache::get_signature_status_all(&caches, signature))
+            .collect()
+    }
+
+    /// Return the status of `signature` along with the slot of the bank whose status cache
+    /// holds it. A signature seen by several banks is reported at the earliest slot, and
+    /// signatures squashed into a root are reported at the root's slot.
+    pub fn get_signature_status_with_slot(
+        &self,
+        signature: &Signature,
+    ) -> Option<(u64, Result<()>)> {
+        let parents = self.parents();
+        parents
+            .iter()
+            .rev()
+            .find_map(|bank| {
+                let status = bank
+                    .status_cache
+                    .read()
+                    .unwrap()
+                    .get_signature_status(signature);
+                status.map(|status| (bank.slot(), status))
+            })
+            .or_else(|| {
+                let status = self
+                    .status_cache
+                    .read()
+                    .unwrap()
+                    .get_signature_status(signature);
+                status.map(|status| (self.slot(), status))
+            })
+    }
+
     pub fn has_signature(&self, signature: &Signature) -> bool {
         let parents = self.parents();
         let mut caches = vec![self.status_cache.read().unwrap()];
@@ -779,6 +2084,20 @@ impl Bank {
         StatusCache::has_signature_all(&caches, signature)
     }
 
+    /// Return the hash of this bank's state: the frozen hash if the bank is frozen,
+    /// otherwise the hash it would freeze with now, without freezing it. The algorithm,
+    /// the parent's hash folded with the hash of this bank's accounts delta, or the
+    /// parent's hash alone if the bank has no accounts of its own, is consensus-critical;
+    /// every node must compute the same value for the same bank.
+    pub fn bank_hash(&self) -> Hash {
+        let hash = self.hash();
+        if hash != Hash::default() {
+            hash
+        } else {
+            self.hash_internal_state()
+        }
+    }
+
     /// Hash the `accounts` HashMap. This represents a validator's interpretation
     ///  of the delta of the ledger since the last vote and up to now
     fn hash_internal_state(&self) -> Hash {
@@ -797,6 +2116,16 @@ impl Bank {
         self.ticks_per_slot
     }
 
+    /// Return the most data a single instruction may carry
+    pub fn max_instruction_data_len(&self) -> usize {
+        MAX_INSTRUCTION_DATA_LEN
+    }
+
+    /// Return the number of slots in a year, derived from this bank's ticks per slot
+    pub fn slots_per_year(&self) -> f64 {
+        SECONDS_PER_YEAR * NUM_TICKS_PER_SECOND as f64 / self.ticks_per_slot as f64
+    }
+
     /// Return the number of ticks since genesis.
     pub fn tick_height(&self) -> u64 {
         // tick_height is using an AtomicUSize because AtomicU64 is not yet a stable API.
@@ -826,6 +2155,37 @@ impl Bank {
         self.epoch_vote_accounts.get(&epoch)
     }
 
+    /// Return the stake that votes must exceed to confirm a slot in `epoch`: two-thirds
+    /// of the lamports in the epoch's vote accounts. An epoch without stake needs none.
+    pub fn supermajority_stake(&self, epoch: u64) -> Option<u64> {
+        self.epoch_vote_accounts(epoch).map(|accounts| {
+            let total_stake: u64 = accounts.values().map(|account| account.lamports).sum();
+            // Divide first so that doubling can't overflow
+            total_stake / 3 * 2 + total_stake % 3 * 2 / 3
+        })
+    }
+
+    /// vote accounts for the specific epoch, borrowed one at a time rather than as a map
+    pub fn epoch_vote_accounts_iter(
+        &self,
+        epoch: u64,
+    ) -> Option<impl Iterator<Item = (&Pubkey, &Account)>> {
+        self.epoch_vote_accounts(epoch).map(HashMap::iter)
+    }
+
+    /// Return the lamports of `epoch`'s vote accounts summed per delegate, since several
+    /// vote accounts may delegate to the same node. Accounts that don't hold a vote state
+    /// are skipped.
+    pub fn delegate_stakes(&self, epoch: u64) -> HashMap<Pubkey, u64> {
+        let mut stakes = HashMap::new();
+        for (_, account) in self.epoch_vote_accounts_iter(epoch).into_iter().flatten() {
+            if let Ok(vote_state) = VoteState::deserialize(&account.data) {
+                *stakes.entry(vote_state.delegate_id).or_insert(0) += account.lamports;
+            }
+        }
+        stakes
+    }
+
     /// given a slot, return the epoch and offset into the epoch this slot falls
     /// e.g. with a fixed number for slots_per_epoch, the calculation is simply:
     ///
@@ -835,6 +2195,20 @@ impl Bank {
         self.epoch_schedule.get_epoch_and_slot_index(slot)
     }
 
+    /// Return this bank's epoch, its slot's offset into the epoch, the epoch's length and
+    /// the bank's tick height, all read at once
+    pub fn get_epoch_info(&self) -> EpochInfo {
+        let absolute_slot = self.slot();
+        let (epoch, slot_index) = self.get_epoch_and_slot_index(absolute_slot);
+        EpochInfo {
+            epoch,
+            slot_index,
+            slots_in_epoch: self.get_slots_in_epoch(epoch),
+            absolute_slot,
+            block_height: self.tick_height(),
+        }
+    }
+
     pub fn is_votable(&self) -> bool {
         let max_tick_height = (self.slot + 1) * self.ticks_per_slot - 1;
         self.is_delta.load(Ordering::Relaxed) && self.tick_height() == max_tick_height
@@ -856,8 +2230,7 @@ impl Bank {
             owner: native_loader::id(),
             executable: true,
         };
-        self.accounts
-            .store_slow(self.accounts_id, &program_id, &bogus_account);
+        self.store_bounded_account(&program_id, &bogus_account);
     }
 
     pub fn is_in_subtree_of(&self, parent: u64) -> bool {
@@ -879,17 +2252,86 @@ impl Bank {
     }
 }
 
+/// A read-only handle on one bank's fork of the accounts store. Squashing the bank or one
+/// of its descendants never removes a fork's entries, so the handle stays valid however far
+/// the bank it came from advances.
+#[derive(Clone)]
+pub struct BankSnapshot {
+    slot: u64,
+    hash: Option<Hash>,
+    accounts: Arc<Accounts>,
+    accounts_id: u64,
+}
+
+impl BankSnapshot {
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// The bank's hash, if it was frozen when the handle was taken
+    pub fn hash(&self) -> Option<Hash> {
+        self.hash
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.accounts.load_slow(self.accounts_id, pubkey)
+    }
+
+    pub fn get_balance(&self, pubkey: &Pubkey) -> u64 {
+        self.get_account(pubkey)
+            .map(|account| Bank::read_balance(&account))
+            .unwrap_or(0)
+    }
+
+    pub fn transaction_count(&self) -> u64 {
+        self.accounts.transaction_count(self.accounts_id)
+    }
+}
+
+/// A consistent, point-in-time copy of a bank's accounts. Cloning is cheap, and the view
+/// keeps reading the same balances however far the bank advances.
+#[derive(Clone)]
+pub struct BankReadView {
+    slot: u64,
+    accounts: Arc<AccountsReadView>,
+}
+
+impl BankReadView {
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.accounts.load(pubkey)
+    }
+
+    pub fn get_balance(&self, pubkey: &Pubkey) -> u64 {
+        self.get_account(pubkey)
+            .map(|account| Bank::read_balance(&account))
+            .unwrap_or(0)
+    }
+
+    pub fn get_program_accounts(&self, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
+        self.accounts.load_by_program(program_id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bincode::serialize;
+    use crate::bank_forks::BankForks;
+    use bincode::{deserialize, serialize};
+    use solana_sdk::account::{KeyedAccount, MAX_PERMITTED_DATA_LENGTH};
+    use solana_sdk::bpf_loader;
     use solana_sdk::genesis_block::{GenesisBlock, BOOTSTRAP_LEADER_LAMPORTS};
     use solana_sdk::hash;
+    use solana_sdk::native_loader::NativeLoaderError;
     use solana_sdk::signature::{Keypair, KeypairUtil};
-    use solana_sdk::system_instruction::SystemInstruction;
-    use solana_sdk::system_program;
-    use solana_sdk::system_transaction::SystemTransaction;
-    use solana_sdk::transaction::{CompiledInstruction, InstructionError};
+    use solana_sdk::system_instruction::SystemError;
+    use solana_sdk::transaction::{
+        CompiledInstruction, Instruction, InstructionError, SanitizeError,
+    };
+    use solana_vote_api::vote_transaction::VoteTransaction;
 
     #[test]
     fn test_bank_new() {
@@ -919,6 +2361,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bank_export_genesis() {
+        let (mut genesis_block, _) =
+            GenesisBlock::new_with_leader(10_000, &Keypair::new().pubkey(), 10);
+        genesis_block.ticks_per_slot = 4;
+        genesis_block.slots_per_epoch = 16;
+        genesis_block.stakers_slot_offset = 8;
+        genesis_block.minimum_balance = Some(2);
+        genesis_block.inflation_pool_lamports = Some(100);
+        genesis_block.rewards_pool = Some(RewardsPool {
+            id: Keypair::new().pubkey(),
+            lamports: 50,
+            authority_id: Keypair::new().pubkey(),
+        });
+        genesis_block
+            .native_programs
+            .push(("solana_noop_program".to_string(), Keypair::new().pubkey()));
+        let bank = Bank::new(&genesis_block);
+
+        let exported = bank.export_genesis().unwrap();
+        assert_eq!(exported.hash(), genesis_block.hash());
+
+        let exported_bank = Bank::new(&exported);
+        assert_eq!(exported_bank.genesis_hash(), bank.genesis_hash());
+        assert_eq!(exported_bank.ticks_per_slot(), bank.ticks_per_slot());
+        assert_eq!(exported_bank.epoch_schedule, bank.epoch_schedule);
+        for pubkey in &[
+            genesis_block.mint_id,
+            genesis_block.bootstrap_leader_id,
+            genesis_block.bootstrap_leader_vote_account_id,
+            inflation_pool::id(),
+        ] {
+            assert_eq!(exported_bank.get_balance(pubkey), bank.get_balance(pubkey));
+        }
+
+        let bank = Arc::new(bank);
+        let child = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        assert_eq!(
+            child.export_genesis().unwrap_err(),
+            BankError::NotGenesisBank { slot: 1 }
+        );
+    }
+
     #[test]
     fn test_two_payments_to_one_party() {
         let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
@@ -954,11 +2439,8 @@ mod tests {
         assert_eq!(bank.get_balance(&key1), 1);
         assert_eq!(bank.get_balance(&key2), 0);
         assert_eq!(bank.get_signature_status(&t1.signatures[0]), Some(Ok(())));
-        // TODO: Transactions that fail to pay a fee could be dropped silently
-        assert_eq!(
-            bank.get_signature_status(&t2.signatures[0]),
-            Some(Err(TransactionError::AccountInUse))
-        );
+        // t2 didn't pay a fee, so it's dropped silently and may be processed again
+        assert_eq!(bank.get_signature_status(&t2.signatures[0]), None);
     }
 
     #[test]
@@ -1004,14 +2486,98 @@ mod tests {
     }
 
     #[test]
-    fn test_one_tx_two_out_atomic_pass() {
+    fn test_one_tx_bad_account_indexes() {
         let (genesis_block, mint_keypair) = GenesisBlock::new(2);
         let key1 = Keypair::new().pubkey();
-        let key2 = Keypair::new().pubkey();
         let bank = Bank::new(&genesis_block);
-        let t1 = SystemTransaction::new_move_many(
-            &mint_keypair,
-            &[(key1, 1), (key2, 1)],
+        let spend = SystemInstruction::Move { lamports: 1 };
+        let new_tx = |program_ids_index, accounts| {
+            let instruction = CompiledInstruction {
+                program_ids_index,
+                data: serialize(&spend).unwrap(),
+                accounts,
+            };
+            Transaction::new_with_compiled_instructions(
+                &[&mint_keypair],
+                &[key1],
+                genesis_block.hash(),
+                0,
+                vec![system_program::id()],
+                vec![instruction],
+            )
+        };
+
+        // Account index past the end of account_keys
+        let t1 = new_tx(0, vec![0, 2]);
+        // Program index past the end of program_ids
+        let t2 = new_tx(1, vec![0, 1]);
+        // The mint passed as both the source and the destination
+        let t3 = new_tx(0, vec![0, 0]);
+        for (tx, err) in &[
+            (t1, SanitizeError::IndexOutOfBounds),
+            (t2, SanitizeError::IndexOutOfBounds),
+            (t3, SanitizeError::DuplicateAccount),
+        ] {
+            assert_eq!(
+                bank.process_transaction(tx),
+                Err(TransactionError::SanitizeFailure(err.clone()))
+            );
+            assert_eq!(bank.get_signature_status(&tx.signatures[0]), None);
+        }
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 2);
+        assert_eq!(bank.get_balance(&key1), 0);
+        assert_eq!(bank.transaction_count(), 0);
+    }
+
+    #[test]
+    fn test_bank_max_instruction_data_len() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let bank = Bank::new(&genesis_block);
+        let max_len = bank.max_instruction_data_len();
+        let new_tx = |len| {
+            let instruction = CompiledInstruction {
+                program_ids_index: 0,
+                data: vec![0; len],
+                accounts: vec![0],
+            };
+            Transaction::new_with_compiled_instructions(
+                &[&mint_keypair],
+                &[],
+                genesis_block.hash(),
+                0,
+                vec![solana_sdk::memo_program::id()],
+                vec![instruction],
+            )
+        };
+
+        // One byte past the limit and the bank refuses the transaction before it runs
+        let tx = new_tx(max_len + 1);
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::InstructionDataTooLarge(0)
+            ))
+        );
+        assert_eq!(bank.get_signature_status(&tx.signatures[0]), None);
+
+        // At the limit the instruction reaches the memo program, which rejects it as too long
+        // a memo
+        let tx = new_tx(max_len);
+        match bank.process_transaction(&tx) {
+            Err(TransactionError::InstructionError(0, _)) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_one_tx_two_out_atomic_pass() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let key1 = Keypair::new().pubkey();
+        let key2 = Keypair::new().pubkey();
+        let bank = Bank::new(&genesis_block);
+        let t1 = SystemTransaction::new_move_many(
+            &mint_keypair,
+            &[(key1, 1), (key2, 1)],
             genesis_block.hash(),
             0,
         );
@@ -1027,7 +2593,8 @@ mod tests {
     // This test demonstrates that fees are paid even when a program fails.
     #[test]
     fn test_detect_failed_duplicate_transactions() {
-        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(2);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(1));
         let bank = Bank::new(&genesis_block);
         let dest = Keypair::new();
 
@@ -1057,6 +2624,53 @@ mod tests {
         assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 1);
     }
 
+    #[test]
+    fn test_duplicate_transaction_releases_locks() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new().pubkey();
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 0);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::DuplicateSignature)
+        );
+
+        // The rejected duplicate doesn't leave the mint's account locked
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 1);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&key), 2);
+    }
+
+    #[test]
+    fn test_detect_batch_duplicate_transactions() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+        let dest = Keypair::new();
+        let tx =
+            SystemTransaction::new_move(&mint_keypair, &dest.pubkey(), 1, genesis_block.hash(), 0);
+        let other =
+            SystemTransaction::new_move(&mint_keypair, &dest.pubkey(), 2, genesis_block.hash(), 0);
+
+        // A copy with the same signature but different contents is still a duplicate
+        let mut forged = other.clone();
+        forged.signatures = tx.signatures.clone();
+
+        // Skip verification, which would reject the forgery before the duplicate check
+        let txs: Vec<_> = vec![tx.clone(), tx.clone(), forged, other]
+            .into_iter()
+            .map(VerifiedTransaction::assume_verified)
+            .collect();
+        let results = bank.process_verified_transactions(&txs);
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Err(TransactionError::DuplicateSignature));
+        assert_eq!(results[2], Err(TransactionError::DuplicateSignature));
+        assert_eq!(results[3], Err(TransactionError::AccountInUse));
+        assert_eq!(bank.get_balance(&dest.pubkey()), 1);
+        assert_eq!(bank.get_signature_status(&tx.signatures[0]), Some(Ok(())));
+        assert_eq!(bank.transaction_count(), 1);
+    }
+
     #[test]
     fn test_account_not_found() {
         let (genesis_block, mint_keypair) = GenesisBlock::new(0);
@@ -1069,6 +2683,67 @@ mod tests {
         assert_eq!(bank.transaction_count(), 0);
     }
 
+    #[test]
+    fn test_bank_process_transaction_timed() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new();
+        let tx =
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 1, bank.last_blockhash(), 0);
+        let (result, load_elapsed, execution_elapsed) = bank.process_transaction_timed(&tx);
+        assert_eq!(result, Ok(()));
+        assert!(load_elapsed > Duration::default());
+        assert!(execution_elapsed > Duration::default());
+        assert_eq!(bank.get_balance(&key.pubkey()), 1);
+
+        // A transaction that fails to load is never executed
+        let unfunded_keypair = Keypair::new();
+        let tx = SystemTransaction::new_move(
+            &unfunded_keypair,
+            &key.pubkey(),
+            1,
+            bank.last_blockhash(),
+            0,
+        );
+        let (result, _load_elapsed, execution_elapsed) = bank.process_transaction_timed(&tx);
+        assert_eq!(result, Err(TransactionError::AccountNotFound));
+        assert!(execution_elapsed < Duration::from_millis(100));
+        assert_eq!(bank.get_balance(&key.pubkey()), 1);
+    }
+
+    #[test]
+    fn test_bank_invalid_program_for_execution() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+
+        // Load a program account but never finalize it
+        let program = Keypair::new();
+        let tx = SystemTransaction::new_program_account(
+            &mint_keypair,
+            &program.pubkey(),
+            genesis_block.hash(),
+            1,
+            4,
+            &bpf_loader::id(),
+            0,
+        );
+        bank.process_transaction(&tx).unwrap();
+        assert!(!bank.get_account(&program.pubkey()).unwrap().executable);
+
+        let tx = Transaction::new_signed(
+            &mint_keypair,
+            &[],
+            &program.pubkey(),
+            &(),
+            genesis_block.hash(),
+            0,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InvalidProgramForExecution)
+        );
+    }
+
     #[test]
     fn test_insufficient_funds() {
         let (genesis_block, mint_keypair) = GenesisBlock::new(11_000);
@@ -1117,6 +2792,39 @@ mod tests {
         assert_eq!(bank.get_balance(&key.pubkey()), 13);
     }
 
+    #[test]
+    fn test_bank_deposit_many() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let key0 = Keypair::new().pubkey();
+        let key1 = Keypair::new().pubkey();
+        let mint_pubkey = mint_keypair.pubkey();
+
+        // Repeated pubkeys get the sum of their credits
+        bank.deposit_many(&[
+            (key0, 10),
+            (mint_pubkey, 1),
+            (key0, 5),
+            (key1, 0),
+            (key0, 2),
+        ]);
+        assert_eq!(bank.get_balance(&key0), 17);
+        assert_eq!(bank.get_balance(&mint_pubkey), 101);
+        assert_eq!(bank.get_account(&key1), None);
+
+        bank.deposit_many(&[]);
+        assert_eq!(bank.get_balance(&key0), 17);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bank_deposit_many_overflow() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new().pubkey();
+        bank.deposit_many(&[(key, std::u64::MAX), (key, 1)]);
+    }
+
     #[test]
     fn test_bank_withdraw() {
         let (genesis_block, _mint_keypair) = GenesisBlock::new(100);
@@ -1143,41 +2851,233 @@ mod tests {
         assert_eq!(bank.get_balance(&key.pubkey()), 1);
     }
 
+    #[test]
+    fn test_bank_withdraw_minimum_balance() {
+        let (mut genesis_block, _mint_keypair) = GenesisBlock::new(100);
+        genesis_block.minimum_balance = Some(5);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        assert_eq!(parent.minimum_balance(), 5);
+        let bank = new_from_parent(&parent);
+        assert_eq!(bank.minimum_balance(), 5);
+
+        let key = Keypair::new();
+        bank.deposit(&key.pubkey(), 10);
+
+        // One lamport above zero, but below the floor
+        assert_eq!(
+            bank.withdraw(&key.pubkey(), 9),
+            Err(TransactionError::WouldBecomeRentPaying)
+        );
+        assert_eq!(bank.get_balance(&key.pubkey()), 10);
+
+        // Staying at the floor
+        assert_eq!(bank.withdraw(&key.pubkey(), 5), Ok(()));
+        assert_eq!(bank.get_balance(&key.pubkey()), 5);
+
+        // Withdrawing to exactly zero closes the account
+        assert_eq!(bank.withdraw(&key.pubkey(), 5), Ok(()));
+        assert_eq!(bank.get_balance(&key.pubkey()), 0);
+        assert_eq!(bank.get_account(&key.pubkey()), None);
+    }
+
     #[test]
     fn test_bank_tx_fee() {
         let leader = Keypair::new().pubkey();
-        let (genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(3));
         let bank = Bank::new(&genesis_block);
         let key1 = Keypair::new();
         let key2 = Keypair::new();
 
         let tx =
-            SystemTransaction::new_move(&mint_keypair, &key1.pubkey(), 2, genesis_block.hash(), 3);
+            SystemTransaction::new_move(&mint_keypair, &key1.pubkey(), 4, genesis_block.hash(), 3);
         let initial_balance = bank.get_balance(&leader);
         assert_eq!(bank.process_transaction(&tx), Ok(()));
         assert_eq!(bank.get_balance(&leader), initial_balance + 3);
-        assert_eq!(bank.get_balance(&key1.pubkey()), 2);
-        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 100 - 5 - 3);
+        assert_eq!(bank.get_balance(&key1.pubkey()), 4);
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 100 - 7 - 3);
 
-        let tx = SystemTransaction::new_move(&key1, &key2.pubkey(), 1, genesis_block.hash(), 1);
+        // Declaring more than the fee schedule charges doesn't cost the payer anything extra
+        let tx = SystemTransaction::new_move(&key1, &key2.pubkey(), 1, genesis_block.hash(), 10);
         assert_eq!(bank.process_transaction(&tx), Ok(()));
-        assert_eq!(bank.get_balance(&leader), initial_balance + 4);
+        assert_eq!(bank.get_balance(&leader), initial_balance + 6);
         assert_eq!(bank.get_balance(&key1.pubkey()), 0);
         assert_eq!(bank.get_balance(&key2.pubkey()), 1);
-        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 100 - 5 - 3);
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 100 - 7 - 3);
+    }
+
+    #[test]
+    fn test_bank_get_fee_for_message() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(100);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(2));
+        let bank = Bank::new(&genesis_block);
+        let key1 = Keypair::new();
+        let new_tx = |keypairs: &[&Keypair]| {
+            Transaction::new_with_compiled_instructions(
+                keypairs,
+                &[],
+                genesis_block.hash(),
+                0,
+                vec![system_program::id()],
+                vec![],
+            )
+        };
+
+        let mut tx = new_tx(&[]);
+        tx.signatures.clear();
+        assert_eq!(bank.get_fee_for_message(&tx), 0);
+        assert_eq!(bank.get_fee_for_message(&new_tx(&[&mint_keypair])), 2);
+        assert_eq!(
+            bank.get_fee_for_message(&new_tx(&[&mint_keypair, &key1])),
+            4
+        );
+    }
+
+    #[test]
+    fn test_bank_verify_account() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let key1 = Keypair::new().pubkey();
+        parent
+            .transfer(3, &mint_keypair, &key1, genesis_block.hash())
+            .unwrap();
+        let bank = new_from_parent(&parent);
+
+        // The account's hash matches whether it's rebuilt by hand or loaded from the bank
+        let account = Account::new(3, 0, &system_program::id());
+        let expected_hash = Bank::hash_account(&key1, &account);
+        assert_eq!(
+            Bank::hash_account(&key1, &bank.get_account(&key1).unwrap()),
+            expected_hash
+        );
+        assert!(bank.verify_account(&key1, &expected_hash));
+        assert!(!bank.verify_account(&mint_keypair.pubkey(), &expected_hash));
+        assert!(!bank.verify_account(&Keypair::new().pubkey(), &Hash::default()));
+
+        bank.withdraw(&key1, 3).unwrap();
+        assert!(!bank.verify_account(&key1, &expected_hash));
+    }
+
+    #[test]
+    fn test_bank_distribute_rewards() {
+        let (mut genesis_block, _) = GenesisBlock::new(1);
+        genesis_block.inflation_pool_lamports = Some(100);
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(bank.inflation_pool_balance(), 100);
+
+        let key1 = Keypair::new().pubkey();
+        let key2 = Keypair::new().pubkey();
+        let total = |bank: &Bank| {
+            bank.inflation_pool_balance() + bank.get_balance(&key1) + bank.get_balance(&key2)
+        };
+
+        assert_eq!(bank.distribute_rewards(&[(key1, 30), (key2, 20)]), 50);
+        assert_eq!(bank.get_balance(&key1), 30);
+        assert_eq!(bank.get_balance(&key2), 20);
+        assert_eq!(bank.inflation_pool_balance(), 50);
+        assert_eq!(total(&bank), 100);
+
+        // The remaining 50 lamports can't cover 100 owed, so each reward is halved
+        let bank = new_from_parent(&Arc::new(bank));
+        assert_eq!(bank.distribute_rewards(&[(key1, 60), (key2, 40)]), 50);
+        assert_eq!(bank.get_balance(&key1), 60);
+        assert_eq!(bank.get_balance(&key2), 40);
+        assert_eq!(bank.inflation_pool_balance(), 0);
+        assert_eq!(total(&bank), 100);
+
+        // An empty pool pays nothing
+        assert_eq!(bank.distribute_rewards(&[(key1, 1)]), 0);
+        assert_eq!(total(&bank), 100);
+    }
+
+    #[test]
+    fn test_bank_fee_for_signatures() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(100);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(3));
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(bank.fee_for_signatures(0), 0);
+        assert_eq!(bank.fee_for_signatures(1), 3);
+        assert_eq!(bank.fee_for_signatures(2), 2 * bank.fee_for_signatures(1));
+
+        let tx = SystemTransaction::new_move(
+            &mint_keypair,
+            &Keypair::new().pubkey(),
+            1,
+            bank.last_blockhash(),
+            0,
+        );
+        assert_eq!(
+            bank.fee_for_signatures(tx.signatures.len()),
+            bank.get_fee_for_message(&tx)
+        );
+    }
+
+    #[test]
+    fn test_bank_insufficient_fee() {
+        let leader = Keypair::new().pubkey();
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(2));
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new().pubkey();
+
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 1);
+        let initial_balance = bank.get_balance(&leader);
+        let mint_balance = bank.get_balance(&mint_keypair.pubkey());
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InsufficientFee)
+        );
+        assert_eq!(bank.get_balance(&key), 0);
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), mint_balance);
+        assert_eq!(bank.get_balance(&leader), initial_balance);
+        assert_eq!(bank.collected_fees(), 0);
+
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 2);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&key), 1);
+        assert_eq!(
+            bank.get_balance(&mint_keypair.pubkey()),
+            mint_balance - 1 - 2
+        );
+        assert_eq!(bank.collected_fees(), 2);
+    }
+
+    #[test]
+    fn test_bank_collected_fees() {
+        let leader = Keypair::new().pubkey();
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(3));
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let key = Keypair::new().pubkey();
+        assert_eq!(parent.collected_fees(), 0);
+
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 2, genesis_block.hash(), 3);
+        assert_eq!(parent.process_transaction(&tx), Ok(()));
+        assert_eq!(parent.collected_fees(), 3);
+
+        // Same collector, but the child only counts its own fees
+        let child = Bank::new_from_parent(&parent, &leader, 1);
+        assert_eq!(child.collected_fees(), 0);
+        let initial_balance = child.get_balance(&leader);
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 3);
+        assert_eq!(child.process_transaction(&tx), Ok(()));
+        assert_eq!(child.collected_fees(), 3);
+        assert_eq!(child.get_balance(&leader), initial_balance + 3);
+        assert_eq!(parent.collected_fees(), 3);
     }
 
     #[test]
     fn test_filter_program_errors_and_collect_fee() {
         let leader = Keypair::new().pubkey();
-        let (genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(2));
         let bank = Bank::new(&genesis_block);
 
         let key = Keypair::new();
         let tx1 =
             SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 2, genesis_block.hash(), 3);
         let tx2 =
-            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 5, genesis_block.hash(), 1);
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 5, genesis_block.hash(), 2);
 
         let results = vec![
             Ok(()),
@@ -1189,7 +3089,7 @@ mod tests {
 
         let initial_balance = bank.get_balance(&leader);
         let results = bank.filter_program_errors_and_collect_fee(&vec![tx1, tx2], &results);
-        assert_eq!(bank.get_balance(&leader), initial_balance + 3 + 1);
+        assert_eq!(bank.get_balance(&leader), initial_balance + 2 + 2);
         assert_eq!(results[0], Ok(()));
         assert_eq!(results[1], Ok(()));
     }
@@ -1221,6 +3121,35 @@ mod tests {
         assert_eq!(bank.transaction_count(), 1);
     }
 
+    #[test]
+    fn test_debits_before_credits_with_retries() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let bank = Bank::new(&genesis_block);
+        let keypair = Keypair::new();
+        let tx0 = SystemTransaction::new_account(
+            &mint_keypair,
+            &keypair.pubkey(),
+            2,
+            genesis_block.hash(),
+            0,
+        );
+        let tx1 = SystemTransaction::new_move(
+            &keypair,
+            &mint_keypair.pubkey(),
+            1,
+            genesis_block.hash(),
+            0,
+        );
+        let txs = vec![tx0, tx1];
+
+        // The credit lands in the first round, so the debit succeeds when it's retried
+        let (results, rounds) = bank.process_transactions_with_retries(&txs, 3);
+        assert_eq!(results, vec![Ok(()), Ok(())]);
+        assert_eq!(rounds, 2);
+        assert_eq!(bank.get_balance(&keypair.pubkey()), 1);
+        assert_eq!(bank.transaction_count(), 2);
+    }
+
     #[test]
     fn test_process_genesis() {
         let dummy_leader_id = Keypair::new().pubkey();
@@ -1232,100 +3161,1557 @@ mod tests {
         assert_eq!(bank.get_balance(&dummy_leader_id), 1);
     }
 
+    fn new_funded_payments(bank: &Bank, count: usize) -> Vec<Transaction> {
+        (0..count)
+            .map(|_| {
+                let payer = Keypair::new();
+                bank.deposit(&payer.pubkey(), 1);
+                SystemTransaction::new_move(
+                    &payer,
+                    &Keypair::new().pubkey(),
+                    1,
+                    bank.last_blockhash(),
+                    0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bank_max_transactions_per_slot() {
+        let (mut genesis_block, _) = GenesisBlock::new(100);
+        genesis_block.max_transactions_per_slot = Some(3);
+        let bank = Arc::new(Bank::new(&genesis_block));
+
+        let txs = new_funded_payments(&bank, 5);
+        let results = bank.process_transactions(&txs);
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 3);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|r| **r == Err(TransactionError::WouldExceedBlockLimit))
+                .count(),
+            2
+        );
+        assert_eq!(bank.transaction_count_in_slot(), 3);
+
+        // The slot is full
+        let txs = new_funded_payments(&bank, 1);
+        assert_eq!(
+            bank.process_transaction(&txs[0]),
+            Err(TransactionError::WouldExceedBlockLimit)
+        );
+
+        // The count is per slot, so a child bank accepts transactions again
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        assert_eq!(bank.transaction_count_in_slot(), 0);
+        assert_eq!(bank.process_transaction(&txs[0]), Ok(()));
+        assert_eq!(bank.transaction_count_in_slot(), 1);
+    }
+
+    #[test]
+    fn test_bank_max_transactions_per_slot_releases_failures() {
+        let (mut genesis_block, _) = GenesisBlock::new(100);
+        genesis_block.max_transactions_per_slot = Some(2);
+        let bank = Bank::new(&genesis_block);
+
+        // Transactions that fail before execution don't use up the slot's capacity
+        let mut txs = new_funded_payments(&bank, 2);
+        txs[0].recent_blockhash = hash::hash(&[0]);
+        let verified_txs: Vec<_> = txs
+            .iter()
+            .cloned()
+            .map(VerifiedTransaction::assume_verified)
+            .collect();
+        let results = bank.process_verified_transactions(&verified_txs);
+        assert_eq!(results[0], Err(TransactionError::BlockhashNotFound));
+        assert_eq!(results[1], Ok(()));
+        assert_eq!(bank.transaction_count_in_slot(), 1);
+
+        // Nor do transactions that failed to lock their accounts
+        let lock_results = bank.lock_accounts(&txs[1..]);
+        assert_eq!(bank.transaction_count_in_slot(), 2);
+        assert_eq!(
+            bank.lock_accounts(&txs[1..]),
+            vec![Err(TransactionError::WouldExceedBlockLimit)]
+        );
+        bank.unlock_uncommitted_accounts(&txs[1..], &lock_results);
+        assert_eq!(bank.transaction_count_in_slot(), 1);
+    }
+
+    #[test]
+    fn test_bank_max_transactions_per_slot_concurrent() {
+        let (mut genesis_block, _) = GenesisBlock::new(100);
+        genesis_block.max_transactions_per_slot = Some(10);
+        let bank = Arc::new(Bank::new(&genesis_block));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let bank = bank.clone();
+                let txs = new_funded_payments(&bank, 8);
+                std::thread::spawn(move || {
+                    bank.process_transactions(&txs)
+                        .iter()
+                        .filter(|r| r.is_ok())
+                        .count()
+                })
+            })
+            .collect();
+        let successes: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(successes, 10);
+        assert_eq!(bank.transaction_count_in_slot(), 10);
+    }
+
+    #[test]
+    fn test_bank_max_transactions_per_slot_batch_duplicates() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(10);
+        genesis_block.max_transactions_per_slot = Some(2);
+        let bank = Bank::new(&genesis_block);
+        let tx = SystemTransaction::new_move(
+            &mint_keypair,
+            &Keypair::new().pubkey(),
+            1,
+            genesis_block.hash(),
+            0,
+        );
+
+        // Only the first copy takes up room in the slot
+        let results = bank.process_transactions(&vec![tx.clone(), tx.clone(), tx]);
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Err(TransactionError::DuplicateSignature));
+        assert_eq!(results[2], Err(TransactionError::DuplicateSignature));
+        assert_eq!(bank.transaction_count_in_slot(), 1);
+    }
+
+    #[test]
+    fn test_bank_minimum_delegation() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(100);
+        genesis_block.minimum_delegation = Some(10);
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(bank.minimum_delegation(), 10);
+
+        let staker_id = Keypair::new().pubkey();
+        let tx =
+            VoteTransaction::new_account(&mint_keypair, &staker_id, bank.last_blockhash(), 9, 0);
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::DelegationBelowMinimum)
+        );
+        assert_eq!(bank.get_balance(&staker_id), 0);
+
+        // Exactly the minimum is enough
+        let tx =
+            VoteTransaction::new_account(&mint_keypair, &staker_id, bank.last_blockhash(), 10, 0);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&staker_id), 10);
+
+        // Children inherit the minimum, and ordinary accounts aren't subject to it
+        let bank = new_from_parent(&Arc::new(bank));
+        assert_eq!(bank.minimum_delegation(), 10);
+        let to = Keypair::new().pubkey();
+        assert!(bank
+            .transfer(1, &mint_keypair, &to, bank.last_blockhash())
+            .is_ok());
+    }
+    #[test]
+    fn test_bank_register_recent_blockhash() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let observed_hash = hash::hash(b"observed");
+        let tick_hash = hash::hash(b"tick");
+        let key = Keypair::new().pubkey();
+
+        // Register the observed hash before and after a slot's worth of ticks
+        let bank0 = Bank::new_from_parent(&parent, &Pubkey::default(), 1);
+        assert!(!bank0.has_blockhash(&observed_hash));
+        bank0.register_recent_blockhash(&observed_hash);
+        assert!(bank0.has_blockhash(&observed_hash));
+        assert_eq!(bank0.last_blockhash(), observed_hash);
+        assert_eq!(bank0.tick_height(), parent.tick_height());
+        for _ in 0..bank0.ticks_per_slot() {
+            bank0.register_tick(&tick_hash);
+        }
+
+        let bank1 = Bank::new_from_parent(&parent, &Pubkey::default(), 1);
+        for _ in 0..bank1.ticks_per_slot() {
+            bank1.register_tick(&tick_hash);
+        }
+        bank1.register_recent_blockhash(&observed_hash);
+        assert_eq!(bank1.last_blockhash(), observed_hash);
+
+        // Transactions may reference the observed hash, and the bank hash doesn't depend
+        // on when it was registered
+        for bank in &[&bank0, &bank1] {
+            let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, observed_hash, 0);
+            assert_eq!(bank.process_transaction(&tx), Ok(()));
+            bank.freeze();
+        }
+        assert_eq!(bank0.hash(), bank1.hash());
+
+        // Frozen banks ignore registrations
+        let late_hash = hash::hash(b"late");
+        bank0.register_recent_blockhash(&late_hash);
+        assert!(!bank0.has_blockhash(&late_hash));
+    }
+
+    #[test]
+    fn test_bank_register_tick_at() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+        let genesis_hash = bank.last_blockhash();
+        let tick_hash = hash::hash(b"tick");
+        let last_tick_height = bank.ticks_per_slot() - 1;
+        for tick_height in 1..last_tick_height {
+            assert_eq!(bank.register_tick_at(&tick_hash, tick_height), Ok(()));
+        }
+
+        // Skipping the slot's last tick doesn't register a blockhash
+        assert_eq!(
+            bank.register_tick_at(&tick_hash, last_tick_height + 1),
+            Err(BankError::TickHeightMismatch {
+                expected: last_tick_height,
+                actual: last_tick_height + 1,
+            })
+        );
+
+        // Neither does registering the previous tick twice
+        assert_eq!(
+            bank.register_tick_at(&tick_hash, last_tick_height - 1),
+            Err(BankError::TickHeightMismatch {
+                expected: last_tick_height,
+                actual: last_tick_height - 1,
+            })
+        );
+        assert_eq!(bank.tick_height(), last_tick_height - 1);
+        assert_eq!(bank.last_blockhash(), genesis_hash);
+
+        assert_eq!(bank.register_tick_at(&tick_hash, last_tick_height), Ok(()));
+        assert_eq!(bank.last_blockhash(), tick_hash);
+    }
+
+    #[test]
+    fn test_bank_register_tick_concurrent() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(10);
+        let bank = Arc::new(Bank::new(&genesis_block));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let bank = bank.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..8 {
+                        bank.register_tick(&hash::hash(b"tick"));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(bank.tick_height(), 32);
+    }
+
+    #[test]
+    fn test_bank_blockhash_age() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+        let genesis_hash = bank.last_blockhash();
+        assert_eq!(bank.blockhash_age(&genesis_hash), Some(0));
+        assert_eq!(bank.blockhash_age(&hash::hash(b"unknown")), None);
+
+        let hashes: Vec<_> = (1..MAX_RECENT_BLOCKHASHES)
+            .map(|i| hash::hash(&serialize(&i).unwrap()))
+            .collect();
+        for hash in &hashes {
+            bank.register_recent_blockhash(hash);
+        }
+        assert_eq!(bank.blockhash_age(&hashes[hashes.len() - 1]), Some(0));
+        assert_eq!(bank.blockhash_age(&hashes[hashes.len() - 2]), Some(1));
+        assert_eq!(bank.blockhash_age(&hashes[0]), Some(hashes.len() - 1));
+
+        // The genesis hash is now the oldest retained
+        assert_eq!(
+            bank.blockhash_age(&genesis_hash),
+            Some(MAX_RECENT_BLOCKHASHES - 1)
+        );
+        bank.register_recent_blockhash(&hash::hash(b"newest"));
+        assert_eq!(bank.blockhash_age(&genesis_hash), None);
+        assert_eq!(
+            bank.blockhash_age(&hashes[0]),
+            Some(MAX_RECENT_BLOCKHASHES - 1)
+        );
+    }
+
+    #[test]
+    fn test_bank_blockhash_status() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+        let genesis_hash = bank.last_blockhash();
+        assert_eq!(
+            bank.blockhash_status(&genesis_hash),
+            BlockhashStatus::Valid { age: 0 }
+        );
+        assert_eq!(
+            bank.blockhash_status(&hash::hash(b"unknown")),
+            BlockhashStatus::Unknown
+        );
+
+        for i in 0..MAX_RECENT_BLOCKHASHES {
+            bank.register_recent_blockhash(&hash::hash(&serialize(&i).unwrap()));
+        }
+        assert_eq!(
+            bank.blockhash_status(&genesis_hash),
+            BlockhashStatus::Valid {
+                age: MAX_RECENT_BLOCKHASHES as u64
+            }
+        );
+
+        // One more hash ages the genesis hash out, and transactions using it are rejected
+        bank.register_recent_blockhash(&hash::hash(b"newest"));
+        assert_eq!(
+            bank.blockhash_status(&genesis_hash),
+            BlockhashStatus::Expired
+        );
+        let tx = SystemTransaction::new_move(
+            &mint_keypair,
+            &Keypair::new().pubkey(),
+            1,
+            genesis_hash,
+            0,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::BlockhashNotFound)
+        );
+        assert_eq!(
+            bank.blockhash_status(&hash::hash(b"unknown")),
+            BlockhashStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_bank_sysvar_accounts() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(10);
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        let clock0 = Clock::from(&bank0.get_account(&clock::id()).unwrap()).unwrap();
+        assert_eq!(clock0, Clock::default());
+        let hashes0 = SlotHashes::from(&bank0.get_account(&slot_hashes::id()).unwrap());
+        assert_eq!(hashes0, Some(SlotHashes::default()));
+
+        for _ in 0..bank0.ticks_per_slot() {
+            bank0.register_tick(&hash::hash(b"tick"));
+        }
+        let slot = bank0.get_slots_in_epoch(0) + 1;
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), slot);
+        let clock1 = Clock::from(&bank1.get_account(&clock::id()).unwrap()).unwrap();
+        assert_eq!(clock1.slot, slot);
+        assert_eq!(clock1.epoch, bank1.get_epoch_and_slot_index(slot).0);
+        assert_eq!(clock1.timestamp, bank0.tick_height() / NUM_TICKS_PER_SECOND);
+        let hashes1 = SlotHashes::from(&bank1.get_account(&slot_hashes::id()).unwrap()).unwrap();
+        assert_eq!(hashes1.inner, vec![(0, bank0.last_blockhash())]);
+
+        // The parent's sysvars are untouched
+        assert_eq!(
+            Clock::from(&bank0.get_account(&clock::id()).unwrap()),
+            Some(clock0)
+        );
+    }
+
+    fn process_sysvar_instruction(
+        _program_id: &Pubkey,
+        keyed_accounts: &mut [KeyedAccount],
+        data: &[u8],
+        _tick_height: u64,
+    ) -> result::Result<(), InstructionError> {
+        let (slot, parent_hash): (u64, Hash) =
+            deserialize(data).map_err(|_| InstructionError::InvalidInstructionData)?;
+        let clock =
+            Clock::from(&keyed_accounts[1].account).ok_or(InstructionError::InvalidAccountData)?;
+        let slot_hashes = SlotHashes::from(&keyed_accounts[2].account)
+            .ok_or(InstructionError::InvalidAccountData)?;
+        if clock.slot != slot || slot_hashes.inner[0].1 != parent_hash {
+            Err(InstructionError::InvalidArgument)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bank_sysvar_accounts_in_instruction() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let mut bank = Bank::new_from_parent(&parent, &Pubkey::default(), 3);
+        let program_id = Pubkey::new(&[4u8; 32]);
+        bank.add_instruction_processor(program_id, process_sysvar_instruction);
+
+        let sysvar_ids = [clock::id(), slot_hashes::id()];
+        let tx = Transaction::new_signed(
+            &mint_keypair,
+            &sysvar_ids,
+            &program_id,
+            &(3u64, parent.last_blockhash()),
+            genesis_block.hash(),
+            0,
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+
+        let tx = Transaction::new_signed(
+            &mint_keypair,
+            &sysvar_ids,
+            &program_id,
+            &(2u64, parent.last_blockhash()),
+            genesis_block.hash(),
+            0,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidArgument
+            ))
+        );
+    }
+
+    fn process_grow_instruction(
+        _program_id: &Pubkey,
+        keyed_accounts: &mut [KeyedAccount],
+        _data: &[u8],
+        _tick_height: u64,
+    ) -> result::Result<(), InstructionError> {
+        keyed_accounts[1].account.data = vec![0; MAX_PERMITTED_DATA_LENGTH + 1];
+        Ok(())
+    }
+
+    #[test]
+    fn test_bank_store_oversized_account() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let mut bank = Bank::new(&genesis_block);
+        let program_id = Pubkey::new(&[4u8; 32]);
+        bank.add_instruction_processor(program_id, process_grow_instruction);
+
+        // The system program refuses to allocate the account
+        let key = Keypair::new().pubkey();
+        let space = MAX_PERMITTED_DATA_LENGTH as u64 + 1;
+        let tx = SystemTransaction::new_program_account(
+            &mint_keypair,
+            &key,
+            genesis_block.hash(),
+            1,
+            space,
+            &program_id,
+            0,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::CustomError(
+                    serialize(&SystemError::InvalidAccountDataLength).unwrap()
+                )
+            ))
+        );
+
+        // A program that grows its account past the cap fails the whole transaction
+        let tx = SystemTransaction::new_program_account(
+            &mint_keypair,
+            &key,
+            genesis_block.hash(),
+            1,
+            0,
+            &program_id,
+            0,
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        let tx = Transaction::new_signed(
+            &mint_keypair,
+            &[key],
+            &program_id,
+            &(),
+            genesis_block.hash(),
+            0,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InvalidAccountDataLength)
+        );
+        assert_eq!(
+            bank.get_signature_status(&tx.signatures[0]),
+            Some(Err(TransactionError::InvalidAccountDataLength))
+        );
+        assert!(bank.get_account(&key).unwrap().data.is_empty());
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 9);
+    }
+
+    fn process_noop_instruction(
+        _program_id: &Pubkey,
+        _keyed_accounts: &mut [KeyedAccount],
+        _data: &[u8],
+        _tick_height: u64,
+    ) -> result::Result<(), InstructionError> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_bank_program_timings() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let mut bank = Bank::new(&genesis_block);
+        let program_id = Pubkey::new(&[4u8; 32]);
+        bank.add_instruction_processor(program_id, process_noop_instruction);
+        let payers: Vec<_> = (0..3).map(|_| Keypair::new()).collect();
+        for payer in &payers {
+            bank.transfer(1_000, &mint_keypair, &payer.pubkey(), bank.last_blockhash())
+                .unwrap();
+        }
+
+        let funding_timings = bank.program_timings();
+        assert_eq!(funding_timings[&system_program::id()].count, 3);
+
+        let blockhash = bank.last_blockhash();
+        let txs = vec![
+            Transaction::new_signed(&payers[0], &[], &program_id, &(), blockhash, 0),
+            SystemTransaction::new_move(&payers[1], &Keypair::new().pubkey(), 10, blockhash, 0),
+            VoteTransaction::new_account(&payers[2], &Keypair::new().pubkey(), blockhash, 10, 0),
+        ];
+        let results = bank.process_transactions(&txs);
+        assert!(results.iter().all(Result::is_ok));
+
+        // Each program is credited with exactly the instructions of its own in the batch
+        let timings = bank.program_timings();
+        assert_eq!(timings.len(), 3);
+        for program_id in &[system_program::id(), solana_vote_api::id(), program_id] {
+            let count = txs
+                .iter()
+                .flat_map(|tx| (0..tx.instructions.len()).map(move |i| tx.program_id(i)))
+                .filter(|tx_program_id| *tx_program_id == program_id)
+                .count() as u64;
+            let funding_count = funding_timings.get(program_id).map_or(0, |t| t.count);
+            assert_eq!(timings[program_id].count - funding_count, count);
+            assert_eq!(timings[program_id].errors, 0);
+        }
+        assert_eq!(timings[&system_program::id()].count, 3 + 2);
+        assert_eq!(timings[&solana_vote_api::id()].count, 1);
+        assert_eq!(timings[&program_id].count, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bank_add_oversized_native_program() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+        let name = "x".repeat(MAX_PERMITTED_DATA_LENGTH + 1);
+        bank.add_native_program(&name, &Pubkey::new(&[4u8; 32]));
+    }
+
+    #[test]
+    fn test_bank_genesis_hash() {
+        let (genesis_block, _) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        assert_eq!(parent.genesis_hash(), genesis_block.hash());
+
+        // registering new blockhashes leaves the genesis hash alone
+        let child = new_from_parent(&parent);
+        for _ in 0..child.ticks_per_slot() {
+            child.register_tick(&hash::hash(b"child"));
+        }
+        assert_ne!(child.last_blockhash(), genesis_block.hash());
+        assert_eq!(child.genesis_hash(), genesis_block.hash());
+    }
+
+    #[test]
+    fn test_bank_last_blockhash_with_fee_calculator() {
+        let (mut genesis_block, _) = GenesisBlock::new(10);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(1));
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let (blockhash, fee_calculator) = parent.last_blockhash_with_fee_calculator();
+        assert_eq!(blockhash, parent.last_blockhash());
+        assert_eq!(fee_calculator, parent.fee_calculator());
+        assert_eq!(fee_calculator, FeeCalculator::new(1));
+
+        // the fee schedule changes before the next blockhash is registered; the
+        // calculator returned is always the current one
+        let mut child = new_from_parent(&parent);
+        assert_eq!(child.fee_calculator(), FeeCalculator::new(1));
+        child.fee_calculator = FeeCalculator::new(2);
+        let (blockhash, fee_calculator) = child.last_blockhash_with_fee_calculator();
+        assert_eq!(blockhash, genesis_block.hash());
+        assert_eq!(fee_calculator, FeeCalculator::new(2));
+
+        let hash = hash::hash(b"child");
+        for _ in 0..child.ticks_per_slot() {
+            child.register_tick(&hash);
+        }
+        let (blockhash, fee_calculator) = child.last_blockhash_with_fee_calculator();
+        assert_eq!(blockhash, child.last_blockhash());
+        assert_eq!(blockhash, hash);
+        assert_eq!(fee_calculator, child.fee_calculator());
+    }
+
+    #[test]
+    fn test_process_transactions_filtered() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new();
+        let txs: Vec<_> = [1, 20]
+            .iter()
+            .map(|lamports| {
+                SystemTransaction::new_move(
+                    &mint_keypair,
+                    &key.pubkey(),
+                    *lamports,
+                    genesis_block.hash(),
+                    0,
+                )
+            })
+            .collect();
+
+        // Drop moves of more than 10 lamports
+        let rejected = TransactionError::InstructionError(0, InstructionError::InvalidArgument);
+        let below_threshold =
+            |tx: &Transaction| match bincode::deserialize(&tx.instructions[0].data) {
+                Ok(SystemInstruction::Move { lamports }) if lamports > 10 => Err(rejected.clone()),
+                _ => Ok(()),
+            };
+
+        let results = bank.process_transactions_filtered(&txs, below_threshold);
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Err(rejected.clone()));
+        assert_eq!(bank.get_balance(&key.pubkey()), 1);
+        assert_eq!(bank.get_signature_status(&txs[1].signatures[0]), None);
+
+        // A predicate that rejects everything never reaches the accounts
+        let results = bank.process_transactions_filtered(&txs, |_| Err(rejected.clone()));
+        assert_eq!(results, vec![Err(rejected.clone()); 2]);
+        assert_eq!(bank.get_balance(&key.pubkey()), 1);
+        assert_eq!(bank.transaction_count(), 1);
+    }
+
+    #[test]
+    fn test_bank_process_transactions_verifies_signatures() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new();
+        let tx =
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 1, bank.last_blockhash(), 0);
+        let mut tampered =
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 2, bank.last_blockhash(), 0);
+        tampered.instructions[0].data =
+            bincode::serialize(&SystemInstruction::Move { lamports: 50 }).unwrap();
+
+        let results = bank.process_transactions(&[tampered.clone(), tx]);
+        assert_eq!(results[0], Err(TransactionError::SignatureFailure));
+        assert_eq!(results[1], Ok(()));
+        assert_eq!(bank.get_signature_status(&tampered.signatures[0]), None);
+        assert_eq!(
+            bank.process_transaction(&tampered),
+            Err(TransactionError::SignatureFailure)
+        );
+        assert_eq!(
+            bank.process_transaction_timed(&tampered).0,
+            Err(TransactionError::SignatureFailure)
+        );
+        assert_eq!(bank.get_balance(&key.pubkey()), 1);
+
+        // Verified transactions go straight to the accounts
+        let tx =
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 3, bank.last_blockhash(), 0);
+        let results =
+            bank.process_verified_transactions(&[VerifiedTransaction::verify(tx).unwrap()]);
+        assert_eq!(results, vec![Ok(())]);
+        assert_eq!(bank.get_balance(&key.pubkey()), 4);
+    }
+
+    #[test]
+    fn test_bank_load_and_execute_transactions_with_write_sets() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let key1 = Keypair::new().pubkey();
+        let key2 = Keypair::new();
+        let key3 = Keypair::new().pubkey();
+        let key4 = Keypair::new();
+        for key in &[key2.pubkey(), key3, key4.pubkey()] {
+            bank.transfer(1, &mint_keypair, key, bank.last_blockhash())
+                .unwrap();
+        }
+
+        let blockhash = bank.last_blockhash();
+        let txs = vec![
+            SystemTransaction::new_move(&mint_keypair, &key1, 2, blockhash, 0),
+            // Touches both accounts without changing either
+            SystemTransaction::new_move(&key2, &key3, 0, blockhash, 0),
+            // Fails, so it has no write set
+            SystemTransaction::new_move(&key4, &Keypair::new().pubkey(), 5, blockhash, 0),
+        ];
+        let lock_results = bank.lock_accounts(&txs);
+        let (_, executed, write_sets) = bank.load_and_execute_transactions_with_write_sets(
+            &txs,
+            lock_results.clone(),
+            MAX_RECENT_BLOCKHASHES,
+        );
+        bank.unlock_uncommitted_accounts(&txs, &lock_results);
+
+        assert!(executed[0].is_ok());
+        assert!(executed[1].is_ok());
+        assert!(executed[2].is_err());
+        let expected: HashSet<_> = [mint_keypair.pubkey(), key1].iter().cloned().collect();
+        assert_eq!(write_sets[0], Some(expected));
+        assert_eq!(write_sets[1], Some(HashSet::new()));
+        assert_eq!(write_sets[2], None);
+    }
+
+    #[test]
+    fn test_bank_set_verify_signatures() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Arc::new(Bank::new(&genesis_block));
+        assert!(bank.verify_signatures());
+        let key = Keypair::new();
+        let mut tx =
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 1, bank.last_blockhash(), 0);
+        tx.signatures[0] = Signature::new(&[1; 64]);
+
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::SignatureFailure)
+        );
+        assert_eq!(bank.get_balance(&key.pubkey()), 0);
+
+        // With verification off the bad signature is accepted, in this bank and its children
+        bank.set_verify_signatures(false);
+        assert!(!bank.verify_signatures());
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&key.pubkey()), 1);
+        let child = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        assert!(!child.verify_signatures());
+
+        child.set_verify_signatures(true);
+        let mut tx =
+            SystemTransaction::new_move(&mint_keypair, &key.pubkey(), 2, child.last_blockhash(), 0);
+        tx.signatures[0] = Signature::new(&[2; 64]);
+        assert_eq!(
+            child.process_transaction_timed(&tx).0,
+            Err(TransactionError::SignatureFailure)
+        );
+        assert_eq!(child.get_balance(&key.pubkey()), 1);
+    }
+
+    #[test]
+    fn test_bank_missing_native_program() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let program_id = Keypair::new().pubkey();
+        bank.add_native_program("solana_missing_program", &program_id);
+
+        let instruction = Instruction::new(program_id, &(), vec![(mint_keypair.pubkey(), true)]);
+        let mut tx = Transaction::new(vec![instruction]);
+        tx.sign(&[&mint_keypair], bank.last_blockhash());
+        let err = NativeLoaderError::LibraryNotFound;
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::CustomError(serialize(&err).unwrap())
+            ))
+        );
+
+        // The bank is still usable
+        let key = Keypair::new().pubkey();
+        assert!(bank
+            .transfer(1, &mint_keypair, &key, bank.last_blockhash())
+            .is_ok());
+        assert_eq!(bank.get_balance(&key), 1);
+    }
+
+    #[test]
+    fn test_bank_loader_cache_redeployed_program() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Arc::new(Bank::new(&genesis_block));
+        let program_id = Keypair::new().pubkey();
+        bank.add_native_program("solana_missing_program", &program_id);
+
+        let instruction = Instruction::new(program_id, &(), vec![(mint_keypair.pubkey(), true)]);
+        let mut tx = Transaction::new(vec![instruction]);
+        tx.sign(&[&mint_keypair], bank.last_blockhash());
+        let load_program = |bank: &Bank| {
+            let mut error_counters = ErrorCounters::default();
+            let loaded = bank.load_accounts(&[tx.clone()], vec![Ok(())], &mut error_counters);
+            let (_, loaders) = loaded[0].clone().unwrap();
+            loaders[0][0].1.clone()
+        };
+        let program = bank.get_account(&program_id).unwrap();
+        assert_eq!(load_program(&bank), program);
+        assert!(bank.loader_cache.contains(&program_id));
+
+        // A child starts from its parent's chains, and drops one when its program is
+        // redeployed
+        let child = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        assert!(child.loader_cache.contains(&program_id));
+        child.add_native_program("solana_noop_program", &program_id);
+        assert!(!child.loader_cache.contains(&program_id));
+
+        // Later slots load the redeployed program, while the parent keeps the original
+        let redeployed = child.get_account(&program_id).unwrap();
+        assert_ne!(redeployed, program);
+        let grandchild = Bank::new_from_parent(&Arc::new(child), &Pubkey::default(), 2);
+        assert_eq!(load_program(&grandchild), redeployed);
+        assert_eq!(load_program(&grandchild), redeployed);
+        assert_eq!(load_program(&bank), program);
+    }
+
+    #[test]
+    fn test_bank_new_account_batch() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+        let t1 = Keypair::new().pubkey();
+        let t2 = Keypair::new().pubkey();
+        let t3 = Keypair::new().pubkey();
+
+        // The mint can't fund all three, so none are created
+        let destinations = vec![(t1, 4), (t2, 4), (t3, 4)];
+        let tx = SystemTransaction::new_account_batch(
+            &mint_keypair,
+            &destinations,
+            genesis_block.hash(),
+            0,
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                2,
+                InstructionError::new_result_with_negative_lamports()
+            ))
+        );
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 10);
+        assert_eq!(bank.get_account(&t1), None);
+        assert_eq!(bank.get_account(&t2), None);
+        assert_eq!(bank.get_account(&t3), None);
+
+        let destinations = vec![(t1, 4), (t2, 4)];
+        let tx = SystemTransaction::new_account_batch(
+            &mint_keypair,
+            &destinations,
+            genesis_block.hash(),
+            0,
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), 2);
+        assert_eq!(bank.get_balance(&t1), 4);
+        assert_eq!(bank.get_balance(&t2), 4);
+    }
+
+    #[test]
+    fn test_interleaving_locks() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(3);
+        let bank = Bank::new(&genesis_block);
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+
+        let tx1 = SystemTransaction::new_account(
+            &mint_keypair,
+            &alice.pubkey(),
+            1,
+            genesis_block.hash(),
+            0,
+        );
+        let pay_alice = vec![tx1];
+
+        let lock_result = bank.lock_accounts(&pay_alice);
+        let results_alice = bank.load_execute_and_commit_transactions(
+            &pay_alice,
+            lock_result.clone(),
+            MAX_RECENT_BLOCKHASHES,
+        );
+        assert_eq!(results_alice[0], Ok(()));
+
+        // try executing an interleaved transfer twice
+        assert_eq!(
+            bank.transfer(1, &mint_keypair, &bob.pubkey(), genesis_block.hash()),
+            Err(TransactionError::AccountInUse)
+        );
+        // the second time should fail as well
+        // this verifies that `unlock_accounts` doesn't unlock `AccountInUse` accounts
+        assert_eq!(
+            bank.transfer(1, &mint_keypair, &bob.pubkey(), genesis_block.hash()),
+            Err(TransactionError::AccountInUse)
+        );
+
+        // retrying can't help while the locks are held outside the batch
+        let pay_bob = vec![SystemTransaction::new_move(
+            &mint_keypair,
+            &bob.pubkey(),
+            1,
+            genesis_block.hash(),
+            0,
+        )];
+        assert_eq!(
+            bank.process_transactions_with_retries(&pay_bob, 3),
+            (vec![Err(TransactionError::AccountInUse)], 3)
+        );
+
+        bank.unlock_accounts(&pay_alice, &lock_result, &results_alice);
+
+        assert!(bank
+            .transfer(2, &mint_keypair, &bob.pubkey(), genesis_block.hash())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_process_transactions_conflicts_resolve_in_input_order() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let payers: Vec<_> = (0..4).map(|_| Keypair::new()).collect();
+        let bob = Keypair::new().pubkey();
+        let carol = Keypair::new().pubkey();
+        let dave = Keypair::new().pubkey();
+        let txs = vec![
+            SystemTransaction::new_move(&payers[0], &bob, 1, genesis_block.hash(), 0),
+            // Conflicts with the first transaction on its payer
+            SystemTransaction::new_move(&payers[0], &carol, 1, genesis_block.hash(), 0),
+            // Conflicts with the first transaction on its recipient
+            SystemTransaction::new_move(&payers[1], &bob, 1, genesis_block.hash(), 0),
+            SystemTransaction::new_move(&payers[2], &dave, 1, genesis_block.hash(), 0),
+            // Conflicts with the transaction before it, which was itself unaffected
+            SystemTransaction::new_move(&payers[3], &dave, 1, genesis_block.hash(), 0),
+        ];
+        let expected = vec![
+            Ok(()),
+            Err(TransactionError::AccountInUse),
+            Err(TransactionError::AccountInUse),
+            Ok(()),
+            Err(TransactionError::AccountInUse),
+        ];
+
+        for _ in 0..100 {
+            let bank = Bank::new(&genesis_block);
+            for payer in &payers {
+                bank.transfer(10, &mint_keypair, &payer.pubkey(), genesis_block.hash())
+                    .unwrap();
+            }
+            assert_eq!(bank.process_transactions(&txs), expected);
+            assert_eq!(bank.get_balance(&bob), 1);
+            assert_eq!(bank.get_balance(&carol), 0);
+            assert_eq!(bank.get_balance(&dave), 1);
+        }
+    }
+
+    #[test]
+    fn test_process_transactions_parallel_matches_sequential() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(1_000);
+        let payers: Vec<_> = (0..8).map(|_| Keypair::new()).collect();
+        let recipients: Vec<_> = (0..4).map(|_| Keypair::new().pubkey()).collect();
+        // Pairs of payers share a recipient, so half of them conflict, and the last payer
+        // overdraws its account
+        let mixed: Vec<_> = payers
+            .iter()
+            .enumerate()
+            .map(|(i, payer)| {
+                let lamports = if i == payers.len() - 1 {
+                    11
+                } else {
+                    i as u64 + 1
+                };
+                let to = &recipients[i / 2];
+                SystemTransaction::new_move(payer, to, lamports, genesis_block.hash(), 0)
+            })
+            .collect();
+        // Every transaction locks the same payer
+        let conflicting: Vec<_> = recipients
+            .iter()
+            .map(|to| SystemTransaction::new_move(&payers[0], to, 1, genesis_block.hash(), 0))
+            .collect();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        for txs in &[mixed, conflicting] {
+            let new_bank = || {
+                let bank = Bank::new(&genesis_block);
+                for payer in &payers {
+                    bank.transfer(10, &mint_keypair, &payer.pubkey(), genesis_block.hash())
+                        .unwrap();
+                }
+                bank
+            };
+            let sequential_bank = new_bank();
+            let parallel_bank = new_bank();
+            let results = sequential_bank.process_transactions(txs);
+            assert!(results.iter().any(|result| result.is_ok()));
+            assert_eq!(
+                parallel_bank.process_transactions_parallel(txs, &pool),
+                results
+            );
+            for pubkey in payers
+                .iter()
+                .map(|payer| payer.pubkey())
+                .chain(recipients.clone())
+            {
+                assert_eq!(
+                    parallel_bank.get_balance(&pubkey),
+                    sequential_bank.get_balance(&pubkey)
+                );
+            }
+            assert_eq!(
+                parallel_bank.transaction_count(),
+                sequential_bank.transaction_count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_interleaving_locks_with_retries() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(6);
+        let bank = Bank::new(&genesis_block);
+        let alice = Keypair::new().pubkey();
+        let bob = Keypair::new().pubkey();
+        let txs: Vec<_> = [alice, bob, alice]
+            .iter()
+            .enumerate()
+            .map(|(i, to)| {
+                SystemTransaction::new_move(
+                    &mint_keypair,
+                    to,
+                    i as u64 + 1,
+                    genesis_block.hash(),
+                    0,
+                )
+            })
+            .collect();
+
+        // Every transaction locks the mint, so a single round only commits the first
+        let results = bank.process_transactions(&txs[..1]);
+        assert_eq!(results, vec![Ok(())]);
+        let results = bank.process_transactions(&txs[1..]);
+        assert_eq!(results, vec![Ok(()), Err(TransactionError::AccountInUse)]);
+
+        // One round per transaction, and each result stays in place
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(
+            bank.process_transactions_with_retries(&txs, 2),
+            (vec![Ok(()), Ok(()), Err(TransactionError::AccountInUse)], 2)
+        );
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(
+            bank.process_transactions_with_retries(&txs, 5),
+            (vec![Ok(()), Ok(()), Ok(())], 3)
+        );
+        assert_eq!(bank.get_balance(&alice), 4);
+        assert_eq!(bank.get_balance(&bob), 2);
+
+        // A round with nothing left to retry is never run
+        let bank = Bank::new(&genesis_block);
+        assert_eq!(
+            bank.process_transactions_with_retries(&txs[..1], 5),
+            (vec![Ok(())], 1)
+        );
+    }
+
+    #[test]
+    fn test_bank_pay_to_self() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(1);
+        let key1 = Keypair::new();
+        let bank = Bank::new(&genesis_block);
+
+        bank.transfer(1, &mint_keypair, &key1.pubkey(), genesis_block.hash())
+            .unwrap();
+        assert_eq!(bank.get_balance(&key1.pubkey()), 1);
+        let tx = SystemTransaction::new_move(&key1, &key1.pubkey(), 1, genesis_block.hash(), 0);
+        let res = bank.process_transactions(&vec![tx.clone()]);
+        assert_eq!(res.len(), 1);
+        assert_eq!(bank.get_balance(&key1.pubkey()), 1);
+
+        // TODO: Why do we convert errors to Oks?
+        //res[0].clone().unwrap_err();
+
+        bank.get_signature_status(&tx.signatures[0])
+            .unwrap()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_bank_get_account_owners() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let program_id = Keypair::new().pubkey();
+        parent.add_native_program("solana_budget_program", &program_id);
+        let key1 = Keypair::new();
+        parent
+            .transfer(1, &mint_keypair, &key1.pubkey(), genesis_block.hash())
+            .unwrap();
+
+        // Accounts stored in a parent are found, and one emptied in the child is gone
+        let bank = new_from_parent(&parent);
+        bank.withdraw(&key1.pubkey(), 1).unwrap();
+        let missing = Keypair::new().pubkey();
+        let pubkeys = [
+            mint_keypair.pubkey(),
+            program_id,
+            genesis_block.bootstrap_leader_vote_account_id,
+            missing,
+            key1.pubkey(),
+        ];
+        assert_eq!(
+            bank.get_account_owners(&pubkeys),
+            vec![
+                Some(system_program::id()),
+                Some(native_loader::id()),
+                Some(solana_vote_api::id()),
+                None,
+                None,
+            ]
+        );
+        assert_eq!(
+            parent.get_account_owners(&[key1.pubkey()]),
+            vec![Some(system_program::id())]
+        );
+    }
+
+    #[test]
+    fn test_bank_reject_self_transfers() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(3);
+        genesis_block.reject_self_transfers = true;
+        let bank = Bank::new(&genesis_block);
+        assert!(bank.reject_self_transfers());
+
+        let key1 = Keypair::new();
+        bank.transfer(2, &mint_keypair, &key1.pubkey(), genesis_block.hash())
+            .unwrap();
+        assert_eq!(
+            bank.transfer(1, &key1, &key1.pubkey(), genesis_block.hash()),
+            Err(TransactionError::SelfTransferNotAllowed)
+        );
+        let tx = SystemTransaction::new_move(&key1, &key1.pubkey(), 1, genesis_block.hash(), 0);
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::SelfTransferNotAllowed)
+        );
+        assert_eq!(bank.get_balance(&key1.pubkey()), 2);
+        assert_eq!(bank.get_signature_status(&tx.signatures[0]), None);
+
+        // Children inherit the policy
+        let bank = new_from_parent(&Arc::new(bank));
+        assert!(bank.reject_self_transfers());
+    }
+
+    #[test]
+    fn test_bank_reject_self_transfers_allows_fee() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(3);
+        genesis_block.reject_self_transfers = true;
+        let bank = Bank::new(&genesis_block);
+
+        // A fee makes the transaction more than a no-op, so the policy lets it through
+        // to the usual checks, which reject the repeated account as they always have
+        let key1 = Keypair::new();
+        bank.transfer(2, &mint_keypair, &key1.pubkey(), genesis_block.hash())
+            .unwrap();
+        let tx = SystemTransaction::new_move(&key1, &key1.pubkey(), 1, genesis_block.hash(), 1);
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::DuplicateAccount
+            ))
+        );
+    }
+
+    fn new_from_parent(parent: &Arc<Bank>) -> Bank {
+        Bank::new_from_parent(parent, &Pubkey::default(), parent.slot() + 1)
+    }
+
+    /// Verify that the parent's vector is computed correctly
+    #[test]
+    fn test_bank_parents() {
+        let (genesis_block, _) = GenesisBlock::new(1);
+        let parent = Arc::new(Bank::new(&genesis_block));
+
+        let bank = new_from_parent(&parent);
+        assert!(Arc::ptr_eq(&bank.parents()[0], &parent));
+    }
+
+    #[test]
+    fn test_bank_accounts_id() {
+        let (genesis_block, _) = GenesisBlock::new(1);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        assert_eq!(parent.accounts_id(), 0);
+
+        let child = Bank::new_from_parent(&parent, &Pubkey::default(), 1);
+        let sibling = Bank::new_from_parent(&parent, &Pubkey::default(), 2);
+        assert_ne!(child.accounts_id(), parent.accounts_id());
+        assert_ne!(sibling.accounts_id(), parent.accounts_id());
+        assert_ne!(sibling.accounts_id(), child.accounts_id());
+    }
+
+    #[test]
+    fn test_bank_snapshot_handle() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let key = Keypair::new();
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        bank0
+            .transfer(1, &mint_keypair, &key.pubkey(), bank0.last_blockhash())
+            .unwrap();
+        bank0.freeze();
+        let snapshot0 = bank0.snapshot_handle();
+        assert_eq!(snapshot0.slot(), 0);
+        assert_eq!(snapshot0.hash(), Some(bank0.hash()));
+
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank1
+            .transfer(2, &mint_keypair, &key.pubkey(), bank1.last_blockhash())
+            .unwrap();
+        let snapshot1 = bank1.snapshot_handle();
+        assert_eq!(snapshot1.hash(), None);
+
+        // Advance past both banks, squash, and let them go
+        bank1.squash();
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        bank2
+            .transfer(3, &mint_keypair, &key.pubkey(), bank2.last_blockhash())
+            .unwrap();
+        bank2.squash();
+        drop(bank0);
+        drop(bank1);
+
+        assert_eq!(snapshot0.get_balance(&key.pubkey()), 1);
+        assert_eq!(snapshot0.transaction_count(), 1);
+        assert_eq!(snapshot1.get_balance(&key.pubkey()), 3);
+        assert_eq!(snapshot1.transaction_count(), 2);
+        assert_eq!(snapshot1.get_balance(&mint_keypair.pubkey()), 7);
+        assert_eq!(bank2.get_balance(&key.pubkey()), 6);
+        assert_eq!(bank2.transaction_count(), 3);
+    }
+
+    #[test]
+    fn test_bank_freeze_read_view() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let key = Keypair::new();
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        bank0
+            .transfer(1, &mint_keypair, &key.pubkey(), bank0.last_blockhash())
+            .unwrap();
+        let view0 = bank0.freeze_read_view();
+
+        // Writes committed after the view is taken don't show through it, even to the same
+        // fork
+        bank0
+            .transfer(2, &mint_keypair, &key.pubkey(), bank0.last_blockhash())
+            .unwrap();
+        assert_eq!(view0.slot(), 0);
+        assert_eq!(view0.get_balance(&key.pubkey()), 1);
+        assert_eq!(bank0.get_balance(&key.pubkey()), 3);
+
+        // A child's view sees the parent's accounts, and survives squashing and dropping
+        // the banks it came from
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank1
+            .transfer(3, &mint_keypair, &key.pubkey(), bank1.last_blockhash())
+            .unwrap();
+        let view1 = bank1.freeze_read_view();
+        bank1.squash();
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        bank2
+            .transfer(4, &mint_keypair, &key.pubkey(), bank2.last_blockhash())
+            .unwrap();
+        bank2.squash();
+        drop(bank0);
+        drop(bank1);
+
+        assert_eq!(view0.get_balance(&key.pubkey()), 1);
+        assert_eq!(view1.get_balance(&key.pubkey()), 6);
+        assert_eq!(view1.get_balance(&mint_keypair.pubkey()), 4);
+        assert_eq!(view1.get_account(&Keypair::new().pubkey()), None);
+        assert!(view1
+            .get_program_accounts(&system_program::id())
+            .iter()
+            .any(|(pubkey, account)| *pubkey == key.pubkey() && account.lamports == 6));
+        assert_eq!(bank2.get_balance(&key.pubkey()), 10);
+    }
+
+    #[test]
+    fn test_bank_freeze_read_view_concurrent_commits() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(1_000);
+        let bank = Arc::new(Bank::new(&genesis_block));
+        let keypairs: Vec<_> = (0..4).map(|_| Keypair::new()).collect();
+        for keypair in &keypairs {
+            bank.transfer(100, &mint_keypair, &keypair.pubkey(), bank.last_blockhash())
+                .unwrap();
+        }
+        let pubkeys: Vec<_> = keypairs.iter().map(|keypair| keypair.pubkey()).collect();
+        let total = 400;
+
+        // Transfers only move lamports among the keypairs, so every consistent view of
+        // them sums to the same total
+        let exit = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let bank = bank.clone();
+            let exit = exit.clone();
+            std::thread::spawn(move || {
+                let mut tick_hash = bank.last_blockhash();
+                while !exit.load(Ordering::Relaxed) {
+                    for (i, from) in keypairs.iter().enumerate() {
+                        let to = keypairs[(i + 1) % keypairs.len()].pubkey();
+                        let tx =
+                            SystemTransaction::new_move(from, &to, 1, bank.last_blockhash(), 0);
+                        bank.process_transaction(&tx).unwrap();
+                    }
+                    // Move on to a fresh blockhash so the next round's moves aren't
+                    // duplicates
+                    for _ in 0..bank.ticks_per_slot() {
+                        tick_hash = hash::hash(tick_hash.as_ref());
+                        bank.register_tick(&tick_hash);
+                    }
+                }
+            })
+        };
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let bank = bank.clone();
+                let pubkeys = pubkeys.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        let view = bank.freeze_read_view();
+                        let balances: Vec<_> = pubkeys
+                            .iter()
+                            .map(|pubkey| view.get_balance(pubkey))
+                            .collect();
+                        assert_eq!(balances.iter().sum::<u64>(), total);
+                        // The view doesn't change while the bank keeps committing
+                        let again: Vec<_> = pubkeys
+                            .iter()
+                            .map(|pubkey| view.get_balance(pubkey))
+                            .collect();
+                        assert_eq!(again, balances);
+                    }
+                })
+            })
+            .collect();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        exit.store(true, Ordering::Relaxed);
+        writer.join().unwrap();
+        let balances: Vec<_> = pubkeys
+            .iter()
+            .map(|pubkey| bank.get_balance(pubkey))
+            .collect();
+        assert_eq!(balances.iter().sum::<u64>(), total);
+    }
+
+    /// Verifies that last ids and status cache are correctly referenced from parent
+    #[test]
+    fn test_bank_parent_duplicate_signature() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
+        let key1 = Keypair::new();
+        let parent = Arc::new(Bank::new(&genesis_block));
+
+        let tx =
+            SystemTransaction::new_move(&mint_keypair, &key1.pubkey(), 1, genesis_block.hash(), 0);
+        assert_eq!(parent.process_transaction(&tx), Ok(()));
+        let bank = new_from_parent(&parent);
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::DuplicateSignature)
+        );
+    }
+
+    #[test]
+    fn test_bank_get_signature_statuses() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let key = Keypair::new().pubkey();
+        let confirmed =
+            SystemTransaction::new_move(&mint_keypair, &key, 1, parent.last_blockhash(), 0);
+        assert_eq!(parent.process_transaction(&confirmed), Ok(()));
+
+        let bank = new_from_parent(&parent);
+        let failed =
+            SystemTransaction::new_move(&mint_keypair, &key, 100, bank.last_blockhash(), 0);
+        assert!(bank.process_transaction(&failed).is_err());
+        let unknown = Signature::new(&[1u8; 64]);
+
+        let statuses =
+            bank.get_signature_statuses(&[unknown, failed.signatures[0], confirmed.signatures[0]]);
+        assert_eq!(
+            statuses,
+            vec![
+                None,
+                bank.get_signature_status(&failed.signatures[0]),
+                Some(Ok(())),
+            ]
+        );
+        assert!(statuses[1].as_ref().unwrap().is_err());
+        assert!(bank.get_signature_statuses(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_bank_get_signature_status_with_slot() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let key = Keypair::new().pubkey();
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 0);
+        let signature = tx.signatures[0];
+        assert_eq!(parent.get_signature_status_with_slot(&signature), None);
+        assert_eq!(parent.process_transaction(&tx), Ok(()));
+
+        let child = Bank::new_from_parent(&parent, &Pubkey::default(), 2);
+        assert_eq!(
+            child.get_signature_status_with_slot(&signature),
+            Some((parent.slot(), Ok(())))
+        );
+
+        // Re-seen by the child, but still reported at the parent's slot
+        child.status_cache.write().unwrap().add(&signature);
+        assert_eq!(
+            child.get_signature_status_with_slot(&signature),
+            Some((parent.slot(), Ok(())))
+        );
+
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, genesis_block.hash(), 1);
+        assert_eq!(child.process_transaction(&tx), Ok(()));
+        assert_eq!(
+            child.get_signature_status_with_slot(&tx.signatures[0]),
+            Some((2, Ok(())))
+        );
+    }
+
+    #[test]
+    fn test_bank_clear_signatures_older_than() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(10);
+        // One cache generation per slot
+        genesis_block.ticks_per_slot = NUM_TICKS_PER_SECOND;
+        let bank = Bank::new(&genesis_block);
+        let key = Keypair::new().pubkey();
+        let blockhash = genesis_block.hash();
+
+        let old_tx = SystemTransaction::new_move(&mint_keypair, &key, 1, blockhash, 0);
+        assert_eq!(bank.process_transaction(&old_tx), Ok(()));
+        for _ in 0..2 * NUM_TICKS_PER_SECOND {
+            bank.register_tick(&Hash::default());
+        }
+        let recent_tx = SystemTransaction::new_move(&mint_keypair, &key, 2, blockhash, 0);
+        assert_eq!(bank.process_transaction(&recent_tx), Ok(()));
+
+        bank.clear_signatures_older_than(1);
+        assert_eq!(bank.process_transaction(&old_tx), Ok(()));
+        assert_eq!(bank.get_balance(&key), 4);
+        assert_eq!(
+            bank.process_transaction(&recent_tx),
+            Err(TransactionError::DuplicateSignature)
+        );
+    }
+
     #[test]
-    fn test_interleaving_locks() {
-        let (genesis_block, mint_keypair) = GenesisBlock::new(3);
+    fn test_bank_clear_signatures_preserve_failures() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
         let bank = Bank::new(&genesis_block);
-        let alice = Keypair::new();
-        let bob = Keypair::new();
-
-        let tx1 = SystemTransaction::new_account(
-            &mint_keypair,
-            &alice.pubkey(),
-            1,
-            genesis_block.hash(),
-            0,
-        );
-        let pay_alice = vec![tx1];
+        let key = Keypair::new().pubkey();
+        let blockhash = genesis_block.hash();
 
-        let lock_result = bank.lock_accounts(&pay_alice);
-        let results_alice = bank.load_execute_and_commit_transactions(
-            &pay_alice,
-            lock_result,
-            MAX_RECENT_BLOCKHASHES,
-        );
-        assert_eq!(results_alice[0], Ok(()));
+        let ok_tx = SystemTransaction::new_move(&mint_keypair, &key, 1, blockhash, 0);
+        assert_eq!(bank.process_transaction(&ok_tx), Ok(()));
+        let failed_tx = SystemTransaction::new_move(&mint_keypair, &key, 100, blockhash, 0);
+        let failure = bank.process_transaction(&failed_tx).unwrap_err();
 
-        // try executing an interleaved transfer twice
+        bank.clear_signatures(true);
+        assert_eq!(bank.get_signature_status(&ok_tx.signatures[0]), None);
         assert_eq!(
-            bank.transfer(1, &mint_keypair, &bob.pubkey(), genesis_block.hash()),
-            Err(TransactionError::AccountInUse)
+            bank.get_signature_status(&failed_tx.signatures[0]),
+            Some(Err(failure))
         );
-        // the second time should fail as well
-        // this verifies that `unlock_accounts` doesn't unlock `AccountInUse` accounts
         assert_eq!(
-            bank.transfer(1, &mint_keypair, &bob.pubkey(), genesis_block.hash()),
-            Err(TransactionError::AccountInUse)
+            bank.process_transaction(&failed_tx),
+            Err(TransactionError::DuplicateSignature)
         );
 
-        bank.unlock_accounts(&pay_alice, &results_alice);
-
-        assert!(bank
-            .transfer(2, &mint_keypair, &bob.pubkey(), genesis_block.hash())
-            .is_ok());
+        bank.clear_signatures(false);
+        assert_eq!(bank.get_signature_status(&failed_tx.signatures[0]), None);
     }
 
     #[test]
-    fn test_bank_pay_to_self() {
-        let (genesis_block, mint_keypair) = GenesisBlock::new(1);
-        let key1 = Keypair::new();
+    fn test_bank_duplicate_signature_expires_with_blockhash() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(10);
+        genesis_block.ticks_per_slot = 1;
         let bank = Bank::new(&genesis_block);
+        let key = Keypair::new().pubkey();
+        let blockhash = genesis_block.hash();
 
-        bank.transfer(1, &mint_keypair, &key1.pubkey(), genesis_block.hash())
-            .unwrap();
-        assert_eq!(bank.get_balance(&key1.pubkey()), 1);
-        let tx = SystemTransaction::new_move(&key1, &key1.pubkey(), 1, genesis_block.hash(), 0);
-        let res = bank.process_transactions(&vec![tx.clone()]);
-        assert_eq!(res.len(), 1);
-        assert_eq!(bank.get_balance(&key1.pubkey()), 1);
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 1, blockhash, 0);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
 
-        // TODO: Why do we convert errors to Oks?
-        //res[0].clone().unwrap_err();
+        // Rejected for as long as its blockhash is recent, and never committed twice
+        for i in 0..3usize {
+            bank.register_tick(&hash::hash(&serialize(&i).unwrap()));
+            assert_eq!(
+                bank.process_transaction(&tx),
+                Err(TransactionError::DuplicateSignature)
+            );
+        }
+        assert_eq!(bank.get_balance(&key), 1);
 
-        bank.get_signature_status(&tx.signatures[0])
-            .unwrap()
-            .unwrap_err();
-    }
+        for i in 3..=MAX_RECENT_BLOCKHASHES + 3 {
+            bank.register_tick(&hash::hash(&serialize(&i).unwrap()));
+        }
+        assert!(!bank.has_blockhash(&blockhash));
 
-    fn new_from_parent(parent: &Arc<Bank>) -> Bank {
-        Bank::new_from_parent(parent, &Pubkey::default(), parent.slot() + 1)
+        // Once its blockhash expires it's refused for that instead
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::BlockhashNotFound)
+        );
+        assert_eq!(bank.get_balance(&key), 1);
     }
 
-    /// Verify that the parent's vector is computed correctly
     #[test]
-    fn test_bank_parents() {
-        let (genesis_block, _) = GenesisBlock::new(1);
-        let parent = Arc::new(Bank::new(&genesis_block));
+    fn test_bank_status_cache_config() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let config = StatusCacheConfig {
+            max_caches: 1,
+            max_entries_per_cache: 2,
+        };
+        let parent = Arc::new(Bank::new_with_status_cache_config(&genesis_block, config));
+        let key = Keypair::new();
+        let txs: Vec<_> = (1..=5)
+            .map(|lamports| {
+                SystemTransaction::new_move(
+                    &mint_keypair,
+                    &key.pubkey(),
+                    lamports,
+                    genesis_block.hash(),
+                    0,
+                )
+            })
+            .collect();
+        for tx in &txs[..2] {
+            assert_eq!(parent.process_transaction(tx), Ok(()));
+        }
+        assert_eq!(
+            parent.status_cache_stats(),
+            StatusCacheStats {
+                num_caches: 1,
+                num_entries: 2
+            }
+        );
 
+        // Children inherit the config
         let bank = new_from_parent(&parent);
-        assert!(Arc::ptr_eq(&bank.parents()[0], &parent));
-    }
-
-    /// Verifies that last ids and status cache are correctly referenced from parent
-    #[test]
-    fn test_bank_parent_duplicate_signature() {
-        let (genesis_block, mint_keypair) = GenesisBlock::new(2);
-        let key1 = Keypair::new();
-        let parent = Arc::new(Bank::new(&genesis_block));
+        for tx in &txs[2..] {
+            assert_eq!(bank.process_transaction(tx), Ok(()));
+        }
+        assert_eq!(
+            bank.status_cache_stats(),
+            StatusCacheStats {
+                num_caches: 3,
+                num_entries: 5
+            }
+        );
 
-        let tx =
-            SystemTransaction::new_move(&mint_keypair, &key1.pubkey(), 1, genesis_block.hash(), 0);
-        assert_eq!(parent.process_transaction(&tx), Ok(()));
-        let bank = new_from_parent(&parent);
+        // The child rotated once and kept a single previous cache
+        bank.squash();
         assert_eq!(
-            bank.process_transaction(&tx),
+            bank.status_cache_stats(),
+            StatusCacheStats {
+                num_caches: 2,
+                num_entries: 3
+            }
+        );
+        assert_eq!(
+            bank.process_transaction(&txs[4]),
             Err(TransactionError::DuplicateSignature)
         );
+        assert_eq!(bank.get_signature_status(&txs[0].signatures[0]), None);
     }
 
     /// Verifies that last ids and accounts are correctly referenced from parent
@@ -1363,9 +4749,33 @@ mod tests {
             .unwrap();
         assert_eq!(bank0.hash_internal_state(), bank1.hash_internal_state());
 
-        // Checkpointing should not change its state
+        // Checkpoints of equal banks at the same slot have equal state
         let bank2 = new_from_parent(&Arc::new(bank1));
-        assert_eq!(bank0.hash_internal_state(), bank2.hash_internal_state());
+        let bank3 = new_from_parent(&Arc::new(bank0));
+        assert_eq!(bank2.hash_internal_state(), bank3.hash_internal_state());
+    }
+
+    #[test]
+    fn test_bank_hash() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let bank = new_from_parent(&parent);
+        assert_eq!(parent.bank_hash(), parent.hash());
+
+        // Computing the hash doesn't freeze the bank
+        let unfrozen_hash = bank.bank_hash();
+        assert!(!bank.is_frozen());
+        bank.transfer(
+            1,
+            &mint_keypair,
+            &Keypair::new().pubkey(),
+            bank.last_blockhash(),
+        )
+        .unwrap();
+        assert_ne!(bank.bank_hash(), unfrozen_hash);
+
+        bank.freeze();
+        assert_eq!(bank.bank_hash(), bank.hash());
     }
 
     #[test]
@@ -1378,18 +4788,21 @@ mod tests {
     #[test]
     fn test_bank_hash_internal_state_squash() {
         let collector_id = Pubkey::default();
-        let bank0 = Arc::new(Bank::new(&GenesisBlock::new(10).0));
-        let bank1 = Bank::new_from_parent(&bank0, &collector_id, 1);
+        let mut bank_forks = BankForks::new(0, Bank::new(&GenesisBlock::new(10).0));
+        let bank0 = bank_forks[0].clone();
+        bank_forks.insert(Bank::new_from_parent(&bank0, &collector_id, 1));
+        let bank1 = bank_forks[1].clone();
 
-        // no delta in bank1, hashes match
-        assert_eq!(bank0.hash_internal_state(), bank1.hash_internal_state());
+        // bank1's only delta is the sysvars it wrote
+        let bank1_state = bank1.hash_internal_state();
+        assert_ne!(bank0.hash_internal_state(), bank1_state);
 
         // remove parent
-        bank1.squash();
+        bank_forks.set_root(1);
         assert!(bank1.parents().is_empty());
 
         // hash should still match
-        assert_eq!(bank0.hash(), bank1.hash());
+        assert_eq!(bank1.hash(), bank1_state);
     }
 
     /// Verifies that last ids and accounts are correctly referenced from parent
@@ -1442,7 +4855,8 @@ mod tests {
     #[test]
     fn test_bank_get_account_in_parent_after_squash() {
         let (genesis_block, mint_keypair) = GenesisBlock::new(500);
-        let parent = Arc::new(Bank::new(&genesis_block));
+        let mut bank_forks = BankForks::new(0, Bank::new(&genesis_block));
+        let parent = bank_forks[0].clone();
 
         let key1 = Keypair::new();
 
@@ -1450,9 +4864,269 @@ mod tests {
             .transfer(1, &mint_keypair, &key1.pubkey(), genesis_block.hash())
             .unwrap();
         assert_eq!(parent.get_balance(&key1.pubkey()), 1);
-        let bank = new_from_parent(&parent);
-        bank.squash();
+        bank_forks.insert(new_from_parent(&parent));
+        bank_forks.set_root(1);
         assert_eq!(parent.get_balance(&key1.pubkey()), 1);
+        assert_eq!(bank_forks[1].get_balance(&key1.pubkey()), 1);
+    }
+
+    #[test]
+    fn test_bank_get_accounts_delta() {
+        let leader = Keypair::new().pubkey();
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new_with_leader(100, &leader, 3);
+        genesis_block.fee_calculator = Some(FeeCalculator::new(1));
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let collector_id = Keypair::new().pubkey();
+        let bank = Bank::new_from_parent(&parent, &collector_id, 1);
+        let sysvar_ids: Vec<_> = bank
+            .get_accounts_delta()
+            .into_iter()
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        assert_eq!(sysvar_ids.len(), 2);
+        assert!(sysvar_ids.contains(&clock::id()));
+        assert!(sysvar_ids.contains(&slot_hashes::id()));
+
+        let key = Keypair::new().pubkey();
+        let tx = SystemTransaction::new_move(&mint_keypair, &key, 2, genesis_block.hash(), 1);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+
+        let mut expected = vec![
+            (
+                mint_keypair.pubkey(),
+                bank.get_account(&mint_keypair.pubkey()).unwrap(),
+            ),
+            (key, bank.get_account(&key).unwrap()),
+            (collector_id, bank.get_account(&collector_id).unwrap()),
+            (clock::id(), bank.get_account(&clock::id()).unwrap()),
+            (
+                slot_hashes::id(),
+                bank.get_account(&slot_hashes::id()).unwrap(),
+            ),
+        ];
+        expected.sort_by_key(|(pubkey, _)| *pubkey);
+        assert_eq!(bank.get_accounts_delta(), expected);
+        assert_eq!(bank.get_balance(&collector_id), 1);
+
+        // Nothing in the parent's delta changed
+        assert!(parent
+            .get_accounts_delta()
+            .iter()
+            .all(|(pubkey, _)| *pubkey != key && *pubkey != collector_id));
+    }
+
+    #[test]
+    fn test_bank_get_program_accounts() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(500);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let key1 = Keypair::new();
+        parent
+            .transfer(2, &mint_keypair, &key1.pubkey(), genesis_block.hash())
+            .unwrap();
+
+        let child = new_from_parent(&parent);
+        let staker_id = Keypair::new().pubkey();
+        let tx =
+            VoteTransaction::new_account(&mint_keypair, &staker_id, child.last_blockhash(), 1, 0);
+        child.process_transaction(&tx).unwrap();
+
+        // Vote accounts stored in the parent and in the child, each listed once
+        let mut vote_ids: Vec<_> = child
+            .get_program_accounts(&solana_vote_api::id())
+            .into_iter()
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        vote_ids.sort();
+        let mut expected_ids = vec![genesis_block.bootstrap_leader_vote_account_id, staker_id];
+        expected_ids.sort();
+        assert_eq!(vote_ids, expected_ids);
+        assert_eq!(parent.get_program_accounts(&solana_vote_api::id()).len(), 1);
+
+        // Draining an account in the child hides the parent's copy
+        let has_key1 = |bank: &Bank| {
+            bank.get_program_accounts(&system_program::id())
+                .iter()
+                .any(|(pubkey, _)| *pubkey == key1.pubkey())
+        };
+        child
+            .transfer(2, &key1, &mint_keypair.pubkey(), child.last_blockhash())
+            .unwrap();
+        assert!(has_key1(&parent));
+        assert!(!has_key1(&child));
+    }
+
+    #[test]
+    fn test_bank_is_executable() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(500);
+        let bank = Bank::new(&genesis_block);
+        let program_id = Keypair::new().pubkey();
+        assert!(!bank.is_executable(&program_id));
+        bank.add_native_program("solana_noop_program", &program_id);
+        assert!(bank.is_executable(&program_id));
+        assert!(!bank.is_executable(&mint_keypair.pubkey()));
+
+        // Program-like data doesn't make an account executable
+        let mut account = bank.get_account(&program_id).unwrap();
+        account.executable = false;
+        let data_id = Keypair::new().pubkey();
+        bank.accounts
+            .store_slow(bank.accounts_id, &data_id, &account)
+            .unwrap();
+        assert!(!bank.is_executable(&data_id));
+    }
+
+    #[test]
+    fn test_bank_epoch_vote_accounts_iter() {
+        let (genesis_block, _) = GenesisBlock::new(10);
+        let bank = Bank::new(&genesis_block);
+
+        // genesis caches the bootstrap leader's vote account for epoch 0
+        let accounts = bank.epoch_vote_accounts_iter(0).unwrap();
+        assert_eq!(accounts.count(), bank.epoch_vote_accounts(0).unwrap().len());
+        assert_eq!(bank.epoch_vote_accounts_iter(0).unwrap().count(), 1);
+        let (pubkey, _) = bank.epoch_vote_accounts_iter(0).unwrap().next().unwrap();
+        assert_eq!(*pubkey, genesis_block.bootstrap_leader_vote_account_id);
+
+        let uncached_epoch = bank.get_stakers_epoch(bank.slot()) + 1;
+        assert!(bank.epoch_vote_accounts_iter(uncached_epoch).is_none());
+    }
+
+    #[test]
+    fn test_bank_delegate_stakes() {
+        let (genesis_block, _) = GenesisBlock::new(500);
+        let mut bank = Bank::new(&genesis_block);
+        let delegate_id = Keypair::new().pubkey();
+        let new_vote_account = |lamports| {
+            let mut account = Account::new(lamports, VoteState::max_size(), &solana_vote_api::id());
+            VoteState::new(&delegate_id)
+                .serialize(&mut account.data)
+                .unwrap();
+            account
+        };
+        let vote_accounts: HashMap<_, _> = vec![
+            (Keypair::new().pubkey(), new_vote_account(3)),
+            (Keypair::new().pubkey(), new_vote_account(4)),
+            (
+                Keypair::new().pubkey(),
+                Account::new(5, 0, &solana_vote_api::id()),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        Arc::make_mut(&mut bank.epoch_vote_accounts).insert(1, vote_accounts);
+
+        let stakes = bank.delegate_stakes(1);
+        assert_eq!(stakes.len(), 1);
+        assert_eq!(stakes[&delegate_id], 7);
+        assert!(bank.delegate_stakes(1000).is_empty());
+    }
+
+    #[test]
+    fn test_bank_largest_accounts() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let capitalization = parent.capitalization();
+        let program_a = Pubkey::new(&[1; 32]);
+        let program_b = Pubkey::new(&[2; 32]);
+
+        // Fund each seeded account from the mint, so the supply stays the same
+        let seed = |bank: &Bank, pubkey: &Pubkey, lamports: u64, owner: &Pubkey| {
+            bank.withdraw(&mint_keypair.pubkey(), lamports).unwrap();
+            bank.store_bounded_account(pubkey, &Account::new(lamports, 0, owner));
+        };
+        let key0 = Pubkey::new(&[10; 32]);
+        let key1 = Pubkey::new(&[11; 32]);
+        let key2 = Pubkey::new(&[12; 32]);
+        let key3 = Pubkey::new(&[13; 32]);
+        seed(&parent, &key2, 2_000, &program_a);
+        seed(&parent, &key3, 2_000, &program_b);
+        let bank = Bank::new_from_parent(&parent, &Pubkey::default(), 1);
+        seed(&bank, &key0, 3_000, &system_program::id());
+        seed(&bank, &key1, 2_000, &program_b);
+
+        // key1, key2 and key3 tie at 2_000 lamports. Ties rank by pubkey, whichever order
+        // and fork the accounts were stored in, including where `n` cuts between them.
+        assert_eq!(
+            bank.largest_accounts(4, AccountFilter::default()),
+            vec![(key0, 3_000), (key1, 2_000), (key2, 2_000), (key3, 2_000)]
+        );
+        assert_eq!(
+            bank.largest_accounts(2, AccountFilter::default()),
+            vec![(key0, 3_000), (key1, 2_000)]
+        );
+        assert_eq!(
+            bank.largest_accounts(3, AccountFilter::excluding(&[program_b])),
+            vec![(key0, 3_000), (key2, 2_000), (mint_keypair.pubkey(), 1_000)]
+        );
+        assert_eq!(
+            parent.largest_accounts(2, AccountFilter::excluding(&[program_a])),
+            vec![
+                (mint_keypair.pubkey(), 1_000 + 3_000 + 2_000),
+                (key3, 2_000)
+            ]
+        );
+        assert_eq!(bank.largest_accounts(0, AccountFilter::default()), vec![]);
+
+        let breakdown = bank.supply_breakdown();
+        assert_eq!(breakdown[&program_a], 2_000);
+        assert_eq!(breakdown[&program_b], 4_000);
+        assert_eq!(breakdown.values().sum::<u64>(), bank.capitalization());
+        assert_eq!(bank.capitalization(), capitalization);
+        assert_eq!(
+            parent.supply_breakdown().values().sum::<u64>(),
+            parent.capitalization()
+        );
+    }
+
+    #[test]
+    fn test_bank_supermajority_stake() {
+        let (genesis_block, _) = GenesisBlock::new(500);
+        let mut bank = Bank::new(&genesis_block);
+        let vote_accounts: HashMap<_, _> = [3, 4, 5]
+            .iter()
+            .map(|lamports| {
+                let account = Account::new(*lamports, 0, &solana_vote_api::id());
+                (Keypair::new().pubkey(), account)
+            })
+            .collect();
+        Arc::make_mut(&mut bank.epoch_vote_accounts).insert(1, vote_accounts);
+        Arc::make_mut(&mut bank.epoch_vote_accounts).insert(2, HashMap::new());
+        let whale = Account::new(u64::max_value(), 0, &solana_vote_api::id());
+        let whale_accounts = [(Keypair::new().pubkey(), whale)].iter().cloned().collect();
+        Arc::make_mut(&mut bank.epoch_vote_accounts).insert(3, whale_accounts);
+
+        assert_eq!(bank.supermajority_stake(1), Some(8));
+        assert_eq!(bank.supermajority_stake(2), Some(0));
+        assert_eq!(bank.supermajority_stake(3), Some(u64::max_value() / 3 * 2));
+        // Genesis only snapshots stakes up to the stakers epoch of slot 0
+        let unsnapshotted_epoch = bank.get_stakers_epoch(0) + 1;
+        assert_eq!(bank.supermajority_stake(unsnapshotted_epoch), None);
+
+        // Register ticks for slots 0..3, stamping each tick with its tick height
+        let ticks_per_slot = bank.ticks_per_slot();
+        let mut bank = Arc::new(bank);
+        for slot in 1..=3 {
+            while bank.tick_height() != slot * ticks_per_slot - 1 {
+                let timestamp = bank.tick_height() + 1;
+                bank.register_tick_with_timestamp(
+                    &hash::hash(&serialize(&timestamp).unwrap()),
+                    timestamp,
+                );
+            }
+            bank = Arc::new(Bank::new_from_parent(&bank, &Pubkey::default(), slot));
+        }
+
+        // Votes from the 3 and 4 lamport accounts don't exceed the threshold; adding the 5
+        // lamport account's vote does
+        let supermajority_stake = bank.supermajority_stake(1).unwrap();
+        assert_eq!(
+            bank.get_confirmation_timestamp(vec![(2, 3), (2, 4)], supermajority_stake),
+            None
+        );
+        assert_eq!(
+            bank.get_confirmation_timestamp(vec![(2, 3), (2, 4), (2, 5)], supermajority_stake),
+            Some(2 * ticks_per_slot - 1)
+        );
     }
 
     #[test]
@@ -1515,6 +5189,85 @@ mod tests {
             SLOTS_PER_EPOCH - (STAKERS_SLOT_OFFSET % SLOTS_PER_EPOCH) + 1,
         );
         assert!(child.epoch_vote_accounts(i).is_some());
+        assert!(parent.epoch_vote_accounts(i).is_none());
+        assert!(!Arc::ptr_eq(
+            &parent.epoch_vote_accounts,
+            &child.epoch_vote_accounts
+        ));
+
+        // child within the parent's epoch shares the parent's stakes
+        let child = Bank::new_from_parent(&parent, &leader_id, 1);
+        assert!(Arc::ptr_eq(
+            &parent.epoch_vote_accounts,
+            &child.epoch_vote_accounts
+        ));
+    }
+
+    #[test]
+    fn test_bank_epoch_vote_accounts_siblings_agree() {
+        let (mut genesis_block, mint_keypair) = GenesisBlock::new(500);
+        const SLOTS_PER_EPOCH: u64 = 8;
+        genesis_block.slots_per_epoch = SLOTS_PER_EPOCH;
+        genesis_block.stakers_slot_offset = SLOTS_PER_EPOCH;
+        genesis_block.epoch_warmup = false;
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+
+        // The last slot before stakers epoch 2 snapshots its stakes when it freezes
+        let parent = Arc::new(Bank::new_from_parent(
+            &bank0,
+            &Pubkey::default(),
+            SLOTS_PER_EPOCH - 1,
+        ));
+        let epoch = parent.get_stakers_epoch(SLOTS_PER_EPOCH);
+        assert_eq!(epoch, 2);
+        assert!(parent.epoch_vote_accounts(epoch).is_none());
+        parent.freeze();
+        let snapshot = parent.frozen_epoch_vote_accounts.read().unwrap()[&epoch].clone();
+
+        // One branch adds a vote account before its sibling is created
+        let child = Arc::new(Bank::new_from_parent(
+            &parent,
+            &Pubkey::default(),
+            SLOTS_PER_EPOCH,
+        ));
+        let staker_id = Keypair::new().pubkey();
+        let tx =
+            VoteTransaction::new_account(&mint_keypair, &staker_id, child.last_blockhash(), 1, 0);
+        child.process_transaction(&tx).unwrap();
+        let grandchild = Bank::new_from_parent(&child, &Pubkey::default(), SLOTS_PER_EPOCH + 1);
+        let sibling = Bank::new_from_parent(&parent, &Pubkey::default(), SLOTS_PER_EPOCH + 1);
+
+        assert_eq!(child.epoch_vote_accounts(epoch), Some(&snapshot));
+        assert_eq!(grandchild.epoch_vote_accounts(epoch), Some(&snapshot));
+        assert_eq!(sibling.epoch_vote_accounts(epoch), Some(&snapshot));
+        assert!(!snapshot.contains_key(&staker_id));
+    }
+
+    #[test]
+    fn test_bank_blockhash_queue_copy_on_write() {
+        let (genesis_block, _) = GenesisBlock::new(10);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let child = new_from_parent(&parent);
+        assert!(Arc::ptr_eq(
+            &parent.blockhash_queue.read().unwrap(),
+            &child.blockhash_queue.read().unwrap()
+        ));
+
+        // registering a hash in the child leaves the parent's queue alone
+        let hash = hash::hash(b"child");
+        for _ in 0..child.ticks_per_slot() {
+            child.register_tick(&hash);
+        }
+        assert_eq!(child.last_blockhash(), hash);
+        assert_eq!(parent.last_blockhash(), genesis_block.hash());
+        assert!(!Arc::ptr_eq(
+            &parent.blockhash_queue.read().unwrap(),
+            &child.blockhash_queue.read().unwrap()
+        ));
+
+        // and a sibling still starts from the parent's queue
+        let sibling = Bank::new_from_parent(&parent, &Pubkey::default(), parent.slot() + 2);
+        assert_eq!(sibling.last_blockhash(), genesis_block.hash());
     }
 
     #[test]
@@ -1548,6 +5301,48 @@ mod tests {
         assert_eq!(bank.get_balance(&key.pubkey()), 0);
     }
 
+    #[test]
+    fn test_bank_close_account() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(500);
+        let parent = Arc::new(Bank::new(&genesis_block));
+        let funded_pubkey = Keypair::new().pubkey();
+        parent.deposit(&funded_pubkey, 10);
+        let bank = new_from_parent(&parent);
+
+        // An account that never existed is already closed
+        assert_eq!(bank.close_account(&Keypair::new().pubkey()), Ok(0));
+
+        // A funded account can't be closed
+        let mint_lamports = bank.get_balance(&mint_keypair.pubkey());
+        assert_eq!(
+            bank.close_account(&mint_keypair.pubkey()),
+            Err(TransactionError::AccountNotEmpty)
+        );
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), mint_lamports);
+
+        // An account created and drained in this bank leaves the store entirely
+        let drained_pubkey = Keypair::new().pubkey();
+        bank.deposit(&drained_pubkey, 10);
+        bank.withdraw(&drained_pubkey, 10).unwrap();
+        assert!(bank
+            .get_accounts_delta()
+            .iter()
+            .any(|(pubkey, _)| *pubkey == drained_pubkey));
+        assert_eq!(bank.close_account(&drained_pubkey), Ok(0));
+        assert!(bank
+            .get_accounts_delta()
+            .iter()
+            .all(|(pubkey, _)| *pubkey != drained_pubkey));
+        assert_eq!(bank.get_account(&drained_pubkey), None);
+
+        // Draining an account the parent funded leaves an entry that hides the parent's,
+        // so closing it mustn't bring the parent's balance back
+        bank.withdraw(&funded_pubkey, 10).unwrap();
+        assert_eq!(bank.close_account(&funded_pubkey), Ok(0));
+        assert_eq!(bank.get_account(&funded_pubkey), None);
+        assert_eq!(parent.get_balance(&funded_pubkey), 10);
+    }
+
     #[test]
     fn test_bank_get_slots_in_epoch() {
         let (genesis_block, _) = GenesisBlock::new(500);
@@ -1559,6 +5354,49 @@ mod tests {
         assert_eq!(bank.get_slots_in_epoch(5000), genesis_block.slots_per_epoch);
     }
 
+    #[test]
+    fn test_bank_get_epoch_info() {
+        let (mut genesis_block, _) = GenesisBlock::new(500);
+        genesis_block.slots_per_epoch = 16;
+        genesis_block.epoch_warmup = true;
+        let parent = Arc::new(Bank::new(&genesis_block));
+        parent.register_tick(&Hash::default());
+
+        // Slot 5 falls in the warmup epoch 2, which is 4 slots long
+        let bank = Bank::new_from_parent(&parent, &Pubkey::default(), 5);
+        let epoch_info = bank.get_epoch_info();
+        assert_eq!(
+            epoch_info,
+            EpochInfo {
+                epoch: 2,
+                slot_index: 2,
+                slots_in_epoch: 4,
+                absolute_slot: 5,
+                block_height: 1,
+            }
+        );
+
+        // Past warmup every epoch is slots_per_epoch long
+        let bank = Bank::new_from_parent(&parent, &Pubkey::default(), 40);
+        let epoch_info = bank.get_epoch_info();
+        let (epoch, slot_index) = bank.get_epoch_and_slot_index(bank.slot());
+        assert_eq!(epoch_info.epoch, epoch);
+        assert_eq!(epoch_info.slot_index, slot_index);
+        assert_eq!(epoch_info.slots_in_epoch, 16);
+        assert_eq!(epoch_info.slots_in_epoch, bank.get_slots_in_epoch(epoch));
+        assert_eq!(epoch_info.absolute_slot, 40);
+        assert_eq!(epoch_info.block_height, bank.tick_height());
+    }
+
+    #[test]
+    fn test_bank_slots_per_year() {
+        let (mut genesis_block, _) = GenesisBlock::new(500);
+        genesis_block.ticks_per_slot = 4;
+        let bank = Bank::new(&genesis_block);
+        // 31_557_600 seconds per year at 10 ticks per second and 4 ticks per slot
+        assert_eq!(bank.slots_per_year(), 78_894_000.0);
+    }
+
     #[test]
     fn test_epoch_schedule() {
         // one week of slots at 8 ticks/slot, 10 ticks/sec is
@@ -1568,7 +5406,7 @@ mod tests {
         for slots_per_epoch in 1..=16 {
             let epoch_schedule = EpochSchedule::new(slots_per_epoch, slots_per_epoch / 2, true);
 
-            let mut last_stakers = 0;
+            let mut last_stakers = epoch_schedule.get_stakers_epoch(0);
             let mut last_epoch = 0;
             let mut last_slots_in_epoch = 1;
             for slot in 0..(2 * slots_per_epoch) {
@@ -1611,6 +5449,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_epoch_schedule_stakers_slot_offsets() {
+        for slots_per_epoch in 1..=16 {
+            for stakers_slot_offset in 0..=(4 * slots_per_epoch) {
+                for &warmup in &[true, false] {
+                    let epoch_schedule =
+                        EpochSchedule::new(slots_per_epoch, stakers_slot_offset, warmup);
+
+                    // stakers_epoch never decreases or skips an epoch, and never trails
+                    //  the slot's own epoch
+                    let mut last_stakers = epoch_schedule.get_stakers_epoch(0);
+                    for slot in 1..(8 * slots_per_epoch + 8) {
+                        let stakers = epoch_schedule.get_stakers_epoch(slot);
+                        assert!(
+                            stakers == last_stakers || stakers == last_stakers + 1,
+                            "slots_per_epoch: {} offset: {} warmup: {} slot: {}",
+                            slots_per_epoch,
+                            stakers_slot_offset,
+                            warmup,
+                            slot
+                        );
+                        let (epoch, _) = epoch_schedule.get_epoch_and_slot_index(slot);
+                        assert!(stakers >= epoch);
+                        last_stakers = stakers;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bank_genesis_epoch_vote_accounts_warmup() {
+        let (mut genesis_block, _) = GenesisBlock::new(500);
+        genesis_block.slots_per_epoch = 8;
+        genesis_block.stakers_slot_offset = 21;
+        genesis_block.epoch_warmup = true;
+        let bank = Bank::new(&genesis_block);
+
+        // slot 21 falls in epoch 4, past the warmup epochs of 1, 2 and 4 slots
+        let stakers_epoch = bank.get_stakers_epoch(0);
+        assert_eq!(stakers_epoch, 4);
+        for epoch in 0..=stakers_epoch {
+            assert!(bank.epoch_vote_accounts(epoch).is_some());
+        }
+        assert!(bank.epoch_vote_accounts(stakers_epoch + 1).is_none());
+    }
+
+    #[test]
+    fn test_bank_get_confirmation_timestamp() {
+        let (genesis_block, _) = GenesisBlock::new(500);
+        let mut bank = Arc::new(Bank::new(&genesis_block));
+        let ticks_per_slot = bank.ticks_per_slot();
+
+        // Register ticks for slots 0..3, stamping each tick with its tick height
+        for slot in 1..=3 {
+            while bank.tick_height() != slot * ticks_per_slot - 1 {
+                let timestamp = bank.tick_height() + 1;
+                bank.register_tick_with_timestamp(
+                    &hash::hash(&serialize(&timestamp).unwrap()),
+                    timestamp,
+                );
+            }
+            bank = Arc::new(Bank::new_from_parent(&bank, &Pubkey::default(), slot));
+        }
+
+        // Not enough stake to cross the supermajority threshold
+        assert_eq!(bank.get_confirmation_timestamp(vec![(2, 3)], 6), None);
+
+        // Supermajority at slot 2; the hash registered at height 2 was stamped at tick 2 * ticks_per_slot - 1
+        assert_eq!(
+            bank.get_confirmation_timestamp(vec![(2, 3), (2, 4)], 6),
+            Some(2 * ticks_per_slot - 1)
+        );
+
+        // Ticks registered without a timestamp leave it unset
+        let mut bank = Arc::new(Bank::new(&genesis_block));
+        for slot in 1..=3 {
+            while bank.tick_height() != slot * ticks_per_slot - 1 {
+                bank.register_tick(&Hash::default());
+            }
+            bank = Arc::new(Bank::new_from_parent(&bank, &Pubkey::default(), slot));
+        }
+        assert_eq!(bank.get_confirmation_timestamp(vec![(2, 7)], 6), None);
+    }
+
     #[test]
     fn test_is_delta_true() {
         let (genesis_block, mint_keypair) = GenesisBlock::new(500);
@@ -1,4 +1,7 @@
 use crate::bank::Bank;
+use bincode::deserialize;
+use serde::de::DeserializeOwned;
+use solana_sdk::hash::hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::script::Script;
 use solana_sdk::signature::{Keypair, KeypairUtil};
@@ -61,6 +64,47 @@ impl<'a> BankClient<'a> {
         let move_instruction = SystemInstruction::new_move(&self.pubkey(), pubkey, lamports);
         self.process_instruction(move_instruction)
     }
+
+    /// Sign `tx` with `keypairs` against the bank's latest blockhash and process it. Unlike
+    /// `process_transaction`, the signers needn't be the client's own keypairs.
+    pub fn send_transaction(
+        &self,
+        keypairs: &[&Keypair],
+        mut tx: Transaction,
+    ) -> Result<(), TransactionError> {
+        tx.sign(keypairs, self.bank.last_blockhash());
+        self.bank.process_transaction(&tx)
+    }
+
+    /// Create and process a transaction from a single instruction signed by `keypair`.
+    pub fn send_instruction(
+        &self,
+        keypair: &Keypair,
+        instruction: Instruction,
+    ) -> Result<(), TransactionError> {
+        self.send_transaction(&[keypair], Transaction::new(vec![instruction]))
+    }
+
+    /// Deserialize the data of the account at `pubkey`, or None if the account doesn't exist
+    /// or its data isn't a `T`.
+    pub fn get_account_data<T: DeserializeOwned>(&self, pubkey: &Pubkey) -> Option<T> {
+        let account = self.bank.get_account(pubkey)?;
+        deserialize(&account.data).ok()
+    }
+
+    /// Register ticks up to the last tick of the next slot boundary, at which point the bank
+    /// records a new blockhash.
+    pub fn advance_slot(&self) {
+        let ticks_per_slot = self.bank.ticks_per_slot();
+        let mut tick_hash = self.bank.last_blockhash();
+        loop {
+            tick_hash = hash(tick_hash.as_ref());
+            self.bank.register_tick(&tick_hash);
+            if self.bank.tick_height() % ticks_per_slot == ticks_per_slot - 1 {
+                break;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -86,4 +130,50 @@ mod tests {
         doe_client.process_instruction(move_instruction).unwrap();
         assert_eq!(bank.get_balance(&bob_pubkey), 42);
     }
+
+    #[test]
+    fn test_bank_client_send_instruction() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let client = BankClient::new(&bank, Keypair::new());
+
+        // Any keypair may sign, not just the client's
+        let bob_pubkey = Keypair::new().pubkey();
+        let instruction = SystemInstruction::new_move(&mint_keypair.pubkey(), &bob_pubkey, 42);
+        client.send_instruction(&mint_keypair, instruction).unwrap();
+        assert_eq!(bank.get_balance(&bob_pubkey), 42);
+    }
+
+    #[test]
+    fn test_bank_client_get_account_data() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let client = BankClient::new(&bank, mint_keypair);
+        let bob_pubkey = Keypair::new().pubkey();
+        assert_eq!(client.get_account_data::<u64>(&bob_pubkey), None);
+
+        // A system account holds no data, so there's nothing to deserialize
+        client.transfer(42, &bob_pubkey).unwrap();
+        assert_eq!(client.get_account_data::<u64>(&bob_pubkey), None);
+    }
+
+    #[test]
+    fn test_bank_client_advance_slot() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let client = BankClient::new(&bank, mint_keypair);
+        let blockhash = bank.last_blockhash();
+
+        client.advance_slot();
+        assert_eq!(
+            bank.tick_height() % bank.ticks_per_slot(),
+            bank.ticks_per_slot() - 1
+        );
+        assert_ne!(bank.last_blockhash(), blockhash);
+
+        // Advancing again moves a full slot ahead
+        let tick_height = bank.tick_height();
+        client.advance_slot();
+        assert_eq!(bank.tick_height(), tick_height + bank.ticks_per_slot());
+    }
 }
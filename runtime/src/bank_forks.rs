@@ -0,0 +1,343 @@
+//! The `bank_forks` module implments BankForks a DAG of checkpointed Banks
+
+use crate::bank::Bank;
+use hashbrown::{HashMap, HashSet};
+use solana_sdk::commitment::Commitment;
+use std::ops::Index;
+use std::sync::Arc;
+
+pub struct BankForks {
+    banks: HashMap<u64, Arc<Bank>>,
+    working_bank: Arc<Bank>,
+    root: u64,
+}
+
+impl Index<u64> for BankForks {
+    type Output = Arc<Bank>;
+    fn index(&self, bank_slot: u64) -> &Arc<Bank> {
+        &self.banks[&bank_slot]
+    }
+}
+
+impl BankForks {
+    pub fn new(bank_slot: u64, bank: Bank) -> Self {
+        let mut banks = HashMap::new();
+        let working_bank = Arc::new(bank);
+        banks.insert(bank_slot, working_bank.clone());
+        Self {
+            banks,
+            working_bank,
+            root: bank_slot,
+        }
+    }
+
+    /// Create a map of bank slot id to the set of ancestors for the bank slot.
+    pub fn ancestors(&self) -> HashMap<u64, HashSet<u64>> {
+        let mut ancestors = HashMap::new();
+        for bank in self.banks.values() {
+            let set = bank.parents().into_iter().map(|b| b.slot()).collect();
+            ancestors.insert(bank.slot(), set);
+        }
+        ancestors
+    }
+
+    /// Create a map of bank slot id to the set of all of its descendants
+    pub fn descendants(&self) -> HashMap<u64, HashSet<u64>> {
+        let mut descendants = HashMap::new();
+        for bank in self.banks.values() {
+            let _ = descendants.entry(bank.slot()).or_insert(HashSet::new());
+            for parent in bank.parents() {
+                descendants
+                    .entry(parent.slot())
+                    .or_insert(HashSet::new())
+                    .insert(bank.slot());
+            }
+        }
+        descendants
+    }
+
+    pub fn frozen_banks(&self) -> HashMap<u64, Arc<Bank>> {
+        self.banks
+            .iter()
+            .filter(|(_, b)| b.is_frozen())
+            .map(|(k, b)| (*k, b.clone()))
+            .collect()
+    }
+
+    pub fn active_banks(&self) -> Vec<u64> {
+        self.banks
+            .iter()
+            .filter(|(_, v)| !v.is_frozen())
+            .map(|(k, _v)| *k)
+            .collect()
+    }
+    pub fn get(&self, bank_slot: u64) -> Option<&Arc<Bank>> {
+        self.banks.get(&bank_slot)
+    }
+
+    pub fn new_from_banks(initial_banks: &[Arc<Bank>]) -> Self {
+        let mut banks = HashMap::new();
+        let working_bank = initial_banks[0].clone();
+        for bank in initial_banks {
+            banks.insert(bank.slot(), bank.clone());
+        }
+        // The banks share the oldest ancestor any of them still references
+        let root = initial_banks
+            .iter()
+            .map(|bank| bank.parents().last().map_or(bank.slot(), |p| p.slot()))
+            .min()
+            .unwrap();
+        Self {
+            banks,
+            working_bank,
+            root,
+        }
+    }
+
+    pub fn insert(&mut self, bank: Bank) {
+        let bank = Arc::new(bank);
+        let prev = self.banks.insert(bank.slot(), bank.clone());
+        assert!(prev.is_none());
+
+        self.working_bank = bank.clone();
+    }
+
+    // TODO: really want to kill this...
+    pub fn working_bank(&self) -> Arc<Bank> {
+        self.working_bank.clone()
+    }
+
+    /// Return the bank a request with `commitment` reads from: the root bank, the newest
+    /// frozen bank on the working bank's fork, or the working bank itself
+    pub fn best_bank(&self, commitment: Commitment) -> Arc<Bank> {
+        match commitment {
+            Commitment::Root => self.banks[&self.root].clone(),
+            Commitment::Frozen => {
+                if self.working_bank.is_frozen() {
+                    self.working_bank.clone()
+                } else {
+                    // Creating a child freezes its parent
+                    self.working_bank
+                        .parent()
+                        .unwrap_or_else(|| self.banks[&self.root].clone())
+                }
+            }
+            Commitment::Recent => self.working_bank.clone(),
+        }
+    }
+
+    /// The slot of the most recent bank squashed into a root
+    pub fn root(&self) -> u64 {
+        self.root
+    }
+
+    /// Squash the bank at `root` and drop every bank that doesn't descend from it. The
+    /// root may be any number of slots past the previous one.
+    pub fn set_root(&mut self, root: u64) {
+        let root_bank = self
+            .banks
+            .get(&root)
+            .expect("root bank didn't exist in bank_forks")
+            .clone();
+        root_bank.squash();
+        self.root = root;
+        self.prune_non_root(root);
+
+        // The working bank may have been on a pruned fork
+        if !self.working_bank.is_in_subtree_of(root) {
+            self.working_bank = self
+                .banks
+                .values()
+                .max_by_key(|bank| bank.slot())
+                .unwrap()
+                .clone();
+        }
+    }
+
+    fn prune_non_root(&mut self, root: u64) {
+        let descendants = self.descendants();
+        self.banks
+            .retain(|slot, _| *slot == root || descendants[&root].contains(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::genesis_block::GenesisBlock;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+
+    #[test]
+    fn test_bank_forks() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let child_bank = Bank::new_from_parent(&bank_forks[0u64], &Pubkey::default(), 1);
+        child_bank.register_tick(&Hash::default());
+        bank_forks.insert(child_bank);
+        assert_eq!(bank_forks[1u64].tick_height(), 1);
+        assert_eq!(bank_forks.working_bank().tick_height(), 1);
+    }
+
+    #[test]
+    fn test_bank_forks_descendants() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let bank0 = bank_forks[0].clone();
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.insert(bank);
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 2);
+        bank_forks.insert(bank);
+        let descendants = bank_forks.descendants();
+        let children: Vec<u64> = descendants[&0].iter().cloned().collect();
+        assert_eq!(children, vec![1, 2]);
+        assert!(descendants[&1].is_empty());
+        assert!(descendants[&2].is_empty());
+    }
+
+    #[test]
+    fn test_bank_forks_ancestors() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let bank0 = bank_forks[0].clone();
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        bank_forks.insert(bank);
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 2);
+        bank_forks.insert(bank);
+        let ancestors = bank_forks.ancestors();
+        assert!(ancestors[&0].is_empty());
+        let parents: Vec<u64> = ancestors[&1].iter().cloned().collect();
+        assert_eq!(parents, vec![0]);
+        let parents: Vec<u64> = ancestors[&2].iter().cloned().collect();
+        assert_eq!(parents, vec![0]);
+    }
+
+    #[test]
+    fn test_bank_forks_frozen_banks() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let child_bank = Bank::new_from_parent(&bank_forks[0u64], &Pubkey::default(), 1);
+        bank_forks.insert(child_bank);
+        assert!(bank_forks.frozen_banks().get(&0).is_some());
+        assert!(bank_forks.frozen_banks().get(&1).is_none());
+    }
+
+    #[test]
+    fn test_bank_forks_best_bank() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let bank0 = bank_forks[0].clone();
+        bank_forks.insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let bank1 = bank_forks[1].clone();
+        bank_forks.insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 2));
+        bank_forks.insert(Bank::new_from_parent(&bank1, &Pubkey::default(), 3));
+
+        // Slot 2 is frozen and newer than slot 1, but isn't on the working bank's fork
+        bank_forks[2].freeze();
+        assert_eq!(bank_forks.best_bank(Commitment::Root).slot(), 0);
+        assert_eq!(bank_forks.best_bank(Commitment::Frozen).slot(), 1);
+        assert_eq!(bank_forks.best_bank(Commitment::Recent).slot(), 3);
+
+        // Once the working bank freezes it's the newest frozen bank too
+        bank_forks[3].freeze();
+        assert_eq!(bank_forks.best_bank(Commitment::Frozen).slot(), 3);
+
+        bank_forks.set_root(1);
+        assert_eq!(bank_forks.best_bank(Commitment::Root).slot(), 1);
+        assert_eq!(bank_forks.best_bank(Commitment::Recent).slot(), 3);
+
+        // A lone working bank that hasn't frozen falls back to the root
+        let bank_forks = BankForks::new(0, Bank::new(&genesis_block));
+        assert_eq!(bank_forks.best_bank(Commitment::Frozen).slot(), 0);
+    }
+
+    #[test]
+    fn test_bank_forks_active_banks() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let child_bank = Bank::new_from_parent(&bank_forks[0u64], &Pubkey::default(), 1);
+        bank_forks.insert(child_bank);
+        assert_eq!(bank_forks.active_banks(), vec![1]);
+    }
+
+    #[test]
+    fn test_bank_forks_set_root_prunes_losing_fork() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let mut bank_forks = BankForks::new(0, Bank::new(&genesis_block));
+        let bank0 = bank_forks[0].clone();
+        bank_forks.insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank_forks.insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 2));
+        let bank1 = bank_forks[1].clone();
+        bank_forks.insert(Bank::new_from_parent(&bank1, &Pubkey::default(), 3));
+        let losing_bank = Arc::downgrade(&bank_forks[2]);
+        drop(bank0);
+        drop(bank1);
+
+        // Slot 2 is newer than the root, but forked off before it
+        bank_forks.set_root(1);
+        assert_eq!(bank_forks.root(), 1);
+        assert!(bank_forks.get(0).is_none());
+        assert!(bank_forks.get(2).is_none());
+        assert!(losing_bank.upgrade().is_none());
+        assert!(bank_forks[1].parents().is_empty());
+        assert_eq!(bank_forks.working_bank().slot(), 3);
+        assert_eq!(bank_forks.active_banks(), vec![3]);
+    }
+
+    #[test]
+    fn test_bank_forks_set_root_working_bank_pruned() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let mut bank_forks = BankForks::new(0, Bank::new(&genesis_block));
+        let bank0 = bank_forks[0].clone();
+        bank_forks.insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank_forks.insert(Bank::new_from_parent(&bank0, &Pubkey::default(), 2));
+        assert_eq!(bank_forks.working_bank().slot(), 2);
+
+        bank_forks.set_root(1);
+        assert_eq!(bank_forks.working_bank().slot(), 1);
+    }
+
+    #[test]
+    fn test_bank_forks_set_root_across_slots() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let mut bank_forks = BankForks::new(0, Bank::new(&genesis_block));
+        let key = Keypair::new().pubkey();
+        for slot in 1..5 {
+            let parent = bank_forks[slot - 1].clone();
+            let bank = Bank::new_from_parent(&parent, &Pubkey::default(), slot);
+            bank.transfer(slot, &mint_keypair, &key, genesis_block.hash())
+                .unwrap();
+            bank_forks.insert(bank);
+        }
+        assert_eq!(bank_forks.root(), 0);
+
+        // Jump the root from slot 0 to slot 3 in one step
+        bank_forks.set_root(3);
+        assert_eq!(bank_forks.root(), 3);
+        let mut slots: Vec<u64> = bank_forks.ancestors().keys().cloned().collect();
+        slots.sort();
+        assert_eq!(slots, vec![3, 4]);
+        assert!(bank_forks[3].parents().is_empty());
+        assert_eq!(bank_forks[3].get_balance(&key), 1 + 2 + 3);
+        assert_eq!(bank_forks[4].get_balance(&key), 1 + 2 + 3 + 4);
+    }
+
+    #[test]
+    fn test_bank_forks_new_from_banks_root() {
+        let (genesis_block, _) = GenesisBlock::new(10_000);
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let bank2 = Arc::new(Bank::new_from_parent(&bank1, &Pubkey::default(), 2));
+        let bank3 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 3));
+        let bank_forks = BankForks::new_from_banks(&[bank2, bank3]);
+        assert_eq!(bank_forks.root(), 0);
+        assert_eq!(bank_forks.working_bank().slot(), 2);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::bank::Bank;
+use solana_sdk::signature::{Keypair, KeypairUtil};
+
+/// Create `count` keypairs and fund each with `lamports`, depositing directly into the bank
+/// rather than sending a transaction per account.
+pub fn create_funded_keypairs(bank: &Bank, count: usize, lamports: u64) -> Vec<Keypair> {
+    (0..count)
+        .map(|_| {
+            let keypair = Keypair::new();
+            bank.deposit(&keypair.pubkey(), lamports);
+            keypair
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::genesis_block::GenesisBlock;
+    use solana_sdk::system_transaction::SystemTransaction;
+
+    #[test]
+    fn test_create_funded_keypairs() {
+        let (genesis_block, _mint_keypair) = GenesisBlock::new(1);
+        let bank = Bank::new(&genesis_block);
+        let keypairs = create_funded_keypairs(&bank, 3, 10);
+        assert_eq!(keypairs.len(), 3);
+        for keypair in &keypairs {
+            assert_eq!(bank.get_balance(&keypair.pubkey()), 10);
+        }
+
+        // The funded accounts can pay for transactions
+        let tx = SystemTransaction::new_move(
+            &keypairs[0],
+            &keypairs[1].pubkey(),
+            1,
+            bank.last_blockhash(),
+            0,
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&keypairs[1].pubkey()), 11);
+    }
+}
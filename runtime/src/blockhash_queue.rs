@@ -1,10 +1,11 @@
 use hashbrown::HashMap;
 use solana_sdk::hash::Hash;
 use solana_sdk::timing::timestamp;
+use std::collections::VecDeque;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct HashAge {
-    timestamp: u64,
+    timestamp: Option<u64>,
     hash_height: u64,
 }
 
@@ -21,6 +22,10 @@ pub struct BlockhashQueue {
 
     /// hashes older than `max_age` will be dropped from the queue
     max_age: usize,
+
+    /// the last `max_age` hashes dropped from the queue, oldest first, so an expired hash
+    ///  can be told apart from one that was never registered
+    expired: VecDeque<Hash>,
 }
 
 impl BlockhashQueue {
@@ -30,6 +35,7 @@ impl BlockhashQueue {
             hash_height: 0,
             last_hash: None,
             max_age,
+            expired: VecDeque::new(),
         }
     }
 
@@ -51,8 +57,26 @@ impl BlockhashQueue {
             _ => false,
         }
     }
+    /// Return the number of hashes registered after `hash`, or None if `hash` is not one
+    /// of the last `max_age` hashes registered
+    pub fn get_hash_age(&self, hash: &Hash) -> Option<u64> {
+        self.hash_age(hash).filter(|age| *age < self.max_age as u64)
+    }
+
+    /// Return the number of hashes registered after `hash`, or None if the queue doesn't
+    /// hold it, even if it's too old to be accepted
+    pub fn hash_age(&self, hash: &Hash) -> Option<u64> {
+        self.ages
+            .get(hash)
+            .map(|age| self.hash_height - age.hash_height)
+    }
+
+    /// Return true if `hash` is held by the queue or was recently dropped from it
+    pub fn was_registered(&self, hash: &Hash) -> bool {
+        self.check_hash(*hash) || self.expired.contains(hash)
+    }
+
     /// check if hash is valid
-    #[cfg(test)]
     pub fn check_hash(&self, hash: Hash) -> bool {
         self.ages.get(&hash).is_some()
     }
@@ -62,14 +86,16 @@ impl BlockhashQueue {
             *hash,
             HashAge {
                 hash_height: 0,
-                timestamp: timestamp(),
+                timestamp: Some(timestamp()),
             },
         );
 
         self.last_hash = Some(*hash);
     }
 
-    pub fn register_hash(&mut self, hash: &Hash) {
+    /// Register a new hash, optionally recording the wall-clock `timestamp`
+    /// at which it was produced
+    pub fn register_hash(&mut self, hash: &Hash, timestamp: Option<u64>) {
         self.hash_height += 1;
         let hash_height = self.hash_height;
 
@@ -77,14 +103,26 @@ impl BlockhashQueue {
         //  because we verify age.nth every place we check for validity
         let max_age = self.max_age;
         if self.ages.len() >= max_age {
-            self.ages
-                .retain(|_, age| hash_height - age.hash_height <= max_age as u64);
+            let mut expired: Vec<_> = self
+                .ages
+                .iter()
+                .filter(|(_, age)| hash_height - age.hash_height > max_age as u64)
+                .map(|(hash, age)| (age.hash_height, *hash))
+                .collect();
+            expired.sort();
+            for (_, hash) in expired {
+                self.ages.remove(&hash);
+                self.expired.push_back(hash);
+            }
+            while self.expired.len() > max_age {
+                self.expired.pop_front();
+            }
         }
         self.ages.insert(
             *hash,
             HashAge {
                 hash_height,
-                timestamp: timestamp(),
+                timestamp,
             },
         );
 
@@ -95,7 +133,7 @@ impl BlockhashQueue {
     pub fn hash_height_to_timestamp(&self, hash_height: u64) -> Option<u64> {
         for age in self.ages.values() {
             if age.hash_height == hash_height {
-                return Some(age.timestamp);
+                return age.timestamp;
             }
         }
         None
@@ -112,7 +150,7 @@ mod tests {
         let last_hash = Hash::default();
         let mut hash_queue = BlockhashQueue::new(100);
         assert!(!hash_queue.check_hash(last_hash));
-        hash_queue.register_hash(&last_hash);
+        hash_queue.register_hash(&last_hash, None);
         assert!(hash_queue.check_hash(last_hash));
         assert_eq!(hash_queue.hash_height(), 1);
     }
@@ -122,7 +160,7 @@ mod tests {
         let mut hash_queue = BlockhashQueue::new(100);
         for i in 0..100 {
             let last_hash = hash(&serialize(&i).unwrap()); // Unique hash
-            hash_queue.register_hash(&last_hash);
+            hash_queue.register_hash(&last_hash, None);
         }
         // Assert we're no longer able to use the oldest hash.
         assert!(!hash_queue.check_hash(last_hash));
@@ -132,8 +170,55 @@ mod tests {
     fn test_queue_init_blockhash() {
         let last_hash = Hash::default();
         let mut hash_queue = BlockhashQueue::new(100);
-        hash_queue.register_hash(&last_hash);
+        hash_queue.register_hash(&last_hash, None);
         assert_eq!(last_hash, hash_queue.last_hash());
         assert!(hash_queue.check_hash_age(last_hash, 0));
     }
+
+    #[test]
+    fn test_get_hash_age() {
+        let mut hash_queue = BlockhashQueue::new(3);
+        assert_eq!(hash_queue.get_hash_age(&hash(&[1])), None);
+        for i in 1..=3 {
+            hash_queue.register_hash(&hash(&[i]), None);
+        }
+        assert_eq!(hash_queue.get_hash_age(&hash(&[3])), Some(0));
+        assert_eq!(hash_queue.get_hash_age(&hash(&[1])), Some(2));
+
+        // The oldest hash ages out even while the queue still holds it
+        hash_queue.register_hash(&hash(&[4]), None);
+        assert!(hash_queue.check_hash(hash(&[1])));
+        assert_eq!(hash_queue.get_hash_age(&hash(&[1])), None);
+        assert_eq!(hash_queue.get_hash_age(&hash(&[2])), Some(2));
+    }
+
+    #[test]
+    fn test_was_registered() {
+        let mut hash_queue = BlockhashQueue::new(2);
+        for i in 1..=3 {
+            hash_queue.register_hash(&hash(&[i]), None);
+        }
+        assert!(hash_queue.check_hash(hash(&[1])));
+
+        // Dropped hashes are remembered for another max_age registrations
+        hash_queue.register_hash(&hash(&[4]), None);
+        assert!(!hash_queue.check_hash(hash(&[1])));
+        assert!(hash_queue.was_registered(&hash(&[1])));
+        assert!(!hash_queue.was_registered(&hash(&[5])));
+        for i in 5..=7 {
+            hash_queue.register_hash(&hash(&[i]), None);
+        }
+        assert!(!hash_queue.was_registered(&hash(&[1])));
+        assert!(hash_queue.was_registered(&hash(&[4])));
+    }
+
+    #[test]
+    fn test_hash_height_to_timestamp() {
+        let mut hash_queue = BlockhashQueue::new(100);
+        hash_queue.register_hash(&hash(&[1]), Some(42));
+        hash_queue.register_hash(&hash(&[2]), None);
+        assert_eq!(hash_queue.hash_height_to_timestamp(1), Some(42));
+        assert_eq!(hash_queue.hash_height_to_timestamp(2), None);
+        assert_eq!(hash_queue.hash_height_to_timestamp(3), None);
+    }
 }
@@ -2,13 +2,17 @@ mod accounts;
 pub mod append_vec;
 pub mod bank;
 pub mod bank_client;
+pub mod bank_forks;
+pub mod bank_utils;
 mod blockhash_queue;
 pub mod bloom;
 pub mod loader_utils;
+mod memo_program;
 mod native_loader;
 mod runtime;
-mod status_cache;
+pub mod status_cache;
 mod system_program;
+pub mod verified_transaction;
 
 #[macro_use]
 extern crate solana_metrics;
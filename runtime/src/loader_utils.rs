@@ -1,4 +1,5 @@
 use crate::bank::Bank;
+use solana_sdk::loader_instruction::WRITE_HEADER_LEN;
 use solana_sdk::loader_transaction::LoaderTransaction;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil};
@@ -19,7 +20,7 @@ pub fn load_program(bank: &Bank, from: &Keypair, loader_id: &Pubkey, program: Ve
     bank.process_transaction(&tx).unwrap();
     assert_eq!(bank.get_signature_status(&tx.signatures[0]), Some(Ok(())));
 
-    let chunk_size = 256; // Size of chunk just needs to fit into tx
+    let chunk_size = bank.max_instruction_data_len() - WRITE_HEADER_LEN;
     let mut offset = 0;
     for chunk in program.chunks(chunk_size) {
         let tx = LoaderTransaction::new_write(
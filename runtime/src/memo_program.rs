@@ -0,0 +1,71 @@
+use bincode::serialize;
+use log::*;
+use solana_sdk::account::KeyedAccount;
+use solana_sdk::memo_instruction::MemoInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::InstructionError;
+
+pub fn entrypoint(
+    _program_id: &Pubkey,
+    _keyed_accounts: &mut [KeyedAccount],
+    data: &[u8],
+    _tick_height: u64,
+) -> Result<(), InstructionError> {
+    let memo = MemoInstruction::parse(data).map_err(|e| {
+        info!("Invalid memo: {:?}", e);
+        InstructionError::CustomError(serialize(&e).unwrap())
+    })?;
+    trace!("memo: {}", memo);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bank::Bank;
+    use solana_sdk::genesis_block::GenesisBlock;
+    use solana_sdk::memo_instruction::MemoError;
+    use solana_sdk::memo_program::MAX_MEMO_LEN;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::system_instruction::SystemInstruction;
+    use solana_sdk::transaction::{Transaction, TransactionError};
+
+    #[test]
+    fn test_move_with_memo() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let to = Keypair::new().pubkey();
+
+        let mut tx = Transaction::new(vec![
+            SystemInstruction::new_move(&mint_keypair.pubkey(), &to, 10),
+            MemoInstruction::new("invoice 42"),
+        ]);
+        tx.sign(&[&mint_keypair], genesis_block.hash());
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.get_balance(&to), 10);
+        assert_eq!(MemoInstruction::memos(&tx), vec!["invoice 42"]);
+    }
+
+    #[test]
+    fn test_memo_too_long() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(100);
+        let bank = Bank::new(&genesis_block);
+        let to = Keypair::new().pubkey();
+
+        let mut tx = Transaction::new(vec![
+            SystemInstruction::new_move(&mint_keypair.pubkey(), &to, 10),
+            MemoInstruction::new(&"x".repeat(MAX_MEMO_LEN + 1)),
+        ]);
+        tx.sign(&[&mint_keypair], genesis_block.hash());
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::CustomError(serialize(&MemoError::TooLong).unwrap())
+            ))
+        );
+
+        // The memo failed, so the move it was attached to didn't happen either
+        assert_eq!(bank.get_balance(&to), 0);
+    }
+}
@@ -1,5 +1,5 @@
 //! Native loader
-use bincode::deserialize;
+use bincode::{deserialize, serialize};
 #[cfg(unix)]
 use libloading::os::unix::*;
 #[cfg(windows)]
@@ -7,6 +7,7 @@ use libloading::os::windows::*;
 use log::*;
 use solana_sdk::account::KeyedAccount;
 use solana_sdk::loader_instruction::LoaderInstruction;
+use solana_sdk::native_loader::NativeLoaderError;
 use solana_sdk::native_program;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::transaction::InstructionError;
@@ -47,6 +48,40 @@ fn create_path(name: &str) -> PathBuf {
     }
 }
 
+/// Load the named native program, checking it was built against this ABI version
+fn load_entrypoint(name: &str) -> Result<Symbol<native_program::Entrypoint>, NativeLoaderError> {
+    let path = create_path(name);
+    // TODO linux tls bug can cause crash on dlclose(), workaround by never unloading
+    let library =
+        Library::open(Some(&path), libc::RTLD_NODELETE | libc::RTLD_NOW).map_err(|e| {
+            warn!("Unable to load {:?}: {:?}", path, e);
+            NativeLoaderError::LibraryNotFound
+        })?;
+    unsafe {
+        let entrypoint = library
+            .get(native_program::ENTRYPOINT.as_bytes())
+            .map_err(|e| {
+                warn!(
+                    "{:?}: Unable to find {:?} in program",
+                    e,
+                    native_program::ENTRYPOINT
+                );
+                NativeLoaderError::EntrypointNotFound
+            })?;
+        let found = library
+            .get::<native_program::AbiVersion>(native_program::ABI_VERSION_SYMBOL.as_bytes())
+            .ok()
+            .map(|abi_version| abi_version());
+        if found != Some(native_program::ABI_VERSION) {
+            return Err(NativeLoaderError::AbiVersionMismatch {
+                expected: native_program::ABI_VERSION,
+                found,
+            });
+        }
+        Ok(entrypoint)
+    }
+}
+
 pub fn entrypoint(
     program_id: &Pubkey,
     keyed_accounts: &mut [KeyedAccount],
@@ -65,29 +100,11 @@ pub fn entrypoint(
             }
         };
         trace!("Call native {:?}", name);
-        let path = create_path(&name);
-        // TODO linux tls bug can cause crash on dlclose(), workaround by never unloading
-        match Library::open(Some(&path), libc::RTLD_NODELETE | libc::RTLD_NOW) {
-            Ok(library) => unsafe {
-                let entrypoint: Symbol<native_program::Entrypoint> =
-                    match library.get(native_program::ENTRYPOINT.as_bytes()) {
-                        Ok(s) => s,
-                        Err(e) => {
-                            warn!(
-                                "{:?}: Unable to find {:?} in program",
-                                e,
-                                native_program::ENTRYPOINT
-                            );
-                            return Err(InstructionError::GenericError);
-                        }
-                    };
-                return entrypoint(program_id, params, ix_data, tick_height);
-            },
-            Err(e) => {
-                warn!("Unable to load: {:?}", e);
-                return Err(InstructionError::GenericError);
-            }
-        }
+        let entrypoint = load_entrypoint(name).map_err(|err| {
+            warn!("Unable to invoke native program {:?}: {:?}", name, err);
+            InstructionError::CustomError(serialize(&err).unwrap())
+        })?;
+        return unsafe { entrypoint(program_id, params, ix_data, tick_height) };
     } else if let Ok(instruction) = deserialize(ix_data) {
         if keyed_accounts[0].signer_key().is_none() {
             warn!("key[0] did not sign the transaction");
@@ -1,8 +1,41 @@
 use crate::native_loader;
+use hashbrown::HashMap;
 use solana_sdk::account::{create_keyed_accounts, Account, KeyedAccount};
+use solana_sdk::memo_program;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::system_program;
-use solana_sdk::transaction::{InstructionError, Transaction, TransactionError};
+use solana_sdk::timing::duration_as_us;
+use solana_sdk::transaction::{
+    InstructionError, SanitizeError, Transaction, TransactionError, MAX_INSTRUCTION_DATA_LEN,
+};
+use std::time::Instant;
+
+/// How many instructions a program executed, how long they took in all, and how many of
+/// them failed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramTiming {
+    pub count: u64,
+    pub total_us: u64,
+    pub errors: u64,
+}
+
+impl ProgramTiming {
+    fn accumulate(&mut self, other: &ProgramTiming) {
+        self.count += other.count;
+        self.total_us += other.total_us;
+        self.errors += other.errors;
+    }
+}
+
+/// Execution timings, keyed by program id
+pub type ProgramTimings = HashMap<Pubkey, ProgramTiming>;
+
+/// Add each of `from`'s timings into `into`
+pub fn merge_program_timings(into: &mut ProgramTimings, from: &ProgramTimings) {
+    for (program_id, timing) in from {
+        into.entry(*program_id).or_default().accumulate(timing);
+    }
+}
 
 /// Return true if the slice has any duplicate elements
 pub fn has_duplicates<T: PartialEq>(xs: &[T]) -> bool {
@@ -17,6 +50,37 @@ pub fn has_duplicates<T: PartialEq>(xs: &[T]) -> bool {
     false
 }
 
+/// Reject a transaction whose instructions reference accounts or programs it doesn't
+/// carry, or that pass the same account to one instruction twice. Execution assumes
+/// neither can happen. Also reject any instruction carrying more than
+/// `MAX_INSTRUCTION_DATA_LEN` bytes of data.
+pub fn sanitize_transaction(tx: &Transaction) -> Result<(), TransactionError> {
+    if !tx.verify_refs() {
+        return Err(TransactionError::SanitizeFailure(
+            SanitizeError::IndexOutOfBounds,
+        ));
+    }
+    if tx
+        .instructions
+        .iter()
+        .any(|instruction| has_duplicates(&instruction.accounts))
+    {
+        return Err(TransactionError::SanitizeFailure(
+            SanitizeError::DuplicateAccount,
+        ));
+    }
+    if let Some(index) = tx
+        .instructions
+        .iter()
+        .position(|instruction| instruction.data.len() > MAX_INSTRUCTION_DATA_LEN)
+    {
+        return Err(TransactionError::SanitizeFailure(
+            SanitizeError::InstructionDataTooLarge(index as u8),
+        ));
+    }
+    Ok(())
+}
+
 /// Get mut references to a subset of elements.
 fn get_subset_unchecked_mut<'a, T>(
     xs: &'a mut [T],
@@ -88,8 +152,10 @@ pub struct Runtime {
 
 impl Default for Runtime {
     fn default() -> Self {
-        let instruction_processors: Vec<(Pubkey, ProcessInstruction)> =
-            vec![(system_program::id(), crate::system_program::entrypoint)];
+        let instruction_processors: Vec<(Pubkey, ProcessInstruction)> = vec![
+            (system_program::id(), crate::system_program::entrypoint),
+            (memo_program::id(), crate::memo_program::entrypoint),
+        ];
 
         Self {
             instruction_processors,
@@ -205,26 +271,38 @@ impl Runtime {
 
     /// Execute a transaction.
     /// This method calls each instruction in the transaction over the set of loaded Accounts
-    /// The accounts are committed back to the bank only if every instruction succeeds
+    /// The accounts are committed back to the bank only if every instruction succeeds.
+    /// Each instruction executed is timed into `timings` under its program id.
     pub fn execute_transaction(
         &self,
         tx: &Transaction,
         loaders: &mut [Vec<(Pubkey, Account)>],
         tx_accounts: &mut [Account],
         tick_height: u64,
+        timings: &mut ProgramTimings,
     ) -> Result<(), TransactionError> {
         for (instruction_index, instruction) in tx.instructions.iter().enumerate() {
             let executable_accounts = &mut loaders[instruction.program_ids_index as usize];
             let mut program_accounts = get_subset_unchecked_mut(tx_accounts, &instruction.accounts)
                 .map_err(|err| TransactionError::InstructionError(instruction_index as u8, err))?;
-            self.execute_instruction(
+            let now = Instant::now();
+            let result = self.execute_instruction(
                 tx,
                 instruction_index,
                 executable_accounts,
                 &mut program_accounts,
                 tick_height,
-            )
-            .map_err(|err| TransactionError::InstructionError(instruction_index as u8, err))?;
+            );
+            let timing = timings
+                .entry(*tx.program_id(instruction_index))
+                .or_default();
+            timing.count += 1;
+            timing.total_us += duration_as_us(&now.elapsed());
+            if result.is_err() {
+                timing.errors += 1;
+            }
+            result
+                .map_err(|err| TransactionError::InstructionError(instruction_index as u8, err))?;
         }
         Ok(())
     }
@@ -233,7 +311,9 @@ impl Runtime {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_sdk::hash::Hash;
     use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::transaction::CompiledInstruction;
 
     #[test]
     fn test_has_duplicates() {
@@ -269,6 +349,76 @@ mod tests {
         get_subset_unchecked_mut(&mut [7, 8], &[2]).unwrap();
     }
 
+    #[test]
+    fn test_sanitize_transaction() {
+        let keypair = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let new_tx = |program_ids_index, accounts| {
+            let instruction = CompiledInstruction {
+                program_ids_index,
+                data: vec![],
+                accounts,
+            };
+            Transaction::new_with_compiled_instructions(
+                &[&keypair],
+                &[to],
+                Hash::default(),
+                0,
+                vec![system_program::id()],
+                vec![instruction],
+            )
+        };
+        assert_eq!(sanitize_transaction(&new_tx(0, vec![0, 1])), Ok(()));
+        assert_eq!(
+            sanitize_transaction(&new_tx(0, vec![0, 2])),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::IndexOutOfBounds
+            ))
+        );
+        assert_eq!(
+            sanitize_transaction(&new_tx(1, vec![0, 1])),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::IndexOutOfBounds
+            ))
+        );
+        assert_eq!(
+            sanitize_transaction(&new_tx(0, vec![1, 1])),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::DuplicateAccount
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sanitize_transaction_instruction_data_len() {
+        let keypair = Keypair::new();
+        let new_tx = |len| {
+            let instruction = CompiledInstruction {
+                program_ids_index: 0,
+                data: vec![0; len],
+                accounts: vec![0],
+            };
+            Transaction::new_with_compiled_instructions(
+                &[&keypair],
+                &[],
+                Hash::default(),
+                0,
+                vec![system_program::id()],
+                vec![instruction],
+            )
+        };
+        assert_eq!(
+            sanitize_transaction(&new_tx(MAX_INSTRUCTION_DATA_LEN)),
+            Ok(())
+        );
+        assert_eq!(
+            sanitize_transaction(&new_tx(MAX_INSTRUCTION_DATA_LEN + 1)),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::InstructionDataTooLarge(0)
+            ))
+        );
+    }
+
     #[test]
     fn test_verify_instruction_change_program_id() {
         fn change_program_id(
@@ -1,6 +1,6 @@
 use crate::bloom::{Bloom, BloomHashIndex};
 use hashbrown::HashMap;
-use solana_sdk::hash::Hash;
+use solana_sdk::hash::{hash, Hash};
 use solana_sdk::signature::Signature;
 use std::collections::VecDeque;
 use std::ops::Deref;
@@ -10,18 +10,54 @@ use std::ops::DerefMut;
 /// Each cache entry is designed to span ~1 second of signatures
 const MAX_CACHE_ENTRIES: usize = solana_sdk::timing::MAX_HASH_AGE_IN_SECONDS;
 
+/// The signature bloom filter is sized for about this many signatures
+const MAX_ENTRIES_PER_CACHE: usize = 1_000_000;
+
 type FailureMap<T> = HashMap<Signature, T>;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusCacheConfig {
+    /// How many previous caches are retained alongside the current one
+    pub max_caches: usize,
+
+    /// How many signatures a cache may hold before it is rotated out early
+    pub max_entries_per_cache: usize,
+}
+
+impl Default for StatusCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_caches: MAX_CACHE_ENTRIES,
+            max_entries_per_cache: MAX_ENTRIES_PER_CACHE,
+        }
+    }
+}
+
+/// Size of the signature caches a bank consults, for metrics
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StatusCacheStats {
+    pub num_caches: usize,
+    pub num_entries: usize,
+}
+
 #[derive(Clone)]
 pub struct StatusCache<T> {
     /// all signatures seen at this checkpoint
     signatures: Bloom<Signature>,
 
+    /// number of signatures added at this checkpoint
+    entries: usize,
+
+    /// the blockhash the bloom filter keys were derived from
+    blockhash: Hash,
+
     /// failures
     failures: FailureMap<T>,
 
     /// Merges are empty unless this is the root checkpoint which cannot be unrolled
     merges: VecDeque<StatusCache<T>>,
+
+    config: StatusCacheConfig,
 }
 
 impl<T: Clone> Default for StatusCache<T> {
@@ -32,13 +68,33 @@ impl<T: Clone> Default for StatusCache<T> {
 
 impl<T: Clone> StatusCache<T> {
     pub fn new(blockhash: &Hash) -> Self {
+        Self::new_with_config(blockhash, StatusCacheConfig::default())
+    }
+    pub fn new_with_config(blockhash: &Hash, config: StatusCacheConfig) -> Self {
         let keys = (0..27).map(|i| blockhash.hash_at_index(i)).collect();
         Self {
             signatures: Bloom::new(38_340_234, keys),
+            entries: 0,
+            blockhash: *blockhash,
             failures: HashMap::new(),
             merges: VecDeque::new(),
+            config,
         }
     }
+    pub fn config(&self) -> StatusCacheConfig {
+        self.config
+    }
+    /// number of signatures held by this checkpoint and its merges
+    pub fn len(&self) -> usize {
+        self.entries + self.merges.iter().map(|c| c.entries).sum::<usize>()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// number of caches held by this checkpoint, counting itself and its merges
+    pub fn num_caches(&self) -> usize {
+        1 + self.merges.len()
+    }
     fn has_signature_merged(&self, sig: &Signature) -> bool {
         for c in &self.merges {
             if c.has_signature(sig) {
@@ -51,9 +107,14 @@ impl<T: Clone> StatusCache<T> {
     pub fn has_signature(&self, sig: &Signature) -> bool {
         self.signatures.contains(&sig) || self.has_signature_merged(sig)
     }
-    /// add a signature
+    /// add a signature, rotating in a new cache first if this one is full
     pub fn add(&mut self, sig: &Signature) {
-        self.signatures.add(&sig)
+        if self.entries >= self.config.max_entries_per_cache {
+            let blockhash = hash(self.blockhash.as_ref());
+            self.new_cache(&blockhash);
+        }
+        self.signatures.add(&sig);
+        self.entries += 1;
     }
     /// Save an error status for a signature
     pub fn save_failure_status(&mut self, sig: &Signature, err: T) {
@@ -64,8 +125,26 @@ impl<T: Clone> StatusCache<T> {
     pub fn clear(&mut self) {
         self.failures.clear();
         self.signatures.clear();
+        self.entries = 0;
         self.merges = VecDeque::new();
     }
+    /// Forget the signatures of successful transactions, keeping failed ones and their
+    /// statuses
+    pub fn clear_successes(&mut self) {
+        self.signatures.clear();
+        for sig in self.failures.keys() {
+            self.signatures.add(sig);
+        }
+        self.entries = self.failures.len();
+        for c in self.merges.iter_mut() {
+            c.clear_successes();
+        }
+    }
+    /// Forget the signatures of all but the `generations` most recently rotated out caches.
+    /// The current cache is always kept.
+    pub fn clear_older_than(&mut self, generations: usize) {
+        self.merges.truncate(generations);
+    }
     fn get_signature_status_merged(&self, sig: &Signature) -> Option<Result<(), T>> {
         for c in &self.merges {
             if c.has_signature(sig) {
@@ -87,25 +166,28 @@ impl<T: Clone> StatusCache<T> {
         // flatten and squash the parent and its merges into self.merges,
         //  returns true if self is full
 
-        self.merges.push_back(StatusCache {
-            signatures: parent.signatures.clone(),
-            failures: parent.failures.clone(),
-            merges: VecDeque::new(),
-        });
+        self.merges.push_back(parent.without_merges());
         for merge in &parent.merges {
-            self.merges.push_back(StatusCache {
-                signatures: merge.signatures.clone(),
-                failures: merge.failures.clone(),
-                merges: VecDeque::new(),
-            });
+            self.merges.push_back(merge.without_merges());
         }
-        self.merges.truncate(MAX_CACHE_ENTRIES);
+        self.merges.truncate(self.config.max_caches);
+
+        self.merges.len() == self.config.max_caches
+    }
 
-        self.merges.len() == MAX_CACHE_ENTRIES
+    fn without_merges(&self) -> Self {
+        StatusCache {
+            signatures: self.signatures.clone(),
+            entries: self.entries,
+            blockhash: self.blockhash,
+            failures: self.failures.clone(),
+            merges: VecDeque::new(),
+            config: self.config,
+        }
     }
 
     /// copy the parents and parents' merges up to this instance, up to
-    ///   MAX_CACHE_ENTRIES deep
+    ///   `max_caches` deep
     pub fn squash<U>(&mut self, parents: &[U])
     where
         U: Deref<Target = Self>,
@@ -119,14 +201,14 @@ impl<T: Clone> StatusCache<T> {
 
     /// Crate a new cache, pushing the old cache into the merged queue
     pub fn new_cache(&mut self, blockhash: &Hash) {
-        let mut old = Self::new(blockhash);
+        let mut old = Self::new_with_config(blockhash, self.config);
         std::mem::swap(&mut old.signatures, &mut self.signatures);
+        std::mem::swap(&mut old.entries, &mut self.entries);
+        std::mem::swap(&mut old.blockhash, &mut self.blockhash);
         std::mem::swap(&mut old.failures, &mut self.failures);
         assert!(old.merges.is_empty());
         self.merges.push_front(old);
-        if self.merges.len() > MAX_CACHE_ENTRIES {
-            self.merges.pop_back();
-        }
+        self.merges.truncate(self.config.max_caches);
     }
     pub fn get_signature_status_all<U>(
         checkpoints: &[U],
@@ -167,7 +249,6 @@ impl<T: Clone> StatusCache<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::hash::hash;
     use solana_sdk::transaction::TransactionError;
 
     type BankStatusCache = StatusCache<TransactionError>;
@@ -232,6 +313,62 @@ mod tests {
         assert!(!first.has_signature(&sig));
     }
 
+    #[test]
+    fn test_new_cache_max_caches() {
+        let config = StatusCacheConfig {
+            max_caches: 2,
+            ..StatusCacheConfig::default()
+        };
+        let mut blockhash = hash(Hash::default().as_ref());
+        let mut cache = BankStatusCache::new_with_config(&blockhash, config);
+        let sigs: Vec<_> = (0..4u8).map(|i| Signature::new(&[i; 64])).collect();
+        for sig in &sigs {
+            cache.add(sig);
+            blockhash = hash(blockhash.as_ref());
+            cache.new_cache(&blockhash);
+        }
+        assert_eq!(cache.num_caches(), 3);
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.has_signature(&sigs[0]));
+        assert!(!cache.has_signature(&sigs[1]));
+        assert!(cache.has_signature(&sigs[2]));
+        assert!(cache.has_signature(&sigs[3]));
+    }
+
+    #[test]
+    fn test_add_max_entries_per_cache() {
+        let config = StatusCacheConfig {
+            max_caches: 2,
+            max_entries_per_cache: 2,
+        };
+        let blockhash = hash(Hash::default().as_ref());
+        let mut cache = BankStatusCache::new_with_config(&blockhash, config);
+        let sigs: Vec<_> = (0..7u8).map(|i| Signature::new(&[i; 64])).collect();
+        for sig in &sigs[..2] {
+            cache.add(sig);
+        }
+        assert_eq!(cache.num_caches(), 1);
+        assert_eq!(cache.len(), 2);
+
+        // The third signature forces a rotation
+        cache.add(&sigs[2]);
+        assert_eq!(cache.num_caches(), 2);
+        assert_eq!(cache.len(), 3);
+
+        // Filling past max_caches evicts the oldest signatures
+        for sig in &sigs[3..] {
+            cache.add(sig);
+        }
+        assert_eq!(cache.num_caches(), 3);
+        assert_eq!(cache.len(), 5);
+        assert!(!cache.has_signature(&sigs[0]));
+        assert!(!cache.has_signature(&sigs[1]));
+        for sig in &sigs[2..] {
+            assert!(cache.has_signature(sig));
+            assert_eq!(cache.get_signature_status(sig), Some(Ok(())));
+        }
+    }
+
     #[test]
     fn test_status_cache_squash_has_signature() {
         let sig = Signature::default();
@@ -317,6 +454,57 @@ mod tests {
         assert_eq!(first.has_signature(&sig), false);
         assert_eq!(first.get_signature_status(&sig), None);
     }
+    #[test]
+    fn test_clear_successes() {
+        let blockhash = hash(Hash::default().as_ref());
+        let success = Signature::new(&[1; 64]);
+        let failure = Signature::new(&[2; 64]);
+        let merged_failure = Signature::new(&[3; 64]);
+        let mut first = BankStatusCache::new(&blockhash);
+        first.add(&merged_failure);
+        first.save_failure_status(&merged_failure, TransactionError::AccountInUse);
+        first.new_cache(&hash(blockhash.as_ref()));
+        first.add(&success);
+        first.add(&failure);
+        first.save_failure_status(&failure, TransactionError::DuplicateSignature);
+
+        first.clear_successes();
+        assert_eq!(first.has_signature(&success), false);
+        assert_eq!(first.get_signature_status(&success), None);
+        assert_eq!(first.has_signature(&failure), true);
+        assert_eq!(
+            first.get_signature_status(&failure),
+            Some(Err(TransactionError::DuplicateSignature)),
+        );
+        assert_eq!(
+            first.get_signature_status(&merged_failure),
+            Some(Err(TransactionError::AccountInUse)),
+        );
+        assert_eq!(first.len(), 2);
+    }
+
+    #[test]
+    fn test_clear_older_than() {
+        let blockhash = hash(Hash::default().as_ref());
+        let sigs: Vec<_> = (0..3u8).map(|i| Signature::new(&[i; 64])).collect();
+        let mut first = BankStatusCache::new(&blockhash);
+        for sig in &sigs {
+            first.add(sig);
+            first.new_cache(&hash(sig.as_ref()));
+        }
+        assert_eq!(first.num_caches(), 4);
+
+        first.clear_older_than(2);
+        assert_eq!(first.num_caches(), 3);
+        assert_eq!(first.has_signature(&sigs[0]), false);
+        assert_eq!(first.has_signature(&sigs[1]), true);
+        assert_eq!(first.has_signature(&sigs[2]), true);
+
+        first.clear_older_than(0);
+        assert_eq!(first.num_caches(), 1);
+        assert!(first.is_empty());
+    }
+
     #[test]
     fn test_clear_signatures_all() {
         let sig = Signature::default();
@@ -1,6 +1,6 @@
 use bincode::serialize;
 use log::*;
-use solana_sdk::account::KeyedAccount;
+use solana_sdk::account::{KeyedAccount, MAX_PERMITTED_DATA_LENGTH};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::system_instruction::{SystemError, SystemInstruction};
 use solana_sdk::system_program;
@@ -29,6 +29,13 @@ fn create_system_account(
         );
         Err(SystemError::AccountAlreadyInUse)?;
     }
+    if space > MAX_PERMITTED_DATA_LENGTH as u64 {
+        info!(
+            "CreateAccount: {} bytes of space exceeds the maximum of {}",
+            space, MAX_PERMITTED_DATA_LENGTH
+        );
+        Err(SystemError::InvalidAccountDataLength)?;
+    }
     if lamports > keyed_accounts[FROM_ACCOUNT_INDEX].account.lamports {
         info!(
             "CreateAccount: insufficient lamports ({}, need {})",
@@ -160,6 +167,27 @@ mod tests {
         assert_eq!(to_account, unchanged_account);
     }
 
+    #[test]
+    fn test_create_oversized_account() {
+        let new_program_owner = Pubkey::new(&[9; 32]);
+        let from = Keypair::new().pubkey();
+        let mut from_account = Account::new(100, 0, &system_program::id());
+
+        let to = Keypair::new().pubkey();
+        let mut to_account = Account::new(0, 0, &Pubkey::default());
+        let unchanged_account = to_account.clone();
+
+        let mut keyed_accounts = [
+            KeyedAccount::new(&from, true, &mut from_account),
+            KeyedAccount::new(&to, false, &mut to_account),
+        ];
+        let space = MAX_PERMITTED_DATA_LENGTH as u64 + 1;
+        let result = create_system_account(&mut keyed_accounts, 50, space, &new_program_owner);
+        assert_eq!(result, Err(SystemError::InvalidAccountDataLength));
+        assert_eq!(from_account.lamports, 100);
+        assert_eq!(to_account, unchanged_account);
+    }
+
     #[test]
     fn test_create_already_owned() {
         // Attempt to create system account in account already owned by another program
@@ -0,0 +1,81 @@
+//! The `verified_transaction` module marks transactions whose signatures are known to
+//! be valid, so Bank can skip checking them again.
+
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::borrow::Borrow;
+use std::ops::Deref;
+
+/// A transaction whose signatures have been verified
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VerifiedTransaction(Transaction);
+
+impl VerifiedTransaction {
+    /// Verify `tx`'s signatures, failing with `SignatureFailure` if any doesn't match
+    pub fn verify(tx: Transaction) -> Result<Self, TransactionError> {
+        if tx.verify_signature() {
+            Ok(VerifiedTransaction(tx))
+        } else {
+            Err(TransactionError::SignatureFailure)
+        }
+    }
+
+    /// Wrap `tx` without checking it. Only for transactions the sigverify stage has
+    /// already verified.
+    pub fn assume_verified(tx: Transaction) -> Self {
+        VerifiedTransaction(tx)
+    }
+
+    pub fn into_transaction(self) -> Transaction {
+        self.0
+    }
+}
+
+impl Deref for VerifiedTransaction {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Transaction {
+        &self.0
+    }
+}
+
+/// Lets Bank process a batch of verified transactions in place
+impl Borrow<Transaction> for VerifiedTransaction {
+    fn borrow(&self) -> &Transaction {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::system_transaction::SystemTransaction;
+
+    #[test]
+    fn test_verified_transaction_verify() {
+        let keypair = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let tx = SystemTransaction::new_move(&keypair, &to, 1, Hash::default(), 0);
+        let verified = VerifiedTransaction::verify(tx.clone()).unwrap();
+        assert_eq!(verified.signatures, tx.signatures);
+        assert_eq!(verified.into_transaction(), tx);
+
+        let mut tampered = tx;
+        tampered.fee = 1;
+        assert_eq!(
+            VerifiedTransaction::verify(tampered),
+            Err(TransactionError::SignatureFailure)
+        );
+    }
+
+    #[test]
+    fn test_verified_transaction_assume_verified() {
+        let keypair = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let mut tx = SystemTransaction::new_move(&keypair, &to, 1, Hash::default(), 0);
+        tx.fee = 1;
+        let verified = VerifiedTransaction::assume_verified(tx.clone());
+        assert_eq!(*verified, tx);
+    }
+}
@@ -1,6 +1,10 @@
 use crate::pubkey::Pubkey;
 use std::{cmp, fmt};
 
+/// The most bytes of data an account may hold. Larger accounts would make hashing and
+/// snapshotting the accounts store impractical.
+pub const MAX_PERMITTED_DATA_LENGTH: usize = 10 * 1024 * 1024;
+
 /// An Account with data that is stored on chain
 #[repr(C)]
 #[derive(Serialize, Deserialize, Clone, Default, Eq, PartialEq)]
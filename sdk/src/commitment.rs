@@ -0,0 +1,36 @@
+//! The `commitment` module describes how settled the bank an RPC request reads from must be.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Commitment {
+    /// The most recent bank squashed into a root
+    Root,
+    /// The newest frozen bank descending from the root
+    Frozen,
+    /// The working bank, whose slot may still be open
+    Recent,
+}
+
+impl Default for Commitment {
+    fn default() -> Self {
+        Commitment::Recent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_serialize() {
+        assert_eq!(Commitment::default(), Commitment::Recent);
+        assert_eq!(
+            serde_json::to_string(&Commitment::Frozen).unwrap(),
+            "\"frozen\""
+        );
+        assert_eq!(
+            serde_json::from_str::<Commitment>("\"root\"").unwrap(),
+            Commitment::Root
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! The `fee_calculator` module describes the fee schedule a bank charges transactions under.
+
+use crate::transaction::Transaction;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeCalculator {
+    /// The number of lamports charged for each signature a transaction carries
+    pub lamports_per_signature: u64,
+}
+
+impl FeeCalculator {
+    pub fn new(lamports_per_signature: u64) -> Self {
+        Self {
+            lamports_per_signature,
+        }
+    }
+
+    /// Return the fee this schedule charges a transaction carrying `num_signatures`
+    pub fn calculate_fee_for_signatures(&self, num_signatures: usize) -> u64 {
+        self.lamports_per_signature * num_signatures as u64
+    }
+
+    /// Return the fee this schedule charges for `tx`
+    pub fn calculate_fee(&self, tx: &Transaction) -> u64 {
+        self.calculate_fee_for_signatures(tx.signatures.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Hash;
+    use crate::signature::{Keypair, KeypairUtil};
+    use crate::system_transaction::SystemTransaction;
+
+    #[test]
+    fn test_fee_calculator_calculate_fee() {
+        let from = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let tx = SystemTransaction::new_move(&from, &to, 1, Hash::default(), 0);
+        assert_eq!(FeeCalculator::default().calculate_fee(&tx), 0);
+        assert_eq!(FeeCalculator::new(2).calculate_fee(&tx), 2);
+    }
+}
@@ -1,5 +1,6 @@
 //! The `genesis_block` module is a library for generating the chain's genesis block.
 
+use crate::fee_calculator::FeeCalculator;
 use crate::hash::{hash, Hash};
 use crate::pubkey::Pubkey;
 use crate::signature::{Keypair, KeypairUtil};
@@ -13,6 +14,15 @@ use std::path::Path;
 // * 1 lamport for the bootstrap leader vote account
 pub const BOOTSTRAP_LEADER_LAMPORTS: u64 = 2;
 
+/// The account vote credits are redeemed from, owned by the rewards program
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RewardsPool {
+    pub id: Pubkey,
+    pub lamports: u64,
+    /// The only account allowed to replenish the pool
+    pub authority_id: Pubkey,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GenesisBlock {
     pub bootstrap_leader_id: Pubkey,
@@ -25,6 +35,27 @@ pub struct GenesisBlock {
     pub stakers_slot_offset: u64,
     pub epoch_warmup: bool,
     pub native_programs: Vec<(String, Pubkey)>,
+    /// Maximum number of transactions committed in a single slot, unlimited if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transactions_per_slot: Option<u64>,
+    /// Minimum lamports a new staking account must be funded with, unlimited if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_delegation: Option<u64>,
+    /// Fewest lamports a withdrawal may leave in an account it doesn't close, unchecked if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_balance: Option<u64>,
+    /// Fee schedule transactions are charged under, free if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_calculator: Option<FeeCalculator>,
+    /// Reject transactions that only move lamports from an account to itself without a fee
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reject_self_transfers: bool,
+    /// Lamports the inflation pool starts with, which rewards are paid from; empty if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inflation_pool_lamports: Option<u64>,
+    /// Pool vote credits are redeemed from, not created if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewards_pool: Option<RewardsPool>,
 }
 
 impl GenesisBlock {
@@ -59,6 +90,13 @@ impl GenesisBlock {
                 stakers_slot_offset: DEFAULT_SLOTS_PER_EPOCH,
                 epoch_warmup: true,
                 native_programs: vec![],
+                max_transactions_per_slot: None,
+                minimum_delegation: None,
+                minimum_balance: None,
+                fee_calculator: None,
+                reject_self_transfers: false,
+                inflation_pool_lamports: None,
+                rewards_pool: None,
             },
             mint_keypair,
         )
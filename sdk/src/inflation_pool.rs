@@ -0,0 +1,17 @@
+//! The `inflation_pool` module names the account staking rewards are paid out of, so new
+//! lamports enter circulation from a balance fixed at genesis rather than being minted.
+
+use crate::pubkey::Pubkey;
+
+const INFLATION_POOL_ID: [u8; 32] = [
+    136, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0,
+];
+
+pub fn id() -> Pubkey {
+    Pubkey::new(&INFLATION_POOL_ID)
+}
+
+pub fn check_id(pubkey: &Pubkey) -> bool {
+    pubkey.as_ref() == INFLATION_POOL_ID
+}
@@ -1,9 +1,14 @@
 pub mod account;
 pub mod bpf_loader;
+pub mod commitment;
+pub mod fee_calculator;
 pub mod genesis_block;
 pub mod hash;
+pub mod inflation_pool;
 pub mod loader_instruction;
 pub mod loader_transaction;
+pub mod memo_instruction;
+pub mod memo_program;
 pub mod native_loader;
 pub mod native_program;
 pub mod packet;
@@ -15,6 +20,7 @@ pub mod signature;
 pub mod system_instruction;
 pub mod system_program;
 pub mod system_transaction;
+pub mod sysvar;
 pub mod timing;
 pub mod transaction;
 
@@ -1,3 +1,12 @@
+use crate::transaction::MAX_INSTRUCTION_DATA_LEN;
+
+/// The serialized size of a `Write` instruction without its bytes: the variant tag, the
+/// offset and the length of `bytes`
+pub const WRITE_HEADER_LEN: usize = 16;
+
+/// The most program bytes a single `Write` instruction can carry
+pub const MAX_WRITE_LEN: usize = MAX_INSTRUCTION_DATA_LEN - WRITE_HEADER_LEN;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum LoaderInstruction {
     /// Write program data into an account
@@ -16,3 +25,37 @@ pub enum LoaderInstruction {
     /// The transaction must be signed by key[0]
     Finalize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Hash;
+    use crate::loader_transaction::LoaderTransaction;
+    use crate::packet::PACKET_DATA_SIZE;
+    use crate::pubkey::Pubkey;
+    use crate::signature::{Keypair, KeypairUtil};
+    use bincode::{serialize, serialized_size};
+
+    #[test]
+    fn test_max_write_len() {
+        let instruction = LoaderInstruction::Write {
+            offset: 0,
+            bytes: vec![0; MAX_WRITE_LEN],
+        };
+        assert_eq!(
+            serialized_size(&instruction).unwrap() as usize,
+            MAX_INSTRUCTION_DATA_LEN
+        );
+
+        // The largest write still fits in a packet
+        let tx = LoaderTransaction::new_write(
+            &Keypair::new(),
+            &Pubkey::default(),
+            0,
+            vec![0; MAX_WRITE_LEN],
+            Hash::default(),
+            0,
+        );
+        assert!(serialize(&tx).unwrap().len() <= PACKET_DATA_SIZE);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::memo_program;
+use crate::transaction::{Instruction, Transaction};
+use std::str;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum MemoError {
+    /// The memo is longer than `memo_program::MAX_MEMO_LEN` bytes
+    TooLong,
+    /// The memo isn't valid UTF-8
+    InvalidUtf8,
+}
+
+pub struct MemoInstruction {}
+
+impl MemoInstruction {
+    /// Attach `memo` to the transaction carrying this instruction. The memo's bytes are the
+    /// instruction data, and no accounts are passed.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(memo: &str) -> Instruction {
+        Instruction {
+            program_ids_index: memo_program::id(),
+            accounts: vec![],
+            data: memo.as_bytes().to_vec(),
+        }
+    }
+
+    /// Return the memo in `data`, if it's one the memo program would accept
+    pub fn parse(data: &[u8]) -> Result<&str, MemoError> {
+        if data.len() > memo_program::MAX_MEMO_LEN {
+            return Err(MemoError::TooLong);
+        }
+        str::from_utf8(data).map_err(|_| MemoError::InvalidUtf8)
+    }
+
+    /// Return the memos attached to `tx`, in instruction order
+    pub fn memos(tx: &Transaction) -> Vec<&str> {
+        tx.instructions
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| memo_program::check_id(tx.program_id(*i)))
+            .filter_map(|(_, instruction)| Self::parse(&instruction.data).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Hash;
+    use crate::signature::{Keypair, KeypairUtil};
+    use crate::system_instruction::SystemInstruction;
+
+    #[test]
+    fn test_memo_instruction_parse() {
+        let instruction = MemoInstruction::new("invoice 42");
+        assert_eq!(instruction.program_ids_index, memo_program::id());
+        assert!(instruction.accounts.is_empty());
+        assert_eq!(MemoInstruction::parse(&instruction.data), Ok("invoice 42"));
+
+        let memo = "x".repeat(memo_program::MAX_MEMO_LEN);
+        assert_eq!(MemoInstruction::parse(memo.as_bytes()), Ok(memo.as_str()));
+        let memo = "x".repeat(memo_program::MAX_MEMO_LEN + 1);
+        assert_eq!(
+            MemoInstruction::parse(memo.as_bytes()),
+            Err(MemoError::TooLong)
+        );
+        assert_eq!(
+            MemoInstruction::parse(&[0xff, 0xfe]),
+            Err(MemoError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn test_memo_instruction_memos() {
+        let from = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let mut tx = Transaction::new(vec![
+            SystemInstruction::new_move(&from.pubkey(), &to, 1),
+            MemoInstruction::new("invoice 42"),
+        ]);
+        tx.sign(&[&from], Hash::default());
+        assert_eq!(MemoInstruction::memos(&tx), vec!["invoice 42"]);
+
+        let tx = Transaction::new(vec![SystemInstruction::new_move(&from.pubkey(), &to, 1)]);
+        assert!(MemoInstruction::memos(&tx).is_empty());
+    }
+}
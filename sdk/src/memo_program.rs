@@ -0,0 +1,17 @@
+use crate::pubkey::Pubkey;
+
+const MEMO_PROGRAM_ID: [u8; 32] = [
+    134, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0,
+];
+
+/// The longest memo, in bytes, the memo program accepts
+pub const MAX_MEMO_LEN: usize = 128;
+
+pub fn id() -> Pubkey {
+    Pubkey::new(&MEMO_PROGRAM_ID)
+}
+
+pub fn check_id(program_id: &Pubkey) -> bool {
+    program_id.as_ref() == MEMO_PROGRAM_ID
+}
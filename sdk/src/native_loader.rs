@@ -5,6 +5,19 @@ const NATIVE_LOADER_PROGRAM_ID: [u8; 32] = [
     1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 ];
 
+/// Reasons a native program couldn't be invoked, reported as an
+/// `InstructionError::CustomError`. Custom errors are truncated, so the program is identified
+/// by the failing instruction's program id rather than by name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum NativeLoaderError {
+    /// The program's shared library couldn't be loaded
+    LibraryNotFound,
+    /// The shared library doesn't export the program entrypoint
+    EntrypointNotFound,
+    /// The shared library was built against a different native program ABI
+    AbiVersionMismatch { expected: u32, found: Option<u32> },
+}
+
 pub fn id() -> Pubkey {
     Pubkey::new(&NATIVE_LOADER_PROGRAM_ID)
 }
@@ -5,6 +5,16 @@ use crate::transaction::InstructionError;
 // All native programs export a symbol named process()
 pub const ENTRYPOINT: &str = "process";
 
+// All native programs export a symbol named solana_abi_version() returning the ABI_VERSION
+// they were built against
+pub const ABI_VERSION_SYMBOL: &str = "solana_abi_version";
+
+// Bump whenever the Entrypoint prototype or the types passed through it change
+pub const ABI_VERSION: u32 = 1;
+
+// Native program ABI_VERSION_SYMBOL prototype
+pub type AbiVersion = unsafe extern "C" fn() -> u32;
+
 // Native program ENTRYPOINT prototype
 pub type Entrypoint = unsafe extern "C" fn(
     program_id: &Pubkey,
@@ -18,6 +28,11 @@ pub type Entrypoint = unsafe extern "C" fn(
 #[macro_export]
 macro_rules! solana_entrypoint(
     ($entrypoint:ident) => (
+        #[no_mangle]
+        pub extern "C" fn solana_abi_version() -> u32 {
+            $crate::native_program::ABI_VERSION
+        }
+
         #[no_mangle]
         pub extern "C" fn process(
             program_id: &Pubkey,
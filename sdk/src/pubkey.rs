@@ -1,6 +1,7 @@
 use bs58;
 use generic_array::typenum::U32;
 use generic_array::GenericArray;
+use std::error;
 use std::fmt;
 use std::mem;
 use std::str::FromStr;
@@ -15,6 +16,17 @@ pub enum ParsePubkeyError {
     Invalid,
 }
 
+impl fmt::Display for ParsePubkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePubkeyError::WrongSize => write!(f, "public key has the wrong size"),
+            ParsePubkeyError::Invalid => write!(f, "public key is not valid base58"),
+        }
+    }
+}
+
+impl error::Error for ParsePubkeyError {}
+
 impl FromStr for Pubkey {
     type Err = ParsePubkeyError;
 
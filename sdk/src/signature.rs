@@ -5,13 +5,15 @@ use bs58;
 use generic_array::typenum::U64;
 use generic_array::GenericArray;
 use ring::signature::Ed25519KeyPair;
-use ring::{rand, signature};
+use ring::{digest, pbkdf2, rand, signature};
 use serde_json;
 use std::error;
 use std::fmt;
 use std::fs::{self, File};
 use std::io::Write;
+use std::mem;
 use std::path::Path;
+use std::str::FromStr;
 use untrusted::Input;
 
 pub type Keypair = Ed25519KeyPair;
@@ -66,6 +68,38 @@ impl fmt::Display for Signature {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSignatureError {
+    WrongSize,
+    Invalid,
+}
+
+impl fmt::Display for ParseSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseSignatureError::WrongSize => write!(f, "signature has the wrong size"),
+            ParseSignatureError::Invalid => write!(f, "signature is not valid base58"),
+        }
+    }
+}
+
+impl error::Error for ParseSignatureError {}
+
+impl FromStr for Signature {
+    type Err = ParseSignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let signature_vec = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| ParseSignatureError::Invalid)?;
+        if signature_vec.len() != mem::size_of::<Signature>() {
+            Err(ParseSignatureError::WrongSize)
+        } else {
+            Ok(Signature::new(&signature_vec))
+        }
+    }
+}
+
 pub trait KeypairUtil {
     fn new() -> Self;
     fn pubkey(&self) -> Pubkey;
@@ -90,6 +124,49 @@ impl KeypairUtil for Ed25519KeyPair {
     }
 }
 
+pub trait SeedableKeypair {
+    fn from_seed(seed: &[u8; 32]) -> Self;
+}
+
+impl SeedableKeypair for Ed25519KeyPair {
+    /// Return the ED25519 keypair whose private key is `seed`
+    fn from_seed(seed: &[u8; 32]) -> Self {
+        Ed25519KeyPair::from_seed_unchecked(Input::from(seed)).expect("from_seed_unchecked")
+    }
+}
+
+/// Number of PBKDF2 rounds BIP39 uses to stretch a seed phrase
+const SEED_PHRASE_ITERATIONS: u32 = 2048;
+
+/// Stretch `words` and `passphrase` into a 64 byte seed the way BIP39 does. The words
+/// are not checked against a wordlist, but runs of whitespace between them are ignored.
+fn bip39_seed(words: &str, passphrase: &str) -> [u8; 64] {
+    let words = words.split_whitespace().collect::<Vec<_>>().join(" ");
+    let salt = format!("mnemonic{}", passphrase);
+    let mut seed = [0u8; 64];
+    pbkdf2::derive(
+        &digest::SHA512,
+        SEED_PHRASE_ITERATIONS,
+        salt.as_bytes(),
+        words.as_bytes(),
+        &mut seed,
+    );
+    seed
+}
+
+/// Return the keypair seed for a BIP39 seed phrase, the first 32 bytes of its BIP39 seed
+pub fn seed_from_seed_phrase(words: &str, passphrase: &str) -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&bip39_seed(words, passphrase)[..32]);
+    seed
+}
+
+/// Return the keypair derived from a BIP39 seed phrase, so the same words always recover
+/// the same keypair
+pub fn keypair_from_seed_phrase(words: &str, passphrase: &str) -> Keypair {
+    Keypair::from_seed(&seed_from_seed_phrase(words, passphrase))
+}
+
 pub fn read_pkcs8(path: &str) -> Result<Vec<u8>, Box<error::Error>> {
     let file = File::open(path.to_string())?;
     let pkcs8: Vec<u8> = serde_json::from_reader(file)?;
@@ -108,9 +185,36 @@ pub fn gen_pkcs8() -> Result<Vec<u8>, Box<error::Error>> {
     Ok(pkcs8_bytes.to_vec())
 }
 
+/// Encode the keypair for `seed` as the PKCS#8 v2 document `gen_pkcs8` produces
+pub fn gen_pkcs8_from_seed(seed: &[u8; 32]) -> Vec<u8> {
+    const PREFIX: [u8; 16] = [
+        0x30, 0x53, 0x02, 0x01, 0x01, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04,
+        0x20,
+    ];
+    const MIDDLE: [u8; 5] = [0xa1, 0x23, 0x03, 0x21, 0x00];
+
+    let keypair = Keypair::from_seed(seed);
+    let mut pkcs8 = PREFIX.to_vec();
+    pkcs8.extend_from_slice(seed);
+    pkcs8.extend_from_slice(&MIDDLE);
+    pkcs8.extend_from_slice(keypair.public_key_bytes());
+    pkcs8
+}
+
 //pub fn gen_keypair_file(outfile: String) -> Result<String, Box<dyn error::Error>> {
 pub fn gen_keypair_file(outfile: String) -> Result<String, Box<error::Error>> {
-    let serialized = serde_json::to_string(&gen_pkcs8()?)?;
+    write_pkcs8_file(&gen_pkcs8()?, outfile)
+}
+
+pub fn gen_keypair_file_from_seed(
+    seed: &[u8; 32],
+    outfile: String,
+) -> Result<String, Box<error::Error>> {
+    write_pkcs8_file(&gen_pkcs8_from_seed(seed), outfile)
+}
+
+fn write_pkcs8_file(pkcs8: &[u8], outfile: String) -> Result<String, Box<error::Error>> {
+    let serialized = serde_json::to_string(pkcs8)?;
 
     if outfile != "-" {
         if let Some(outdir) = Path::new(&outfile).parent() {
@@ -121,3 +225,96 @@ pub fn gen_keypair_file(outfile: String) -> Result<String, Box<error::Error>> {
     }
     Ok(serialized)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_fromstr() {
+        let signature = Keypair::new().sign_message(&[0u8]);
+        let mut signature_base58_str = bs58::encode(signature.0).into_string();
+        assert_eq!(signature_base58_str.parse::<Signature>(), Ok(signature));
+
+        signature_base58_str.push_str(&bs58::encode(signature.0).into_string());
+        assert_eq!(
+            signature_base58_str.parse::<Signature>(),
+            Err(ParseSignatureError::WrongSize)
+        );
+
+        signature_base58_str.truncate(signature_base58_str.len() / 2);
+        assert_eq!(signature_base58_str.parse::<Signature>(), Ok(signature));
+
+        signature_base58_str.truncate(signature_base58_str.len() / 2);
+        assert_eq!(
+            signature_base58_str.parse::<Signature>(),
+            Err(ParseSignatureError::WrongSize)
+        );
+
+        // throw some non-base58 stuff in there
+        let mut signature_base58_str = bs58::encode(signature.0).into_string();
+        signature_base58_str.replace_range(..1, "I");
+        assert_eq!(
+            signature_base58_str.parse::<Signature>(),
+            Err(ParseSignatureError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_keypair_from_seed() {
+        // RFC 8032, section 7.1, test 1
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(
+            &hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap(),
+        );
+        let keypair = Keypair::from_seed(&seed);
+        assert_eq!(
+            hex::encode(keypair.public_key_bytes()),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+        assert_eq!(Keypair::from_seed(&seed).pubkey(), keypair.pubkey());
+        assert_ne!(Keypair::from_seed(&[0u8; 32]).pubkey(), keypair.pubkey());
+    }
+
+    #[test]
+    fn test_bip39_seed() {
+        // BIP39 reference vector for the all-zero entropy
+        let words = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let seed = bip39_seed(words, "TREZOR");
+        assert_eq!(
+            hex::encode(&seed[..]),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+
+        let spaced = format!("  {}\n", words.replace(" ", "   "));
+        assert_eq!(&bip39_seed(&spaced, "TREZOR")[..], &seed[..]);
+    }
+
+    #[test]
+    fn test_keypair_from_seed_phrase() {
+        let words = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let keypair = keypair_from_seed_phrase(words, "TREZOR");
+        assert_eq!(
+            hex::encode(keypair.public_key_bytes()),
+            "51425909c1e61287d378cf7af24fed87fa767e19a3462f7a01c93f95d73c465b"
+        );
+        assert_eq!(
+            keypair_from_seed_phrase(words, "TREZOR").pubkey(),
+            keypair.pubkey()
+        );
+        assert_ne!(
+            keypair_from_seed_phrase(words, "").pubkey(),
+            keypair.pubkey()
+        );
+    }
+
+    #[test]
+    fn test_gen_keypair_file_from_seed() {
+        let seed = [7u8; 32];
+        let serialized = gen_keypair_file_from_seed(&seed, "-".to_string()).unwrap();
+        let pkcs8: Vec<u8> = serde_json::from_str(&serialized).unwrap();
+        let keypair = Ed25519KeyPair::from_pkcs8(Input::from(&pkcs8)).unwrap();
+        assert_eq!(keypair.pubkey(), Keypair::from_seed(&seed).pubkey());
+    }
+}
@@ -7,6 +7,7 @@ pub enum SystemError {
     AccountAlreadyInUse,
     ResultWithNegativeLamports,
     SourceNotSystemAccount,
+    InvalidAccountDataLength,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -15,7 +16,7 @@ pub enum SystemInstruction {
     /// * Transaction::keys[0] - source
     /// * Transaction::keys[1] - new account key
     /// * lamports - number of lamports to transfer to the new account
-    /// * space - memory to allocate if greater then zero
+    /// * space - memory to allocate if greater then zero, at most `MAX_PERMITTED_DATA_LENGTH`
     /// * program_id - the program id of the new account
     CreateAccount {
         lamports: u64,
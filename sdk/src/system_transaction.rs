@@ -1,11 +1,56 @@
 //! The `system_transaction` module provides functionality for creating system transactions.
 
 use crate::hash::Hash;
+use crate::packet::PACKET_DATA_SIZE;
 use crate::pubkey::Pubkey;
-use crate::signature::Keypair;
+use crate::signature::{Keypair, KeypairUtil, Signature};
 use crate::system_instruction::SystemInstruction;
 use crate::system_program;
 use crate::transaction::{CompiledInstruction, Transaction};
+use std::collections::HashSet;
+use std::{error, fmt};
+
+/// The most destinations a single `new_move_many` transaction may pay, so every account
+/// index fits in a u8
+pub const MAX_MOVE_MANY_DESTINATIONS: usize = 254;
+
+/// Reasons `SystemTransaction::try_new_move_many` refuses to build a transaction. Each
+/// variant carries the index of the offending entry in `moves`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SystemTransactionError {
+    /// The entry moves zero lamports
+    ZeroLamports(usize),
+    /// The entry pays a destination an earlier entry already pays
+    DuplicateDestination(usize),
+    /// Adding the entry's lamports to the running total overflows a u64
+    LamportsOverflow(usize),
+    /// The entry is past the `MAX_MOVE_MANY_DESTINATIONS` limit
+    TooManyDestinations(usize),
+    /// The moves are individually fine, but the signed transaction wouldn't fit in a packet
+    TooLarge,
+}
+
+impl fmt::Display for SystemTransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SystemTransactionError::ZeroLamports(i) => write!(f, "move {} is for zero lamports", i),
+            SystemTransactionError::DuplicateDestination(i) => {
+                write!(f, "move {} repeats an earlier destination", i)
+            }
+            SystemTransactionError::LamportsOverflow(i) => {
+                write!(f, "move {} overflows the total lamports", i)
+            }
+            SystemTransactionError::TooManyDestinations(i) => write!(
+                f,
+                "move {} exceeds the limit of {} destinations",
+                i, MAX_MOVE_MANY_DESTINATIONS
+            ),
+            SystemTransactionError::TooLarge => write!(f, "transaction is too large for a packet"),
+        }
+    }
+}
+
+impl error::Error for SystemTransactionError {}
 
 pub struct SystemTransaction {}
 
@@ -54,6 +99,37 @@ impl SystemTransaction {
             fee,
         )
     }
+    /// Create and sign a transaction to create a system account for each destination. If
+    /// any account can't be created, none are.
+    pub fn new_account_batch(
+        from: &Keypair,
+        destinations: &[(Pubkey, u64)],
+        recent_blockhash: Hash,
+        fee: u64,
+    ) -> Transaction {
+        let instructions: Vec<_> = destinations
+            .iter()
+            .enumerate()
+            .map(|(i, (_, lamports))| {
+                let create = SystemInstruction::CreateAccount {
+                    lamports: *lamports,
+                    space: 0,
+                    program_id: system_program::id(),
+                };
+                CompiledInstruction::new(0, &create, vec![0, i as u8 + 1])
+            })
+            .collect();
+        let to_keys: Vec<_> = destinations.iter().map(|(to_key, _)| *to_key).collect();
+
+        Transaction::new_with_compiled_instructions(
+            &[from],
+            &to_keys,
+            recent_blockhash,
+            fee,
+            vec![system_program::id()],
+            instructions,
+        )
+    }
     /// Create and sign new SystemInstruction::Assign transaction
     pub fn new_assign(
         from_keypair: &Keypair,
@@ -98,6 +174,18 @@ impl SystemTransaction {
         recent_blockhash: Hash,
         fee: u64,
     ) -> Transaction {
+        let (to_keys, instructions) = Self::move_many_instructions(moves);
+        Transaction::new_with_compiled_instructions(
+            &[from],
+            &to_keys,
+            recent_blockhash,
+            fee,
+            vec![system_program::id()],
+            instructions,
+        )
+    }
+
+    fn move_many_instructions(moves: &[(Pubkey, u64)]) -> (Vec<Pubkey>, Vec<CompiledInstruction>) {
         let instructions: Vec<_> = moves
             .iter()
             .enumerate()
@@ -107,22 +195,57 @@ impl SystemTransaction {
             })
             .collect();
         let to_keys: Vec<_> = moves.iter().map(|(to_key, _)| *to_key).collect();
+        (to_keys, instructions)
+    }
 
-        Transaction::new_with_compiled_instructions(
-            &[from],
-            &to_keys,
+    /// Like `new_move_many`, but first check that the moves would make a sensible
+    /// transaction: every amount is nonzero, no destination repeats, the total fits in a
+    /// u64, there are no more than `MAX_MOVE_MANY_DESTINATIONS` destinations and the signed
+    /// transaction fits in a packet.
+    pub fn try_new_move_many(
+        from: &Keypair,
+        moves: &[(Pubkey, u64)],
+        recent_blockhash: Hash,
+        fee: u64,
+    ) -> Result<Transaction, SystemTransactionError> {
+        let mut destinations = HashSet::new();
+        let mut total = fee;
+        for (i, (to, lamports)) in moves.iter().enumerate() {
+            if i >= MAX_MOVE_MANY_DESTINATIONS {
+                return Err(SystemTransactionError::TooManyDestinations(i));
+            }
+            if *lamports == 0 {
+                return Err(SystemTransactionError::ZeroLamports(i));
+            }
+            if !destinations.insert(to) {
+                return Err(SystemTransactionError::DuplicateDestination(i));
+            }
+            total = total
+                .checked_add(*lamports)
+                .ok_or(SystemTransactionError::LamportsOverflow(i))?;
+        }
+
+        let (to_keys, instructions) = Self::move_many_instructions(moves);
+        let mut account_keys = vec![from.pubkey()];
+        account_keys.extend(to_keys);
+        let unsigned = Transaction {
+            signatures: vec![Signature::default()],
+            account_keys,
             recent_blockhash,
             fee,
-            vec![system_program::id()],
+            program_ids: vec![system_program::id()],
             instructions,
-        )
+        };
+        if unsigned.serialized_size().unwrap() > PACKET_DATA_SIZE as u64 {
+            return Err(SystemTransactionError::TooLarge);
+        }
+        Ok(Self::new_move_many(from, moves, recent_blockhash, fee))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::signature::KeypairUtil;
 
     #[test]
     fn test_move_many() {
@@ -139,4 +262,89 @@ mod tests {
         assert_eq!(tx.instructions[0].accounts, vec![0, 1]);
         assert_eq!(tx.instructions[1].accounts, vec![0, 2]);
     }
+
+    #[test]
+    fn test_try_new_move_many_matches_new_move_many() {
+        let from = Keypair::new();
+        let moves = vec![(Keypair::new().pubkey(), 1), (Keypair::new().pubkey(), 2)];
+
+        let tx = SystemTransaction::try_new_move_many(&from, &moves, Hash::default(), 3).unwrap();
+        assert_eq!(
+            bincode::serialize(&tx).unwrap(),
+            bincode::serialize(&SystemTransaction::new_move_many(
+                &from,
+                &moves,
+                Hash::default(),
+                3
+            ))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_try_new_move_many_rejects() {
+        let from = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let try_new = |moves: &[(Pubkey, u64)], fee| {
+            SystemTransaction::try_new_move_many(&from, moves, Hash::default(), fee)
+        };
+
+        assert_eq!(
+            try_new(&[(to, 1), (Keypair::new().pubkey(), 0)], 0),
+            Err(SystemTransactionError::ZeroLamports(1))
+        );
+        assert_eq!(
+            try_new(&[(to, 1), (Keypair::new().pubkey(), 1), (to, 1)], 0),
+            Err(SystemTransactionError::DuplicateDestination(2))
+        );
+        assert_eq!(
+            try_new(&[(to, u64::max_value()), (Keypair::new().pubkey(), 1)], 0),
+            Err(SystemTransactionError::LamportsOverflow(1))
+        );
+        assert_eq!(
+            try_new(&[(to, u64::max_value())], 1),
+            Err(SystemTransactionError::LamportsOverflow(0))
+        );
+
+        let moves: Vec<_> = (0..=MAX_MOVE_MANY_DESTINATIONS)
+            .map(|_| (Keypair::new().pubkey(), 1))
+            .collect();
+        assert!(try_new(&moves[..4], 0).is_ok());
+        assert_eq!(
+            try_new(&moves[..MAX_MOVE_MANY_DESTINATIONS], 0),
+            Err(SystemTransactionError::TooLarge)
+        );
+        assert_eq!(
+            try_new(&moves, 0),
+            Err(SystemTransactionError::TooManyDestinations(
+                MAX_MOVE_MANY_DESTINATIONS
+            ))
+        );
+    }
+
+    #[test]
+    fn test_new_account_batch() {
+        let from = Keypair::new();
+        let t1 = Keypair::new();
+        let t2 = Keypair::new();
+        let destinations = vec![(t1.pubkey(), 1), (t2.pubkey(), 2)];
+
+        let tx = SystemTransaction::new_account_batch(&from, &destinations, Hash::default(), 0);
+        assert_eq!(
+            tx.account_keys,
+            vec![from.pubkey(), t1.pubkey(), t2.pubkey()]
+        );
+        assert_eq!(tx.instructions.len(), 2);
+        assert_eq!(tx.instructions[0].accounts, vec![0, 1]);
+        assert_eq!(tx.instructions[1].accounts, vec![0, 2]);
+        assert_eq!(
+            bincode::deserialize::<SystemInstruction>(&tx.instructions[1].data).unwrap(),
+            SystemInstruction::CreateAccount {
+                lamports: 2,
+                space: 0,
+                program_id: system_program::id(),
+            }
+        );
+        assert!(tx.verify_signature());
+    }
 }
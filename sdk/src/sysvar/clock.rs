@@ -0,0 +1,68 @@
+//! The `clock` sysvar holds the bank's slot, epoch and a timestamp derived from its ticks.
+
+use crate::account::Account;
+use crate::pubkey::Pubkey;
+use crate::sysvar;
+use bincode::{deserialize, serialize_into, serialized_size};
+
+const CLOCK_ID: [u8; 32] = [
+    135, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0,
+];
+
+pub fn id() -> Pubkey {
+    Pubkey::new(&CLOCK_ID)
+}
+
+pub fn check_id(pubkey: &Pubkey) -> bool {
+    pubkey.as_ref() == CLOCK_ID
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    pub slot: u64,
+    pub epoch: u64,
+    /// Seconds since genesis, estimated from the tick height
+    pub timestamp: u64,
+}
+
+impl Clock {
+    pub fn size_of() -> usize {
+        serialized_size(&Clock::default()).unwrap() as usize
+    }
+
+    /// Read the clock out of the clock sysvar account
+    pub fn from(account: &Account) -> Option<Self> {
+        deserialize(&account.data).ok()
+    }
+
+    /// Write the clock into the clock sysvar account
+    pub fn to(&self, account: &mut Account) -> Option<()> {
+        serialize_into(&mut account.data[..], self).ok()
+    }
+}
+
+/// Return a clock sysvar account holding `clock`
+pub fn create_account(lamports: u64, clock: &Clock) -> Account {
+    let mut account = Account::new(lamports, Clock::size_of(), &sysvar::id());
+    clock.to(&mut account).unwrap();
+    account
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_account() {
+        let clock = Clock {
+            slot: 3,
+            epoch: 1,
+            timestamp: 2,
+        };
+        let account = create_account(1, &clock);
+        assert_eq!(account.owner, sysvar::id());
+        assert_eq!(Clock::from(&account), Some(clock));
+        assert_eq!(Clock::from(&Account::new(1, 0, &sysvar::id())), None);
+    }
+}
@@ -0,0 +1,26 @@
+//! The `sysvar` module holds the well-known accounts the bank maintains so programs can
+//! read cluster state, such as the current slot, without trusting instruction arguments.
+
+use crate::pubkey::Pubkey;
+
+pub mod clock;
+pub mod slot_hashes;
+
+const SYSVAR_PROGRAM_ID: [u8; 32] = [
+    135, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0,
+];
+
+/// The owner of every sysvar account
+pub fn id() -> Pubkey {
+    Pubkey::new(&SYSVAR_PROGRAM_ID)
+}
+
+pub fn check_id(program_id: &Pubkey) -> bool {
+    program_id.as_ref() == SYSVAR_PROGRAM_ID
+}
+
+/// Return true if `pubkey` is one of the sysvar accounts
+pub fn is_sysvar_id(pubkey: &Pubkey) -> bool {
+    clock::check_id(pubkey) || slot_hashes::check_id(pubkey)
+}
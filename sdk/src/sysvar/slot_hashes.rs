@@ -0,0 +1,103 @@
+//! The `slot_hashes` sysvar holds the last blockhash registered by each recent slot,
+//! newest first.
+
+use crate::account::Account;
+use crate::hash::Hash;
+use crate::pubkey::Pubkey;
+use crate::sysvar;
+use crate::timing::MAX_RECENT_BLOCKHASHES;
+use bincode::{deserialize, serialize_into, serialized_size};
+
+const SLOT_HASHES_ID: [u8; 32] = [
+    135, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0,
+];
+
+/// The number of slots the sysvar remembers, matching the blockhash queue
+pub const MAX_SLOT_HASHES: usize = MAX_RECENT_BLOCKHASHES;
+
+pub fn id() -> Pubkey {
+    Pubkey::new(&SLOT_HASHES_ID)
+}
+
+pub fn check_id(pubkey: &Pubkey) -> bool {
+    pubkey.as_ref() == SLOT_HASHES_ID
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SlotHashes {
+    pub inner: Vec<(u64, Hash)>,
+}
+
+impl SlotHashes {
+    pub fn size_of() -> usize {
+        let full = SlotHashes {
+            inner: vec![(0, Hash::default()); MAX_SLOT_HASHES],
+        };
+        serialized_size(&full).unwrap() as usize
+    }
+
+    /// Record the last hash of `slot`, dropping the oldest entry once full
+    pub fn add(&mut self, slot: u64, hash: Hash) {
+        self.inner.insert(0, (slot, hash));
+        self.inner.truncate(MAX_SLOT_HASHES);
+    }
+
+    /// Return the hash recorded for `slot`, if it is still remembered
+    pub fn get(&self, slot: u64) -> Option<&Hash> {
+        self.inner
+            .iter()
+            .find(|(hash_slot, _)| *hash_slot == slot)
+            .map(|(_, hash)| hash)
+    }
+
+    /// Read the slot hashes out of the slot_hashes sysvar account
+    pub fn from(account: &Account) -> Option<Self> {
+        deserialize(&account.data).ok()
+    }
+
+    /// Write the slot hashes into the slot_hashes sysvar account
+    pub fn to(&self, account: &mut Account) -> Option<()> {
+        serialize_into(&mut account.data[..], self).ok()
+    }
+}
+
+/// Return an empty slot_hashes sysvar account
+pub fn create_account(lamports: u64) -> Account {
+    let mut account = Account::new(lamports, SlotHashes::size_of(), &sysvar::id());
+    SlotHashes::default().to(&mut account).unwrap();
+    account
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::hash;
+
+    #[test]
+    fn test_slot_hashes_add() {
+        let mut slot_hashes = SlotHashes::default();
+        for slot in 0..=MAX_SLOT_HASHES as u64 {
+            slot_hashes.add(slot, hash(&slot.to_le_bytes()));
+        }
+        assert_eq!(slot_hashes.inner.len(), MAX_SLOT_HASHES);
+        assert_eq!(slot_hashes.inner[0].0, MAX_SLOT_HASHES as u64);
+        assert_eq!(slot_hashes.get(0), None);
+        assert_eq!(slot_hashes.get(1), Some(&hash(&1u64.to_le_bytes())));
+    }
+
+    #[test]
+    fn test_slot_hashes_account() {
+        let mut account = create_account(1);
+        assert_eq!(account.owner, sysvar::id());
+        assert_eq!(SlotHashes::from(&account), Some(SlotHashes::default()));
+
+        // A full list still fits in the account
+        let mut slot_hashes = SlotHashes::default();
+        for slot in 0..MAX_SLOT_HASHES as u64 {
+            slot_hashes.add(slot, Hash::default());
+        }
+        slot_hashes.to(&mut account).unwrap();
+        assert_eq!(SlotHashes::from(&account), Some(slot_hashes));
+    }
+}
@@ -17,8 +17,12 @@ use std::fmt;
 use std::io::{Cursor, Read, Write};
 use std::mem::size_of;
 
+/// The most data a single instruction may carry. The bank rejects transactions with larger
+/// instructions, and `Transaction::try_new` refuses to build them.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 300;
+
 /// Reasons the runtime might have rejected an instruction.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum InstructionError {
     /// Deprecated! Use CustomError instead!
     /// The program instruction returned an error
@@ -78,6 +82,53 @@ impl InstructionError {
     }
 }
 
+impl fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstructionError::GenericError => write!(f, "generic program error"),
+            InstructionError::InvalidArgument => write!(f, "invalid program argument"),
+            InstructionError::InvalidInstructionData => write!(f, "invalid instruction data"),
+            InstructionError::InvalidAccountData => write!(f, "invalid account data"),
+            InstructionError::AccountDataTooSmall => write!(f, "account data too small"),
+            InstructionError::IncorrectProgramId => {
+                write!(f, "account not owned by the expected program")
+            }
+            InstructionError::MissingRequiredSignature => {
+                write!(f, "missing required signature")
+            }
+            InstructionError::AccountAlreadyInitialized => {
+                write!(f, "account already initialized")
+            }
+            InstructionError::UninitializedAccount => write!(f, "account not initialized"),
+            InstructionError::UnbalancedInstruction => {
+                write!(f, "sum of account balances changed")
+            }
+            InstructionError::ModifiedProgramId => write!(f, "program modified an account's owner"),
+            InstructionError::ExternalAccountLamportSpend => {
+                write!(f, "program spent lamports of an account it doesn't own")
+            }
+            InstructionError::ExternalAccountDataModified => {
+                write!(f, "program modified data of an account it doesn't own")
+            }
+            InstructionError::DuplicateAccountIndex => {
+                write!(f, "account referenced more than once")
+            }
+            InstructionError::CustomError(data) => {
+                // Render the error bytes as one hex number, e.g. [0, 0, 0, 5] is 0x5
+                let hex: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+                let hex = hex.trim_start_matches('0');
+                write!(
+                    f,
+                    "custom program error 0x{}",
+                    if hex.is_empty() { "0" } else { hex }
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InstructionError {}
+
 /// An instruction to execute a program
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct GenericInstruction<P, Q> {
@@ -142,8 +193,41 @@ impl CompiledInstruction {
     }
 }
 
+/// Why a transaction failed sanitization, as carried by `TransactionError::SanitizeFailure`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum SanitizeError {
+    /// An instruction references an account or program index past the end of the
+    /// transaction's lists
+    IndexOutOfBounds,
+
+    /// An instruction passes the same account more than once
+    DuplicateAccount,
+
+    /// The instruction at the given index carries more than `MAX_INSTRUCTION_DATA_LEN`
+    /// bytes of data
+    InstructionDataTooLarge(u8),
+}
+
+impl fmt::Display for SanitizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanitizeError::IndexOutOfBounds => {
+                write!(f, "an instruction references invalid account indexes")
+            }
+            SanitizeError::DuplicateAccount => {
+                write!(f, "an instruction passes the same account twice")
+            }
+            SanitizeError::InstructionDataTooLarge(index) => write!(
+                f,
+                "instruction {} carries more than {} bytes of data",
+                index, MAX_INSTRUCTION_DATA_LEN
+            ),
+        }
+    }
+}
+
 /// Reasons a transaction might be rejected.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum TransactionError {
     /// This Pubkey is being processed in another transaction
     AccountInUse,
@@ -175,8 +259,131 @@ pub enum TransactionError {
 
     /// Transaction has a fee but has no signature present
     MissingSignatureForFee,
+
+    /// The slot already holds the maximum number of transactions allowed by the
+    /// genesis block; the transaction may be retried in a later slot
+    WouldExceedBlockLimit,
+
+    /// The transaction creates a staking account with fewer lamports than the
+    /// genesis block's minimum delegation
+    DelegationBelowMinimum,
+
+    /// The transaction is malformed in a way execution can't tolerate; the detail says how
+    SanitizeFailure(SanitizeError),
+
+    /// The transaction declares a fee lower than the bank's fee schedule charges for it
+    InsufficientFee,
+
+    /// A withdrawal would leave the account with a nonzero balance below the genesis
+    /// block's minimum balance; it must either be closed or stay above the floor
+    WouldBecomeRentPaying,
+
+    /// An instruction's program id account exists but is not executable, typically
+    /// because the program was loaded but never finalized
+    InvalidProgramForExecution,
+
+    /// The transaction only moves lamports from an account to itself and pays no fee,
+    /// which the genesis block's self-transfer policy rejects
+    SelfTransferNotAllowed,
+
+    /// A signature doesn't verify against the account key it signs for
+    SignatureFailure,
+
+    /// The transaction would store an account with more than `MAX_PERMITTED_DATA_LENGTH`
+    /// bytes of data
+    InvalidAccountDataLength,
+
+    /// The account still holds lamports, so it can't be closed
+    AccountNotEmpty,
+}
+
+impl TransactionError {
+    /// Return true if resubmitting the same transaction may succeed. `BlockhashNotFound`
+    /// is only worth retrying once the transaction is re-signed with a fresh blockhash.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TransactionError::AccountInUse
+            | TransactionError::BlockhashNotFound
+            | TransactionError::WouldExceedBlockLimit => true,
+            TransactionError::AccountLoadedTwice
+            | TransactionError::AccountNotFound
+            | TransactionError::InsufficientFundsForFee
+            | TransactionError::DuplicateSignature
+            | TransactionError::InstructionError(_, _)
+            | TransactionError::CallChainTooDeep
+            | TransactionError::MissingSignatureForFee
+            | TransactionError::DelegationBelowMinimum
+            | TransactionError::SanitizeFailure(_)
+            | TransactionError::InsufficientFee
+            | TransactionError::WouldBecomeRentPaying
+            | TransactionError::InvalidProgramForExecution
+            | TransactionError::SelfTransferNotAllowed
+            | TransactionError::SignatureFailure
+            | TransactionError::InvalidAccountDataLength
+            | TransactionError::AccountNotEmpty => false,
+        }
+    }
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionError::AccountInUse => {
+                write!(f, "an account is in use by another transaction")
+            }
+            TransactionError::AccountLoadedTwice => {
+                write!(f, "an account appears more than once in the transaction")
+            }
+            TransactionError::AccountNotFound => write!(f, "the fee payer account was not found"),
+            TransactionError::InsufficientFundsForFee => {
+                write!(f, "insufficient funds to pay the transaction fee")
+            }
+            TransactionError::DuplicateSignature => {
+                write!(f, "the transaction has already been processed")
+            }
+            TransactionError::BlockhashNotFound => write!(
+                f,
+                "blockhash not found; re-sign the transaction with a recent blockhash"
+            ),
+            TransactionError::InstructionError(index, err) => {
+                write!(f, "instruction {} failed: {}", index, err)
+            }
+            TransactionError::CallChainTooDeep => write!(f, "loader call chain too deep"),
+            TransactionError::MissingSignatureForFee => {
+                write!(f, "the transaction has a fee but no signature")
+            }
+            TransactionError::WouldExceedBlockLimit => {
+                write!(f, "the slot is full; retry in a later slot")
+            }
+            TransactionError::DelegationBelowMinimum => {
+                write!(f, "staking account is below the minimum delegation")
+            }
+            TransactionError::SanitizeFailure(err) => write!(f, "{}", err),
+            TransactionError::InsufficientFee => {
+                write!(f, "the declared fee is below the fee schedule")
+            }
+            TransactionError::WouldBecomeRentPaying => {
+                write!(f, "the account would be left below the minimum balance")
+            }
+            TransactionError::InvalidProgramForExecution => {
+                write!(f, "the program is not finalized or deployed")
+            }
+            TransactionError::SelfTransferNotAllowed => {
+                write!(f, "transfers from an account to itself are not allowed")
+            }
+            TransactionError::SignatureFailure => write!(f, "a signature failed to verify"),
+            TransactionError::InvalidAccountDataLength => {
+                write!(f, "an account's data exceeds the maximum permitted length")
+            }
+            TransactionError::AccountNotEmpty => {
+                write!(f, "the account still holds lamports and can't be closed")
+            }
+        }
+    }
 }
 
+impl std::error::Error for TransactionError {}
+
 /// An atomic transaction
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Transaction {
@@ -201,6 +408,20 @@ impl Transaction {
         Script::new(instructions).compile()
     }
 
+    /// Like `new`, but fail with `SanitizeError::InstructionDataTooLarge` rather than build a
+    /// transaction the bank would reject for an instruction over `MAX_INSTRUCTION_DATA_LEN`.
+    pub fn try_new(instructions: Vec<Instruction>) -> Result<Self, TransactionError> {
+        if let Some(index) = instructions
+            .iter()
+            .position(|instruction| instruction.data.len() > MAX_INSTRUCTION_DATA_LEN)
+        {
+            return Err(TransactionError::SanitizeFailure(
+                SanitizeError::InstructionDataTooLarge(index as u8),
+            ));
+        }
+        Ok(Self::new(instructions))
+    }
+
     pub fn new_with_blockhash_and_fee<T: Serialize>(
         from_pubkey: &Pubkey,
         transaction_keys: &[Pubkey],
@@ -346,11 +567,15 @@ impl Transaction {
         self.sign_unchecked(keypairs, recent_blockhash);
     }
 
-    /// Verify only the transaction signature.
+    /// Verify only the transaction signatures, each against the account key it signs for.
     pub fn verify_signature(&self) -> bool {
-        self.signatures
-            .iter()
-            .all(|s| s.verify(&self.from().as_ref(), &self.message()))
+        let message = self.message();
+        self.signatures.len() <= self.account_keys.len()
+            && self
+                .signatures
+                .iter()
+                .zip(&self.account_keys)
+                .all(|(signature, pubkey)| signature.verify(pubkey.as_ref(), &message))
     }
 
     /// Verify that references in the instructions are valid
@@ -525,6 +750,217 @@ mod tests {
     use crate::signature::Keypair;
     use bincode::deserialize;
 
+    #[test]
+    fn test_transaction_error_is_retryable() {
+        assert!(TransactionError::AccountInUse.is_retryable());
+        assert!(TransactionError::BlockhashNotFound.is_retryable());
+        assert!(TransactionError::WouldExceedBlockLimit.is_retryable());
+
+        assert!(!TransactionError::AccountLoadedTwice.is_retryable());
+        assert!(!TransactionError::AccountNotFound.is_retryable());
+        assert!(!TransactionError::InsufficientFundsForFee.is_retryable());
+        assert!(!TransactionError::DuplicateSignature.is_retryable());
+        assert!(
+            !TransactionError::InstructionError(0, InstructionError::GenericError).is_retryable()
+        );
+        assert!(!TransactionError::CallChainTooDeep.is_retryable());
+        assert!(!TransactionError::MissingSignatureForFee.is_retryable());
+        assert!(!TransactionError::DelegationBelowMinimum.is_retryable());
+        assert!(!TransactionError::SanitizeFailure(SanitizeError::DuplicateAccount).is_retryable());
+        assert!(!TransactionError::InsufficientFee.is_retryable());
+        assert!(!TransactionError::WouldBecomeRentPaying.is_retryable());
+        assert!(!TransactionError::SelfTransferNotAllowed.is_retryable());
+        assert!(!TransactionError::SignatureFailure.is_retryable());
+        assert!(!TransactionError::InvalidAccountDataLength.is_retryable());
+        assert!(!TransactionError::AccountNotEmpty.is_retryable());
+    }
+
+    #[test]
+    fn test_instruction_error_display() {
+        let messages = vec![
+            (InstructionError::GenericError, "generic program error"),
+            (
+                InstructionError::InvalidArgument,
+                "invalid program argument",
+            ),
+            (
+                InstructionError::InvalidInstructionData,
+                "invalid instruction data",
+            ),
+            (InstructionError::InvalidAccountData, "invalid account data"),
+            (
+                InstructionError::AccountDataTooSmall,
+                "account data too small",
+            ),
+            (
+                InstructionError::IncorrectProgramId,
+                "account not owned by the expected program",
+            ),
+            (
+                InstructionError::MissingRequiredSignature,
+                "missing required signature",
+            ),
+            (
+                InstructionError::AccountAlreadyInitialized,
+                "account already initialized",
+            ),
+            (
+                InstructionError::UninitializedAccount,
+                "account not initialized",
+            ),
+            (
+                InstructionError::UnbalancedInstruction,
+                "sum of account balances changed",
+            ),
+            (
+                InstructionError::ModifiedProgramId,
+                "program modified an account's owner",
+            ),
+            (
+                InstructionError::ExternalAccountLamportSpend,
+                "program spent lamports of an account it doesn't own",
+            ),
+            (
+                InstructionError::ExternalAccountDataModified,
+                "program modified data of an account it doesn't own",
+            ),
+            (
+                InstructionError::DuplicateAccountIndex,
+                "account referenced more than once",
+            ),
+            (
+                InstructionError::CustomError(vec![0, 0, 0, 5]),
+                "custom program error 0x5",
+            ),
+            (
+                InstructionError::CustomError(vec![1, 0]),
+                "custom program error 0x100",
+            ),
+            (
+                InstructionError::CustomError(vec![]),
+                "custom program error 0x0",
+            ),
+        ];
+        for (err, message) in messages {
+            assert_eq!(err.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_transaction_error_display() {
+        let messages = vec![
+            (
+                TransactionError::AccountInUse,
+                "an account is in use by another transaction",
+            ),
+            (
+                TransactionError::AccountLoadedTwice,
+                "an account appears more than once in the transaction",
+            ),
+            (
+                TransactionError::AccountNotFound,
+                "the fee payer account was not found",
+            ),
+            (
+                TransactionError::InsufficientFundsForFee,
+                "insufficient funds to pay the transaction fee",
+            ),
+            (
+                TransactionError::DuplicateSignature,
+                "the transaction has already been processed",
+            ),
+            (
+                TransactionError::BlockhashNotFound,
+                "blockhash not found; re-sign the transaction with a recent blockhash",
+            ),
+            (
+                TransactionError::InstructionError(
+                    1,
+                    InstructionError::CustomError(vec![0, 0, 0, 5]),
+                ),
+                "instruction 1 failed: custom program error 0x5",
+            ),
+            (
+                TransactionError::CallChainTooDeep,
+                "loader call chain too deep",
+            ),
+            (
+                TransactionError::MissingSignatureForFee,
+                "the transaction has a fee but no signature",
+            ),
+            (
+                TransactionError::WouldExceedBlockLimit,
+                "the slot is full; retry in a later slot",
+            ),
+            (
+                TransactionError::DelegationBelowMinimum,
+                "staking account is below the minimum delegation",
+            ),
+            (
+                TransactionError::SanitizeFailure(SanitizeError::IndexOutOfBounds),
+                "an instruction references invalid account indexes",
+            ),
+            (
+                TransactionError::SanitizeFailure(SanitizeError::DuplicateAccount),
+                "an instruction passes the same account twice",
+            ),
+            (
+                TransactionError::SanitizeFailure(SanitizeError::InstructionDataTooLarge(2)),
+                "instruction 2 carries more than 300 bytes of data",
+            ),
+            (
+                TransactionError::InsufficientFee,
+                "the declared fee is below the fee schedule",
+            ),
+            (
+                TransactionError::WouldBecomeRentPaying,
+                "the account would be left below the minimum balance",
+            ),
+            (
+                TransactionError::SelfTransferNotAllowed,
+                "transfers from an account to itself are not allowed",
+            ),
+            (
+                TransactionError::SignatureFailure,
+                "a signature failed to verify",
+            ),
+            (
+                TransactionError::InvalidAccountDataLength,
+                "an account's data exceeds the maximum permitted length",
+            ),
+            (
+                TransactionError::AccountNotEmpty,
+                "the account still holds lamports and can't be closed",
+            ),
+        ];
+        for (err, message) in messages {
+            assert_eq!(err.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_transaction_try_new_instruction_data_len() {
+        let program_id = Keypair::new().pubkey();
+        let from = Keypair::new().pubkey();
+        let new_instruction = |len| {
+            let mut instruction = Instruction::new(program_id, &0u8, vec![(from, true)]);
+            instruction.data = vec![0; len];
+            instruction
+        };
+
+        let tx = Transaction::try_new(vec![new_instruction(MAX_INSTRUCTION_DATA_LEN)]).unwrap();
+        assert_eq!(tx.data(0).len(), MAX_INSTRUCTION_DATA_LEN);
+        assert_eq!(
+            Transaction::try_new(vec![
+                new_instruction(0),
+                new_instruction(MAX_INSTRUCTION_DATA_LEN + 1)
+            ]),
+            Err(TransactionError::SanitizeFailure(
+                SanitizeError::InstructionDataTooLarge(1)
+            ))
+        );
+    }
+
     #[test]
     fn test_refs() {
         let key = Keypair::new();
@@ -732,4 +1168,26 @@ mod tests {
         tx.sign(&[&keypair0], Hash::default());
         assert_eq!(tx.instructions[0], CompiledInstruction::new(0, &0, vec![0]));
     }
+
+    #[test]
+    fn test_transaction_verify_signature() {
+        let program_id = Pubkey::default();
+        let keypair0 = Keypair::new();
+        let keypair1 = Keypair::new();
+        let mut tx = Transaction::new(vec![Instruction::new(
+            program_id,
+            &0,
+            vec![(keypair0.pubkey(), true), (keypair1.pubkey(), true)],
+        )]);
+        tx.sign(&[&keypair0, &keypair1], Hash::default());
+        assert!(tx.verify_signature());
+
+        // Each signature must verify against its own key
+        tx.signatures.swap(0, 1);
+        assert!(!tx.verify_signature());
+        tx.signatures.swap(0, 1);
+
+        tx.fee = 1;
+        assert!(!tx.verify_signature());
+    }
 }
@@ -1 +1,3 @@
+#[cfg(any(test, feature = "local-rpc"))]
+pub mod local_rpc_client_request;
 pub mod wallet;
@@ -0,0 +1,310 @@
+//! The `local_rpc_client_request` module answers RPC requests from an in-process bank, so
+//! wallet commands can run end-to-end without a fullnode, drone or RPC server. Transactions
+//! are processed as soon as they're sent, and airdrops are paid from a local mint.
+
+use bincode::deserialize;
+use serde_json::{json, Value};
+use solana_client::client_error::{self, ClientError};
+use solana_client::generic_rpc_client_request::GenericRpcClientRequest;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_signature_status::RpcSignatureStatus;
+use solana_runtime::bank::{AccountFilter, Bank};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// JSON-RPC error code of a request whose parameters couldn't be parsed
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code of a request the node can't answer
+const INVALID_REQUEST: i64 = -32600;
+
+pub struct LocalRpcClientRequest {
+    bank: Arc<Bank>,
+    mint_keypair: Arc<Keypair>,
+}
+
+impl LocalRpcClientRequest {
+    /// Answer requests from `bank`, paying airdrops from `mint_keypair`
+    pub fn new(bank: Arc<Bank>, mint_keypair: Arc<Keypair>) -> Self {
+        Self { bank, mint_keypair }
+    }
+}
+
+/// Return an RpcClient whose requests are answered by `bank`, paying airdrops from
+/// `mint_keypair`
+pub fn new_local_rpc_client(bank: Arc<Bank>, mint_keypair: Arc<Keypair>) -> RpcClient {
+    RpcClient::new_with_client_request(Box::new(LocalRpcClientRequest::new(bank, mint_keypair)))
+}
+
+fn invalid_request(message: &str) -> ClientError {
+    ClientError::RpcError {
+        code: INVALID_REQUEST,
+        message: message.to_string(),
+    }
+}
+
+fn invalid_params(index: usize) -> ClientError {
+    ClientError::RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: expected a value at {}", index),
+    }
+}
+
+/// Return the `index`th parameter of a request
+fn param(params: &Option<Value>, index: usize) -> client_error::Result<&Value> {
+    params
+        .as_ref()
+        .and_then(|params| params.get(index))
+        .ok_or_else(|| invalid_params(index))
+}
+
+fn u64_param(params: &Option<Value>, index: usize) -> client_error::Result<u64> {
+    param(params, index)?
+        .as_u64()
+        .ok_or_else(|| invalid_params(index))
+}
+
+fn str_param(params: &Option<Value>, index: usize) -> client_error::Result<&str> {
+    param(params, index)?
+        .as_str()
+        .ok_or_else(|| invalid_params(index))
+}
+
+fn pubkey_param(params: &Option<Value>, index: usize) -> client_error::Result<Pubkey> {
+    Pubkey::from_str(str_param(params, index)?).map_err(|_| invalid_request("Invalid pubkey"))
+}
+
+fn signature_param(params: &Option<Value>, index: usize) -> client_error::Result<Signature> {
+    Signature::from_str(str_param(params, index)?).map_err(|_| invalid_request("Invalid signature"))
+}
+
+/// Map a transaction's result to the status a fullnode reports for it
+fn signature_status(status: Option<Result<(), TransactionError>>) -> RpcSignatureStatus {
+    match status {
+        None => RpcSignatureStatus::SignatureNotFound,
+        Some(Ok(())) => RpcSignatureStatus::Confirmed,
+        Some(Err(TransactionError::AccountInUse)) => RpcSignatureStatus::AccountInUse,
+        Some(Err(TransactionError::AccountLoadedTwice)) => RpcSignatureStatus::AccountLoadedTwice,
+        Some(Err(TransactionError::InstructionError(_, _))) => {
+            RpcSignatureStatus::ProgramRuntimeError
+        }
+        Some(Err(TransactionError::InvalidProgramForExecution)) => {
+            RpcSignatureStatus::ProgramNotExecutable
+        }
+        Some(Err(_)) => RpcSignatureStatus::GenericFailure,
+    }
+}
+
+impl LocalRpcClientRequest {
+    /// Process `transaction` and return its signature. A transaction the bank rejects
+    /// without recording a status fails here, since it would never be confirmed.
+    fn process_transaction(&self, transaction: &Transaction) -> client_error::Result<Value> {
+        let signature = *transaction
+            .signatures
+            .get(0)
+            .ok_or_else(|| invalid_request("Transaction is unsigned"))?;
+        if let Err(err) = self.bank.process_transaction(transaction) {
+            if self.bank.get_signature_status(&signature).is_none() {
+                return Err(ClientError::TransactionError(err));
+            }
+        }
+        Ok(json!(signature.to_string()))
+    }
+
+    fn keyed_lamports(lamports: impl IntoIterator<Item = (Pubkey, u64)>) -> Value {
+        let lamports: Vec<_> = lamports
+            .into_iter()
+            .map(|(pubkey, lamports)| (pubkey.to_string(), lamports))
+            .collect();
+        json!(lamports)
+    }
+
+    fn keyed_accounts(accounts: impl IntoIterator<Item = (Pubkey, Account)>) -> Value {
+        let accounts: Vec<_> = accounts
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey.to_string(), account))
+            .collect();
+        json!(accounts)
+    }
+}
+
+impl GenericRpcClientRequest for LocalRpcClientRequest {
+    fn send(
+        &self,
+        request: &RpcRequest,
+        params: Option<Value>,
+        _retries: usize,
+    ) -> client_error::Result<Value> {
+        let bank = &self.bank;
+        let value = match request {
+            RpcRequest::ConfirmTransaction => {
+                let signature = signature_param(&params, 0)?;
+                json!(
+                    signature_status(bank.get_signature_status(&signature))
+                        == RpcSignatureStatus::Confirmed
+                )
+            }
+            RpcRequest::GetAccountInfo => {
+                let pubkey = pubkey_param(&params, 0)?;
+                let account = bank
+                    .get_account(&pubkey)
+                    .ok_or_else(|| invalid_request("Account not found"))?;
+                json!(account)
+            }
+            RpcRequest::GetBalance => json!(bank.get_balance(&pubkey_param(&params, 0)?)),
+            RpcRequest::GetEpochVoteAccounts => {
+                let epoch = u64_param(&params, 0)?;
+                let accounts = bank
+                    .epoch_vote_accounts(epoch)
+                    .ok_or_else(|| invalid_request("Epoch stakes not snapshotted"))?;
+                Self::keyed_accounts(
+                    accounts
+                        .iter()
+                        .map(|(pubkey, account)| (*pubkey, account.clone())),
+                )
+            }
+            RpcRequest::GetFeeCalculator => json!(bank.fee_calculator()),
+            RpcRequest::GetGenesisHash => json!(bs58::encode(bank.genesis_hash()).into_string()),
+            RpcRequest::GetLargestAccounts => {
+                let n = u64_param(&params, 0)? as usize;
+                let excluded_owners = match params.as_ref().and_then(|params| params.get(1)) {
+                    Some(owners) => owners
+                        .as_array()
+                        .ok_or_else(|| invalid_params(1))?
+                        .iter()
+                        .map(|owner| {
+                            owner
+                                .as_str()
+                                .and_then(|owner| Pubkey::from_str(owner).ok())
+                                .ok_or_else(|| invalid_request("Invalid pubkey"))
+                        })
+                        .collect::<client_error::Result<Vec<_>>>()?,
+                    None => vec![],
+                };
+                Self::keyed_lamports(
+                    bank.largest_accounts(n, AccountFilter::excluding(&excluded_owners)),
+                )
+            }
+            RpcRequest::GetMinimumDelegation => json!(bank.minimum_delegation()),
+            RpcRequest::GetProgramAccounts => {
+                Self::keyed_accounts(bank.get_program_accounts(&pubkey_param(&params, 0)?))
+            }
+            RpcRequest::GetRecentBlockhash => {
+                json!(bs58::encode(bank.last_blockhash()).into_string())
+            }
+            RpcRequest::GetSignatureStatus => {
+                let signature = signature_param(&params, 0)?;
+                json!(signature_status(bank.get_signature_status(&signature)))
+            }
+            RpcRequest::GetSignatureStatuses => {
+                let signatures = param(&params, 0)?
+                    .as_array()
+                    .ok_or_else(|| invalid_params(0))?
+                    .iter()
+                    .map(|signature| {
+                        signature
+                            .as_str()
+                            .and_then(|signature| Signature::from_str(signature).ok())
+                            .ok_or_else(|| invalid_request("Invalid signature"))
+                    })
+                    .collect::<client_error::Result<Vec<_>>>()?;
+                json!(bank.get_signature_statuses(&signatures))
+            }
+            RpcRequest::GetSignatureConfirmationCount => {
+                let signature = signature_param(&params, 0)?;
+                json!(bank
+                    .get_signature_status_with_slot(&signature)
+                    .map(|(slot, _)| bank.slot() - slot))
+            }
+            RpcRequest::GetSlot => json!(bank.slot()),
+            RpcRequest::GetSupply => {
+                let mut supply: Vec<_> = bank.supply_breakdown().into_iter().collect();
+                supply.sort_by(|(a_owner, a_lamports), (b_owner, b_lamports)| {
+                    b_lamports.cmp(a_lamports).then(a_owner.cmp(b_owner))
+                });
+                Self::keyed_lamports(supply)
+            }
+            RpcRequest::GetTransactionCount => json!(bank.transaction_count()),
+            RpcRequest::GetTransactionCountAndSlot => {
+                json!((bank.transaction_count(), bank.slot()))
+            }
+            RpcRequest::RequestAirdrop => {
+                let pubkey = pubkey_param(&params, 0)?;
+                let lamports = u64_param(&params, 1)?;
+                let signature = bank
+                    .transfer(lamports, &self.mint_keypair, &pubkey, bank.last_blockhash())
+                    .map_err(ClientError::TransactionError)?;
+                json!(signature.to_string())
+            }
+            RpcRequest::SendTransaction => {
+                let data: Vec<u8> = serde_json::from_value(param(&params, 0)?.clone())
+                    .map_err(|_| invalid_params(0))?;
+                let transaction: Transaction =
+                    deserialize(&data).map_err(|_| invalid_request("Invalid transaction"))?;
+                self.process_transaction(&transaction)?
+            }
+            request => Err(invalid_request(&format!(
+                "{} isn't supported by a local bank",
+                request.method()
+            )))?,
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::genesis_block::GenesisBlock;
+    use solana_sdk::signature::KeypairUtil;
+    use solana_sdk::system_transaction::SystemTransaction;
+
+    #[test]
+    fn test_local_rpc_client() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let bank = Arc::new(Bank::new(&genesis_block));
+        let rpc_client = new_local_rpc_client(bank.clone(), Arc::new(mint_keypair));
+
+        let alice = Keypair::new();
+        let bob = Keypair::new().pubkey();
+        let signature = rpc_client.request_airdrop(&alice.pubkey(), 50).unwrap();
+        assert_eq!(
+            rpc_client.get_signature_status(&signature).unwrap(),
+            RpcSignatureStatus::Confirmed
+        );
+        assert_eq!(
+            rpc_client.retry_get_balance(&alice.pubkey(), 0).unwrap(),
+            Some(50)
+        );
+
+        let blockhash = rpc_client.get_recent_blockhash().unwrap();
+        assert_eq!(blockhash, bank.last_blockhash());
+        let mut tx = SystemTransaction::new_move(&alice, &bob, 20, blockhash, 0);
+        rpc_client
+            .send_and_confirm_transaction(&mut tx, &alice)
+            .unwrap();
+        assert_eq!(bank.get_balance(&bob), 20);
+        assert_eq!(rpc_client.get_account(&bob).unwrap().lamports, 20);
+
+        // A transaction the bank can't charge a fee for is never recorded, so sending fails
+        let carol = Keypair::new();
+        let tx = SystemTransaction::new_move(&carol, &bob, 1, blockhash, 0);
+        assert!(rpc_client.send_transaction(&tx).is_err());
+
+        let largest = rpc_client
+            .get_largest_accounts(2, &[solana_vote_api::id()])
+            .unwrap();
+        assert_eq!(largest[1], (alice.pubkey(), 30));
+        let supply = rpc_client.get_supply().unwrap();
+        assert_eq!(
+            supply.iter().map(|(_, lamports)| lamports).sum::<u64>(),
+            bank.capitalization()
+        );
+
+        assert!(rpc_client.fullnode_exit().is_err());
+    }
+}
@@ -2,9 +2,18 @@ use clap::{
     crate_description, crate_name, crate_version, App, AppSettings, Arg, ArgGroup, ArgMatches,
     SubCommand,
 };
-use solana_sdk::signature::{gen_keypair_file, read_keypair, KeypairUtil};
-use solana_wallet::wallet::{parse_command, process_command, WalletConfig, WalletError};
-use std::error;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{
+    gen_keypair_file, keypair_from_seed_phrase, read_keypair, Keypair, KeypairUtil,
+};
+use solana_wallet::wallet::{
+    parse_command, parse_confirm_mode, process_command, WalletConfig, WalletError,
+};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::exit;
+use std::{env, error, mem};
 
 pub fn parse_args(matches: &ArgMatches<'_>) -> Result<WalletConfig, Box<dyn error::Error>> {
     let host = matches
@@ -45,49 +54,106 @@ pub fn parse_args(matches: &ArgMatches<'_>) -> Result<WalletConfig, Box<dyn erro
         .parse()
         .or_else(|_| Err(WalletError::BadParameter("Invalid rpc port".to_string())))?;
 
-    let mut path = dirs::home_dir().expect("home directory");
-    let id_path = if matches.is_present("keypair") {
-        matches.value_of("keypair").unwrap()
+    let expected_genesis_hash =
+        if let Some(expected_genesis_hash) = matches.value_of("expected_genesis_hash") {
+            let genesis_hash = bs58::decode(expected_genesis_hash)
+                .into_vec()
+                .ok()
+                .filter(|genesis_hash| genesis_hash.len() == mem::size_of::<Hash>())
+                .ok_or_else(|| {
+                    WalletError::BadParameter("Invalid expected genesis hash".to_string())
+                })?;
+            Some(Hash::new(&genesis_hash))
+        } else {
+            None
+        };
+
+    let max_slots_behind = matches
+        .value_of("max_slots_behind")
+        .unwrap()
+        .parse()
+        .or_else(|_| {
+            Err(WalletError::BadParameter(
+                "Invalid max slots behind".to_string(),
+            ))
+        })?;
+
+    let id = if matches.is_present("seed_phrase") {
+        prompt_seed_phrase()?
     } else {
-        path.extend(&[".config", "solana", "id.json"]);
-        if !path.exists() {
-            gen_keypair_file(path.to_str().unwrap().to_string())?;
-            println!("New keypair generated at: {:?}", path.to_str().unwrap());
-        }
+        let mut path = dirs::home_dir().expect("home directory");
+        let id_path = if matches.is_present("keypair") {
+            matches.value_of("keypair").unwrap()
+        } else {
+            path.extend(&[".config", "solana", "id.json"]);
+            if !path.exists() {
+                gen_keypair_file(path.to_str().unwrap().to_string())?;
+                println!("New keypair generated at: {:?}", path.to_str().unwrap());
+            }
 
-        path.to_str().unwrap()
+            path.to_str().unwrap()
+        };
+        read_keypair(id_path).or_else(|err| {
+            Err(WalletError::BadParameter(format!(
+                "{}: Unable to open keypair file: {}",
+                err, id_path
+            )))
+        })?
     };
-    let id = read_keypair(id_path).or_else(|err| {
-        Err(WalletError::BadParameter(format!(
-            "{}: Unable to open keypair file: {}",
-            err, id_path
-        )))
-    })?;
+
+    let confirm = parse_confirm_mode(matches);
 
     let command = parse_command(&id.pubkey(), &matches)?;
 
     Ok(WalletConfig {
         id,
+        audit_log: matches.value_of("audit_log").map(PathBuf::from),
         command,
+        confirm,
         drone_host,
         drone_port,
+        expected_genesis_hash,
         host,
+        max_slots_behind,
+        reference_rpc_client: matches
+            .value_of("reference_rpc")
+            .map(|url| RpcClient::new(url.to_string())),
         rpc_client: None,
         rpc_host,
         rpc_port,
         rpc_tls: matches.is_present("rpc_tls"),
+        strict_health: matches.is_present("strict_health"),
+        verbose: matches.is_present("verbose"),
     })
 }
 
-fn main() -> Result<(), Box<dyn error::Error>> {
-    solana_logger::setup();
+/// Ask on the terminal for a seed phrase and passphrase and recover the identity from them
+fn prompt_seed_phrase() -> Result<Keypair, Box<dyn error::Error>> {
+    let mut words = String::new();
+    print!("Seed phrase: ");
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut words)?;
+    if words.trim().is_empty() {
+        Err(WalletError::BadParameter("Empty seed phrase".to_string()))?;
+    }
+
+    let mut passphrase = String::new();
+    print!("Passphrase (empty for none): ");
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut passphrase)?;
+    let passphrase = passphrase.trim_end_matches(|c| c == '\n' || c == '\r');
+
+    Ok(keypair_from_seed_phrase(&words, passphrase))
+}
 
-    let (default_host, default_rpc_port, default_drone_port) = {
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let (default_host, default_rpc_port, default_drone_port, default_max_slots_behind) = {
         let defaults = WalletConfig::default();
         (
             defaults.host.to_string(),
             defaults.rpc_port.to_string(),
             defaults.drone_port.to_string(),
+            defaults.max_slots_behind.to_string(),
         )
     };
 
@@ -147,6 +213,63 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .takes_value(true)
                 .help("/path/to/id.json"),
         )
+        .arg(
+            Arg::with_name("seed_phrase")
+                .long("seed-phrase")
+                .conflicts_with("keypair")
+                .help("Prompt for a seed phrase and use the keypair it derives as the identity"),
+        )
+        .arg(
+            Arg::with_name("expected_genesis_hash")
+                .long("expected-genesis-hash")
+                .value_name("HASH")
+                .takes_value(true)
+                .help("Refuse to submit transactions unless the cluster's genesis hash matches"),
+        )
+        .arg(
+            Arg::with_name("reference_rpc")
+                .long("reference-rpc")
+                .value_name("URL")
+                .takes_value(true)
+                .help("Warn before submitting transactions if the RPC node trails this node"),
+        )
+        .arg(
+            Arg::with_name("max_slots_behind")
+                .long("max-slots-behind")
+                .value_name("NUM")
+                .takes_value(true)
+                .default_value(&default_max_slots_behind)
+                .help("How many slots the RPC node may trail the reference node"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .global(true)
+                .help("Log every RPC request and response, and each transaction before it's sent, to stderr"),
+        )
+        .arg(
+            Arg::with_name("strict_health")
+                .long("strict-health")
+                .help("Refuse to submit transactions unless the RPC node is caught up"),
+        )
+        .arg(
+            Arg::with_name("confirm")
+                .long("confirm")
+                .value_name("WHEN")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["auto", "always"])
+                .help("Show a summary and ask before submitting; without a value, only when stdin is a terminal"),
+        )
+        .arg(
+            Arg::with_name("audit_log")
+                .long("audit-log")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Append the message hash and signatures of every transaction signed to this file"),
+        )
         .subcommand(SubCommand::with_name("address").about("Get your public key"))
         .subcommand(
             SubCommand::with_name("airdrop")
@@ -160,7 +283,18 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .help("The number of lamports to request"),
                 ),
         )
-        .subcommand(SubCommand::with_name("balance").about("Get your balance"))
+        .subcommand(
+            SubCommand::with_name("balance")
+                .about("Get your balance")
+                .arg(
+                    Arg::with_name("commitment")
+                        .long("commitment")
+                        .value_name("COMMITMENT")
+                        .takes_value(true)
+                        .possible_values(&["root", "frozen", "recent"])
+                        .help("Read from the root bank, the newest frozen bank, or the working bank [default: recent]"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("cancel")
                 .about("Cancel a transfer")
@@ -181,8 +315,30 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .index(1)
                         .value_name("SIGNATURE")
                         .takes_value(true)
+                        .multiple(true)
                         .required(true)
-                        .help("The transaction signature to confirm"),
+                        .help("The transaction signatures to confirm"),
+                )
+                .arg(
+                    Arg::with_name("follow")
+                        .long("follow")
+                        .help("Keep reporting the transaction's confirmation depth as it grows"),
+                )
+                .arg(
+                    Arg::with_name("target_depth")
+                        .long("target-depth")
+                        .value_name("NUM")
+                        .takes_value(true)
+                        .default_value("32")
+                        .help("With --follow, stop once the transaction is this many slots deep"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("60")
+                        .help("With --follow, give up after this many seconds"),
                 ),
         )
         .subcommand(
@@ -242,7 +398,47 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 ), // TODO: Add "loader" argument; current default is bpf_loader
         )
         .subcommand(
-            SubCommand::with_name("get-transaction-count").about("Get current transaction count"),
+            SubCommand::with_name("drain")
+                .about("Send the entire balance, less the fee, to another address")
+                .arg(
+                    Arg::with_name("to")
+                        .index(1)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The pubkey of recipient"),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .long("yes")
+                        .help("Skip the confirmation prompt"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("get-genesis-hash")
+                .about("Get the hash of the cluster's genesis block"),
+        )
+        .subcommand(
+            SubCommand::with_name("get-transaction-count")
+                .about("Get current transaction count")
+                .arg(
+                    Arg::with_name("sample")
+                        .long("sample")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .help("Also report transactions and slots per second over a window of this many seconds"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("monitor")
+                .about("Print transactions involving an account as they're confirmed")
+                .arg(
+                    Arg::with_name("pubkey")
+                        .index(1)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .help("The account to monitor [default: your public key]"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("pay")
@@ -291,6 +487,115 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     Arg::with_name("cancelable")
                         .long("cancelable")
                         .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("auto_cancel_after")
+                        .long("auto-cancel-after")
+                        .value_name("SECS")
+                        .takes_value(true)
+                        .requires("cancelable")
+                        .help(
+                            "Cancel the contract if it hasn't been claimed after this many seconds",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("memo")
+                        .long("memo")
+                        .value_name("TEXT")
+                        .takes_value(true)
+                        .help("Attach a note of up to 128 bytes to the payment"),
+                )
+                .arg(
+                    Arg::with_name("allow_unfunded_recipient")
+                        .long("allow-unfunded-recipient")
+                        .help("Pay even if the recipient has no account yet"),
+                )
+                .arg(
+                    Arg::with_name("verify")
+                        .long("verify")
+                        .conflicts_with_all(&["timestamp", "witness", "every"])
+                        .help("Report the recipient's balance change once the payment confirms"),
+                )
+                .arg(
+                    Arg::with_name("export_unsigned")
+                        .long("export-unsigned")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with_all(&["timestamp", "witness", "every", "verify"])
+                        .help("Save the unsigned payment to PATH for signing rather than send it"),
+                )
+                .arg(
+                    Arg::with_name("every")
+                        .long("every")
+                        .value_name("DURATION")
+                        .takes_value(true)
+                        .requires_all(&["timestamp", "count"])
+                        .conflicts_with_all(&["witness", "cancelable", "memo"])
+                        .help(
+                            "Pay in installments this far apart, the first at --after; \
+                             e.g. 30d, 12h, 15m or 90s",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .value_name("NUM")
+                        .takes_value(true)
+                        .requires("every")
+                        .help("The number of installments"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("redirect")
+                .about("Cancel a transfer, sending the refund to another address")
+                .arg(
+                    Arg::with_name("process_id")
+                        .index(1)
+                        .value_name("PROCESS_ID")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The process id of the transfer to redirect"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .index(2)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The pubkey of the new recipient"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("release-due")
+                .about("Send the current time to a scheduled payment, releasing every installment that's due")
+                .arg(
+                    Arg::with_name("process_id")
+                        .index(1)
+                        .value_name("PROCESS_ID")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The process id of the scheduled payment"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("replay-ledger")
+                .about("Replay serialized transactions against a local bank")
+                .setting(AppSettings::Hidden)
+                .arg(
+                    Arg::with_name("ledger")
+                        .index(1)
+                        .value_name("LEDGER_DIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Directory containing the genesis block to start from"),
+                )
+                .arg(
+                    Arg::with_name("transactions")
+                        .index(2)
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("File of bincode-serialized transactions to process in order"),
                 ),
         )
         .subcommand(
@@ -311,6 +616,12 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .takes_value(true)
                         .required(true)
                         .help("The process id of the transfer to authorize"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .takes_value(false)
+                        .help("Send the signature without checking that the contract expects it"),
                 ),
         )
         .subcommand(
@@ -338,12 +649,134 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .value_name("DATETIME")
                         .takes_value(true)
                         .help("Optional arbitrary timestamp to apply"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .takes_value(false)
+                        .help("Send the timestamp without checking that the contract expects it"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("setup-validator")
+                .about("Create a validator's identity and a vote account delegated to it")
+                .arg(
+                    Arg::with_name("identity")
+                        .long("identity")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Identity keypair file, generated if it doesn't exist"),
+                )
+                .arg(
+                    Arg::with_name("vote_account")
+                        .long("vote-account")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Vote account keypair file, generated if it doesn't exist"),
+                )
+                .arg(
+                    Arg::with_name("stake")
+                        .long("stake")
+                        .value_name("LAMPORTS")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The number of lamports to fund the vote account with"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-account")
+                .about("Show the contents of an account")
+                .arg(
+                    Arg::with_name("account_id")
+                        .index(1)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Account pubkey"),
+                )
+                .arg(
+                    Arg::with_name("program")
+                        .long("program")
+                        .value_name("PROGRAM")
+                        .takes_value(true)
+                        .possible_values(&["budget", "rewards", "vote"])
+                        .help("Decode the account data as this program's state"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-delegations")
+                .about("Show the vote accounts delegated to a node")
+                .arg(
+                    Arg::with_name("node_id")
+                        .index(1)
+                        .value_name("NODE_PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The node the vote accounts are delegated to"),
+                )
+                .arg(
+                    Arg::with_name("epoch")
+                        .long("epoch")
+                        .value_name("EPOCH")
+                        .takes_value(true)
+                        .help("Use the stakes snapshotted for this epoch's leader schedule"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("sign-transaction")
+                .about("Add your signature to a transaction exported for several signers")
+                .arg(
+                    Arg::with_name("path")
+                        .index(1)
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The exported transaction, updated with your signature"),
+                )
+                .arg(
+                    Arg::with_name("submit")
+                        .long("submit")
+                        .help("Send the transaction once every signer has signed it"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("supply")
+                .about("Show how the supply is split among programs and the largest holders")
+                .arg(
+                    Arg::with_name("top")
+                        .long("top")
+                        .value_name("NUM")
+                        .takes_value(true)
+                        .help("Also list the NUM largest accounts"),
+                )
+                .arg(
+                    Arg::with_name("exclude")
+                        .long("exclude")
+                        .value_name("PROGRAM_ID")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .requires("top")
+                        .help("Leave accounts owned by these programs out of the largest accounts"),
                 ),
         )
         .get_matches();
 
+    // The RPC trace is logged at info level, which RUST_LOG hides by default
+    if matches.is_present("verbose") && env::var_os("RUST_LOG").is_none() {
+        env::set_var("RUST_LOG", "solana_client=info");
+    }
+    solana_logger::setup();
+
     let config = parse_args(&matches)?;
-    let result = process_command(&config)?;
-    println!("{}", result);
+    match process_command(&config) {
+        Ok(result) => println!("{}", result),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            exit(1);
+        }
+    }
     Ok(())
 }
@@ -5,43 +5,81 @@ use log::*;
 use serde_json;
 use serde_json::json;
 use solana_budget_api;
+use solana_budget_api::budget_expr::BudgetExpr;
+use solana_budget_api::budget_script::BudgetScript;
+use solana_budget_api::budget_state::BudgetState;
 use solana_budget_api::budget_transaction::BudgetTransaction;
-use solana_client::rpc_client::{get_rpc_request_str, RpcClient};
-#[cfg(not(test))]
-use solana_drone::drone::request_airdrop_transaction;
+use solana_budget_api::payment_plan::{Payment, Witness};
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::{get_rpc_request_str, NodeHealth, RpcClient};
+use solana_client::rpc_request::{MAX_GET_LARGEST_ACCOUNTS, MAX_GET_SIGNATURE_STATUSES};
+use solana_client::rpc_signature_status::RpcSignatureStatus;
+use solana_client::tracing_rpc_client_request::redact_signature;
 use solana_drone::drone::DRONE_PORT;
+#[cfg(not(test))]
+use solana_drone::drone::{request_airdrop_transaction, request_drone_info};
 #[cfg(test)]
-use solana_drone::drone_mock::request_airdrop_transaction;
+use solana_drone::drone_mock::{request_airdrop_transaction, request_drone_info};
+use solana_rewards_api::rewards_state::RewardsState;
+use solana_runtime::bank::Bank;
+use solana_sdk::account::Account;
 use solana_sdk::bpf_loader;
-use solana_sdk::hash::Hash;
+use solana_sdk::commitment::Commitment;
+use solana_sdk::fee_calculator::FeeCalculator;
+use solana_sdk::genesis_block::GenesisBlock;
+use solana_sdk::hash::{hash, Hash};
+use solana_sdk::loader_instruction::MAX_WRITE_LEN;
 use solana_sdk::loader_transaction::LoaderTransaction;
+use solana_sdk::memo_instruction::MemoInstruction;
+use solana_sdk::memo_program::MAX_MEMO_LEN;
+use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::rpc_port::DEFAULT_RPC_PORT;
-use solana_sdk::signature::{Keypair, KeypairUtil, Signature};
+use solana_sdk::signature::{gen_keypair_file, read_keypair, Keypair, KeypairUtil, Signature};
+use solana_sdk::system_instruction::SystemInstruction;
 use solana_sdk::system_transaction::SystemTransaction;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError};
 use solana_vote_api::vote_instruction::VoteInstruction;
+use solana_vote_api::vote_state::VoteState;
 use solana_vote_api::vote_transaction::VoteTransaction;
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, Read, Write};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::{error, fmt, mem};
-
-const USERDATA_CHUNK_SIZE: usize = 256;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use std::{cmp, error, fmt};
+
+/// How long to wait between the two slot readings of the node health check
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// How far the node may trail the reference node before it's reported as not caught up
+const DEFAULT_MAX_SLOTS_BEHIND: u64 = 64;
+/// The most installments a `pay --every` schedule fits in one packet
+const MAX_SCHEDULE_PAYMENTS: u32 = 5;
 
 #[derive(Debug, PartialEq)]
 pub enum WalletCommand {
     Address,
     Airdrop(u64),
-    Balance,
+    Balance(Commitment),
     Cancel(Pubkey),
-    Confirm(Signature),
+    Confirm(Vec<Signature>),
+    // FollowConfirmation(signature, target_depth, timeout_secs)
+    FollowConfirmation(Signature, u64, u64),
     // ConfigureStakingAccount(delegate_id, authorized_voter_id)
     ConfigureStakingAccount(Option<Pubkey>, Option<Pubkey>),
     CreateStakingAccount(Pubkey, u64),
     Deploy(String),
-    GetTransactionCount,
-    // Pay(lamports, to, timestamp, timestamp_pubkey, witness(es), cancelable)
+    // Drain(destination)
+    Drain(Pubkey),
+    GetGenesisHash,
+    // GetTransactionCount(sample_secs)
+    GetTransactionCount(Option<u64>),
+    // Monitor(pubkey)
+    Monitor(Option<Pubkey>),
+    // Pay(lamports, to, timestamp, timestamp_pubkey, witness(es), cancelable, auto_cancel_after, memo,
+    //     allow_unfunded_recipient, verify, export_unsigned)
     Pay(
         u64,
         Pubkey,
@@ -49,11 +87,43 @@ pub enum WalletCommand {
         Option<Pubkey>,
         Option<Vec<Pubkey>>,
         Option<Pubkey>,
+        Option<u64>,
+        Option<String>,
+        bool,
+        bool,
+        Option<PathBuf>,
+    ),
+    // PayOnSchedule(lamports, to, start, interval, count, timestamp_pubkey,
+    //               allow_unfunded_recipient)
+    PayOnSchedule(
+        u64,
+        Pubkey,
+        DateTime<Utc>,
+        chrono::Duration,
+        u32,
+        Option<Pubkey>,
+        bool,
     ),
-    // TimeElapsed(to, process_id, timestamp)
-    TimeElapsed(Pubkey, Pubkey, DateTime<Utc>),
-    // Witness(to, process_id)
-    Witness(Pubkey, Pubkey),
+    // Redirect(process_id, to)
+    Redirect(Pubkey, Pubkey),
+    // ReleaseDue(process_id)
+    ReleaseDue(Pubkey),
+    // ReplayLedger(ledger_path, transactions_path)
+    ReplayLedger(PathBuf, PathBuf),
+    // SetupValidator(identity_path, vote_account_path, stake)
+    SetupValidator(PathBuf, PathBuf, u64),
+    // SignTransaction(path, submit)
+    SignTransaction(PathBuf, bool),
+    // ShowAccount(pubkey, program)
+    ShowAccount(Pubkey, Option<String>),
+    // ShowDelegations(node_id, epoch)
+    ShowDelegations(Pubkey, Option<u64>),
+    // Supply(top, excluded_owners)
+    Supply(Option<usize>, Vec<Pubkey>),
+    // TimeElapsed(to, process_id, timestamp, force)
+    TimeElapsed(Pubkey, Pubkey, DateTime<Utc>, bool),
+    // Witness(to, process_id, force)
+    Witness(Pubkey, Pubkey, bool),
 }
 
 #[derive(Debug, Clone)]
@@ -61,12 +131,30 @@ pub enum WalletError {
     CommandNotRecognized(String),
     BadParameter(String),
     DynamicProgramError(String),
+    // GenesisHashMismatch(expected, found)
+    GenesisHashMismatch(Hash, Hash),
     RpcRequestError(String),
+    ConnectionError(String),
+    UnhealthyNode(String),
 }
 
 impl fmt::Display for WalletError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "invalid")
+        match self {
+            WalletError::CommandNotRecognized(message) => {
+                write!(f, "command not recognized: {}", message)
+            }
+            WalletError::BadParameter(message) => write!(f, "bad parameter: {}", message),
+            WalletError::DynamicProgramError(message) => write!(f, "program error: {}", message),
+            WalletError::GenesisHashMismatch(expected, found) => write!(
+                f,
+                "genesis hash mismatch: expected {}, cluster reports {}",
+                expected, found
+            ),
+            WalletError::RpcRequestError(message) => write!(f, "rpc request failed: {}", message),
+            WalletError::ConnectionError(message) => write!(f, "connection failed: {}", message),
+            WalletError::UnhealthyNode(message) => write!(f, "unhealthy node: {}", message),
+        }
     }
 }
 
@@ -81,30 +169,81 @@ impl error::Error for WalletError {
     }
 }
 
+impl From<ClientError> for WalletError {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::Reqwest(err) => WalletError::ConnectionError(err.to_string()),
+            err => WalletError::RpcRequestError(err.to_string()),
+        }
+    }
+}
+
+/// Report an RPC client failure as the `WalletError` it amounts to
+fn map_client_error(err: Box<dyn error::Error>) -> Box<dyn error::Error> {
+    match err.downcast::<ClientError>() {
+        Ok(err) => Box::new(WalletError::from(*err)),
+        Err(err) => err,
+    }
+}
+
+/// When to ask on the terminal before submitting a state-changing command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfirmMode {
+    Never,
+    /// Only when stdin is a terminal, so scripts aren't left waiting for an answer
+    Auto,
+    Always,
+}
+
+impl ConfirmMode {
+    /// Return true if a command should wait for an answer before submitting
+    pub fn should_prompt(self, stdin_is_tty: bool) -> bool {
+        match self {
+            ConfirmMode::Never => false,
+            ConfirmMode::Auto => stdin_is_tty,
+            ConfirmMode::Always => true,
+        }
+    }
+}
+
 pub struct WalletConfig {
     pub id: Keypair,
+    pub audit_log: Option<PathBuf>,
     pub command: WalletCommand,
+    pub confirm: ConfirmMode,
     pub drone_host: Option<IpAddr>,
     pub drone_port: u16,
+    pub expected_genesis_hash: Option<Hash>,
     pub host: IpAddr,
+    pub max_slots_behind: u64,
+    pub reference_rpc_client: Option<RpcClient>,
     pub rpc_client: Option<RpcClient>,
     pub rpc_host: Option<IpAddr>,
     pub rpc_port: u16,
     pub rpc_tls: bool,
+    pub strict_health: bool,
+    pub verbose: bool,
 }
 
 impl Default for WalletConfig {
     fn default() -> WalletConfig {
         WalletConfig {
-            command: WalletCommand::Balance,
+            audit_log: None,
+            command: WalletCommand::Balance(Commitment::default()),
+            confirm: ConfirmMode::Never,
             drone_host: None,
             drone_port: DRONE_PORT,
+            expected_genesis_hash: None,
             host: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
             id: Keypair::new(),
+            max_slots_behind: DEFAULT_MAX_SLOTS_BEHIND,
+            reference_rpc_client: None,
             rpc_client: None,
             rpc_host: None,
             rpc_port: DEFAULT_RPC_PORT,
             rpc_tls: false,
+            strict_health: false,
+            verbose: false,
         }
     }
 }
@@ -122,6 +261,103 @@ impl WalletConfig {
     }
 }
 
+fn parse_error(matches: &ArgMatches<'_>, message: String) -> WalletError {
+    eprintln!("{}", matches.usage());
+    WalletError::BadParameter(message)
+}
+
+/// Return the base58-encoded public key in argument `name`
+pub fn pubkey_of(matches: &ArgMatches<'_>, name: &str) -> Result<Pubkey, WalletError> {
+    let value = matches
+        .value_of(name)
+        .ok_or_else(|| parse_error(matches, format!("Missing {}", name)))?;
+    value
+        .parse()
+        .map_err(|err| parse_error(matches, format!("Invalid {} public key: {}", name, err)))
+}
+
+/// Return all the base58-encoded public keys in argument `name`
+pub fn pubkeys_of(matches: &ArgMatches<'_>, name: &str) -> Result<Vec<Pubkey>, WalletError> {
+    let values = matches
+        .values_of(name)
+        .ok_or_else(|| parse_error(matches, format!("Missing {}", name)))?;
+    values
+        .map(|value| {
+            value.parse::<Pubkey>().map_err(|err| {
+                parse_error(matches, format!("Invalid {} public key: {}", name, err))
+            })
+        })
+        .collect()
+}
+
+/// Return all the base58-encoded signatures in argument `name`
+pub fn signatures_of(matches: &ArgMatches<'_>, name: &str) -> Result<Vec<Signature>, WalletError> {
+    let values = matches
+        .values_of(name)
+        .ok_or_else(|| parse_error(matches, format!("Missing {}", name)))?;
+    values
+        .map(|value| {
+            value
+                .parse::<Signature>()
+                .map_err(|err| parse_error(matches, format!("Invalid {} signature: {}", name, err)))
+        })
+        .collect()
+}
+
+/// Return the base58-encoded signature in argument `name`
+pub fn signature_of(matches: &ArgMatches<'_>, name: &str) -> Result<Signature, WalletError> {
+    let value = matches
+        .value_of(name)
+        .ok_or_else(|| parse_error(matches, format!("Missing {}", name)))?;
+    value
+        .parse()
+        .map_err(|err| parse_error(matches, format!("Invalid {} signature: {}", name, err)))
+}
+
+/// Parse a duration such as `90s`, `15m`, `12h` or `30d`. A bare number is in seconds.
+fn parse_duration(value: &str) -> Result<chrono::Duration, String> {
+    let (digits, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let n: i64 = digits
+        .parse()
+        .map_err(|_| format!("Invalid duration: {}", value))?;
+    let seconds_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => Err(format!(
+            "Invalid duration unit in {}; use s, m, h or d",
+            value
+        ))?,
+    };
+    let seconds = n
+        .checked_mul(seconds_per_unit)
+        .filter(|seconds| *seconds <= chrono::Duration::max_value().num_seconds())
+        .ok_or_else(|| format!("Duration is too long: {}", value))?;
+    if seconds <= 0 {
+        Err(format!("Duration must be positive: {}", value))?;
+    }
+    Ok(chrono::Duration::seconds(seconds))
+}
+
+/// Return when to ask before submitting the command in `matches`. Draining an account asks
+/// like `--confirm` does unless `--yes` is passed.
+pub fn parse_confirm_mode(matches: &ArgMatches<'_>) -> ConfirmMode {
+    let confirm = match matches.value_of("confirm") {
+        Some("always") => ConfirmMode::Always,
+        _ if matches.is_present("confirm") => ConfirmMode::Auto,
+        _ => ConfirmMode::Never,
+    };
+    match matches.subcommand() {
+        ("drain", Some(drain_matches)) if drain_matches.is_present("yes") => ConfirmMode::Never,
+        ("drain", Some(_)) if confirm == ConfirmMode::Never => ConfirmMode::Auto,
+        _ => confirm,
+    }
+}
+
 pub fn parse_command(
     pubkey: &Pubkey,
     matches: &ArgMatches<'_>,
@@ -132,68 +368,61 @@ pub fn parse_command(
             let lamports = airdrop_matches.value_of("lamports").unwrap().parse()?;
             Ok(WalletCommand::Airdrop(lamports))
         }
-        ("balance", Some(_balance_matches)) => Ok(WalletCommand::Balance),
+        ("balance", Some(balance_matches)) => {
+            let commitment = match balance_matches.value_of("commitment") {
+                Some("root") => Commitment::Root,
+                Some("frozen") => Commitment::Frozen,
+                _ => Commitment::Recent,
+            };
+            Ok(WalletCommand::Balance(commitment))
+        }
         ("cancel", Some(cancel_matches)) => {
-            let pubkey_vec = bs58::decode(cancel_matches.value_of("process_id").unwrap())
-                .into_vec()
-                .expect("base58-encoded public key");
-
-            if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                eprintln!("{}", cancel_matches.usage());
-                Err(WalletError::BadParameter("Invalid public key".to_string()))?;
-            }
-            let process_id = Pubkey::new(&pubkey_vec);
+            let process_id = pubkey_of(cancel_matches, "process_id")?;
             Ok(WalletCommand::Cancel(process_id))
         }
         ("confirm", Some(confirm_matches)) => {
-            let signatures = bs58::decode(confirm_matches.value_of("signature").unwrap())
-                .into_vec()
-                .expect("base58-encoded signature");
-
-            if signatures.len() == mem::size_of::<Signature>() {
-                let signature = Signature::new(&signatures);
-                Ok(WalletCommand::Confirm(signature))
-            } else {
-                eprintln!("{}", confirm_matches.usage());
-                Err(WalletError::BadParameter("Invalid signature".to_string()))
+            let signatures = signatures_of(confirm_matches, "signature")?;
+            if signatures.len() > MAX_GET_SIGNATURE_STATUSES {
+                Err(WalletError::BadParameter(format!(
+                    "At most {} signatures may be confirmed at once",
+                    MAX_GET_SIGNATURE_STATUSES
+                )))?;
+            }
+            if confirm_matches.is_present("follow") {
+                if signatures.len() != 1 {
+                    Err(parse_error(
+                        confirm_matches,
+                        "--follow takes exactly one signature".to_string(),
+                    ))?;
+                }
+                let target_depth = confirm_matches.value_of("target_depth").unwrap().parse()?;
+                let timeout = confirm_matches.value_of("timeout").unwrap().parse()?;
+                return Ok(WalletCommand::FollowConfirmation(
+                    signatures[0],
+                    target_depth,
+                    timeout,
+                ));
             }
+            Ok(WalletCommand::Confirm(signatures))
         }
         ("configure-staking-account", Some(staking_config_matches)) => {
-            let delegate_id = staking_config_matches
-                .value_of("delegate")
-                .map(|pubkey_string| {
-                    let pubkey_vec = bs58::decode(pubkey_string)
-                        .into_vec()
-                        .expect("base58-encoded public key");
-                    // TODO: Add valid pubkey check
-                    Pubkey::new(&pubkey_vec)
-                });
-            let authorized_voter_id =
-                staking_config_matches
-                    .value_of("authorize")
-                    .map(|pubkey_string| {
-                        let pubkey_vec = bs58::decode(pubkey_string)
-                            .into_vec()
-                            .expect("base58-encoded public key");
-                        // TODO: Add valid pubkey check
-                        Pubkey::new(&pubkey_vec)
-                    });
+            let delegate_id = if staking_config_matches.is_present("delegate") {
+                Some(pubkey_of(staking_config_matches, "delegate")?)
+            } else {
+                None
+            };
+            let authorized_voter_id = if staking_config_matches.is_present("authorize") {
+                Some(pubkey_of(staking_config_matches, "authorize")?)
+            } else {
+                None
+            };
             Ok(WalletCommand::ConfigureStakingAccount(
                 delegate_id,
                 authorized_voter_id,
             ))
         }
         ("create-staking-account", Some(staking_matches)) => {
-            let voting_account_string = staking_matches.value_of("voting_account_id").unwrap();
-            let voting_account_vec = bs58::decode(voting_account_string)
-                .into_vec()
-                .expect("base58-encoded public key");
-
-            if voting_account_vec.len() != mem::size_of::<Pubkey>() {
-                eprintln!("{}", staking_matches.usage());
-                Err(WalletError::BadParameter("Invalid public key".to_string()))?;
-            }
-            let voting_account_id = Pubkey::new(&voting_account_vec);
+            let voting_account_id = pubkey_of(staking_matches, "voting_account_id")?;
             let lamports = staking_matches.value_of("lamports").unwrap().parse()?;
             Ok(WalletCommand::CreateStakingAccount(
                 voting_account_id,
@@ -206,21 +435,31 @@ pub fn parse_command(
                 .unwrap()
                 .to_string(),
         )),
-        ("get-transaction-count", Some(_matches)) => Ok(WalletCommand::GetTransactionCount),
+        ("drain", Some(drain_matches)) => {
+            let to = pubkey_of(drain_matches, "to")?;
+            Ok(WalletCommand::Drain(to))
+        }
+        ("get-genesis-hash", Some(_genesis_hash_matches)) => Ok(WalletCommand::GetGenesisHash),
+        ("get-transaction-count", Some(count_matches)) => {
+            let sample = if let Some(sample) = count_matches.value_of("sample") {
+                Some(sample.parse()?)
+            } else {
+                None
+            };
+            Ok(WalletCommand::GetTransactionCount(sample))
+        }
+        ("monitor", Some(monitor_matches)) => {
+            let pubkey = if monitor_matches.is_present("pubkey") {
+                Some(pubkey_of(monitor_matches, "pubkey")?)
+            } else {
+                None
+            };
+            Ok(WalletCommand::Monitor(pubkey))
+        }
         ("pay", Some(pay_matches)) => {
             let lamports = pay_matches.value_of("lamports").unwrap().parse()?;
             let to = if pay_matches.is_present("to") {
-                let pubkey_vec = bs58::decode(pay_matches.value_of("to").unwrap())
-                    .into_vec()
-                    .expect("base58-encoded public key");
-
-                if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                    eprintln!("{}", pay_matches.usage());
-                    Err(WalletError::BadParameter(
-                        "Invalid to public key".to_string(),
-                    ))?;
-                }
-                Pubkey::new(&pubkey_vec)
+                pubkey_of(pay_matches, "to")?
             } else {
                 *pubkey
             };
@@ -236,37 +475,12 @@ pub fn parse_command(
                 None
             };
             let timestamp_pubkey = if pay_matches.is_present("timestamp_pubkey") {
-                let pubkey_vec = bs58::decode(pay_matches.value_of("timestamp_pubkey").unwrap())
-                    .into_vec()
-                    .expect("base58-encoded public key");
-
-                if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                    eprintln!("{}", pay_matches.usage());
-                    Err(WalletError::BadParameter(
-                        "Invalid timestamp public key".to_string(),
-                    ))?;
-                }
-                Some(Pubkey::new(&pubkey_vec))
+                Some(pubkey_of(pay_matches, "timestamp_pubkey")?)
             } else {
                 None
             };
             let witness_vec = if pay_matches.is_present("witness") {
-                let witnesses = pay_matches.values_of("witness").unwrap();
-                let mut collection = Vec::new();
-                for witness in witnesses {
-                    let pubkey_vec = bs58::decode(witness)
-                        .into_vec()
-                        .expect("base58-encoded public key");
-
-                    if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                        eprintln!("{}", pay_matches.usage());
-                        Err(WalletError::BadParameter(
-                            "Invalid witness public key".to_string(),
-                        ))?;
-                    }
-                    collection.push(Pubkey::new(&pubkey_vec));
-                }
-                Some(collection)
+                Some(pubkeys_of(pay_matches, "witness")?)
             } else {
                 None
             };
@@ -275,6 +489,32 @@ pub fn parse_command(
             } else {
                 None
             };
+            let auto_cancel_after = match pay_matches.value_of("auto_cancel_after") {
+                Some(secs) => Some(secs.parse()?),
+                None => None,
+            };
+            let memo = pay_matches.value_of("memo").map(|memo| memo.to_string());
+            let allow_unfunded_recipient = pay_matches.is_present("allow_unfunded_recipient");
+            let verify = pay_matches.is_present("verify");
+            let export_unsigned = pay_matches.value_of("export_unsigned").map(PathBuf::from);
+
+            if let Some(every) = pay_matches.value_of("every") {
+                let start = timestamp.ok_or_else(|| {
+                    parse_error(pay_matches, "A schedule requires --after".to_string())
+                })?;
+                let interval =
+                    parse_duration(every).map_err(|message| parse_error(pay_matches, message))?;
+                let count = pay_matches.value_of("count").unwrap().parse()?;
+                return Ok(WalletCommand::PayOnSchedule(
+                    lamports,
+                    to,
+                    start,
+                    interval,
+                    count,
+                    timestamp_pubkey,
+                    allow_unfunded_recipient,
+                ));
+            }
 
             Ok(WalletCommand::Pay(
                 lamports,
@@ -283,50 +523,82 @@ pub fn parse_command(
                 timestamp_pubkey,
                 witness_vec,
                 cancelable,
+                auto_cancel_after,
+                memo,
+                allow_unfunded_recipient,
+                verify,
+                export_unsigned,
             ))
         }
-        ("send-signature", Some(sig_matches)) => {
-            let pubkey_vec = bs58::decode(sig_matches.value_of("to").unwrap())
-                .into_vec()
-                .expect("base58-encoded public key");
-
-            if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                eprintln!("{}", sig_matches.usage());
-                Err(WalletError::BadParameter("Invalid public key".to_string()))?;
-            }
-            let to = Pubkey::new(&pubkey_vec);
-
-            let pubkey_vec = bs58::decode(sig_matches.value_of("process_id").unwrap())
-                .into_vec()
-                .expect("base58-encoded public key");
-
-            if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                eprintln!("{}", sig_matches.usage());
-                Err(WalletError::BadParameter("Invalid public key".to_string()))?;
+        ("redirect", Some(redirect_matches)) => {
+            let process_id = pubkey_of(redirect_matches, "process_id")?;
+            let to = pubkey_of(redirect_matches, "to")?;
+            Ok(WalletCommand::Redirect(process_id, to))
+        }
+        ("release-due", Some(release_matches)) => {
+            let process_id = pubkey_of(release_matches, "process_id")?;
+            Ok(WalletCommand::ReleaseDue(process_id))
+        }
+        ("replay-ledger", Some(replay_matches)) => Ok(WalletCommand::ReplayLedger(
+            PathBuf::from(replay_matches.value_of("ledger").unwrap()),
+            PathBuf::from(replay_matches.value_of("transactions").unwrap()),
+        )),
+        ("setup-validator", Some(setup_matches)) => {
+            let stake = setup_matches.value_of("stake").unwrap().parse()?;
+            Ok(WalletCommand::SetupValidator(
+                PathBuf::from(setup_matches.value_of("identity").unwrap()),
+                PathBuf::from(setup_matches.value_of("vote_account").unwrap()),
+                stake,
+            ))
+        }
+        ("show-account", Some(show_account_matches)) => {
+            let account_id = pubkey_of(show_account_matches, "account_id")?;
+            let program = show_account_matches
+                .value_of("program")
+                .map(|program| program.to_string());
+            Ok(WalletCommand::ShowAccount(account_id, program))
+        }
+        ("show-delegations", Some(delegations_matches)) => {
+            let node_id = pubkey_of(delegations_matches, "node_id")?;
+            let epoch = if let Some(epoch) = delegations_matches.value_of("epoch") {
+                Some(epoch.parse()?)
+            } else {
+                None
+            };
+            Ok(WalletCommand::ShowDelegations(node_id, epoch))
+        }
+        ("sign-transaction", Some(sign_matches)) => Ok(WalletCommand::SignTransaction(
+            PathBuf::from(sign_matches.value_of("path").unwrap()),
+            sign_matches.is_present("submit"),
+        )),
+        ("supply", Some(supply_matches)) => {
+            let top = if let Some(top) = supply_matches.value_of("top") {
+                Some(top.parse()?)
+            } else {
+                None
+            };
+            if top.map_or(false, |top| top > MAX_GET_LARGEST_ACCOUNTS) {
+                Err(WalletError::BadParameter(format!(
+                    "At most {} accounts may be listed",
+                    MAX_GET_LARGEST_ACCOUNTS
+                )))?;
             }
-            let process_id = Pubkey::new(&pubkey_vec);
-            Ok(WalletCommand::Witness(to, process_id))
+            let excluded_owners = if supply_matches.is_present("exclude") {
+                pubkeys_of(supply_matches, "exclude")?
+            } else {
+                vec![]
+            };
+            Ok(WalletCommand::Supply(top, excluded_owners))
+        }
+        ("send-signature", Some(sig_matches)) => {
+            let to = pubkey_of(sig_matches, "to")?;
+            let process_id = pubkey_of(sig_matches, "process_id")?;
+            let force = sig_matches.is_present("force");
+            Ok(WalletCommand::Witness(to, process_id, force))
         }
         ("send-timestamp", Some(timestamp_matches)) => {
-            let pubkey_vec = bs58::decode(timestamp_matches.value_of("to").unwrap())
-                .into_vec()
-                .expect("base58-encoded public key");
-
-            if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                eprintln!("{}", timestamp_matches.usage());
-                Err(WalletError::BadParameter("Invalid public key".to_string()))?;
-            }
-            let to = Pubkey::new(&pubkey_vec);
-
-            let pubkey_vec = bs58::decode(timestamp_matches.value_of("process_id").unwrap())
-                .into_vec()
-                .expect("base58-encoded public key");
-
-            if pubkey_vec.len() != mem::size_of::<Pubkey>() {
-                eprintln!("{}", timestamp_matches.usage());
-                Err(WalletError::BadParameter("Invalid public key".to_string()))?;
-            }
-            let process_id = Pubkey::new(&pubkey_vec);
+            let to = pubkey_of(timestamp_matches, "to")?;
+            let process_id = pubkey_of(timestamp_matches, "process_id")?;
             let dt = if timestamp_matches.is_present("datetime") {
                 // Parse input for serde_json
                 let date_string = if !timestamp_matches
@@ -342,7 +614,8 @@ pub fn parse_command(
             } else {
                 Utc::now()
             };
-            Ok(WalletCommand::TimeElapsed(to, process_id, dt))
+            let force = timestamp_matches.is_present("force");
+            Ok(WalletCommand::TimeElapsed(to, process_id, dt, force))
         }
         ("", None) => {
             eprintln!("{}", matches.usage());
@@ -367,6 +640,18 @@ fn process_airdrop(
         "Requesting airdrop of {:?} lamports from {}",
         lamports, drone_addr
     );
+    // Older drones don't answer GetInfo; let the airdrop request itself fail in that case
+    match request_drone_info(&drone_addr) {
+        Ok(drone_info) => {
+            if lamports > drone_info.window_remaining {
+                Err(WalletError::BadParameter(format!(
+                    "Drone can only dispense {} more lamports right now (per-request cap: {})",
+                    drone_info.window_remaining, drone_info.per_request_cap
+                )))?;
+            }
+        }
+        Err(err) => debug!("Unable to query drone info: {:?}", err),
+    }
     let previous_balance = match rpc_client.retry_get_balance(&config.id.pubkey(), 5)? {
         Some(lamports) => lamports,
         None => Err(WalletError::RpcRequestError(
@@ -396,8 +681,13 @@ fn process_airdrop(
     Ok(format!("Your balance is: {:?}", current_balance))
 }
 
-fn process_balance(config: &WalletConfig, rpc_client: &RpcClient) -> ProcessResult {
-    let balance = rpc_client.retry_get_balance(&config.id.pubkey(), 5)?;
+fn process_balance(
+    config: &WalletConfig,
+    rpc_client: &RpcClient,
+    commitment: Commitment,
+) -> ProcessResult {
+    let balance =
+        rpc_client.retry_get_balance_with_commitment(&config.id.pubkey(), 5, commitment)?;
     match balance {
         Some(0) => Ok("No account found! Request an airdrop to get started.".to_string()),
         Some(lamports) => Ok(format!("Your balance is: {:?}", lamports)),
@@ -407,19 +697,64 @@ fn process_balance(config: &WalletConfig, rpc_client: &RpcClient) -> ProcessResu
     }
 }
 
-fn process_confirm(rpc_client: &RpcClient, signature: Signature) -> ProcessResult {
-    match rpc_client.get_signature_status(&signature.to_string()) {
-        Ok(status) => {
-            if status == solana_client::rpc_signature_status::RpcSignatureStatus::Confirmed {
-                Ok("Confirmed".to_string())
-            } else {
-                Ok("Not found".to_string())
+fn process_confirm(rpc_client: &RpcClient, signatures: &[Signature]) -> ProcessResult {
+    let statuses = rpc_client
+        .get_signature_statuses(signatures)
+        .map_err(|err| WalletError::RpcRequestError(format!("Unable to confirm: {:?}", err)))?;
+    let statuses = statuses.into_iter().map(|status| match status {
+        Some(Ok(())) => "Confirmed".to_string(),
+        Some(Err(TransactionError::InvalidProgramForExecution)) => {
+            "Failed: program not finalized/deployed".to_string()
+        }
+        Some(Err(err)) => format!("Failed: {}", err),
+        None => "Not found".to_string(),
+    });
+
+    // A lone signature's status needs no label
+    if signatures.len() == 1 {
+        return Ok(statuses.collect());
+    }
+    let lines: Vec<_> = signatures
+        .iter()
+        .zip(statuses)
+        .map(|(signature, status)| format!("{}: {}", signature, status))
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// Poll the confirmation depth of `signature` every `interval`, writing each new depth to
+/// `writer`, until it reaches `target_depth` or `timeout` passes
+fn follow_confirmation<W: Write>(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    target_depth: u64,
+    timeout: Duration,
+    interval: Duration,
+    writer: &mut W,
+) -> ProcessResult {
+    let start = Instant::now();
+    let mut last_depth = None;
+    loop {
+        if let Some(depth) = rpc_client.get_signature_confirmation_count(signature)? {
+            if last_depth != Some(depth) {
+                writeln!(writer, "Depth: {}", depth)?;
+                last_depth = Some(depth);
+            }
+            if depth >= target_depth {
+                return Ok(format!("Confirmed at depth {}", depth));
             }
         }
-        Err(err) => Err(WalletError::RpcRequestError(format!(
-            "Unable to confirm: {:?}",
-            err
-        )))?,
+        if start.elapsed() >= timeout {
+            let message = match last_depth {
+                Some(depth) => format!(
+                    "timed out at depth {} of {}: {}",
+                    depth, target_depth, signature
+                ),
+                None => format!("timed out before the signature was seen: {}", signature),
+            };
+            Err(WalletError::RpcRequestError(message))?;
+        }
+        sleep(interval);
     }
 }
 
@@ -445,7 +780,7 @@ fn process_configure_staking(
     }
     let mut tx = Transaction::new(ixs);
     tx.sign(&[&config.id], recent_blockhash);
-    let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
     Ok(signature_str.to_string())
 }
 
@@ -455,10 +790,17 @@ fn process_create_staking(
     voting_account_id: &Pubkey,
     lamports: u64,
 ) -> ProcessResult {
+    let minimum_delegation = rpc_client.get_minimum_delegation()?;
+    if lamports < minimum_delegation {
+        Err(WalletError::BadParameter(format!(
+            "Staking accounts must be created with at least {} lamports",
+            minimum_delegation
+        )))?;
+    }
     let recent_blockhash = rpc_client.get_recent_blockhash()?;
     let mut tx =
         VoteTransaction::new_account(&config.id, voting_account_id, recent_blockhash, lamports, 0);
-    let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
     Ok(signature_str.to_string())
 }
 
@@ -500,36 +842,38 @@ fn process_deploy(
         0,
     );
     trace!("Creating program account");
-    rpc_client
-        .send_and_confirm_transaction(&mut tx, &config.id)
-        .map_err(|_| {
-            WalletError::DynamicProgramError("Program allocate space failed".to_string())
-        })?;
+    send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id).map_err(|_| {
+        WalletError::DynamicProgramError("Program allocate space failed".to_string())
+    })?;
 
     trace!("Writing program data");
+    let fee_calculator = rpc_client.get_fee_calculator()?;
     let write_transactions: Vec<_> = program_data
-        .chunks(USERDATA_CHUNK_SIZE)
+        .chunks(MAX_WRITE_LEN)
         .zip(0..)
         .map(|(chunk, i)| {
-            LoaderTransaction::new_write(
+            let mut tx = LoaderTransaction::new_write(
                 &program_id,
                 &bpf_loader::id(),
-                (i * USERDATA_CHUNK_SIZE) as u32,
+                (i * MAX_WRITE_LEN) as u32,
                 chunk.to_vec(),
                 blockhash,
                 0,
-            )
+            );
+            apply_fee(&fee_calculator, &mut tx, &program_id);
+            tx
         })
         .collect();
+    for tx in &write_transactions {
+        audit_signed_transaction(config, tx)?;
+    }
     rpc_client.send_and_confirm_transactions(write_transactions, &program_id)?;
 
     trace!("Finalizing program account");
     let mut tx = LoaderTransaction::new_finalize(&program_id, &bpf_loader::id(), blockhash, 0);
-    rpc_client
-        .send_and_confirm_transaction(&mut tx, &program_id)
-        .map_err(|_| {
-            WalletError::DynamicProgramError("Program finalize transaction failed".to_string())
-        })?;
+    send_and_confirm_with_fee(rpc_client, config, &mut tx, &program_id).map_err(|_| {
+        WalletError::DynamicProgramError("Program finalize transaction failed".to_string())
+    })?;
 
     Ok(json!({
         "programId": format!("{}", program_id.pubkey()),
@@ -537,6 +881,25 @@ fn process_deploy(
     .to_string())
 }
 
+/// Refuse to pay `to` if it has no account, since a pubkey nobody has used before is
+/// more likely a typo than a new recipient
+fn check_recipient_funded(
+    rpc_client: &RpcClient,
+    to: &Pubkey,
+) -> Result<(), Box<dyn error::Error>> {
+    match rpc_client.retry_get_balance(to, 5)? {
+        Some(0) => Err(WalletError::BadParameter(format!(
+            "Recipient {} has no account; check the address, or pass --allow-unfunded-recipient to pay it anyway",
+            to
+        )))?,
+        Some(_) => Ok(()),
+        None => Err(WalletError::RpcRequestError(
+            "Received result of an unexpected type".to_string(),
+        ))?,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn process_pay(
     rpc_client: &RpcClient,
     config: &WalletConfig,
@@ -546,13 +909,99 @@ fn process_pay(
     timestamp_pubkey: Option<Pubkey>,
     witnesses: &Option<Vec<Pubkey>>,
     cancelable: Option<Pubkey>,
+    auto_cancel_after: Option<u64>,
+    memo: &Option<String>,
+    allow_unfunded_recipient: bool,
+    verify: bool,
+    export_unsigned: &Option<PathBuf>,
 ) -> ProcessResult {
+    if let Some(memo) = memo {
+        if timestamp.is_some() || witnesses.is_some() {
+            Err(WalletError::BadParameter(
+                "A memo can only be attached to an immediate payment".to_string(),
+            ))?;
+        }
+        if memo.len() > MAX_MEMO_LEN {
+            Err(WalletError::BadParameter(format!(
+                "Memo is {} bytes, but at most {} are allowed",
+                memo.len(),
+                MAX_MEMO_LEN
+            )))?;
+        }
+    }
+    if verify && (timestamp.is_some() || witnesses.is_some()) {
+        Err(WalletError::BadParameter(
+            "Only an immediate payment can be verified".to_string(),
+        ))?;
+    }
+    if export_unsigned.is_some() && (timestamp.is_some() || witnesses.is_some() || verify) {
+        Err(WalletError::BadParameter(
+            "Only an immediate, unverified payment can be exported for signing".to_string(),
+        ))?;
+    }
+    if auto_cancel_after.is_some() {
+        if timestamp == None && *witnesses == None {
+            Err(WalletError::BadParameter(
+                "Auto-cancel requires a timestamp or signature contract".to_string(),
+            ))?;
+        }
+        if cancelable.is_none() {
+            Err(WalletError::BadParameter(
+                "Auto-cancel requires a cancelable contract".to_string(),
+            ))?;
+        }
+    }
+    if !allow_unfunded_recipient {
+        check_recipient_funded(rpc_client, to)?;
+    }
+
     let blockhash = rpc_client.get_recent_blockhash()?;
 
     if timestamp == None && *witnesses == None {
-        let mut tx = SystemTransaction::new_move(&config.id, to, lamports, blockhash, 0);
-        let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
-        Ok(signature_str.to_string())
+        let mut tx = match memo {
+            Some(memo) => {
+                let mut tx = Transaction::new(vec![
+                    SystemInstruction::new_move(&config.id.pubkey(), to, lamports),
+                    MemoInstruction::new(memo),
+                ]);
+                tx.sign(&[&config.id], blockhash);
+                tx
+            }
+            None => SystemTransaction::new_move(&config.id, to, lamports, blockhash, 0),
+        };
+        if let Some(path) = export_unsigned {
+            tx.fee = rpc_client.get_fee_calculator()?.calculate_fee(&tx);
+            let signers = tx.account_keys[..tx.signatures.len()].to_vec();
+            let partial = PartialTransaction::new(tx, signers);
+            partial.save(path)?;
+            return Ok(partial.describe(path));
+        }
+        if !verify {
+            let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+            return Ok(signature_str.to_string());
+        }
+
+        let previous_balance = match rpc_client.retry_get_balance(to, 5)? {
+            Some(lamports) => lamports,
+            None => Err(WalletError::RpcRequestError(
+                "Received result of an unexpected type".to_string(),
+            ))?,
+        };
+        let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+        let current_balance = rpc_client
+            .retry_get_balance(to, 5)?
+            .unwrap_or(previous_balance);
+
+        // Other transactions may touch the recipient between the two readings, so a mismatch
+        // is reported rather than treated as a failed payment
+        let observed_increase = current_balance as i64 - previous_balance as i64;
+        Ok(json!({
+            "signature": signature_str,
+            "expectedIncrease": lamports,
+            "observedIncrease": observed_increase,
+            "verified": observed_increase == lamports as i64,
+        })
+        .to_string())
     } else if *witnesses == None {
         let dt = timestamp.unwrap();
         let dt_pubkey = match timestamp_pubkey {
@@ -573,13 +1022,22 @@ fn process_pay(
             lamports,
             blockhash,
         );
-        let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
+        let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
 
-        Ok(json!({
+        let mut result = json!({
             "signature": signature_str,
             "processId": format!("{}", contract_state.pubkey()),
-        })
-        .to_string())
+        });
+        if let Some(secs) = auto_cancel_after {
+            wait_for_claim_or_cancel(
+                rpc_client,
+                config,
+                &contract_state.pubkey(),
+                secs,
+                &mut result,
+            )?;
+        }
+        Ok(result.to_string())
     } else if timestamp == None {
         let blockhash = rpc_client.get_recent_blockhash()?;
 
@@ -603,829 +1061,4153 @@ fn process_pay(
             lamports,
             blockhash,
         );
-        let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
+        let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
 
-        Ok(json!({
+        let mut result = json!({
             "signature": signature_str,
             "processId": format!("{}", contract_state.pubkey()),
-        })
-        .to_string())
+        });
+        if let Some(secs) = auto_cancel_after {
+            wait_for_claim_or_cancel(
+                rpc_client,
+                config,
+                &contract_state.pubkey(),
+                secs,
+                &mut result,
+            )?;
+        }
+        Ok(result.to_string())
     } else {
         Ok("Combo transactions not yet handled".to_string())
     }
 }
 
-fn process_cancel(rpc_client: &RpcClient, config: &WalletConfig, pubkey: &Pubkey) -> ProcessResult {
+/// Create a contract paying `to` in `count` tranches, the first at `start` and one every
+/// `interval` after that, each released by a timestamp from `timestamp_pubkey`
+#[allow(clippy::too_many_arguments)]
+fn process_pay_on_schedule(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    lamports: u64,
+    to: &Pubkey,
+    start: DateTime<Utc>,
+    interval: chrono::Duration,
+    count: u32,
+    timestamp_pubkey: Option<Pubkey>,
+    allow_unfunded_recipient: bool,
+) -> ProcessResult {
+    if count == 0 {
+        Err(WalletError::BadParameter(
+            "A schedule needs at least one payment".to_string(),
+        ))?;
+    }
+    if count > MAX_SCHEDULE_PAYMENTS {
+        Err(WalletError::BadParameter(format!(
+            "A schedule of {} payments doesn't fit in one transaction; use at most {}",
+            count, MAX_SCHEDULE_PAYMENTS
+        )))?;
+    }
+    let last_unlock = (1..count)
+        .try_fold(chrono::Duration::zero(), |span, _| {
+            span.checked_add(&interval)
+        })
+        .and_then(|span| start.checked_add_signed(span));
+    if last_unlock.is_none() {
+        Err(WalletError::BadParameter(format!(
+            "A schedule of {} payments every {} is too long",
+            count, interval
+        )))?;
+    }
+    if lamports < u64::from(count) {
+        Err(WalletError::BadParameter(format!(
+            "Can't split {} lamports into {} payments",
+            lamports, count
+        )))?;
+    }
+    if !allow_unfunded_recipient {
+        check_recipient_funded(rpc_client, to)?;
+    }
+    let dt_pubkey = timestamp_pubkey.unwrap_or_else(|| config.id.pubkey());
+    let contract_state = Keypair::new();
+    let script = BudgetScript::pay_on_schedule(
+        &config.id.pubkey(),
+        to,
+        &contract_state.pubkey(),
+        start,
+        interval,
+        count,
+        &dt_pubkey,
+        lamports,
+    );
+    let mut tx = script.compile();
+    if tx.serialized_size()? as usize > PACKET_DATA_SIZE {
+        Err(WalletError::BadParameter(format!(
+            "A schedule of {} payments doesn't fit in one transaction; use fewer",
+            count
+        )))?;
+    }
+
     let blockhash = rpc_client.get_recent_blockhash()?;
-    let mut tx =
-        BudgetTransaction::new_signature(&config.id, pubkey, &config.id.pubkey(), blockhash);
-    let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
-    Ok(signature_str.to_string())
+    tx.sign(&[&config.id], blockhash);
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+
+    let schedule: Vec<_> =
+        match BudgetExpr::new_vesting_payment(start, interval, count, &dt_pubkey, lamports, to) {
+            BudgetExpr::Vesting(_, _, tranches) => tranches
+                .iter()
+                .map(|tranche| {
+                    json!({
+                        "unlock": tranche.unlock.to_rfc3339(),
+                        "lamports": tranche.lamports,
+                    })
+                })
+                .collect(),
+            _ => unreachable!(),
+        };
+    Ok(json!({
+        "signature": signature_str,
+        "processId": format!("{}", contract_state.pubkey()),
+        "schedule": schedule,
+    })
+    .to_string())
 }
 
-fn process_get_transaction_count(rpc_client: &RpcClient) -> ProcessResult {
-    let transaction_count = rpc_client.get_transaction_count()?;
-    Ok(transaction_count.to_string())
+/// Return the payment a timestamp of `now` from `authority` would release from the vesting
+/// contract at `process_id`, or an error if it isn't `authority`'s to release or nothing is due.
+fn verify_release_due(
+    process_id: &Pubkey,
+    account: &Account,
+    authority: &Pubkey,
+    now: DateTime<Utc>,
+) -> Result<Payment, WalletError> {
+    if account.owner != solana_budget_api::id() {
+        Err(WalletError::BadParameter(format!(
+            "{} is not a budget contract",
+            process_id
+        )))?;
+    }
+    let mut expr = BudgetState::deserialize(&account.data)
+        .ok()
+        .and_then(|budget_state| budget_state.pending_budget)
+        .ok_or_else(|| {
+            WalletError::BadParameter(format!("Contract {} has no pending payment", process_id))
+        })?;
+    match expr {
+        BudgetExpr::Vesting(dt_pubkey, _, _) if dt_pubkey != *authority => {
+            Err(WalletError::BadParameter(format!(
+                "Contract {} expects a timestamp from {}, not {}",
+                process_id, dt_pubkey, authority
+            )))?
+        }
+        BudgetExpr::Vesting(..) => {}
+        _ => Err(WalletError::BadParameter(format!(
+            "Contract {} doesn't pay on a schedule",
+            process_id
+        )))?,
+    }
+    expr.release_tranches(&Witness::Timestamp(now), authority)
+        .ok_or_else(|| {
+            WalletError::BadParameter(format!(
+                "No payments of contract {} are due yet",
+                process_id
+            ))
+        })
 }
 
-fn process_time_elapsed(
+/// Send the current time to the vesting contract at `process_id`, releasing every tranche
+/// that's due
+fn process_release_due(
     rpc_client: &RpcClient,
     config: &WalletConfig,
-    drone_addr: SocketAddr,
-    to: &Pubkey,
-    pubkey: &Pubkey,
-    dt: DateTime<Utc>,
+    process_id: &Pubkey,
 ) -> ProcessResult {
-    let balance = rpc_client.retry_get_balance(&config.id.pubkey(), 5)?;
+    let account = rpc_client.get_account(process_id).map_err(|_| {
+        WalletError::BadParameter(format!("Unable to find contract {}", process_id))
+    })?;
+    let now = Utc::now();
+    let payment = verify_release_due(process_id, &account, &config.id.pubkey(), now)?;
 
-    if let Some(0) = balance {
-        request_and_confirm_airdrop(&rpc_client, &drone_addr, &config.id.pubkey(), 1)?;
+    let blockhash = rpc_client.get_recent_blockhash()?;
+    let mut tx =
+        BudgetTransaction::new_timestamp(&config.id, process_id, &payment.to, now, blockhash);
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+    Ok(json!({
+        "signature": signature_str,
+        "released": payment.lamports,
+    })
+    .to_string())
+}
+
+/// Poll the contract at `process_id` for up to `secs` seconds, sending a cancel if it still
+/// hasn't been claimed by then. The outcome is recorded in `result`.
+fn wait_for_claim_or_cancel(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    process_id: &Pubkey,
+    secs: u64,
+    result: &mut serde_json::Value,
+) -> Result<(), Box<dyn error::Error>> {
+    let deadline = Instant::now() + Duration::from_secs(secs);
+    loop {
+        // Once claimed, the contract has paid out all its lamports
+        if rpc_client.get_balance(process_id)? == 0 {
+            result["status"] = json!("claimed");
+            return Ok(());
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        sleep(cmp::min(deadline - now, Duration::from_secs(1)));
     }
 
+    let signature_str = process_cancel(rpc_client, config, process_id)?;
+    result["status"] = json!("canceled");
+    result["cancelSignature"] = json!(signature_str);
+    Ok(())
+}
+
+fn process_cancel(rpc_client: &RpcClient, config: &WalletConfig, pubkey: &Pubkey) -> ProcessResult {
     let blockhash = rpc_client.get_recent_blockhash()?;
+    let mut tx =
+        BudgetTransaction::new_signature(&config.id, pubkey, &config.id.pubkey(), blockhash);
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+    Ok(signature_str.to_string())
+}
 
-    let mut tx = BudgetTransaction::new_timestamp(&config.id, pubkey, to, dt, blockhash);
-    let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
+/// Return an error unless the contract at `process_id` is still pending and `authority` may
+/// cancel it, distinguishing a contract that already paid out from the wrong authority.
+fn verify_cancel_authority(
+    process_id: &Pubkey,
+    account: &Account,
+    authority: &Pubkey,
+) -> Result<(), WalletError> {
+    if account.owner != solana_budget_api::id() {
+        Err(WalletError::BadParameter(format!(
+            "{} is not a budget contract",
+            process_id
+        )))?;
+    }
+    let mut expr = BudgetState::deserialize(&account.data)
+        .ok()
+        .and_then(|budget_state| budget_state.pending_budget)
+        .ok_or_else(|| {
+            WalletError::BadParameter(format!(
+                "Contract {} has already been paid or canceled",
+                process_id
+            ))
+        })?;
 
-    Ok(signature_str.to_string())
+    // Only the cancel authority's signature refunds the contract to itself
+    expr.apply_witness(&Witness::Signature, authority);
+    match expr.final_payment() {
+        Some(ref payment) if payment.to == *authority => Ok(()),
+        _ => Err(WalletError::BadParameter(format!(
+            "{} is not the cancel authority of contract {}",
+            authority, process_id
+        ))),
+    }
 }
 
-fn process_witness(
+/// Cancel the contract at `process_id`, sending its refund to `to` instead of this wallet
+fn process_redirect(
     rpc_client: &RpcClient,
     config: &WalletConfig,
-    drone_addr: SocketAddr,
+    process_id: &Pubkey,
     to: &Pubkey,
-    pubkey: &Pubkey,
 ) -> ProcessResult {
-    let balance = rpc_client.retry_get_balance(&config.id.pubkey(), 5)?;
-
-    if let Some(0) = balance {
-        request_and_confirm_airdrop(&rpc_client, &drone_addr, &config.id.pubkey(), 1)?;
-    }
+    let account = rpc_client.get_account(process_id).map_err(|_| {
+        WalletError::BadParameter(format!("Unable to find contract {}", process_id))
+    })?;
+    verify_cancel_authority(process_id, &account, &config.id.pubkey())?;
 
     let blockhash = rpc_client.get_recent_blockhash()?;
-    let mut tx = BudgetTransaction::new_signature(&config.id, pubkey, to, blockhash);
-    let signature_str = rpc_client.send_and_confirm_transaction(&mut tx, &config.id)?;
-
+    let mut tx = BudgetTransaction::new_signature(&config.id, process_id, to, blockhash);
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
     Ok(signature_str.to_string())
 }
 
-pub fn process_command(config: &WalletConfig) -> ProcessResult {
-    if let WalletCommand::Address = config.command {
-        // Get address of this client
-        return Ok(format!("{}", config.id.pubkey()));
+/// Move the identity's entire balance, less the transaction fee, to `to`
+fn process_drain(rpc_client: &RpcClient, config: &WalletConfig, to: &Pubkey) -> ProcessResult {
+    let balance = rpc_client
+        .retry_get_balance(&config.id.pubkey(), 5)?
+        .ok_or_else(|| {
+            WalletError::RpcRequestError("Received result of an unexpected type".to_string())
+        })?;
+    let fee_calculator = rpc_client.get_fee_calculator()?;
+    let blockhash = rpc_client.get_recent_blockhash()?;
+
+    // The fee doesn't depend on the amount moved, so price the transaction before filling it in
+    let fee = fee_calculator.calculate_fee(&SystemTransaction::new_move(
+        &config.id, to, 0, blockhash, 0,
+    ));
+    if balance <= fee {
+        Err(WalletError::BadParameter(format!(
+            "Balance of {} lamports can't cover the {} lamport fee",
+            balance, fee
+        )))?;
     }
 
-    let drone_addr = config.drone_addr();
+    let mut tx = SystemTransaction::new_move(&config.id, to, balance - fee, blockhash, fee);
+    let signature_str = send_and_confirm_audited(rpc_client, config, &mut tx, &config.id)?;
+    Ok(signature_str.to_string())
+}
 
-    let mut _rpc_client;
-    let rpc_client = if config.rpc_client.is_none() {
-        let rpc_addr = config.rpc_addr();
-        _rpc_client = RpcClient::new(rpc_addr);
-        &_rpc_client
-    } else {
-        // Primarily for testing
-        config.rpc_client.as_ref().unwrap()
+fn process_get_genesis_hash(rpc_client: &RpcClient) -> ProcessResult {
+    let genesis_hash = rpc_client.get_genesis_hash()?;
+    Ok(genesis_hash.to_string())
+}
+
+fn process_get_transaction_count(
+    rpc_client: &RpcClient,
+    sample: Option<Duration>,
+) -> ProcessResult {
+    let interval = match sample {
+        None => {
+            let transaction_count = rpc_client.get_transaction_count()?;
+            return Ok(transaction_count.to_string());
+        }
+        Some(interval) if interval == Duration::from_secs(0) => Err(WalletError::BadParameter(
+            "Sample window must be at least one second".to_string(),
+        ))?,
+        Some(interval) => interval,
     };
 
-    match config.command {
-        // Get address of this client
-        WalletCommand::Address => unreachable!(),
+    let start = rpc_client.get_transaction_count_and_slot()?;
+    sleep(interval);
+    let end = rpc_client.get_transaction_count_and_slot()?;
+    Ok(sample_rates(start, end, interval).to_string())
+}
 
-        // Request an airdrop from Solana Drone;
-        WalletCommand::Airdrop(lamports) => {
-            process_airdrop(&rpc_client, config, drone_addr, lamports)
+fn process_monitor(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    pubkey: Option<Pubkey>,
+) -> ProcessResult {
+    let pubkey = pubkey.unwrap_or_else(|| config.id.pubkey());
+    let mut seen = HashSet::new();
+    loop {
+        for line in poll_new_signatures(rpc_client, &pubkey, &mut seen)? {
+            println!("{}", line);
         }
+        sleep(Duration::from_secs(1));
+    }
+}
 
-        // Check client balance
-        WalletCommand::Balance => process_balance(config, &rpc_client),
+/// Return a line for each transaction involving `pubkey` that has settled since the last
+/// poll. Pending transactions are kept out of `seen` so their final status is reported once
+/// they settle.
+fn poll_new_signatures(
+    rpc_client: &RpcClient,
+    pubkey: &Pubkey,
+    seen: &mut HashSet<String>,
+) -> Result<Vec<String>, Box<dyn error::Error>> {
+    let mut lines = vec![];
+    for signature in rpc_client.get_signatures_for_address(pubkey)? {
+        if seen.contains(&signature) {
+            continue;
+        }
+        let status = rpc_client.get_signature_status(&signature)?;
+        if status == RpcSignatureStatus::SignatureNotFound {
+            continue;
+        }
+        lines.push(format!("{} {:?}", signature, status));
+        seen.insert(signature);
+    }
+    Ok(lines)
+}
 
-        // Cancel a contract by contract Pubkey
-        WalletCommand::Cancel(pubkey) => process_cancel(&rpc_client, config, &pubkey),
+/// Summarize two `(transaction_count, slot)` readings taken `interval` apart. A reading that
+/// went backwards means the node restarted in between, so its rate is reported as "n/a".
+fn sample_rates(start: (u64, u64), end: (u64, u64), interval: Duration) -> serde_json::Value {
+    let secs = interval.as_secs() as f64 + f64::from(interval.subsec_nanos()) / 1_000_000_000.0;
+    let rate = |start: u64, end: u64| {
+        if end < start {
+            json!("n/a")
+        } else {
+            json!((end - start) as f64 / secs)
+        }
+    };
+    json!({
+        "transactionCount": end.0,
+        "transactionsPerSecond": rate(start.0, end.0),
+        "slotsPerSecond": rate(start.1, end.1),
+    })
+}
 
-        // Confirm the last client transaction by signature
-        WalletCommand::Confirm(signature) => process_confirm(&rpc_client, signature),
+/// Read the keypair at `path`, first generating one there if the file doesn't exist
+fn read_or_gen_keypair(path: &Path) -> Result<Keypair, Box<dyn error::Error>> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| WalletError::BadParameter(format!("Invalid path: {:?}", path)))?;
+    if !Path::new(path).exists() {
+        gen_keypair_file(path.to_string())?;
+    }
+    Ok(read_keypair(path)?)
+}
 
-        // Configure staking account already created
-        WalletCommand::ConfigureStakingAccount(delegate_option, authorized_voter_option) => {
-            process_configure_staking(
-                &rpc_client,
+/// Return the state of the vote account `vote_account_id`, or None if it hasn't been
+/// created yet
+fn get_vote_state(
+    rpc_client: &RpcClient,
+    vote_account_id: &Pubkey,
+) -> Result<Option<VoteState>, Box<dyn error::Error>> {
+    match rpc_client.retry_get_balance(vote_account_id, 5)? {
+        Some(0) => return Ok(None),
+        Some(_) => (),
+        None => Err(WalletError::RpcRequestError(
+            "Received result of an unexpected type".to_string(),
+        ))?,
+    }
+    let account = rpc_client.get_account(vote_account_id)?;
+    if account.owner != solana_vote_api::id() {
+        Err(WalletError::BadParameter(format!(
+            "{} is not a vote account",
+            vote_account_id
+        )))?;
+    }
+    Ok(Some(VoteState::deserialize(&account.data)?))
+}
+
+/// Create the keypairs at `identity_path` and `vote_account_path` if needed, then create
+/// a vote account holding `stake` lamports and delegate it to the identity, airdropping
+/// to the identity first if it can't pay for that. Steps that are already done are
+/// skipped, so a failed setup can simply be run again.
+fn process_setup_validator(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    drone_addr: SocketAddr,
+    identity_path: &Path,
+    vote_account_path: &Path,
+    stake: u64,
+) -> ProcessResult {
+    let identity = read_or_gen_keypair(identity_path)?;
+    let vote_keypair = read_or_gen_keypair(vote_account_path)?;
+    let identity_id = identity.pubkey();
+    let vote_account_id = vote_keypair.pubkey();
+
+    let fee_calculator = rpc_client.get_fee_calculator()?;
+    let mut airdrop_lamports = 0;
+    let mut signatures = vec![];
+    match get_vote_state(rpc_client, &vote_account_id)? {
+        Some(ref vote_state) if vote_state.delegate_id == identity_id => (),
+        Some(_) => {
+            let blockhash = rpc_client.get_recent_blockhash()?;
+            let mut tx =
+                VoteTransaction::delegate_vote_account(&vote_keypair, blockhash, &identity_id, 0);
+            signatures.push(send_and_confirm_with_fee(
+                rpc_client,
                 config,
-                delegate_option,
-                authorized_voter_option,
-            )
+                &mut tx,
+                &vote_keypair,
+            )?);
         }
-
-        // Create staking account
-        WalletCommand::CreateStakingAccount(voting_account_id, lamports) => {
-            process_create_staking(&rpc_client, config, &voting_account_id, lamports)
+        None => {
+            let minimum_delegation = rpc_client.get_minimum_delegation()?;
+            if stake < minimum_delegation {
+                Err(WalletError::BadParameter(format!(
+                    "Staking accounts must be created with at least {} lamports",
+                    minimum_delegation
+                )))?;
+            }
+            let blockhash = rpc_client.get_recent_blockhash()?;
+            let mut tx = VoteTransaction::new_account_with_delegate(
+                &identity,
+                &vote_keypair,
+                &identity_id,
+                blockhash,
+                stake,
+                0,
+            );
+            tx.fee = fee_calculator.calculate_fee(&tx);
+            tx.sign(&[&identity, &vote_keypair], blockhash);
+
+            let balance = rpc_client
+                .retry_get_balance(&identity_id, 5)?
+                .ok_or_else(|| {
+                    WalletError::RpcRequestError(
+                        "Received result of an unexpected type".to_string(),
+                    )
+                })?;
+            if balance < stake + tx.fee {
+                airdrop_lamports = stake + tx.fee - balance;
+                request_and_confirm_airdrop(
+                    rpc_client,
+                    &drone_addr,
+                    &identity_id,
+                    airdrop_lamports,
+                )?;
+            }
+            signatures.push(send_and_confirm_audited_with_signers(
+                rpc_client,
+                config,
+                &mut tx,
+                &[&identity, &vote_keypair],
+            )?);
         }
+    }
 
-        // Deploy a custom program to the chain
-        WalletCommand::Deploy(ref program_location) => {
-            process_deploy(&rpc_client, config, program_location)
-        }
+    Ok(json!({
+        "identity": format!("{}", identity_id),
+        "voteAccount": format!("{}", vote_account_id),
+        "stake": stake,
+        "airdropLamports": airdrop_lamports,
+        "signatures": signatures,
+    })
+    .to_string())
+}
 
-        WalletCommand::GetTransactionCount => process_get_transaction_count(&rpc_client),
+/// Render an account as JSON, or, if `program` names the program that owns it, as the
+/// pretty-printed program state. A `program` hint that doesn't match the account's owner
+/// falls back to the raw output.
+fn format_account(pubkey: &Pubkey, account: &Account, program: Option<&str>) -> ProcessResult {
+    let raw_account = || {
+        json!({
+            "pubkey": format!("{}", pubkey),
+            "lamports": account.lamports,
+            "owner": format!("{}", account.owner),
+            "executable": account.executable,
+            "data": bs58::encode(&account.data).into_string(),
+        })
+        .to_string()
+    };
 
-        // If client has positive balance, pay lamports to another address
-        WalletCommand::Pay(
-            lamports,
-            to,
-            timestamp,
-            timestamp_pubkey,
-            ref witnesses,
-            cancelable,
-        ) => process_pay(
-            &rpc_client,
-            config,
-            lamports,
-            &to,
-            timestamp,
-            timestamp_pubkey,
-            witnesses,
-            cancelable,
+    let program = match program {
+        Some(program) => program,
+        None => return Ok(raw_account()),
+    };
+    let (program_id, decoded) = match program {
+        "budget" => (
+            solana_budget_api::id(),
+            BudgetState::deserialize(&account.data).map(|state| format!("{:#?}", state)),
         ),
+        "rewards" => (
+            solana_rewards_api::id(),
+            RewardsState::deserialize(&account.data).map(|state| format!("{:#?}", state)),
+        ),
+        "vote" => (
+            solana_vote_api::id(),
+            VoteState::deserialize(&account.data).map(|state| format!("{:#?}", state)),
+        ),
+        _ => Err(WalletError::BadParameter(format!(
+            "Unknown program: {}",
+            program
+        )))?,
+    };
 
-        // Apply time elapsed to contract
-        WalletCommand::TimeElapsed(to, pubkey, dt) => {
-            process_time_elapsed(&rpc_client, config, drone_addr, &to, &pubkey, dt)
-        }
-
-        // Apply witness signature to contract
-        WalletCommand::Witness(to, pubkey) => {
-            process_witness(&rpc_client, config, drone_addr, &to, &pubkey)
+    if account.owner != program_id {
+        eprintln!(
+            "Warning: account {} is owned by {}, not the {} program; showing raw account",
+            pubkey, account.owner, program
+        );
+        return Ok(raw_account());
+    }
+    match decoded {
+        Ok(decoded) => Ok(decoded),
+        Err(err) => {
+            eprintln!(
+                "Warning: unable to decode account {} as {} state: {:?}; showing raw account",
+                pubkey, program, err
+            );
+            Ok(raw_account())
         }
     }
 }
 
-// Quick and dirty Keypair that assumes the client will do retries but not update the
-// blockhash. If the client updates the blockhash, the signature will be invalid.
-// TODO: Parse `msg` and use that data to make a new airdrop request.
-struct DroneKeypair {
+/// A transaction passed between the parties that must sign it, each adding their signature
+#[derive(Debug, PartialEq)]
+struct PartialTransaction {
+    /// The transaction, without signatures
     transaction: Transaction,
+    /// The keys that must sign it, its leading account keys
+    signers: Vec<Pubkey>,
+    /// The signatures collected so far, in the order they were added
+    signatures: Vec<(Pubkey, Signature)>,
 }
 
-impl DroneKeypair {
-    fn new_keypair(
-        drone_addr: &SocketAddr,
-        to_pubkey: &Pubkey,
-        lamports: u64,
-        blockhash: Hash,
-    ) -> Result<Self, Box<dyn error::Error>> {
-        let transaction = request_airdrop_transaction(drone_addr, to_pubkey, lamports, blockhash)?;
-        Ok(Self { transaction })
+impl PartialTransaction {
+    fn new(mut transaction: Transaction, signers: Vec<Pubkey>) -> Self {
+        transaction.signatures.clear();
+        Self {
+            transaction,
+            signers,
+            signatures: vec![],
+        }
     }
 
-    fn airdrop_transaction(&self) -> Transaction {
-        self.transaction.clone()
+    /// Read the transaction written to `path`, failing if its signer list doesn't lead its
+    /// account keys or any signature it carries is invalid
+    fn load(path: &Path) -> Result<Self, Box<dyn error::Error>> {
+        let bad_file =
+            |message: &str| WalletError::BadParameter(format!("{}: {}", path.display(), message));
+        let value: serde_json::Value = serde_json::from_reader(File::open(path)?)?;
+        let transaction: Transaction = value["transaction"]
+            .as_str()
+            .and_then(|data| bs58::decode(data).into_vec().ok())
+            .and_then(|data| bincode::deserialize(&data).ok())
+            .ok_or_else(|| bad_file("no transaction"))?;
+        let parse_pubkey = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|pubkey| pubkey.parse().ok())
+                .ok_or_else(|| bad_file("malformed pubkey"))
+        };
+        let signers: Vec<Pubkey> = value["signers"]
+            .as_array()
+            .ok_or_else(|| bad_file("no signers"))?
+            .iter()
+            .map(&parse_pubkey)
+            .collect::<Result<_, _>>()?;
+        let mut partial = Self::new(transaction, signers);
+        let signatures = value["signatures"]
+            .as_array()
+            .ok_or_else(|| bad_file("no signatures"))?;
+        for entry in signatures {
+            let signature = entry["signature"]
+                .as_str()
+                .and_then(|signature| signature.parse().ok())
+                .ok_or_else(|| bad_file("malformed signature"))?;
+            partial.add_signature(parse_pubkey(&entry["pubkey"])?, signature)?;
+        }
+
+        let account_keys = &partial.transaction.account_keys;
+        if partial.signers.len() > account_keys.len()
+            || partial.signers[..] != account_keys[..partial.signers.len()]
+        {
+            Err(bad_file(
+                "signers don't match the transaction's account keys",
+            ))?;
+        }
+        Ok(partial)
     }
-}
 
-impl KeypairUtil for DroneKeypair {
-    fn new() -> Self {
-        unimplemented!();
+    fn save(&self, path: &Path) -> Result<(), Box<dyn error::Error>> {
+        let signatures: Vec<_> = self
+            .signatures
+            .iter()
+            .map(|(pubkey, signature)| {
+                json!({
+                    "pubkey": pubkey.to_string(),
+                    "signature": signature.to_string(),
+                })
+            })
+            .collect();
+        let value = json!({
+            "transaction": bs58::encode(bincode::serialize(&self.transaction)?).into_string(),
+            "signers": self.signers.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            "signatures": signatures,
+        });
+        fs::write(path, serde_json::to_string_pretty(&value)?)?;
+        Ok(())
     }
 
-    /// Return the public key of the keypair used to sign votes
-    fn pubkey(&self) -> Pubkey {
-        self.transaction.account_keys[0]
+    /// The signers that haven't signed yet
+    fn missing_signers(&self) -> Vec<Pubkey> {
+        self.signers
+            .iter()
+            .filter(|signer| self.signatures.iter().all(|(pubkey, _)| pubkey != *signer))
+            .cloned()
+            .collect()
     }
 
-    fn sign_message(&self, _msg: &[u8]) -> Signature {
-        self.transaction.signatures[0]
+    /// Add `pubkey`'s `signature`, failing unless `pubkey` must sign and hasn't yet, and
+    /// `signature` signs this transaction
+    fn add_signature(&mut self, pubkey: Pubkey, signature: Signature) -> Result<(), WalletError> {
+        if !self.missing_signers().contains(&pubkey) {
+            let message = if self.signers.contains(&pubkey) {
+                format!("{} already signed this transaction", pubkey)
+            } else {
+                format!("{} isn't a signer of this transaction", pubkey)
+            };
+            Err(WalletError::BadParameter(message))?;
+        }
+        if !signature.verify(pubkey.as_ref(), &self.transaction.message()) {
+            Err(WalletError::BadParameter(format!(
+                "The signature of {} doesn't match this transaction",
+                pubkey
+            )))?;
+        }
+        self.signatures.push((pubkey, signature));
+        Ok(())
+    }
+
+    fn sign<T: KeypairUtil>(&mut self, keypair: &T) -> Result<(), WalletError> {
+        let signature = keypair.sign_message(&self.transaction.message());
+        self.add_signature(keypair.pubkey(), signature)
+    }
+
+    /// The transaction with every signature in place, failing if any is still missing
+    fn into_signed(self) -> Result<Transaction, WalletError> {
+        let missing = self.missing_signers();
+        if !missing.is_empty() {
+            let missing: Vec<_> = missing.iter().map(Pubkey::to_string).collect();
+            Err(WalletError::BadParameter(format!(
+                "Still waiting on signatures from {}",
+                missing.join(", ")
+            )))?;
+        }
+        let PartialTransaction {
+            mut transaction,
+            signers,
+            signatures,
+        } = self;
+        transaction.signatures = signers
+            .iter()
+            .map(|signer| {
+                signatures
+                    .iter()
+                    .find(|(pubkey, _)| pubkey == signer)
+                    .unwrap()
+                    .1
+            })
+            .collect();
+        Ok(transaction)
+    }
+
+    /// Report that the transaction was written to `path` and who has yet to sign it
+    fn describe(&self, path: &Path) -> String {
+        let missing: Vec<_> = self
+            .missing_signers()
+            .iter()
+            .map(Pubkey::to_string)
+            .collect();
+        json!({
+            "path": path.display().to_string(),
+            "missingSigners": missing,
+        })
+        .to_string()
     }
 }
 
-pub fn request_and_confirm_airdrop(
+fn process_sign_transaction(
     rpc_client: &RpcClient,
-    drone_addr: &SocketAddr,
-    to_pubkey: &Pubkey,
-    lamports: u64,
-) -> Result<(), Box<dyn error::Error>> {
-    let blockhash = rpc_client.get_recent_blockhash()?;
-    let keypair = DroneKeypair::new_keypair(drone_addr, to_pubkey, lamports, blockhash)?;
-    let mut tx = keypair.airdrop_transaction();
-    rpc_client.send_and_confirm_transaction(&mut tx, &keypair)?;
-    Ok(())
-}
+    config: &WalletConfig,
+    path: &Path,
+    submit: bool,
+) -> ProcessResult {
+    let mut partial = PartialTransaction::load(path)?;
+    // Signing again is a no-op, so the last signer may submit in a separate step
+    let signed = partial
+        .signatures
+        .iter()
+        .any(|(pubkey, _)| *pubkey == config.id.pubkey());
+    if !signed {
+        partial.sign(&config.id)?;
+    }
+    if !submit {
+        partial.save(path)?;
+        return Ok(partial.describe(path));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::{App, Arg, ArgGroup, SubCommand};
-    use serde_json::Value;
-    use solana_client::mock_rpc_client_request::SIGNATURE;
-    use solana_sdk::signature::{gen_keypair_file, read_keypair, read_pkcs8, Keypair, KeypairUtil};
-    use std::fs;
-    use std::net::{Ipv4Addr, SocketAddr};
-    use std::path::{Path, PathBuf};
+    let mut tx = partial.into_signed()?;
+    audit_signed_transaction(config, &tx)?;
+    rpc_client.trace(&describe_transaction(&tx));
+    let signature_str = rpc_client.send_and_confirm_presigned_transaction(&mut tx)?;
+    Ok(signature_str)
+}
 
-    #[test]
-    fn test_wallet_config_drone_addr() {
-        let mut config = WalletConfig::default();
-        assert_eq!(
-            config.drone_addr(),
-            SocketAddr::new(config.host, config.drone_port)
-        );
+fn process_show_account(
+    rpc_client: &RpcClient,
+    pubkey: &Pubkey,
+    program: Option<&str>,
+) -> ProcessResult {
+    let account = rpc_client.get_account(pubkey)?;
+    format_account(pubkey, &account, program)
+}
 
-        config.drone_port = 1234;
-        assert_eq!(config.drone_addr(), SocketAddr::new(config.host, 1234));
+/// List the vote accounts delegated to `node_id`, either as they stand now or as
+/// snapshotted for `epoch`'s leader schedule
+fn process_show_delegations(
+    rpc_client: &RpcClient,
+    node_id: &Pubkey,
+    epoch: Option<u64>,
+) -> ProcessResult {
+    let mut vote_accounts = match epoch {
+        Some(epoch) => rpc_client.get_epoch_vote_accounts(epoch)?,
+        None => rpc_client.get_program_accounts(&solana_vote_api::id())?,
+    };
+    vote_accounts.sort_by_key(|(pubkey, _)| *pubkey);
+
+    let delegations: Vec<_> = vote_accounts
+        .iter()
+        .filter_map(|(pubkey, account)| {
+            let vote_state = VoteState::deserialize(&account.data).ok()?;
+            if vote_state.delegate_id != *node_id {
+                return None;
+            }
+            Some(json!({
+                "voteAccount": format!("{}", pubkey),
+                "lamports": account.lamports,
+                "credits": vote_state.credits(),
+                "lastVote": vote_state.votes.back().map(|lockout| lockout.slot),
+            }))
+        })
+        .collect();
+    Ok(json!(delegations).to_string())
+}
 
-        config.drone_host = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
-        assert_eq!(
-            config.drone_addr(),
-            SocketAddr::new(config.drone_host.unwrap(), 1234)
-        );
+/// Show the lamports held by all accounts summed per owning program and, if `top` is
+/// given, the `top` largest balances not owned by any of `excluded_owners`
+fn process_supply(
+    rpc_client: &RpcClient,
+    top: Option<usize>,
+    excluded_owners: &[Pubkey],
+) -> ProcessResult {
+    let supply = rpc_client.get_supply()?;
+    let total: u64 = supply.iter().map(|(_, lamports)| lamports).sum();
+    let owners: Vec<_> = supply
+        .iter()
+        .map(|(owner, lamports)| {
+            json!({
+                "program": format!("{}", owner),
+                "lamports": lamports,
+            })
+        })
+        .collect();
+    let mut result = json!({
+        "total": total,
+        "owners": owners,
+    });
+    if let Some(top) = top {
+        let largest: Vec<_> = rpc_client
+            .get_largest_accounts(top, excluded_owners)?
+            .iter()
+            .map(|(pubkey, lamports)| {
+                json!({
+                    "account": format!("{}", pubkey),
+                    "lamports": lamports,
+                })
+            })
+            .collect();
+        result["largest"] = json!(largest);
     }
+    Ok(result.to_string())
+}
 
-    #[test]
-    fn test_wallet_config_rpc_addr() {
-        let mut config = WalletConfig::default();
-        assert_eq!(config.rpc_addr(), "http://127.0.0.1:8899");
-        config.rpc_port = 1234;
-        assert_eq!(config.rpc_addr(), "http://127.0.0.1:1234");
-        config.rpc_host = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
-        assert_eq!(config.rpc_addr(), "http://127.0.0.2:1234");
+/// Return an error naming what the contract expects if it isn't waiting on `witness` from
+/// `from`, or if the witness would complete a payment to someone other than `to`.
+fn verify_witness_target(
+    process_id: &Pubkey,
+    account: &Account,
+    witness: &Witness,
+    from: &Pubkey,
+    to: &Pubkey,
+) -> Result<(), WalletError> {
+    if account.owner != solana_budget_api::id() {
+        Err(WalletError::BadParameter(format!(
+            "{} is not a budget contract",
+            process_id
+        )))?;
     }
+    let expr = BudgetState::deserialize(&account.data)
+        .ok()
+        .and_then(|budget_state| budget_state.pending_budget)
+        .ok_or_else(|| {
+            WalletError::BadParameter(format!("Contract {} has no pending payment", process_id))
+        })?;
 
-    #[test]
-    fn test_wallet_parse_command() {
-        let test_commands = App::new("test")
-            .subcommand(SubCommand::with_name("address").about("Get your public key"))
-            .subcommand(
-                SubCommand::with_name("airdrop")
-                    .about("Request a batch of lamports")
-                    .arg(
-                        Arg::with_name("lamports")
-                            .index(1)
-                            .value_name("NUM")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The number of lamports to request"),
-                    ),
+    let (kind, witness_pubkeys) = match witness {
+        Witness::Signature => ("signature", expr.witness_pubkeys(witness)),
+        Witness::Timestamp(_) => ("timestamp", expr.witness_pubkeys(witness)),
+        Witness::BlockHeight(_) => ("block height", vec![]),
+    };
+    if !witness_pubkeys.contains(&from) {
+        let expected: Vec<_> = witness_pubkeys.iter().map(|key| key.to_string()).collect();
+        Err(WalletError::BadParameter(if expected.is_empty() {
+            format!("Contract {} doesn't accept a {}", process_id, kind)
+        } else {
+            format!(
+                "Contract {} expects a {} from {}, not {}",
+                process_id,
+                kind,
+                expected.join(" or "),
+                from
             )
-            .subcommand(SubCommand::with_name("balance").about("Get your balance"))
-            .subcommand(
-                SubCommand::with_name("cancel")
-                    .about("Cancel a transfer")
-                    .arg(
-                        Arg::with_name("process_id")
-                            .index(1)
-                            .value_name("PROCESS_ID")
-                            .takes_value(true)
+        }))?;
+    }
+
+    let mut expr = expr;
+    expr.apply_witness(witness, from);
+    if let Some(payment) = expr.final_payment() {
+        if payment.to != *to {
+            Err(WalletError::BadParameter(format!(
+                "Contract {} pays {}, not {}",
+                process_id, payment.to, to
+            )))?;
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the contract at `process_id` and check that it expects `witness` from this wallet
+fn check_witness_target(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    witness: &Witness,
+    to: &Pubkey,
+    process_id: &Pubkey,
+) -> Result<(), WalletError> {
+    let account = rpc_client.get_account(process_id).map_err(|_| {
+        WalletError::BadParameter(format!("Unable to find contract {}", process_id))
+    })?;
+    verify_witness_target(process_id, &account, witness, &config.id.pubkey(), to)
+}
+
+fn process_time_elapsed(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    drone_addr: SocketAddr,
+    to: &Pubkey,
+    pubkey: &Pubkey,
+    dt: DateTime<Utc>,
+) -> ProcessResult {
+    let balance = rpc_client.retry_get_balance(&config.id.pubkey(), 5)?;
+
+    if let Some(0) = balance {
+        request_and_confirm_airdrop(&rpc_client, &drone_addr, &config.id.pubkey(), 1)?;
+    }
+
+    let blockhash = rpc_client.get_recent_blockhash()?;
+
+    let mut tx = BudgetTransaction::new_timestamp(&config.id, pubkey, to, dt, blockhash);
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+
+    Ok(signature_str.to_string())
+}
+
+fn process_witness(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    drone_addr: SocketAddr,
+    to: &Pubkey,
+    pubkey: &Pubkey,
+) -> ProcessResult {
+    let balance = rpc_client.retry_get_balance(&config.id.pubkey(), 5)?;
+
+    if let Some(0) = balance {
+        request_and_confirm_airdrop(&rpc_client, &drone_addr, &config.id.pubkey(), 1)?;
+    }
+
+    let blockhash = rpc_client.get_recent_blockhash()?;
+    let mut tx = BudgetTransaction::new_signature(&config.id, pubkey, to, blockhash);
+    let signature_str = send_and_confirm_with_fee(rpc_client, config, &mut tx, &config.id)?;
+
+    Ok(signature_str.to_string())
+}
+
+/// Process the transactions serialized in `transactions_path` against a local bank started
+/// from the genesis block in `ledger_path`, for reproducing divergence without a cluster. A
+/// failed transaction is reported and skipped rather than ending the replay.
+fn process_replay_ledger(ledger_path: &Path, transactions_path: &Path) -> ProcessResult {
+    let genesis_block = GenesisBlock::load(&ledger_path.to_string_lossy())?;
+    let file = File::open(transactions_path)?;
+    let transactions: Vec<Transaction> = bincode::deserialize_from(file)?;
+
+    let bank = Bank::new(&genesis_block);
+    let mut lines = vec![];
+    for (i, tx) in transactions.iter().enumerate() {
+        if let Err(err) = bank.process_transaction(tx) {
+            lines.push(format!("Transaction {} failed: {}", i, err));
+        }
+    }
+    bank.freeze();
+
+    lines.push(format!("Bank hash: {}", bank.hash()));
+    lines.push(format!("Transaction count: {}", bank.transaction_count()));
+    Ok(lines.join("\n"))
+}
+
+/// Return true if `command` submits transactions to the cluster
+fn modifies_state(command: &WalletCommand) -> bool {
+    match command {
+        WalletCommand::Address
+        | WalletCommand::Balance(_)
+        | WalletCommand::Confirm(_)
+        | WalletCommand::FollowConfirmation(..)
+        | WalletCommand::GetGenesisHash
+        | WalletCommand::GetTransactionCount(_)
+        | WalletCommand::Monitor(_)
+        | WalletCommand::Pay(.., Some(_))
+        | WalletCommand::ReplayLedger(_, _)
+        | WalletCommand::ShowAccount(_, _)
+        | WalletCommand::ShowDelegations(_, _)
+        | WalletCommand::Supply(_, _) => false,
+        WalletCommand::Airdrop(_)
+        | WalletCommand::Cancel(_)
+        | WalletCommand::ConfigureStakingAccount(_, _)
+        | WalletCommand::CreateStakingAccount(_, _)
+        | WalletCommand::Deploy(_)
+        | WalletCommand::Drain(_)
+        | WalletCommand::Pay(..)
+        | WalletCommand::PayOnSchedule(..)
+        | WalletCommand::ReleaseDue(_)
+        | WalletCommand::Redirect(_, _)
+        | WalletCommand::SetupValidator(..)
+        | WalletCommand::TimeElapsed(_, _, _, _)
+        | WalletCommand::Witness(_, _, _) => true,
+        WalletCommand::SignTransaction(_, submit) => *submit,
+    }
+}
+
+/// Return the name, recipient and amount to show when asking before submitting `command`
+fn command_summary(command: &WalletCommand) -> (&'static str, Option<Pubkey>, Option<u64>) {
+    match *command {
+        WalletCommand::Airdrop(lamports) => ("airdrop", None, Some(lamports)),
+        WalletCommand::Cancel(_) => ("cancel", None, None),
+        WalletCommand::ConfigureStakingAccount(_, _) => ("configure-staking-account", None, None),
+        WalletCommand::CreateStakingAccount(voting_account_id, lamports) => (
+            "create-staking-account",
+            Some(voting_account_id),
+            Some(lamports),
+        ),
+        WalletCommand::Deploy(_) => ("deploy", None, None),
+        WalletCommand::Drain(to) => ("drain", Some(to), None),
+        WalletCommand::Pay(lamports, to, ..) | WalletCommand::PayOnSchedule(lamports, to, ..) => {
+            ("pay", Some(to), Some(lamports))
+        }
+        WalletCommand::Redirect(_, to) => ("redirect", Some(to), None),
+        WalletCommand::ReleaseDue(_) => ("release-due", None, None),
+        WalletCommand::SetupValidator(_, _, stake) => ("setup-validator", None, Some(stake)),
+        WalletCommand::SignTransaction(_, _) => ("sign-transaction", None, None),
+        WalletCommand::TimeElapsed(to, _, _, _) => ("send-timestamp", Some(to), None),
+        WalletCommand::Witness(to, _, _) => ("send-signature", Some(to), None),
+        _ => unreachable!("{:?} doesn't modify state", command),
+    }
+}
+
+/// Print `summary` to `writer` and return true if the answer read from `reader` is yes
+pub fn confirm_submission<R: BufRead, W: Write>(
+    summary: &str,
+    reader: &mut R,
+    writer: &mut W,
+) -> io::Result<bool> {
+    write!(writer, "{}\nSubmit? [y/N] ", summary)?;
+    writer.flush()?;
+    let mut answer = String::new();
+    reader.read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Ask on `writer` before submitting `config.command`, if `config.confirm` calls for it, and
+/// read the answer from `reader`. Return false if the user declined.
+fn confirm_command<R: BufRead, W: Write>(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    stdin_is_tty: bool,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<bool, Box<dyn error::Error>> {
+    if !config.confirm.should_prompt(stdin_is_tty) {
+        return Ok(true);
+    }
+
+    let (name, recipient, lamports) = command_summary(&config.command);
+    let fee_calculator = rpc_client.get_fee_calculator()?;
+    let blockhash = rpc_client.get_recent_blockhash()?;
+    let mut summary = vec![format!("Command: {}", name)];
+    if let Some(recipient) = recipient {
+        summary.push(format!("Recipient: {}", recipient));
+    }
+    if let Some(lamports) = lamports {
+        summary.push(format!("Lamports: {}", lamports));
+    }
+    summary.push(format!(
+        "Fee: {} lamports per signature",
+        fee_calculator.lamports_per_signature
+    ));
+    summary.push(format!("Blockhash: {}", blockhash));
+
+    Ok(confirm_submission(&summary.join("\n"), reader, writer)?)
+}
+
+/// Fail unless the cluster behind `rpc_client` was started from the `expected` genesis block
+fn check_genesis_hash(
+    rpc_client: &RpcClient,
+    expected: Option<Hash>,
+) -> Result<(), Box<dyn error::Error>> {
+    if let Some(expected) = expected {
+        let found = rpc_client.get_genesis_hash()?;
+        if found != expected {
+            Err(WalletError::GenesisHashMismatch(expected, found))?;
+        }
+    }
+    Ok(())
+}
+
+/// Warn when the node behind `rpc_client` isn't producing slots or trails
+/// `config.reference_rpc_client`, or fail if `config.strict_health` is set
+fn check_node_health(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+) -> Result<(), Box<dyn error::Error>> {
+    let problem = match rpc_client.health_check(
+        config.reference_rpc_client.as_ref(),
+        config.max_slots_behind,
+        HEALTH_CHECK_INTERVAL,
+    ) {
+        Ok(NodeHealth::CaughtUp) => return Ok(()),
+        Ok(health) => health.to_string(),
+        Err(err) => format!("unable to check node health: {}", err),
+    };
+    if config.strict_health {
+        Err(WalletError::UnhealthyNode(problem))?;
+    } else {
+        eprintln!("Warning: {}", problem);
+    }
+    Ok(())
+}
+
+pub fn process_command(config: &WalletConfig) -> ProcessResult {
+    if let WalletCommand::Address = config.command {
+        // Get address of this client
+        return Ok(format!("{}", config.id.pubkey()));
+    }
+
+    let drone_addr = config.drone_addr();
+
+    let mut _rpc_client;
+    let rpc_client = if config.rpc_client.is_none() {
+        let rpc_addr = config.rpc_addr();
+        _rpc_client = RpcClient::new(rpc_addr);
+        if config.verbose {
+            _rpc_client = _rpc_client.with_request_logging();
+        }
+        &_rpc_client
+    } else {
+        // Primarily for testing
+        config.rpc_client.as_ref().unwrap()
+    };
+
+    // Refuse to send transactions to a cluster other than the one the user expects
+    if modifies_state(&config.command) {
+        check_genesis_hash(&rpc_client, config.expected_genesis_hash).map_err(map_client_error)?;
+        check_node_health(&rpc_client, config)?;
+        let stdin = io::stdin();
+        if !confirm_command(
+            &rpc_client,
+            config,
+            atty::is(atty::Stream::Stdin),
+            &mut stdin.lock(),
+            &mut io::stdout(),
+        )
+        .map_err(map_client_error)?
+        {
+            return Ok("Aborted".to_string());
+        }
+    }
+
+    let result = match config.command {
+        // Get address of this client
+        WalletCommand::Address => unreachable!(),
+
+        // Request an airdrop from Solana Drone;
+        WalletCommand::Airdrop(lamports) => {
+            process_airdrop(&rpc_client, config, drone_addr, lamports)
+        }
+
+        // Check client balance
+        WalletCommand::Balance(commitment) => process_balance(config, &rpc_client, commitment),
+
+        // Cancel a contract by contract Pubkey
+        WalletCommand::Cancel(pubkey) => process_cancel(&rpc_client, config, &pubkey),
+
+        // Confirm client transactions by signature
+        WalletCommand::Confirm(ref signatures) => process_confirm(&rpc_client, signatures),
+
+        // Report the confirmation depth of a transaction as it grows
+        WalletCommand::FollowConfirmation(ref signature, target_depth, timeout) => {
+            follow_confirmation(
+                &rpc_client,
+                signature,
+                target_depth,
+                Duration::from_secs(timeout),
+                Duration::from_secs(1),
+                &mut io::stdout(),
+            )
+        }
+
+        // Configure staking account already created
+        WalletCommand::ConfigureStakingAccount(delegate_option, authorized_voter_option) => {
+            process_configure_staking(
+                &rpc_client,
+                config,
+                delegate_option,
+                authorized_voter_option,
+            )
+        }
+
+        // Create staking account
+        WalletCommand::CreateStakingAccount(voting_account_id, lamports) => {
+            process_create_staking(&rpc_client, config, &voting_account_id, lamports)
+        }
+
+        // Deploy a custom program to the chain
+        WalletCommand::Deploy(ref program_location) => {
+            process_deploy(&rpc_client, config, program_location)
+        }
+
+        // Move the entire balance to another address
+        WalletCommand::Drain(to) => process_drain(&rpc_client, config, &to),
+
+        // Get the hash of the cluster's genesis block
+        WalletCommand::GetGenesisHash => process_get_genesis_hash(&rpc_client),
+
+        WalletCommand::GetTransactionCount(sample) => {
+            process_get_transaction_count(&rpc_client, sample.map(Duration::from_secs))
+        }
+
+        // Print new transactions involving an account until interrupted
+        WalletCommand::Monitor(pubkey) => process_monitor(&rpc_client, config, pubkey),
+
+        // If client has positive balance, pay lamports to another address
+        WalletCommand::Pay(
+            lamports,
+            to,
+            timestamp,
+            timestamp_pubkey,
+            ref witnesses,
+            cancelable,
+            auto_cancel_after,
+            ref memo,
+            allow_unfunded_recipient,
+            verify,
+            ref export_unsigned,
+        ) => process_pay(
+            &rpc_client,
+            config,
+            lamports,
+            &to,
+            timestamp,
+            timestamp_pubkey,
+            witnesses,
+            cancelable,
+            auto_cancel_after,
+            memo,
+            allow_unfunded_recipient,
+            verify,
+            export_unsigned,
+        ),
+
+        // Pay in installments on a schedule
+        WalletCommand::PayOnSchedule(
+            lamports,
+            to,
+            start,
+            interval,
+            count,
+            timestamp_pubkey,
+            allow_unfunded_recipient,
+        ) => process_pay_on_schedule(
+            &rpc_client,
+            config,
+            lamports,
+            &to,
+            start,
+            interval,
+            count,
+            timestamp_pubkey,
+            allow_unfunded_recipient,
+        ),
+
+        // Show the contents of an account, optionally decoded by its owning program
+        WalletCommand::ShowAccount(pubkey, ref program) => {
+            process_show_account(&rpc_client, &pubkey, program.as_ref().map(String::as_str))
+        }
+
+        // List the vote accounts delegated to a node
+        WalletCommand::ShowDelegations(node_id, epoch) => {
+            process_show_delegations(&rpc_client, &node_id, epoch)
+        }
+
+        // Show how the supply is split among programs and who holds the most
+        WalletCommand::Supply(top, ref excluded_owners) => {
+            process_supply(&rpc_client, top, excluded_owners)
+        }
+
+        // Send a cancelable contract's refund to someone else
+        WalletCommand::Redirect(process_id, to) => {
+            process_redirect(&rpc_client, config, &process_id, &to)
+        }
+
+        // Release the due installments of a scheduled payment
+        WalletCommand::ReleaseDue(process_id) => {
+            process_release_due(&rpc_client, config, &process_id)
+        }
+
+        // Replay serialized transactions against a local bank
+        WalletCommand::ReplayLedger(ref ledger_path, ref transactions_path) => {
+            process_replay_ledger(ledger_path, transactions_path)
+        }
+
+        // Create a validator identity and a vote account delegated to it
+        WalletCommand::SetupValidator(ref identity_path, ref vote_account_path, stake) => {
+            process_setup_validator(
+                &rpc_client,
+                config,
+                drone_addr,
+                identity_path,
+                vote_account_path,
+                stake,
+            )
+        }
+
+        // Add this client's signature to a transaction other parties must sign too
+        WalletCommand::SignTransaction(ref path, submit) => {
+            process_sign_transaction(&rpc_client, config, path, submit)
+        }
+
+        // Apply time elapsed to contract
+        WalletCommand::TimeElapsed(to, pubkey, dt, force) => {
+            if !force {
+                check_witness_target(&rpc_client, config, &Witness::Timestamp(dt), &to, &pubkey)?;
+            }
+            process_time_elapsed(&rpc_client, config, drone_addr, &to, &pubkey, dt)
+        }
+
+        // Apply witness signature to contract
+        WalletCommand::Witness(to, pubkey, force) => {
+            if !force {
+                check_witness_target(&rpc_client, config, &Witness::Signature, &to, &pubkey)?;
+            }
+            process_witness(&rpc_client, config, drone_addr, &to, &pubkey)
+        }
+    };
+    result.map_err(map_client_error)
+}
+
+// Quick and dirty Keypair that assumes the client will do retries but not update the
+// blockhash. If the client updates the blockhash, the signature will be invalid.
+// TODO: Parse `msg` and use that data to make a new airdrop request.
+struct DroneKeypair {
+    transaction: Transaction,
+}
+
+impl DroneKeypair {
+    fn new_keypair(
+        drone_addr: &SocketAddr,
+        to_pubkey: &Pubkey,
+        lamports: u64,
+        blockhash: Hash,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        let transaction = request_airdrop_transaction(drone_addr, to_pubkey, lamports, blockhash)?;
+        Ok(Self { transaction })
+    }
+
+    fn airdrop_transaction(&self) -> Transaction {
+        self.transaction.clone()
+    }
+}
+
+impl KeypairUtil for DroneKeypair {
+    fn new() -> Self {
+        unimplemented!();
+    }
+
+    /// Return the public key of the keypair used to sign votes
+    fn pubkey(&self) -> Pubkey {
+        self.transaction.account_keys[0]
+    }
+
+    fn sign_message(&self, _msg: &[u8]) -> Signature {
+        self.transaction.signatures[0]
+    }
+}
+
+/// Declare the fee `fee_calculator` charges for `tx`, re-signing it with `signer`
+fn apply_fee<T: KeypairUtil>(fee_calculator: &FeeCalculator, tx: &mut Transaction, signer: &T) {
+    tx.fee = fee_calculator.calculate_fee(tx);
+    let blockhash = tx.recent_blockhash;
+    tx.sign(&[signer], blockhash);
+}
+
+/// Describe `tx` for the RPC trace, with its signatures cut short
+fn describe_transaction(tx: &Transaction) -> String {
+    let signatures: Vec<_> = tx
+        .signatures
+        .iter()
+        .map(|signature| redact_signature(&signature.to_string()))
+        .collect();
+    let to_strings =
+        |pubkeys: &[Pubkey]| -> Vec<String> { pubkeys.iter().map(Pubkey::to_string).collect() };
+    let instructions: Vec<_> = tx
+        .instructions
+        .iter()
+        .map(|instruction| {
+            json!({
+                "programIdIndex": instruction.program_ids_index,
+                "accounts": instruction.accounts,
+                "data": bs58::encode(&instruction.data).into_string(),
+            })
+        })
+        .collect();
+    let description = json!({
+        "signatures": signatures,
+        "accountKeys": to_strings(&tx.account_keys),
+        "recentBlockhash": tx.recent_blockhash.to_string(),
+        "fee": tx.fee,
+        "programIds": to_strings(&tx.program_ids),
+        "instructions": instructions,
+    });
+    format!(
+        "sending transaction {}",
+        serde_json::to_string_pretty(&description).unwrap()
+    )
+}
+
+/// Append `tx`'s message hash and signatures to the audit log, if one is configured
+fn audit_signed_transaction(config: &WalletConfig, tx: &Transaction) -> io::Result<()> {
+    if let Some(ref audit_log) = config.audit_log {
+        let signatures: Vec<_> = tx.signatures.iter().map(Signature::to_string).collect();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(audit_log)?;
+        writeln!(
+            file,
+            "{} {} {}",
+            Utc::now().to_rfc3339(),
+            hash(&tx.message()),
+            signatures.join(",")
+        )?;
+    }
+    Ok(())
+}
+
+/// Send `tx`, recording it in the audit log before it goes out and again if it's re-signed
+/// along the way, so the log covers transactions that later fail
+fn send_and_confirm_audited<T: KeypairUtil>(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    tx: &mut Transaction,
+    signer: &T,
+) -> Result<String, Box<dyn error::Error>> {
+    send_and_confirm_audited_with_signers(rpc_client, config, tx, &[signer])
+}
+
+/// Like `send_and_confirm_audited`, for a transaction signed by each of `signers`
+fn send_and_confirm_audited_with_signers<T: KeypairUtil>(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    tx: &mut Transaction,
+    signers: &[&T],
+) -> Result<String, Box<dyn error::Error>> {
+    audit_signed_transaction(config, tx)?;
+    rpc_client.trace(&describe_transaction(tx));
+    let signatures = tx.signatures.clone();
+    let result = rpc_client.send_and_confirm_transaction_with_signers(tx, signers);
+    if tx.signatures != signatures {
+        audit_signed_transaction(config, tx)?;
+    }
+    Ok(result?)
+}
+
+/// Send `tx` with the fee the cluster's current fee schedule charges for it
+fn send_and_confirm_with_fee<T: KeypairUtil>(
+    rpc_client: &RpcClient,
+    config: &WalletConfig,
+    tx: &mut Transaction,
+    signer: &T,
+) -> Result<String, Box<dyn error::Error>> {
+    let fee_calculator = rpc_client.get_fee_calculator()?;
+    apply_fee(&fee_calculator, tx, signer);
+    send_and_confirm_audited(rpc_client, config, tx, signer)
+}
+
+pub fn request_and_confirm_airdrop(
+    rpc_client: &RpcClient,
+    drone_addr: &SocketAddr,
+    to_pubkey: &Pubkey,
+    lamports: u64,
+) -> Result<(), Box<dyn error::Error>> {
+    let blockhash = rpc_client.get_recent_blockhash()?;
+    let keypair = DroneKeypair::new_keypair(drone_addr, to_pubkey, lamports, blockhash)?;
+    let mut tx = keypair.airdrop_transaction();
+    rpc_client.send_and_confirm_transaction(&mut tx, &keypair)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{App, AppSettings, Arg, ArgGroup, SubCommand};
+    use serde_json::Value;
+    use solana_client::mock_rpc_client_request::{
+        monitor_signature, BALANCE_INCREMENT, LAMPORTS_PER_SIGNATURE, MINIMUM_DELEGATION, PUBKEY,
+        SIGNATURE, SLOT, STAKED_SUPPLY, SUPPLY, VALIDATOR_IDENTITY_SEED,
+    };
+    use solana_drone::drone::REQUEST_CAP;
+    use solana_sdk::signature::{
+        gen_keypair_file, gen_keypair_file_from_seed, gen_pkcs8_from_seed, read_keypair,
+        read_pkcs8, Keypair, KeypairUtil, SeedableKeypair,
+    };
+    use solana_vote_api::vote_state::MAX_LOCKOUT_HISTORY;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::io::Cursor;
+    use std::mem;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_wallet_config_drone_addr() {
+        let mut config = WalletConfig::default();
+        assert_eq!(
+            config.drone_addr(),
+            SocketAddr::new(config.host, config.drone_port)
+        );
+
+        config.drone_port = 1234;
+        assert_eq!(config.drone_addr(), SocketAddr::new(config.host, 1234));
+
+        config.drone_host = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+        assert_eq!(
+            config.drone_addr(),
+            SocketAddr::new(config.drone_host.unwrap(), 1234)
+        );
+    }
+
+    #[test]
+    fn test_wallet_config_rpc_addr() {
+        let mut config = WalletConfig::default();
+        assert_eq!(config.rpc_addr(), "http://127.0.0.1:8899");
+        config.rpc_port = 1234;
+        assert_eq!(config.rpc_addr(), "http://127.0.0.1:1234");
+        config.rpc_host = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+        assert_eq!(config.rpc_addr(), "http://127.0.0.2:1234");
+    }
+
+    #[test]
+    fn test_wallet_parse_command() {
+        let test_commands = App::new("test")
+            .subcommand(SubCommand::with_name("address").about("Get your public key"))
+            .subcommand(
+                SubCommand::with_name("airdrop")
+                    .about("Request a batch of lamports")
+                    .arg(
+                        Arg::with_name("lamports")
+                            .index(1)
+                            .value_name("NUM")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The number of lamports to request"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("balance")
+                    .about("Get your balance")
+                    .arg(
+                        Arg::with_name("commitment")
+                            .long("commitment")
+                            .value_name("COMMITMENT")
+                            .takes_value(true)
+                            .possible_values(&["root", "frozen", "recent"])
+                            .help("Read from the root bank, the newest frozen bank, or the working bank [default: recent]"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("cancel")
+                    .about("Cancel a transfer")
+                    .arg(
+                        Arg::with_name("process_id")
+                            .index(1)
+                            .value_name("PROCESS_ID")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The process id of the transfer to cancel"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("confirm")
+                    .about("Confirm transaction by signature")
+                    .arg(
+                        Arg::with_name("signature")
+                            .index(1)
+                            .value_name("SIGNATURE")
+                            .takes_value(true)
+                            .multiple(true)
+                            .required(true)
+                            .help("The transaction signatures to confirm"),
+                    )
+                    .arg(
+                        Arg::with_name("follow")
+                            .long("follow")
+                            .help("Keep reporting the transaction's confirmation depth as it grows"),
+                    )
+                    .arg(
+                        Arg::with_name("target_depth")
+                            .long("target-depth")
+                            .value_name("NUM")
+                            .takes_value(true)
+                            .default_value("32")
+                            .help("With --follow, stop once the transaction is this many slots deep"),
+                    )
+                    .arg(
+                        Arg::with_name("timeout")
+                            .long("timeout")
+                            .value_name("SECONDS")
+                            .takes_value(true)
+                            .default_value("60")
+                            .help("With --follow, give up after this many seconds"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("configure-staking-account")
+                    .about("Configure staking account for node")
+                    .group(
+                        ArgGroup::with_name("options")
+                            .args(&["delegate", "authorize"])
+                            .multiple(true)
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::with_name("delegate")
+                            .long("delegate-account")
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .help("Address to delegate this vote account to"),
+                    )
+                    .arg(
+                        Arg::with_name("authorize")
+                            .long("authorize-voter")
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .help("Vote signer to authorize"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("create-staking-account")
+                    .about("Create staking account for node")
+                    .arg(
+                        Arg::with_name("voting_account_id")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Staking account address to fund"),
+                    )
+                    .arg(
+                        Arg::with_name("lamports")
+                            .index(2)
+                            .value_name("NUM")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The number of lamports to send to staking account"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("deploy")
+                    .about("Deploy a program")
+                    .arg(
+                        Arg::with_name("program_location")
+                            .index(1)
+                            .value_name("PATH")
+                            .takes_value(true)
+                            .required(true)
+                            .help("/path/to/program.o"),
+                    ), // TODO: Add "loader" argument; current default is bpf_loader
+            )
+            .subcommand(
+                SubCommand::with_name("drain")
+                    .about("Send the entire balance, less the fee, to another address")
+                    .arg(
+                        Arg::with_name("to")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The pubkey of recipient"),
+                    )
+                    .arg(
+                        Arg::with_name("yes")
+                            .long("yes")
+                            .help("Skip the confirmation prompt"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("get-genesis-hash")
+                    .about("Get the hash of the cluster's genesis block"),
+            )
+            .subcommand(
+                SubCommand::with_name("get-transaction-count")
+                    .about("Get current transaction count")
+                    .arg(
+                        Arg::with_name("sample")
+                            .long("sample")
+                            .value_name("SECONDS")
+                            .takes_value(true)
+                            .help("Also report transactions and slots per second over a window of this many seconds"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("monitor")
+                    .about("Print transactions involving an account as they're confirmed")
+                    .arg(
+                        Arg::with_name("pubkey")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .help("The account to monitor [default: your public key]"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("pay")
+                    .about("Send a payment")
+                    .arg(
+                        Arg::with_name("to")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The pubkey of recipient"),
+                    )
+                    .arg(
+                        Arg::with_name("lamports")
+                            .index(2)
+                            .value_name("NUM")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The number of lamports to send"),
+                    )
+                    .arg(
+                        Arg::with_name("timestamp")
+                            .long("after")
+                            .value_name("DATETIME")
+                            .takes_value(true)
+                            .help("A timestamp after which transaction will execute"),
+                    )
+                    .arg(
+                        Arg::with_name("timestamp_pubkey")
+                            .long("require-timestamp-from")
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .requires("timestamp")
+                            .help("Require timestamp from this third party"),
+                    )
+                    .arg(
+                        Arg::with_name("witness")
+                            .long("require-signature-from")
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .multiple(true)
+                            .use_delimiter(true)
+                            .help("Any third party signatures required to unlock the lamports"),
+                    )
+                    .arg(
+                        Arg::with_name("cancelable")
+                            .long("cancelable")
+                            .takes_value(false),
+                    )
+                    .arg(
+                        Arg::with_name("auto_cancel_after")
+                            .long("auto-cancel-after")
+                            .value_name("SECS")
+                            .takes_value(true)
+                            .requires("cancelable"),
+                    )
+                    .arg(
+                        Arg::with_name("memo")
+                            .long("memo")
+                            .value_name("TEXT")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("allow_unfunded_recipient")
+                            .long("allow-unfunded-recipient"),
+                    )
+                    .arg(
+                        Arg::with_name("verify")
+                            .long("verify")
+                            .conflicts_with_all(&["timestamp", "witness", "every"]),
+                    )
+                    .arg(
+                        Arg::with_name("export_unsigned")
+                            .long("export-unsigned")
+                            .value_name("PATH")
+                            .takes_value(true)
+                            .conflicts_with_all(&["timestamp", "witness", "every", "verify"]),
+                    )
+                    .arg(
+                        Arg::with_name("every")
+                            .long("every")
+                            .value_name("DURATION")
+                            .takes_value(true)
+                            .requires_all(&["timestamp", "count"])
+                            .conflicts_with_all(&["witness", "cancelable", "memo"]),
+                    )
+                    .arg(
+                        Arg::with_name("count")
+                            .long("count")
+                            .value_name("NUM")
+                            .takes_value(true)
+                            .requires("every"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("redirect")
+                    .about("Cancel a transfer, sending the refund to another address")
+                    .arg(
+                        Arg::with_name("process_id")
+                            .index(1)
+                            .value_name("PROCESS_ID")
+                            .takes_value(true)
                             .required(true)
-                            .help("The process id of the transfer to cancel"),
+                            .help("The process id of the transfer to redirect"),
+                    )
+                    .arg(
+                        Arg::with_name("to")
+                            .index(2)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The pubkey of the new recipient"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("release-due").arg(
+                    Arg::with_name("process_id")
+                        .index(1)
+                        .value_name("PROCESS_ID")
+                        .takes_value(true)
+                        .required(true),
+                ),
+            )
+            .subcommand(
+                SubCommand::with_name("replay-ledger")
+                    .about("Replay serialized transactions against a local bank")
+                    .setting(AppSettings::Hidden)
+                    .arg(
+                        Arg::with_name("ledger")
+                            .index(1)
+                            .value_name("LEDGER_DIR")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Directory containing the genesis block to start from"),
+                    )
+                    .arg(
+                        Arg::with_name("transactions")
+                            .index(2)
+                            .value_name("PATH")
+                            .takes_value(true)
+                            .required(true)
+                            .help("File of bincode-serialized transactions to process in order"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("send-signature")
+                    .about("Send a signature to authorize a transfer")
+                    .arg(
+                        Arg::with_name("to")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The pubkey of recipient"),
+                    )
+                    .arg(
+                        Arg::with_name("process_id")
+                            .index(2)
+                            .value_name("PROCESS_ID")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The process id of the transfer to authorize"),
+                    )
+                    .arg(
+                        Arg::with_name("force")
+                            .long("force")
+                            .takes_value(false)
+                            .help("Send the signature without checking that the contract expects it"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("send-timestamp")
+                    .about("Send a timestamp to unlock a transfer")
+                    .arg(
+                        Arg::with_name("to")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The pubkey of recipient"),
+                    )
+                    .arg(
+                        Arg::with_name("process_id")
+                            .index(2)
+                            .value_name("PROCESS_ID")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The process id of the transfer to unlock"),
+                    )
+                    .arg(
+                        Arg::with_name("datetime")
+                            .long("date")
+                            .value_name("DATETIME")
+                            .takes_value(true)
+                            .help("Optional arbitrary timestamp to apply"),
+                    )
+                    .arg(
+                        Arg::with_name("force")
+                            .long("force")
+                            .takes_value(false)
+                            .help("Send the timestamp without checking that the contract expects it"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("setup-validator")
+                    .about("Create a validator's identity and a vote account delegated to it")
+                    .arg(
+                        Arg::with_name("identity")
+                            .long("identity")
+                            .value_name("PATH")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Identity keypair file, generated if it doesn't exist"),
+                    )
+                    .arg(
+                        Arg::with_name("vote_account")
+                            .long("vote-account")
+                            .value_name("PATH")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Vote account keypair file, generated if it doesn't exist"),
+                    )
+                    .arg(
+                        Arg::with_name("stake")
+                            .long("stake")
+                            .value_name("LAMPORTS")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The number of lamports to fund the vote account with"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("show-account")
+                    .about("Show the contents of an account")
+                    .arg(
+                        Arg::with_name("account_id")
+                            .index(1)
+                            .value_name("PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Account pubkey"),
+                    )
+                    .arg(
+                        Arg::with_name("program")
+                            .long("program")
+                            .value_name("PROGRAM")
+                            .takes_value(true)
+                            .possible_values(&["budget", "rewards", "vote"])
+                            .help("Decode the account data as this program's state"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("show-delegations")
+                    .about("Show the vote accounts delegated to a node")
+                    .arg(
+                        Arg::with_name("node_id")
+                            .index(1)
+                            .value_name("NODE_PUBKEY")
+                            .takes_value(true)
+                            .required(true)
+                            .help("The node the vote accounts are delegated to"),
+                    )
+                    .arg(
+                        Arg::with_name("epoch")
+                            .long("epoch")
+                            .value_name("EPOCH")
+                            .takes_value(true)
+                            .help("Use the stakes snapshotted for this epoch's leader schedule"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("sign-transaction")
+                    .arg(
+                        Arg::with_name("path")
+                            .index(1)
+                            .value_name("PATH")
+                            .takes_value(true)
+                            .required(true),
+                    )
+                    .arg(Arg::with_name("submit").long("submit")),
+            )
+            .subcommand(
+                SubCommand::with_name("supply")
+                    .arg(
+                        Arg::with_name("top")
+                            .long("top")
+                            .value_name("NUM")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("exclude")
+                            .long("exclude")
+                            .value_name("PROGRAM_ID")
+                            .takes_value(true)
+                            .multiple(true)
+                            .use_delimiter(true)
+                            .requires("top"),
                     ),
+            );
+        let pubkey = Keypair::new().pubkey();
+        let pubkey_string = format!("{}", pubkey);
+        let witness0 = Keypair::new().pubkey();
+        let witness0_string = format!("{}", witness0);
+        let witness1 = Keypair::new().pubkey();
+        let witness1_string = format!("{}", witness1);
+        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+
+        // Test Airdrop Subcommand
+        let test_airdrop = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "airdrop", "50"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_airdrop).unwrap(),
+            WalletCommand::Airdrop(50)
+        );
+        let test_bad_airdrop = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "airdrop", "notint"]);
+        assert!(parse_command(&pubkey, &test_bad_airdrop).is_err());
+
+        // Test Balance Subcommand
+        let test_balance = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "balance"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_balance).unwrap(),
+            WalletCommand::Balance(Commitment::Recent)
+        );
+        let test_balance_frozen = test_commands.clone().get_matches_from(vec![
+            "test",
+            "balance",
+            "--commitment",
+            "frozen",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_balance_frozen).unwrap(),
+            WalletCommand::Balance(Commitment::Frozen)
+        );
+        let test_bad_balance = test_commands.clone().get_matches_from_safe(vec![
+            "test",
+            "balance",
+            "--commitment",
+            "final",
+        ]);
+        assert!(test_bad_balance.is_err());
+
+        // Test Cancel Subcommand
+        let test_cancel =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "cancel", &pubkey_string]);
+        assert_eq!(
+            parse_command(&pubkey, &test_cancel).unwrap(),
+            WalletCommand::Cancel(pubkey)
+        );
+
+        // Test Redirect Subcommand
+        let to = Keypair::new().pubkey();
+        let to_string = format!("{}", to);
+        let test_redirect = test_commands.clone().get_matches_from(vec![
+            "test",
+            "redirect",
+            &pubkey_string,
+            &to_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_redirect).unwrap(),
+            WalletCommand::Redirect(pubkey, to)
+        );
+        let test_redirect_no_recipient =
+            test_commands
+                .clone()
+                .get_matches_from_safe(vec!["test", "redirect", &pubkey_string]);
+        assert!(test_redirect_no_recipient.is_err());
+
+        // Test ReplayLedger Subcommand
+        let test_replay_ledger = test_commands.clone().get_matches_from(vec![
+            "test",
+            "replay-ledger",
+            "ledger",
+            "transactions.bin",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_replay_ledger).unwrap(),
+            WalletCommand::ReplayLedger(PathBuf::from("ledger"), PathBuf::from("transactions.bin"))
+        );
+
+        // Test SetupValidator Subcommand
+        let test_setup_validator = test_commands.clone().get_matches_from(vec![
+            "test",
+            "setup-validator",
+            "--identity",
+            "identity.json",
+            "--vote-account",
+            "vote.json",
+            "--stake",
+            "42",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_setup_validator).unwrap(),
+            WalletCommand::SetupValidator(
+                PathBuf::from("identity.json"),
+                PathBuf::from("vote.json"),
+                42
+            )
+        );
+        let test_bad_stake = test_commands.clone().get_matches_from(vec![
+            "test",
+            "setup-validator",
+            "--identity",
+            "identity.json",
+            "--vote-account",
+            "vote.json",
+            "--stake",
+            "lots",
+        ]);
+        assert!(parse_command(&pubkey, &test_bad_stake).is_err());
+
+        // Test Confirm Subcommand
+        let signature = Signature::new(&vec![1; 64]);
+        let signature_string = format!("{:?}", signature);
+        let test_confirm =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "confirm", &signature_string]);
+        assert_eq!(
+            parse_command(&pubkey, &test_confirm).unwrap(),
+            WalletCommand::Confirm(vec![signature])
+        );
+        let other_signature = Signature::new(&vec![2; 64]);
+        let other_signature_string = format!("{:?}", other_signature);
+        let test_confirm_many = test_commands.clone().get_matches_from(vec![
+            "test",
+            "confirm",
+            &signature_string,
+            &other_signature_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_confirm_many).unwrap(),
+            WalletCommand::Confirm(vec![signature, other_signature])
+        );
+        let too_many_signatures = vec![signature_string.as_str(); MAX_GET_SIGNATURE_STATUSES + 1];
+        let test_confirm_too_many = test_commands.clone().get_matches_from(
+            vec!["test", "confirm"]
+                .into_iter()
+                .chain(too_many_signatures),
+        );
+        assert!(parse_command(&pubkey, &test_confirm_too_many).is_err());
+        let test_confirm_follow = test_commands.clone().get_matches_from(vec![
+            "test",
+            "confirm",
+            &signature_string,
+            "--follow",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_confirm_follow).unwrap(),
+            WalletCommand::FollowConfirmation(signature, 32, 60)
+        );
+        let test_confirm_follow = test_commands.clone().get_matches_from(vec![
+            "test",
+            "confirm",
+            &signature_string,
+            "--follow",
+            "--target-depth",
+            "5",
+            "--timeout",
+            "10",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_confirm_follow).unwrap(),
+            WalletCommand::FollowConfirmation(signature, 5, 10)
+        );
+        let test_confirm_follow_many = test_commands.clone().get_matches_from(vec![
+            "test",
+            "confirm",
+            &signature_string,
+            &other_signature_string,
+            "--follow",
+        ]);
+        assert!(parse_command(&pubkey, &test_confirm_follow_many).is_err());
+        let test_bad_signature = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "confirm", "deadbeef"]);
+        assert!(parse_command(&pubkey, &test_bad_signature).is_err());
+
+        // Test Drain Subcommand
+        let test_drain =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "drain", &pubkey_string]);
+        assert_eq!(
+            parse_command(&pubkey, &test_drain).unwrap(),
+            WalletCommand::Drain(pubkey)
+        );
+        let test_drain_yes =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "drain", &pubkey_string, "--yes"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_drain_yes).unwrap(),
+            WalletCommand::Drain(pubkey)
+        );
+
+        // Test GetGenesisHash Subcommand
+        let test_genesis_hash = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "get-genesis-hash"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_genesis_hash).unwrap(),
+            WalletCommand::GetGenesisHash
+        );
+
+        // Test GetTransactionCount Subcommand
+        let test_count = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "get-transaction-count"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_count).unwrap(),
+            WalletCommand::GetTransactionCount(None)
+        );
+        let test_count_sample = test_commands.clone().get_matches_from(vec![
+            "test",
+            "get-transaction-count",
+            "--sample",
+            "5",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_count_sample).unwrap(),
+            WalletCommand::GetTransactionCount(Some(5))
+        );
+        let test_bad_sample = test_commands.clone().get_matches_from(vec![
+            "test",
+            "get-transaction-count",
+            "--sample",
+            "soon",
+        ]);
+        assert!(parse_command(&pubkey, &test_bad_sample).is_err());
+
+        // Test Monitor Subcommand
+        let test_monitor = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "monitor"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_monitor).unwrap(),
+            WalletCommand::Monitor(None)
+        );
+        let test_monitor_pubkey =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "monitor", &pubkey_string]);
+        assert_eq!(
+            parse_command(&pubkey, &test_monitor_pubkey).unwrap(),
+            WalletCommand::Monitor(Some(pubkey))
+        );
+        let test_bad_monitor_pubkey = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "monitor", "deadbeef"]);
+        assert!(parse_command(&pubkey, &test_bad_monitor_pubkey).is_err());
+
+        // Test ConfigureStakingAccount Subcommand
+        let second_pubkey = Keypair::new().pubkey();
+        let second_pubkey_string = format!("{}", second_pubkey);
+        let test_configure_staking_account = test_commands.clone().get_matches_from(vec![
+            "test",
+            "configure-staking-account",
+            "--delegate-account",
+            &pubkey_string,
+            "--authorize-voter",
+            &second_pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_configure_staking_account).unwrap(),
+            WalletCommand::ConfigureStakingAccount(Some(pubkey), Some(second_pubkey))
+        );
+        let test_configure_staking_account = test_commands.clone().get_matches_from(vec![
+            "test",
+            "configure-staking-account",
+            "--delegate-account",
+            &pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_configure_staking_account).unwrap(),
+            WalletCommand::ConfigureStakingAccount(Some(pubkey), None)
+        );
+
+        // Test CreateStakingAccount SubCommand
+        let test_create_staking_account = test_commands.clone().get_matches_from(vec![
+            "test",
+            "create-staking-account",
+            &pubkey_string,
+            "50",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_create_staking_account).unwrap(),
+            WalletCommand::CreateStakingAccount(pubkey, 50)
+        );
+        let test_bad_pubkey = test_commands.clone().get_matches_from(vec![
+            "test",
+            "create-staking-account",
+            "deadbeef",
+            "50",
+        ]);
+        assert!(parse_command(&pubkey, &test_bad_pubkey).is_err());
+
+        // Test Deploy Subcommand
+        let test_deploy =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "deploy", "/Users/test/program.o"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_deploy).unwrap(),
+            WalletCommand::Deploy("/Users/test/program.o".to_string())
+        );
+
+        // Test Simple Pay Subcommand
+        let test_pay =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "pay", &pubkey_string, "50"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay).unwrap(),
+            WalletCommand::Pay(50, pubkey, None, None, None, None, None, None, false, false, None)
+        );
+        let test_bad_pubkey = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "pay", "deadbeef", "50"]);
+        assert!(parse_command(&pubkey, &test_bad_pubkey).is_err());
+
+        // Test Pay Subcommand w/ Unfunded Recipient
+        let test_pay_unfunded = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--allow-unfunded-recipient",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_unfunded).unwrap(),
+            WalletCommand::Pay(50, pubkey, None, None, None, None, None, None, true, false, None)
+        );
+
+        // Test Pay Subcommand w/ Verify
+        let test_pay_verify = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--verify",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_verify).unwrap(),
+            WalletCommand::Pay(50, pubkey, None, None, None, None, None, None, false, true, None)
+        );
+
+        // Test Pay Subcommand w/ Export
+        let test_pay_export = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--export-unsigned",
+            "payment.json",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_export).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+                Some(PathBuf::from("payment.json"))
             )
-            .subcommand(
-                SubCommand::with_name("confirm")
-                    .about("Confirm transaction by signature")
-                    .arg(
-                        Arg::with_name("signature")
-                            .index(1)
-                            .value_name("SIGNATURE")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The transaction signature to confirm"),
-                    ),
+        );
+
+        // Test SignTransaction Subcommand
+        let test_sign_transaction = test_commands.clone().get_matches_from(vec![
+            "test",
+            "sign-transaction",
+            "payment.json",
+            "--submit",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_sign_transaction).unwrap(),
+            WalletCommand::SignTransaction(PathBuf::from("payment.json"), true)
+        );
+
+        // Test Pay Subcommand w/ Schedule
+        let test_pay_schedule = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "100",
+            "--after",
+            "2018-09-19T17:30:59",
+            "--every",
+            "30d",
+            "--count",
+            "3",
+        ]);
+        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_schedule).unwrap(),
+            WalletCommand::PayOnSchedule(
+                100,
+                pubkey,
+                dt,
+                chrono::Duration::days(30),
+                3,
+                None,
+                false
             )
-            .subcommand(
-                SubCommand::with_name("configure-staking-account")
-                    .about("Configure staking account for node")
-                    .group(
-                        ArgGroup::with_name("options")
-                            .args(&["delegate", "authorize"])
-                            .multiple(true)
-                            .required(true),
-                    )
-                    .arg(
-                        Arg::with_name("delegate")
-                            .long("delegate-account")
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .help("Address to delegate this vote account to"),
-                    )
-                    .arg(
-                        Arg::with_name("authorize")
-                            .long("authorize-voter")
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .help("Vote signer to authorize"),
-                    ),
+        );
+        let test_pay_schedule_without_start = test_commands.clone().get_matches_from_safe(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "100",
+            "--every",
+            "30d",
+            "--count",
+            "3",
+        ]);
+        assert!(test_pay_schedule_without_start.is_err());
+        let test_pay_bad_interval = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "100",
+            "--after",
+            "2018-09-19T17:30:59",
+            "--every",
+            "30y",
+            "--count",
+            "3",
+        ]);
+        assert!(parse_command(&pubkey, &test_pay_bad_interval).is_err());
+
+        // Test ReleaseDue Subcommand
+        let test_release_due =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "release-due", &pubkey_string]);
+        assert_eq!(
+            parse_command(&pubkey, &test_release_due).unwrap(),
+            WalletCommand::ReleaseDue(pubkey)
+        );
+
+        // Test Pay Subcommand w/ Memo
+        let test_pay_memo = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--memo",
+            "invoice 42",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_memo).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("invoice 42".to_string()),
+                false,
+                false,
+                None
             )
-            .subcommand(
-                SubCommand::with_name("create-staking-account")
-                    .about("Create staking account for node")
-                    .arg(
-                        Arg::with_name("voting_account_id")
-                            .index(1)
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .required(true)
-                            .help("Staking account address to fund"),
-                    )
-                    .arg(
-                        Arg::with_name("lamports")
-                            .index(2)
-                            .value_name("NUM")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The number of lamports to send to staking account"),
-                    ),
+        );
+
+        // Test Pay Subcommand w/ Witness
+        let test_pay_multiple_witnesses = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--require-signature-from",
+            &witness0_string,
+            "--require-signature-from",
+            &witness1_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_multiple_witnesses).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                None,
+                None,
+                Some(vec![witness0, witness1]),
+                None,
+                None,
+                None,
+                false,
+                false,
+                None
             )
-            .subcommand(
-                SubCommand::with_name("deploy")
-                    .about("Deploy a program")
-                    .arg(
-                        Arg::with_name("program_location")
-                            .index(1)
-                            .value_name("PATH")
-                            .takes_value(true)
-                            .required(true)
-                            .help("/path/to/program.o"),
-                    ), // TODO: Add "loader" argument; current default is bpf_loader
+        );
+        let test_pay_single_witness = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--require-signature-from",
+            &witness0_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_single_witness).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                None,
+                None,
+                Some(vec![witness0]),
+                None,
+                None,
+                None,
+                false,
+                false,
+                None
             )
-            .subcommand(
-                SubCommand::with_name("get-transaction-count")
-                    .about("Get current transaction count"),
+        );
+
+        // Test Pay Subcommand w/ Timestamp
+        let test_pay_timestamp = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--after",
+            "2018-09-19T17:30:59",
+            "--require-timestamp-from",
+            &witness0_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_timestamp).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                Some(dt),
+                Some(witness0),
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+                None
             )
-            .subcommand(
-                SubCommand::with_name("pay")
-                    .about("Send a payment")
-                    .arg(
-                        Arg::with_name("to")
-                            .index(1)
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The pubkey of recipient"),
-                    )
-                    .arg(
-                        Arg::with_name("lamports")
-                            .index(2)
-                            .value_name("NUM")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The number of lamports to send"),
-                    )
-                    .arg(
-                        Arg::with_name("timestamp")
-                            .long("after")
-                            .value_name("DATETIME")
-                            .takes_value(true)
-                            .help("A timestamp after which transaction will execute"),
-                    )
-                    .arg(
-                        Arg::with_name("timestamp_pubkey")
-                            .long("require-timestamp-from")
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .requires("timestamp")
-                            .help("Require timestamp from this third party"),
-                    )
-                    .arg(
-                        Arg::with_name("witness")
-                            .long("require-signature-from")
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .multiple(true)
-                            .use_delimiter(true)
-                            .help("Any third party signatures required to unlock the lamports"),
-                    )
-                    .arg(
-                        Arg::with_name("cancelable")
-                            .long("cancelable")
-                            .takes_value(false),
-                    ),
+        );
+
+        // Test Pay Subcommand w/ Auto-Cancel
+        let test_pay_auto_cancel = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--after",
+            "2018-09-19T17:30:59",
+            "--cancelable",
+            "--auto-cancel-after",
+            "30",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_auto_cancel).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                Some(dt),
+                None,
+                None,
+                Some(pubkey),
+                Some(30),
+                None,
+                false,
+                false,
+                None
             )
-            .subcommand(
-                SubCommand::with_name("send-signature")
-                    .about("Send a signature to authorize a transfer")
-                    .arg(
-                        Arg::with_name("to")
-                            .index(1)
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The pubkey of recipient"),
-                    )
-                    .arg(
-                        Arg::with_name("process_id")
-                            .index(2)
-                            .value_name("PROCESS_ID")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The process id of the transfer to authorize"),
-                    ),
+        );
+        let test_pay_auto_cancel_not_cancelable =
+            test_commands.clone().get_matches_from_safe(vec![
+                "test",
+                "pay",
+                &pubkey_string,
+                "50",
+                "--after",
+                "2018-09-19T17:30:59",
+                "--auto-cancel-after",
+                "30",
+            ]);
+        assert!(test_pay_auto_cancel_not_cancelable.is_err());
+
+        // Test Send-Signature Subcommand
+        let test_send_signature = test_commands.clone().get_matches_from(vec![
+            "test",
+            "send-signature",
+            &pubkey_string,
+            &pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_send_signature).unwrap(),
+            WalletCommand::Witness(pubkey, pubkey, false)
+        );
+        let test_send_signature_force = test_commands.clone().get_matches_from(vec![
+            "test",
+            "send-signature",
+            &pubkey_string,
+            &pubkey_string,
+            "--force",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_send_signature_force).unwrap(),
+            WalletCommand::Witness(pubkey, pubkey, true)
+        );
+        let test_pay_multiple_witnesses = test_commands.clone().get_matches_from(vec![
+            "test",
+            "pay",
+            &pubkey_string,
+            "50",
+            "--after",
+            "2018-09-19T17:30:59",
+            "--require-signature-from",
+            &witness0_string,
+            "--require-timestamp-from",
+            &witness0_string,
+            "--require-signature-from",
+            &witness1_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_pay_multiple_witnesses).unwrap(),
+            WalletCommand::Pay(
+                50,
+                pubkey,
+                Some(dt),
+                Some(witness0),
+                Some(vec![witness0, witness1]),
+                None,
+                None,
+                None,
+                false,
+                false,
+                None
             )
-            .subcommand(
-                SubCommand::with_name("send-timestamp")
-                    .about("Send a timestamp to unlock a transfer")
-                    .arg(
-                        Arg::with_name("to")
-                            .index(1)
-                            .value_name("PUBKEY")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The pubkey of recipient"),
-                    )
-                    .arg(
-                        Arg::with_name("process_id")
-                            .index(2)
-                            .value_name("PROCESS_ID")
-                            .takes_value(true)
-                            .required(true)
-                            .help("The process id of the transfer to unlock"),
-                    )
-                    .arg(
-                        Arg::with_name("datetime")
-                            .long("date")
-                            .value_name("DATETIME")
-                            .takes_value(true)
-                            .help("Optional arbitrary timestamp to apply"),
-                    ),
-            );
+        );
+
+        // Test Send-Timestamp Subcommand
+        let test_send_timestamp = test_commands.clone().get_matches_from(vec![
+            "test",
+            "send-timestamp",
+            &pubkey_string,
+            &pubkey_string,
+            "--date",
+            "2018-09-19T17:30:59",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_send_timestamp).unwrap(),
+            WalletCommand::TimeElapsed(pubkey, pubkey, dt, false)
+        );
+        let test_send_timestamp_force = test_commands.clone().get_matches_from(vec![
+            "test",
+            "send-timestamp",
+            &pubkey_string,
+            &pubkey_string,
+            "--date",
+            "2018-09-19T17:30:59",
+            "--force",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_send_timestamp_force).unwrap(),
+            WalletCommand::TimeElapsed(pubkey, pubkey, dt, true)
+        );
+        let test_bad_timestamp = test_commands.clone().get_matches_from(vec![
+            "test",
+            "send-timestamp",
+            &pubkey_string,
+            &pubkey_string,
+            "--date",
+            "20180919T17:30:59",
+        ]);
+        assert!(parse_command(&pubkey, &test_bad_timestamp).is_err());
+
+        // Test ShowAccount Subcommand
+        let test_show_account =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "show-account", &pubkey_string]);
+        assert_eq!(
+            parse_command(&pubkey, &test_show_account).unwrap(),
+            WalletCommand::ShowAccount(pubkey, None)
+        );
+        let test_show_account_program = test_commands.clone().get_matches_from(vec![
+            "test",
+            "show-account",
+            &pubkey_string,
+            "--program",
+            "budget",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_show_account_program).unwrap(),
+            WalletCommand::ShowAccount(pubkey, Some("budget".to_string()))
+        );
+
+        // Test ShowDelegations Subcommand
+        let test_show_delegations = test_commands.clone().get_matches_from(vec![
+            "test",
+            "show-delegations",
+            &pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_show_delegations).unwrap(),
+            WalletCommand::ShowDelegations(pubkey, None)
+        );
+        let test_show_delegations_epoch = test_commands.clone().get_matches_from(vec![
+            "test",
+            "show-delegations",
+            &pubkey_string,
+            "--epoch",
+            "3",
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_show_delegations_epoch).unwrap(),
+            WalletCommand::ShowDelegations(pubkey, Some(3))
+        );
+
+        // Test Supply Subcommand
+        let test_supply = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "supply"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_supply).unwrap(),
+            WalletCommand::Supply(None, vec![])
+        );
+        let test_supply_top = test_commands.clone().get_matches_from(vec![
+            "test",
+            "supply",
+            "--top",
+            "5",
+            "--exclude",
+            &format!("{},{}", witness0_string, witness1_string),
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_supply_top).unwrap(),
+            WalletCommand::Supply(Some(5), vec![witness0, witness1])
+        );
+        let too_many_accounts = (MAX_GET_LARGEST_ACCOUNTS + 1).to_string();
+        let test_supply_too_many = test_commands.clone().get_matches_from(vec![
+            "test",
+            "supply",
+            "--top",
+            &too_many_accounts,
+        ]);
+        assert!(parse_command(&pubkey, &test_supply_too_many).is_err());
+
+        // Malformed base58 and wrong-length keys are rejected by every subcommand
+        for bad_key in &["deadbeef", "0OIl", &pubkey_string.repeat(3)] {
+            let bad_args: Vec<Vec<&str>> = vec![
+                vec!["test", "cancel", bad_key],
+                vec!["test", "confirm", bad_key],
+                vec![
+                    "test",
+                    "configure-staking-account",
+                    "--delegate-account",
+                    bad_key,
+                ],
+                vec![
+                    "test",
+                    "configure-staking-account",
+                    "--authorize-voter",
+                    bad_key,
+                ],
+                vec!["test", "create-staking-account", bad_key, "50"],
+                vec!["test", "drain", bad_key],
+                vec!["test", "pay", bad_key, "50"],
+                vec![
+                    "test",
+                    "pay",
+                    &pubkey_string,
+                    "50",
+                    "--require-signature-from",
+                    bad_key,
+                ],
+                vec![
+                    "test",
+                    "pay",
+                    &pubkey_string,
+                    "50",
+                    "--after",
+                    "2018-09-19T17:30:59",
+                    "--require-timestamp-from",
+                    bad_key,
+                ],
+                vec!["test", "send-signature", bad_key, &pubkey_string],
+                vec!["test", "send-signature", &pubkey_string, bad_key],
+                vec!["test", "send-timestamp", bad_key, &pubkey_string],
+                vec!["test", "send-timestamp", &pubkey_string, bad_key],
+                vec!["test", "show-account", bad_key],
+                vec!["test", "show-delegations", bad_key],
+            ];
+            for args in bad_args {
+                let matches = test_commands.clone().get_matches_from(args.clone());
+                assert!(
+                    parse_command(&pubkey, &matches).is_err(),
+                    "{:?} should fail to parse",
+                    args
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pubkey_of() {
         let pubkey = Keypair::new().pubkey();
-        let pubkey_string = format!("{}", pubkey);
-        let witness0 = Keypair::new().pubkey();
-        let witness0_string = format!("{}", witness0);
-        let witness1 = Keypair::new().pubkey();
-        let witness1_string = format!("{}", witness1);
-        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+        let pubkey_string = pubkey.to_string();
+        let app = App::new("test").arg(Arg::with_name("to").takes_value(true));
+
+        let matches = app.clone().get_matches_from(vec!["test", &pubkey_string]);
+        assert_eq!(pubkey_of(&matches, "to").unwrap(), pubkey);
+
+        let matches = app.clone().get_matches_from(vec!["test", "0OIl"]);
+        assert!(pubkey_of(&matches, "to").is_err());
+
+        let matches = app.clone().get_matches_from(vec!["test", "deadbeef"]);
+        assert!(pubkey_of(&matches, "to").is_err());
+
+        let matches = app.clone().get_matches_from(vec!["test"]);
+        assert!(pubkey_of(&matches, "to").is_err());
+    }
+
+    #[test]
+    fn test_wallet_process_command() {
+        // Success cases
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey().to_string();
+        config.id = keypair;
+        config.command = WalletCommand::Address;
+        assert_eq!(process_command(&config).unwrap(), pubkey);
+
+        config.command = WalletCommand::Balance(Commitment::default());
+        assert_eq!(process_command(&config).unwrap(), "Your balance is: 50");
+
+        let process_id = Keypair::new().pubkey();
+        config.command = WalletCommand::Cancel(process_id);
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        let good_signature = Signature::new(&bs58::decode(SIGNATURE).into_vec().unwrap());
+        config.command = WalletCommand::Confirm(vec![good_signature]);
+        assert_eq!(process_command(&config).unwrap(), "Confirmed");
 
-        // Test Airdrop Subcommand
-        let test_airdrop = test_commands
-            .clone()
-            .get_matches_from(vec!["test", "airdrop", "50"]);
+        let bob_pubkey = Keypair::new().pubkey();
+        config.command = WalletCommand::ConfigureStakingAccount(None, Some(bob_pubkey));
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        config.command = WalletCommand::CreateStakingAccount(bob_pubkey, 10);
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        config.command = WalletCommand::CreateStakingAccount(bob_pubkey, MINIMUM_DELEGATION);
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        config.command = WalletCommand::CreateStakingAccount(bob_pubkey, MINIMUM_DELEGATION - 1);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::GetGenesisHash;
+        assert_eq!(process_command(&config).unwrap(), PUBKEY);
+
+        config.command = WalletCommand::GetTransactionCount(None);
+        assert_eq!(process_command(&config).unwrap(), "1234");
+
+        config.command = WalletCommand::GetTransactionCount(Some(0));
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, None, false, false, None,
+        );
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        let memo = Some("invoice 42".to_string());
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, memo, false, false, None,
+        );
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        let memo = Some("x".repeat(MAX_MEMO_LEN + 1));
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, memo, false, false, None,
+        );
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::BadParameter(message)) => {
+                assert!(message.contains("at most 128"), message.clone())
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        let date_string = "\"2018-09-19T17:30:59Z\"";
+        let dt: DateTime<Utc> = serde_json::from_str(&date_string).unwrap();
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            Some(dt),
+            Some(config.id.pubkey()),
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        );
+        let result = process_command(&config);
+        let json: Value = serde_json::from_str(&result.unwrap()).unwrap();
         assert_eq!(
-            parse_command(&pubkey, &test_airdrop).unwrap(),
-            WalletCommand::Airdrop(50)
+            json.as_object()
+                .unwrap()
+                .get("signature")
+                .unwrap()
+                .as_str()
+                .unwrap(),
+            SIGNATURE.to_string()
         );
-        let test_bad_airdrop = test_commands
-            .clone()
-            .get_matches_from(vec!["test", "airdrop", "notint"]);
-        assert!(parse_command(&pubkey, &test_bad_airdrop).is_err());
 
-        // Test Cancel Subcommand
-        let test_cancel =
-            test_commands
-                .clone()
-                .get_matches_from(vec!["test", "cancel", &pubkey_string]);
+        let witness = Keypair::new().pubkey();
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            None,
+            None,
+            Some(vec![witness]),
+            Some(config.id.pubkey()),
+            None,
+            None,
+            false,
+            false,
+            None,
+        );
+        let result = process_command(&config);
+        let json: Value = serde_json::from_str(&result.unwrap()).unwrap();
         assert_eq!(
-            parse_command(&pubkey, &test_cancel).unwrap(),
-            WalletCommand::Cancel(pubkey)
+            json.as_object()
+                .unwrap()
+                .get("signature")
+                .unwrap()
+                .as_str()
+                .unwrap(),
+            SIGNATURE.to_string()
         );
 
-        // Test Confirm Subcommand
-        let signature = Signature::new(&vec![1; 64]);
-        let signature_string = format!("{:?}", signature);
-        let test_confirm =
-            test_commands
-                .clone()
-                .get_matches_from(vec!["test", "confirm", &signature_string]);
+        let process_id = Keypair::new().pubkey();
+        config.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt, true);
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        let witness = Keypair::new().pubkey();
+        config.command = WalletCommand::Witness(bob_pubkey, witness, true);
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        config.command = WalletCommand::ShowAccount(bob_pubkey, None);
+        let result = process_command(&config);
+        let json: Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(json["lamports"], 50);
+
+        // Need airdrop cases
+        config.command = WalletCommand::Airdrop(50);
+        assert!(process_command(&config).is_err());
+
+        // Requests beyond the drone's remaining capacity are rejected up front
+        config.command = WalletCommand::Airdrop(REQUEST_CAP + 1);
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::BadParameter(message)) => {
+                assert!(message.contains("Drone can only dispense"), message.clone())
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        config.rpc_client = Some(RpcClient::new_mock("airdrop".to_string()));
+        config.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt, true);
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        let witness = Keypair::new().pubkey();
+        config.command = WalletCommand::Witness(bob_pubkey, witness, true);
+        let signature = process_command(&config);
+        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+
+        // bad_sig_status cases
+        config.rpc_client = Some(RpcClient::new_mock("bad_sig_status".to_string()));
+        let missing_signature = Signature::new(&bs58::decode("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW").into_vec().unwrap());
+        config.command = WalletCommand::Confirm(vec![missing_signature]);
+        assert_eq!(process_command(&config).unwrap(), "Not found");
+
+        config.rpc_client = Some(RpcClient::new_mock("program_not_executable".to_string()));
+        config.command = WalletCommand::Confirm(vec![missing_signature]);
         assert_eq!(
-            parse_command(&pubkey, &test_confirm).unwrap(),
-            WalletCommand::Confirm(signature)
+            process_command(&config).unwrap(),
+            "Failed: program not finalized/deployed"
         );
-        let test_bad_signature = test_commands
-            .clone()
-            .get_matches_from(vec!["test", "confirm", "deadbeef"]);
-        assert!(parse_command(&pubkey, &test_bad_signature).is_err());
 
-        // Test ConfigureStakingAccount Subcommand
-        let second_pubkey = Keypair::new().pubkey();
-        let second_pubkey_string = format!("{}", second_pubkey);
-        let test_configure_staking_account = test_commands.clone().get_matches_from(vec![
-            "test",
-            "configure-staking-account",
-            "--delegate-account",
-            &pubkey_string,
-            "--authorize-voter",
-            &second_pubkey_string,
-        ]);
+        // A lone failed signature reports its error like each of several would
+        config.rpc_client = Some(RpcClient::new_mock("account_in_use".to_string()));
+        config.command = WalletCommand::Confirm(vec![missing_signature]);
         assert_eq!(
-            parse_command(&pubkey, &test_configure_staking_account).unwrap(),
-            WalletCommand::ConfigureStakingAccount(Some(pubkey), Some(second_pubkey))
+            process_command(&config).unwrap(),
+            format!("Failed: {}", TransactionError::AccountInUse)
         );
-        let test_configure_staking_account = test_commands.clone().get_matches_from(vec![
-            "test",
-            "configure-staking-account",
-            "--delegate-account",
-            &pubkey_string,
-        ]);
+
+        // Several signatures get one line each, in order
+        config.rpc_client = Some(RpcClient::new_mock("mixed_sig_statuses".to_string()));
+        let signatures = vec![good_signature, missing_signature, Signature::new(&[3; 64])];
+        config.command = WalletCommand::Confirm(signatures.clone());
         assert_eq!(
-            parse_command(&pubkey, &test_configure_staking_account).unwrap(),
-            WalletCommand::ConfigureStakingAccount(Some(pubkey), None)
+            process_command(&config).unwrap(),
+            format!(
+                "{}: Confirmed\n{}: Failed: program not finalized/deployed\n{}: Not found",
+                signatures[0], signatures[1], signatures[2]
+            )
+        );
+
+        // Failure cases
+        config.rpc_client = Some(RpcClient::new_mock("fails".to_string()));
+
+        config.command = WalletCommand::Airdrop(50);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::Balance(Commitment::default());
+        assert!(process_command(&config).is_err());
+
+        let any_signature = Signature::new(&bs58::decode(SIGNATURE).into_vec().unwrap());
+        config.command = WalletCommand::Confirm(vec![any_signature]);
+        let err = process_command(&config).unwrap_err();
+        assert!(err.to_string().contains("Unable to confirm"));
+        config.command = WalletCommand::Confirm(vec![any_signature, any_signature]);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::ConfigureStakingAccount(None, Some(bob_pubkey));
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::CreateStakingAccount(bob_pubkey, 10);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::GetTransactionCount(None);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::GetTransactionCount(Some(1));
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, None, false, false, None,
+        );
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            Some(dt),
+            Some(config.id.pubkey()),
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        );
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            None,
+            None,
+            Some(vec![witness]),
+            Some(config.id.pubkey()),
+            None,
+            None,
+            false,
+            false,
+            None,
+        );
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt, true);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::ShowAccount(bob_pubkey, None);
+        assert!(process_command(&config).is_err());
+    }
+
+    #[test]
+    fn test_wallet_pay_auto_cancel() {
+        let mut config = WalletConfig::default();
+        let bob_pubkey = Keypair::new().pubkey();
+        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+
+        // The contract is still funded at the deadline, so it gets canceled
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            Some(dt),
+            None,
+            None,
+            Some(config.id.pubkey()),
+            Some(0),
+            None,
+            true,
+            false,
+            None,
+        );
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["status"], "canceled");
+        assert_eq!(json["cancelSignature"], SIGNATURE);
+
+        // The recipient claimed the contract, emptying it, so there's nothing to cancel. This
+        // mock reports every account empty, hence allowing the unfunded recipient above.
+        config.rpc_client = Some(RpcClient::new_mock("airdrop".to_string()));
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["status"], "claimed");
+        assert!(json.get("cancelSignature").is_none());
+
+        // Auto-cancel needs a cancelable contract
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            Some(dt),
+            None,
+            None,
+            None,
+            Some(0),
+            None,
+            false,
+            false,
+            None,
+        );
+        assert!(process_command(&config).is_err());
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            None,
+            None,
+            None,
+            Some(config.id.pubkey()),
+            Some(0),
+            None,
+            false,
+            false,
+            None,
         );
+        assert!(process_command(&config).is_err());
+    }
 
-        // Test CreateStakingAccount SubCommand
-        let test_create_staking_account = test_commands.clone().get_matches_from(vec![
-            "test",
-            "create-staking-account",
-            &pubkey_string,
-            "50",
-        ]);
-        assert_eq!(
-            parse_command(&pubkey, &test_create_staking_account).unwrap(),
-            WalletCommand::CreateStakingAccount(pubkey, 50)
+    #[test]
+    fn test_wallet_pay_unfunded_recipient() {
+        let mut config = WalletConfig::default();
+        let bob_pubkey = Keypair::new().pubkey();
+
+        // The recipient already has an account
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, None, false, false, None,
         );
-        let test_bad_pubkey = test_commands.clone().get_matches_from(vec![
-            "test",
-            "create-staking-account",
-            "deadbeef",
-            "50",
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // The recipient has never been seen, so paying it must be asked for explicitly
+        config.rpc_client = Some(RpcClient::new_mock("airdrop".to_string()));
+        let err = process_command(&config).unwrap_err();
+        assert!(err.to_string().contains("--allow-unfunded-recipient"));
+
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, None, true, false, None,
+        );
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+    }
+
+    #[test]
+    fn test_wallet_sign_transaction() {
+        let alice_seed = [1; 32];
+        let bob_seed = [2; 32];
+        let alice = Keypair::from_seed(&alice_seed);
+        let bob = Keypair::from_seed(&bob_seed);
+        let to = Keypair::new().pubkey();
+        let tx = Transaction::new(vec![
+            SystemInstruction::new_move(&alice.pubkey(), &to, 1),
+            SystemInstruction::new_move(&bob.pubkey(), &to, 2),
         ]);
-        assert!(parse_command(&pubkey, &test_bad_pubkey).is_err());
+        let signers = tx.account_keys[..2].to_vec();
+        assert_eq!(signers, vec![alice.pubkey(), bob.pubkey()]);
+        let path = PathBuf::from(tmp_file_path("test_wallet_sign_transaction"));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        PartialTransaction::new(tx, signers).save(&path).unwrap();
 
-        // Test Deploy Subcommand
-        let test_deploy =
-            test_commands
-                .clone()
-                .get_matches_from(vec!["test", "deploy", "/Users/test/program.o"]);
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+
+        // Alice can't submit before Bob signs
+        config.id = Keypair::from_seed(&alice_seed);
+        config.command = WalletCommand::SignTransaction(path.clone(), true);
+        let err = process_command(&config).unwrap_err();
+        assert!(err.to_string().contains(&bob.pubkey().to_string()));
+
+        config.command = WalletCommand::SignTransaction(path.clone(), false);
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["missingSigners"], json!([bob.pubkey().to_string()]));
+
+        // Only the transaction's signers may sign it
+        config.id = Keypair::new();
+        assert!(process_command(&config).is_err());
+
+        config.id = Keypair::from_seed(&bob_seed);
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["missingSigners"], json!([]));
+        let signed = PartialTransaction::load(&path)
+            .unwrap()
+            .into_signed()
+            .unwrap();
+        assert!(signed.verify_signature());
+        assert_eq!(signed.signatures.len(), 2);
+
+        config.command = WalletCommand::SignTransaction(path.clone(), true);
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // A signature that doesn't sign the transaction is rejected
+        let mut partial = PartialTransaction::load(&path).unwrap();
+        partial.signatures[0].1 = Signature::default();
+        partial.save(&path).unwrap();
+        assert!(PartialTransaction::load(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_wallet_pay_export_unsigned() {
+        let path = PathBuf::from(tmp_file_path("test_wallet_pay_export_unsigned"));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        config.command = WalletCommand::Pay(
+            10,
+            Keypair::new().pubkey(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            Some(path.clone()),
+        );
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
         assert_eq!(
-            parse_command(&pubkey, &test_deploy).unwrap(),
-            WalletCommand::Deploy("/Users/test/program.o".to_string())
+            json["missingSigners"],
+            json!([config.id.pubkey().to_string()])
         );
+        let partial = PartialTransaction::load(&path).unwrap();
+        assert!(partial.transaction.signatures.is_empty());
+        assert_eq!(partial.transaction.fee, LAMPORTS_PER_SIGNATURE);
 
-        // Test Simple Pay Subcommand
-        let test_pay =
-            test_commands
-                .clone()
-                .get_matches_from(vec!["test", "pay", &pubkey_string, "50"]);
-        assert_eq!(
-            parse_command(&pubkey, &test_pay).unwrap(),
-            WalletCommand::Pay(50, pubkey, None, None, None, None)
+        config.command = WalletCommand::SignTransaction(path.clone(), true);
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_wallet_pay_verify() {
+        let mut config = WalletConfig::default();
+        let bob_pubkey = Keypair::new().pubkey();
+
+        // The recipient's balance rises by exactly the payment
+        config.rpc_client = Some(RpcClient::new_mock("balance_grows".to_string()));
+        config.command = WalletCommand::Pay(
+            BALANCE_INCREMENT,
+            bob_pubkey,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            true,
+            None,
         );
-        let test_bad_pubkey = test_commands
-            .clone()
-            .get_matches_from(vec!["test", "pay", "deadbeef", "50"]);
-        assert!(parse_command(&pubkey, &test_bad_pubkey).is_err());
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["signature"], SIGNATURE);
+        assert_eq!(json["expectedIncrease"], BALANCE_INCREMENT);
+        assert_eq!(json["observedIncrease"], BALANCE_INCREMENT);
+        assert_eq!(json["verified"], true);
 
-        // Test Pay Subcommand w/ Witness
-        let test_pay_multiple_witnesses = test_commands.clone().get_matches_from(vec![
-            "test",
-            "pay",
-            &pubkey_string,
-            "50",
-            "--require-signature-from",
-            &witness0_string,
-            "--require-signature-from",
-            &witness1_string,
-        ]);
-        assert_eq!(
-            parse_command(&pubkey, &test_pay_multiple_witnesses).unwrap(),
-            WalletCommand::Pay(50, pubkey, None, None, Some(vec![witness0, witness1]), None)
+        // The balance didn't move as expected, so the observed change is reported
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["signature"], SIGNATURE);
+        assert_eq!(json["expectedIncrease"], BALANCE_INCREMENT);
+        assert_eq!(json["observedIncrease"], 0);
+        assert_eq!(json["verified"], false);
+
+        // Only an immediate payment moves lamports by the time it confirms
+        let date_string = "\"2018-09-19T17:30:59Z\"";
+        let dt: DateTime<Utc> = serde_json::from_str(&date_string).unwrap();
+        config.command = WalletCommand::Pay(
+            10,
+            bob_pubkey,
+            Some(dt),
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+            true,
+            None,
         );
-        let test_pay_single_witness = test_commands.clone().get_matches_from(vec![
-            "test",
-            "pay",
-            &pubkey_string,
-            "50",
-            "--require-signature-from",
-            &witness0_string,
-        ]);
-        assert_eq!(
-            parse_command(&pubkey, &test_pay_single_witness).unwrap(),
-            WalletCommand::Pay(50, pubkey, None, None, Some(vec![witness0]), None)
+        assert!(process_command(&config).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(chrono::Duration::seconds(90)));
+        assert_eq!(parse_duration("90s"), Ok(chrono::Duration::seconds(90)));
+        assert_eq!(parse_duration("15m"), Ok(chrono::Duration::minutes(15)));
+        assert_eq!(parse_duration("12h"), Ok(chrono::Duration::hours(12)));
+        assert_eq!(parse_duration("30d"), Ok(chrono::Duration::days(30)));
+        assert!(parse_duration("0d").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3w").is_err());
+        assert!(parse_duration("99999999999999999s").is_err());
+        assert!(parse_duration("9999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_wallet_pay_on_schedule() {
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        let bob_pubkey = Keypair::new().pubkey();
+        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+        let interval = chrono::Duration::days(30);
+
+        config.command =
+            WalletCommand::PayOnSchedule(100, bob_pubkey, dt, interval, 3, None, false);
+        let result: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(result["signature"], SIGNATURE);
+        let schedule = result["schedule"].as_array().unwrap();
+        let lamports: Vec<_> = schedule
+            .iter()
+            .map(|tranche| tranche["lamports"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lamports, vec![33, 33, 34]);
+        assert_eq!(schedule[2]["unlock"], (dt + interval * 2).to_rfc3339());
+
+        // Every installment must be at least a lamport
+        config.command = WalletCommand::PayOnSchedule(2, bob_pubkey, dt, interval, 3, None, false);
+        assert!(process_command(&config).is_err());
+
+        // Too many installments for one transaction
+        config.command =
+            WalletCommand::PayOnSchedule(100, bob_pubkey, dt, interval, 6, None, false);
+        assert!(process_command(&config).is_err());
+        config.command = WalletCommand::PayOnSchedule(
+            100,
+            bob_pubkey,
+            dt,
+            interval,
+            u32::max_value(),
+            None,
+            false,
         );
+        assert!(process_command(&config).is_err());
 
-        // Test Pay Subcommand w/ Timestamp
-        let test_pay_timestamp = test_commands.clone().get_matches_from(vec![
-            "test",
-            "pay",
-            &pubkey_string,
-            "50",
-            "--after",
-            "2018-09-19T17:30:59",
-            "--require-timestamp-from",
-            &witness0_string,
-        ]);
+        // A schedule that would end past the last representable date
+        let interval = chrono::Duration::max_value();
+        config.command =
+            WalletCommand::PayOnSchedule(100, bob_pubkey, dt, interval, 2, None, false);
+        assert!(process_command(&config).is_err());
+    }
+
+    #[test]
+    fn test_wallet_release_due() {
+        let process_id = Keypair::new().pubkey();
+        let authority = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let start = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+        let interval = chrono::Duration::days(30);
+        let expr = BudgetExpr::new_vesting_payment(start, interval, 3, &authority, 100, &to);
+        let space = BudgetState::size_of(&expr) as usize;
+        let budget_state = BudgetState::new(expr);
+        let mut account = Account::new(100, space, &solana_budget_api::id());
+        budget_state.serialize(&mut account.data).unwrap();
+
+        let now = start + interval + chrono::Duration::days(1);
         assert_eq!(
-            parse_command(&pubkey, &test_pay_timestamp).unwrap(),
-            WalletCommand::Pay(50, pubkey, Some(dt), Some(witness0), None, None)
+            verify_release_due(&process_id, &account, &authority, now).unwrap(),
+            Payment { lamports: 66, to }
         );
+        let message = match verify_release_due(&process_id, &account, &to, now) {
+            Err(WalletError::BadParameter(message)) => message,
+            result => panic!("unexpected result: {:?}", result),
+        };
+        assert!(message.contains(&format!("expects a timestamp from {}", authority)));
+        assert!(verify_release_due(&process_id, &account, &authority, start - interval).is_err());
+
+        // Only vesting contracts release installments
+        let expr = BudgetExpr::new_future_payment(start, &authority, 100, &to);
+        let mut future_account = account.clone();
+        BudgetState::new(expr)
+            .serialize(&mut future_account.data)
+            .unwrap();
+        assert!(verify_release_due(&process_id, &future_account, &authority, now).is_err());
 
-        // Test Send-Signature Subcommand
-        let test_send_signature = test_commands.clone().get_matches_from(vec![
-            "test",
-            "send-signature",
-            &pubkey_string,
-            &pubkey_string,
-        ]);
+        // The mock's accounts aren't budget contracts
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        config.command = WalletCommand::ReleaseDue(process_id);
+        assert!(process_command(&config).is_err());
+    }
+
+    #[test]
+    fn test_wallet_get_transaction_count_sample() {
+        // Scripted readings: 100 more transactions and 10 more slots per request
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let interval = Some(Duration::from_millis(250));
+        let json: Value =
+            serde_json::from_str(&process_get_transaction_count(&rpc_client, interval).unwrap())
+                .unwrap();
+        assert_eq!(json["transactionCount"], 1334);
+        assert_eq!(json["transactionsPerSecond"], 400.0);
+        assert_eq!(json["slotsPerSecond"], 40.0);
+
+        // The counter went backwards between readings
+        let rpc_client = RpcClient::new_mock("transaction_count_reset".to_string());
+        let json: Value =
+            serde_json::from_str(&process_get_transaction_count(&rpc_client, interval).unwrap())
+                .unwrap();
+        assert_eq!(json["transactionCount"], 617);
+        assert_eq!(json["transactionsPerSecond"], "n/a");
+        assert_eq!(json["slotsPerSecond"], 4.0);
+
+        // An empty window has no rate
+        let zero = Some(Duration::from_secs(0));
+        assert!(process_get_transaction_count(&rpc_client, zero).is_err());
+    }
+
+    #[test]
+    fn test_wallet_poll_new_signatures() {
+        let pubkey = Keypair::new().pubkey();
+        let mut seen = HashSet::new();
+
+        // An account with no activity prints nothing
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        for _ in 0..3 {
+            let lines = poll_new_signatures(&rpc_client, &pubkey, &mut seen).unwrap();
+            assert!(lines.is_empty());
+        }
+
+        // The first transaction is still pending when it appears, so it's only printed
+        // once confirmed, and each later poll surfaces exactly one more
+        let rpc_client = RpcClient::new_mock("monitor".to_string());
+        let lines = poll_new_signatures(&rpc_client, &pubkey, &mut seen).unwrap();
+        assert!(lines.is_empty());
+        for n in 0..3 {
+            let lines = poll_new_signatures(&rpc_client, &pubkey, &mut seen).unwrap();
+            assert_eq!(lines, vec![format!("{} Confirmed", monitor_signature(n))]);
+        }
+        assert_eq!(seen.len(), 3);
+    }
+
+    #[test]
+    fn test_wallet_follow_confirmation() {
+        let signature = Signature::default();
+
+        // Unseen on the first poll, then one slot deeper on each poll after that
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let mut output = vec![];
+        let result = follow_confirmation(
+            &rpc_client,
+            &signature,
+            2,
+            Duration::from_secs(60),
+            Duration::from_millis(0),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(result, "Confirmed at depth 2");
         assert_eq!(
-            parse_command(&pubkey, &test_send_signature).unwrap(),
-            WalletCommand::Witness(pubkey, pubkey)
+            String::from_utf8(output).unwrap(),
+            "Depth: 0\nDepth: 1\nDepth: 2\n"
         );
-        let test_pay_multiple_witnesses = test_commands.clone().get_matches_from(vec![
-            "test",
-            "pay",
-            &pubkey_string,
-            "50",
-            "--after",
-            "2018-09-19T17:30:59",
-            "--require-signature-from",
-            &witness0_string,
-            "--require-timestamp-from",
-            &witness0_string,
-            "--require-signature-from",
-            &witness1_string,
-        ]);
-        assert_eq!(
-            parse_command(&pubkey, &test_pay_multiple_witnesses).unwrap(),
-            WalletCommand::Pay(
-                50,
-                pubkey,
-                Some(dt),
-                Some(witness0),
-                Some(vec![witness0, witness1]),
-                None
-            )
+
+        // A signature that never appears times out rather than spinning forever
+        let rpc_client = RpcClient::new_mock("bad_sig_status".to_string());
+        let mut output = vec![];
+        assert!(follow_confirmation(
+            &rpc_client,
+            &signature,
+            2,
+            Duration::from_millis(10),
+            Duration::from_millis(1),
+            &mut output,
+        )
+        .is_err());
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_wallet_expected_genesis_hash() {
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        let bob_pubkey = Keypair::new().pubkey();
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, None, false, false, None,
         );
 
-        // Test Send-Timestamp Subcommand
-        let test_send_timestamp = test_commands.clone().get_matches_from(vec![
-            "test",
-            "send-timestamp",
-            &pubkey_string,
-            &pubkey_string,
-            "--date",
-            "2018-09-19T17:30:59",
-        ]);
+        // Unset expectation
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // Matching
+        let cluster_genesis_hash = Hash::new(&bs58::decode(PUBKEY).into_vec().unwrap());
+        config.expected_genesis_hash = Some(cluster_genesis_hash);
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // Mismatching
+        let other_genesis_hash = Hash::new(&[1; 32]);
+        config.expected_genesis_hash = Some(other_genesis_hash);
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::GenesisHashMismatch(expected, found)) => {
+                assert_eq!(*expected, other_genesis_hash);
+                assert_eq!(*found, cluster_genesis_hash);
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        // Commands that don't submit transactions are still allowed
+        config.command = WalletCommand::Balance(Commitment::default());
+        assert_eq!(process_command(&config).unwrap(), "Your balance is: 50");
+    }
+
+    #[test]
+    fn test_wallet_apply_fee() {
+        let keypair = Keypair::new();
+        let to = Keypair::new().pubkey();
+        let mut tx = SystemTransaction::new_move(&keypair, &to, 1, Hash::default(), 0);
+        apply_fee(
+            &FeeCalculator::new(LAMPORTS_PER_SIGNATURE),
+            &mut tx,
+            &keypair,
+        );
+        assert_eq!(tx.fee, LAMPORTS_PER_SIGNATURE);
+        assert!(tx.verify_signature());
+
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let mut tx = SystemTransaction::new_move(&keypair, &to, 1, Hash::default(), 0);
         assert_eq!(
-            parse_command(&pubkey, &test_send_timestamp).unwrap(),
-            WalletCommand::TimeElapsed(pubkey, pubkey, dt)
+            send_and_confirm_with_fee(&rpc_client, &WalletConfig::default(), &mut tx, &keypair)
+                .unwrap(),
+            SIGNATURE
         );
-        let test_bad_timestamp = test_commands.clone().get_matches_from(vec![
-            "test",
-            "send-timestamp",
-            &pubkey_string,
-            &pubkey_string,
-            "--date",
-            "20180919T17:30:59",
-        ]);
-        assert!(parse_command(&pubkey, &test_bad_timestamp).is_err());
+        assert_eq!(tx.fee, LAMPORTS_PER_SIGNATURE);
     }
 
     #[test]
-    fn test_wallet_process_command() {
-        // Success cases
+    fn test_wallet_witness_target() {
+        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
+        let process_id = Keypair::new().pubkey();
+        let authority = Keypair::new().pubkey();
+        let canceler = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let expr = BudgetExpr::new_cancelable_future_payment(dt, &authority, 42, &to, &canceler);
+        let space = BudgetState::size_of(&expr) as usize;
+        let budget_state = BudgetState::new(expr);
+        let mut account = Account::new(42, space, &solana_budget_api::id());
+        budget_state.serialize(&mut account.data).unwrap();
+
+        let verify = |witness: &Witness, from: &Pubkey, to: &Pubkey| {
+            verify_witness_target(&process_id, &account, witness, from, to).map_err(|err| match err
+            {
+                WalletError::BadParameter(message) => message,
+                _ => panic!("unexpected error: {:?}", err),
+            })
+        };
+
+        // Matching
+        assert!(verify(&Witness::Timestamp(dt), &authority, &to).is_ok());
+        assert!(verify(&Witness::Signature, &canceler, &canceler).is_ok());
+
+        // Mismatching
+        let message = verify(&Witness::Timestamp(dt), &canceler, &to).unwrap_err();
+        assert!(message.contains(&format!("expects a timestamp from {}", authority)));
+        let message = verify(&Witness::Signature, &authority, &to).unwrap_err();
+        assert!(message.contains(&format!("expects a signature from {}", canceler)));
+        let message = verify(&Witness::Timestamp(dt), &authority, &canceler).unwrap_err();
+        assert!(message.contains(&format!("pays {}", to)));
+
+        let mut system_account = account.clone();
+        system_account.owner = Pubkey::default();
+        assert!(verify_witness_target(
+            &process_id,
+            &system_account,
+            &Witness::Signature,
+            &canceler,
+            &canceler
+        )
+        .is_err());
+
+        // The mock's accounts aren't budget contracts
         let mut config = WalletConfig::default();
         config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        config.command = WalletCommand::Witness(to, process_id, false);
+        assert!(process_command(&config).is_err());
+        config.command = WalletCommand::TimeElapsed(to, process_id, dt, false);
+        assert!(process_command(&config).is_err());
+        config.command = WalletCommand::TimeElapsed(to, process_id, dt, true);
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
 
-        let keypair = Keypair::new();
-        let pubkey = keypair.pubkey().to_string();
-        config.id = keypair;
-        config.command = WalletCommand::Address;
-        assert_eq!(process_command(&config).unwrap(), pubkey);
-
-        config.command = WalletCommand::Balance;
-        assert_eq!(process_command(&config).unwrap(), "Your balance is: 50");
+        // Nonexistent contract
+        config.rpc_client = Some(RpcClient::new_mock("fails".to_string()));
+        config.command = WalletCommand::Witness(to, process_id, false);
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::BadParameter(message)) => {
+                assert!(message.contains("Unable to find contract"), message.clone())
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
 
+    #[test]
+    fn test_wallet_redirect() {
+        let dt = Utc.ymd(2018, 9, 19).and_hms(17, 30, 59);
         let process_id = Keypair::new().pubkey();
-        config.command = WalletCommand::Cancel(process_id);
-        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+        let authority = Keypair::new().pubkey();
+        let canceler = Keypair::new().pubkey();
+        let to = Keypair::new().pubkey();
+        let expr = BudgetExpr::new_cancelable_future_payment(dt, &authority, 42, &to, &canceler);
+        let space = BudgetState::size_of(&expr) as usize;
+        let budget_state = BudgetState::new(expr);
+        let mut account = Account::new(42, space, &solana_budget_api::id());
+        budget_state.serialize(&mut account.data).unwrap();
+
+        let verify = |account: &Account, authority: &Pubkey| {
+            verify_cancel_authority(&process_id, account, authority).map_err(|err| match err {
+                WalletError::BadParameter(message) => message,
+                _ => panic!("unexpected error: {:?}", err),
+            })
+        };
 
-        let good_signature = Signature::new(&bs58::decode(SIGNATURE).into_vec().unwrap());
-        config.command = WalletCommand::Confirm(good_signature);
-        assert_eq!(process_command(&config).unwrap(), "Confirmed");
+        assert!(verify(&account, &canceler).is_ok());
 
-        let bob_pubkey = Keypair::new().pubkey();
-        config.command = WalletCommand::ConfigureStakingAccount(None, Some(bob_pubkey));
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+        // Neither the timestamp authority nor the payee may redirect the refund
+        let message = verify(&account, &authority).unwrap_err();
+        assert!(message.contains("is not the cancel authority"));
+        let message = verify(&account, &to).unwrap_err();
+        assert!(message.contains("is not the cancel authority"));
 
-        config.command = WalletCommand::CreateStakingAccount(bob_pubkey, 10);
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+        // A contract that already paid out has nothing left to redirect
+        let mut paid_account = account.clone();
+        BudgetState::default()
+            .serialize(&mut paid_account.data)
+            .unwrap();
+        let message = verify(&paid_account, &canceler).unwrap_err();
+        assert!(message.contains("already been paid or canceled"));
 
-        config.command = WalletCommand::GetTransactionCount;
-        assert_eq!(process_command(&config).unwrap(), "1234");
+        // The mock's accounts aren't budget contracts
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        config.command = WalletCommand::Redirect(process_id, to);
+        assert!(process_command(&config).is_err());
 
-        config.command = WalletCommand::Pay(10, bob_pubkey, None, None, None, None);
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+        // Nonexistent contract
+        config.rpc_client = Some(RpcClient::new_mock("fails".to_string()));
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::BadParameter(message)) => {
+                assert!(message.contains("Unable to find contract"), message.clone())
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
 
-        let date_string = "\"2018-09-19T17:30:59Z\"";
-        let dt: DateTime<Utc> = serde_json::from_str(&date_string).unwrap();
-        config.command = WalletCommand::Pay(
-            10,
-            bob_pubkey,
-            Some(dt),
-            Some(config.id.pubkey()),
-            None,
-            None,
+    #[test]
+    fn test_wallet_drain() {
+        let mut config = WalletConfig::default();
+        let bob_pubkey = Keypair::new().pubkey();
+        config.command = WalletCommand::Drain(bob_pubkey);
+
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // An empty account can't pay the fee
+        assert!(LAMPORTS_PER_SIGNATURE > 0);
+        config.rpc_client = Some(RpcClient::new_mock("airdrop".to_string()));
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::BadParameter(message)) => {
+                assert!(message.contains("can't cover"), message.clone())
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_wallet_confirm_submission() {
+        let mut output = vec![];
+        let confirmed =
+            confirm_submission("Command: pay", &mut Cursor::new("y\n"), &mut output).unwrap();
+        assert!(confirmed);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Command: pay\nSubmit? [y/N] "
         );
-        let result = process_command(&config);
-        let json: Value = serde_json::from_str(&result.unwrap()).unwrap();
+
+        for answer in &["n\n", "\n", "maybe\n", ""] {
+            let confirmed =
+                confirm_submission("Command: pay", &mut Cursor::new(answer), &mut vec![]).unwrap();
+            assert!(!confirmed, "{:?}", answer);
+        }
+
+        // Without a terminal only --confirm=always asks
+        assert!(!ConfirmMode::Auto.should_prompt(false));
+        assert!(ConfirmMode::Auto.should_prompt(true));
+        assert!(ConfirmMode::Always.should_prompt(false));
+        assert!(!ConfirmMode::Never.should_prompt(true));
+
+        // A non-interactive test run skips the prompt and submits
+        let mut config = WalletConfig::default();
+        config.confirm = ConfirmMode::Auto;
+        config.command = WalletCommand::Drain(Keypair::new().pubkey());
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // `drain --confirm` asks once, with the wallet-wide summary
+        let rpc_client = config.rpc_client.as_ref().unwrap();
+        let mut output = vec![];
+        let confirmed = confirm_command(
+            rpc_client,
+            &config,
+            true,
+            &mut Cursor::new("y\n"),
+            &mut output,
+        )
+        .unwrap();
+        assert!(confirmed);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Command: drain\n"));
+        assert_eq!(output.matches("Submit? [y/N]").count(), 1);
+        let confirmed = confirm_command(
+            rpc_client,
+            &config,
+            true,
+            &mut Cursor::new("n\n"),
+            &mut vec![],
+        )
+        .unwrap();
+        assert!(!confirmed);
+    }
+
+    #[test]
+    fn test_parse_confirm_mode() {
+        let test_commands = App::new("test")
+            .arg(
+                Arg::with_name("confirm")
+                    .long("confirm")
+                    .takes_value(true)
+                    .min_values(0)
+                    .require_equals(true)
+                    .possible_values(&["auto", "always"]),
+            )
+            .subcommand(SubCommand::with_name("airdrop"))
+            .subcommand(
+                SubCommand::with_name("drain")
+                    .arg(Arg::with_name("to").index(1))
+                    .arg(Arg::with_name("yes").long("yes")),
+            );
+        let parse =
+            |args: Vec<&str>| parse_confirm_mode(&test_commands.clone().get_matches_from(args));
+
+        assert_eq!(parse(vec!["test", "airdrop"]), ConfirmMode::Never);
         assert_eq!(
-            json.as_object()
-                .unwrap()
-                .get("signature")
-                .unwrap()
-                .as_str()
-                .unwrap(),
-            SIGNATURE.to_string()
+            parse(vec!["test", "--confirm=always", "airdrop"]),
+            ConfirmMode::Always
         );
 
-        let witness = Keypair::new().pubkey();
-        config.command = WalletCommand::Pay(
-            10,
-            bob_pubkey,
-            None,
-            None,
-            Some(vec![witness]),
-            Some(config.id.pubkey()),
+        // Draining asks, through the same prompt as --confirm, unless --yes is passed
+        let to = Keypair::new().pubkey().to_string();
+        assert_eq!(parse(vec!["test", "drain", &to]), ConfirmMode::Auto);
+        assert_eq!(
+            parse(vec!["test", "--confirm", "drain", &to]),
+            ConfirmMode::Auto
         );
-        let result = process_command(&config);
-        let json: Value = serde_json::from_str(&result.unwrap()).unwrap();
         assert_eq!(
-            json.as_object()
-                .unwrap()
-                .get("signature")
-                .unwrap()
-                .as_str()
-                .unwrap(),
-            SIGNATURE.to_string()
+            parse(vec!["test", "--confirm=always", "drain", &to]),
+            ConfirmMode::Always
+        );
+        assert_eq!(
+            parse(vec!["test", "drain", &to, "--yes"]),
+            ConfirmMode::Never
         );
+    }
 
-        let process_id = Keypair::new().pubkey();
-        config.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt);
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+    #[test]
+    fn test_wallet_node_health() {
+        let mut config = WalletConfig::default();
+        config.command = WalletCommand::Drain(Keypair::new().pubkey());
 
-        let witness = Keypair::new().pubkey();
-        config.command = WalletCommand::Witness(bob_pubkey, witness);
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+        // A stalled node only draws a warning, unless health is strictly enforced
+        config.rpc_client = Some(RpcClient::new_mock("stalled".to_string()));
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+        config.strict_health = true;
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::UnhealthyNode(message)) => {
+                assert_eq!(message, &format!("node is stalled at slot {}", SLOT))
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
 
-        // Need airdrop cases
-        config.command = WalletCommand::Airdrop(50);
+        // A node that's advancing passes without a reference node
+        config.rpc_client = Some(RpcClient::new_mock("lagging".to_string()));
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // ...but not once it's compared with one
+        config.rpc_client = Some(RpcClient::new_mock("lagging".to_string()));
+        config.reference_rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::UnhealthyNode(message)) => {
+                assert!(
+                    message.contains("slots behind the reference node"),
+                    "{}",
+                    message
+                )
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+        config.max_slots_behind = SLOT;
+        config.rpc_client = Some(RpcClient::new_mock("lagging".to_string()));
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
+
+        // Read-only commands skip the check
+        config.command = WalletCommand::Balance(Commitment::default());
+        config.rpc_client = Some(RpcClient::new_mock("stalled".to_string()));
+        assert!(process_command(&config).is_ok());
+    }
+
+    #[test]
+    fn test_wallet_audit_log() {
+        let audit_log = tmp_file_path("test_wallet_audit_log");
+        fs::create_dir_all(Path::new(&audit_log).parent().unwrap()).unwrap();
+        let mut config = WalletConfig::default();
+        config.audit_log = Some(PathBuf::from(&audit_log));
+        config.command = WalletCommand::Drain(Keypair::new().pubkey());
+
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        process_command(&config).unwrap();
+
+        // The transaction is recorded even though the cluster rejects it
+        config.rpc_client = Some(RpcClient::new_mock("program_not_executable".to_string()));
         assert!(process_command(&config).is_err());
 
-        config.rpc_client = Some(RpcClient::new_mock("airdrop".to_string()));
-        config.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt);
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+        let log = fs::read_to_string(&audit_log).unwrap();
+        let lines: Vec<_> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let fields: Vec<_> = line.split(' ').collect();
+            assert_eq!(fields.len(), 3);
+            assert_eq!(bs58::decode(fields[1]).into_vec().unwrap().len(), 32);
+            assert_eq!(bs58::decode(fields[2]).into_vec().unwrap().len(), 64);
+        }
+        fs::remove_file(&audit_log).unwrap();
+    }
 
-        let witness = Keypair::new().pubkey();
-        config.command = WalletCommand::Witness(bob_pubkey, witness);
-        let signature = process_command(&config);
-        assert_eq!(signature.unwrap(), SIGNATURE.to_string());
+    #[test]
+    fn test_wallet_verbose_trace() {
+        let lines = Rc::new(RefCell::new(vec![]));
+        let trace_lines = lines.clone();
+        let seed = [3; 32];
+        let mut config = WalletConfig::default();
+        config.id = Keypair::from_seed(&seed);
+        config.rpc_client = Some(
+            RpcClient::new_mock("succeeds".to_string())
+                .with_trace(move |line| trace_lines.borrow_mut().push(line.to_string())),
+        );
+        let bob_pubkey = Keypair::new().pubkey();
+        config.command = WalletCommand::Pay(
+            10, bob_pubkey, None, None, None, None, None, None, false, false, None,
+        );
+        assert_eq!(process_command(&config).unwrap(), SIGNATURE);
 
-        // bad_sig_status cases
-        config.rpc_client = Some(RpcClient::new_mock("bad_sig_status".to_string()));
-        let missing_signature = Signature::new(&bs58::decode("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW").into_vec().unwrap());
-        config.command = WalletCommand::Confirm(missing_signature);
-        assert_eq!(process_command(&config).unwrap(), "Not found");
+        let lines = lines.borrow();
+        let methods: Vec<_> = lines
+            .iter()
+            .map(|line| line.split(' ').next().unwrap())
+            .collect();
+        assert!(methods.contains(&"getRecentBlockhash"));
+        assert!(methods.contains(&"sendTransaction"));
+
+        // The composed transaction is traced before it's sent
+        let sent = methods
+            .iter()
+            .position(|method| *method == "sendTransaction");
+        let described = methods.iter().position(|method| *method == "sending");
+        assert!(described.unwrap() < sent.unwrap());
+        assert!(lines[described.unwrap()].contains(&bob_pubkey.to_string()));
+
+        // Signatures are cut short and the identity's secret key never appears
+        let pkcs8 = gen_pkcs8_from_seed(&seed);
+        let secrets = [
+            serde_json::to_string(&seed.to_vec()).unwrap(),
+            bs58::encode(&seed[..]).into_string(),
+            serde_json::to_string(&pkcs8).unwrap(),
+            bs58::encode(&pkcs8).into_string(),
+        ];
+        for line in lines.iter() {
+            assert!(!line.contains(SIGNATURE));
+            for secret in &secrets {
+                assert!(!line.contains(secret));
+            }
+        }
+        assert!(lines
+            .iter()
+            .any(|line| line.contains(&format!("\"{}...\"", &SIGNATURE[..8]))));
+    }
 
-        // Failure cases
+    #[test]
+    fn test_wallet_client_error() {
+        let err = ClientError::from(serde_json::from_str::<Value>("{\"result\":").unwrap_err());
+        match WalletError::from(err) {
+            WalletError::RpcRequestError(message) => {
+                assert!(message.contains("unexpected response"), message)
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let err = ClientError::RpcError {
+            code: -32602,
+            message: "Invalid params".to_string(),
+        };
+        match WalletError::from(err) {
+            WalletError::RpcRequestError(message) => {
+                assert_eq!(message, "RPC error -32602: Invalid params")
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        // Client errors surface from `process_command` as wallet errors
+        let mut config = WalletConfig::default();
         config.rpc_client = Some(RpcClient::new_mock("fails".to_string()));
+        config.command = WalletCommand::GetGenesisHash;
+        let err = process_command(&config).unwrap_err();
+        match err.downcast_ref::<WalletError>() {
+            Some(WalletError::RpcRequestError(message)) => {
+                assert!(message.contains("GetGenesisHash"), message.clone())
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
 
-        config.command = WalletCommand::Airdrop(50);
-        assert!(process_command(&config).is_err());
+    #[test]
+    fn test_wallet_show_delegations() {
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
+        let node_id: Pubkey = PUBKEY.parse().unwrap();
 
-        config.command = WalletCommand::Balance;
-        assert!(process_command(&config).is_err());
+        // Live vote accounts: [1; 32] and [2; 32] are delegated to the node, [3; 32] isn't
+        config.command = WalletCommand::ShowDelegations(node_id, None);
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        let delegations = json.as_array().unwrap();
+        assert_eq!(delegations.len(), 2);
+        assert_eq!(
+            delegations[0]["voteAccount"],
+            Pubkey::new(&[1; 32]).to_string()
+        );
+        assert_eq!(delegations[0]["lamports"], 10);
+        assert_eq!(delegations[0]["credits"], 1);
+        assert_eq!(delegations[0]["lastVote"], MAX_LOCKOUT_HISTORY as u64);
+        assert_eq!(
+            delegations[1]["voteAccount"],
+            Pubkey::new(&[2; 32]).to_string()
+        );
+        assert_eq!(delegations[1]["lamports"], 20);
+        assert_eq!(delegations[1]["credits"], 0);
+        assert_eq!(delegations[1]["lastVote"], Value::Null);
+
+        // The epoch 0 snapshot predates [2; 32]
+        config.command = WalletCommand::ShowDelegations(node_id, Some(0));
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        let delegations = json.as_array().unwrap();
+        assert_eq!(delegations.len(), 1);
+        assert_eq!(
+            delegations[0]["voteAccount"],
+            Pubkey::new(&[1; 32]).to_string()
+        );
 
-        let any_signature = Signature::new(&bs58::decode(SIGNATURE).into_vec().unwrap());
-        config.command = WalletCommand::Confirm(any_signature);
+        // No stakes were snapshotted for epoch 1
+        config.command = WalletCommand::ShowDelegations(node_id, Some(1));
         assert!(process_command(&config).is_err());
 
-        config.command = WalletCommand::ConfigureStakingAccount(None, Some(bob_pubkey));
-        assert!(process_command(&config).is_err());
+        // Nothing is delegated to an unknown node
+        config.command = WalletCommand::ShowDelegations(Keypair::new().pubkey(), None);
+        assert_eq!(process_command(&config).unwrap(), "[]");
+    }
 
-        config.command = WalletCommand::CreateStakingAccount(bob_pubkey, 10);
-        assert!(process_command(&config).is_err());
+    #[test]
+    fn test_wallet_supply() {
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("succeeds".to_string()));
 
-        config.command = WalletCommand::GetTransactionCount;
-        assert!(process_command(&config).is_err());
+        config.command = WalletCommand::Supply(None, vec![]);
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["total"], SUPPLY);
+        assert_eq!(json["owners"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            json["owners"][1]["program"],
+            solana_vote_api::id().to_string()
+        );
+        assert_eq!(json["owners"][1]["lamports"], STAKED_SUPPLY);
+        assert_eq!(json["largest"], Value::Null);
+
+        config.command = WalletCommand::Supply(Some(1), vec![solana_vote_api::id()]);
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        let largest = json["largest"].as_array().unwrap();
+        assert_eq!(largest.len(), 1);
+        assert_eq!(largest[0]["account"], PUBKEY);
+        assert_eq!(largest[0]["lamports"], 50);
+    }
 
-        config.command = WalletCommand::Pay(10, bob_pubkey, None, None, None, None);
-        assert!(process_command(&config).is_err());
+    #[test]
+    fn test_wallet_setup_validator() {
+        let identity_path = tmp_file_path("test_wallet_setup_validator_identity");
+        let vote_account_path = tmp_file_path("test_wallet_setup_validator_vote");
+        let stake = MINIMUM_DELEGATION;
 
-        config.command = WalletCommand::Pay(
-            10,
-            bob_pubkey,
-            Some(dt),
-            Some(config.id.pubkey()),
-            None,
-            None,
+        // Nothing exists yet, and the identity has no lamports to create the vote account with
+        let mut config = WalletConfig::default();
+        config.rpc_client = Some(RpcClient::new_mock("airdrop".to_string()));
+        config.command = WalletCommand::SetupValidator(
+            PathBuf::from(&identity_path),
+            PathBuf::from(&vote_account_path),
+            stake,
+        );
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        let identity = read_keypair(&identity_path).unwrap();
+        let vote_keypair = read_keypair(&vote_account_path).unwrap();
+        assert_eq!(json["identity"], identity.pubkey().to_string());
+        assert_eq!(json["voteAccount"], vote_keypair.pubkey().to_string());
+        assert_eq!(json["stake"], stake);
+        assert_eq!(json["airdropLamports"], stake + 2 * LAMPORTS_PER_SIGNATURE);
+        assert_eq!(json["signatures"], json!([SIGNATURE]));
+
+        // Running it again reuses the keypairs
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["identity"], identity.pubkey().to_string());
+        assert_eq!(json["voteAccount"], vote_keypair.pubkey().to_string());
+
+        // Too little stake to create a vote account with
+        config.command = WalletCommand::SetupValidator(
+            PathBuf::from(&identity_path),
+            PathBuf::from(&vote_account_path),
+            MINIMUM_DELEGATION - 1,
         );
         assert!(process_command(&config).is_err());
 
-        config.command = WalletCommand::Pay(
-            10,
-            bob_pubkey,
-            None,
-            None,
-            Some(vec![witness]),
-            Some(config.id.pubkey()),
+        fs::remove_file(&identity_path).unwrap();
+        fs::remove_file(&vote_account_path).unwrap();
+
+        // The vote account already exists and is delegated to the identity
+        gen_keypair_file_from_seed(&VALIDATOR_IDENTITY_SEED, identity_path.clone()).unwrap();
+        config.rpc_client = Some(RpcClient::new_mock("validator_exists".to_string()));
+        config.command = WalletCommand::SetupValidator(
+            PathBuf::from(&identity_path),
+            PathBuf::from(&vote_account_path),
+            stake,
         );
-        assert!(process_command(&config).is_err());
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        let identity = Keypair::from_seed(&VALIDATOR_IDENTITY_SEED);
+        assert_eq!(json["identity"], identity.pubkey().to_string());
+        assert_eq!(json["airdropLamports"], 0);
+        assert_eq!(json["signatures"], json!([]));
+
+        // Delegated to someone else, so only the delegation is sent
+        fs::remove_file(&identity_path).unwrap();
+        let json: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        assert_eq!(json["airdropLamports"], 0);
+        assert_eq!(json["signatures"], json!([SIGNATURE]));
+
+        fs::remove_file(&identity_path).unwrap();
+        fs::remove_file(&vote_account_path).unwrap();
+    }
 
-        config.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt);
-        assert!(process_command(&config).is_err());
+    #[test]
+    fn test_sample_rates() {
+        let elapsed = Duration::from_millis(2500);
+        let json = sample_rates((1000, 40), (1500, 45), elapsed);
+        assert_eq!(json["transactionCount"], 1500);
+        assert_eq!(json["transactionsPerSecond"], 200.0);
+        assert_eq!(json["slotsPerSecond"], 2.0);
+
+        let json = sample_rates((1000, 40), (10, 1), elapsed);
+        assert_eq!(json["transactionCount"], 10);
+        assert_eq!(json["transactionsPerSecond"], "n/a");
+        assert_eq!(json["slotsPerSecond"], "n/a");
+    }
+
+    #[test]
+    fn test_wallet_show_budget_account() {
+        let account_id = Keypair::new().pubkey();
+        let bob_pubkey = Keypair::new().pubkey();
+        let budget_expr = BudgetExpr::new_authorized_payment(&bob_pubkey, 10, &bob_pubkey);
+        let budget_state = BudgetState::new(budget_expr);
+        let mut account = Account::new(10, 512, &solana_budget_api::id());
+        budget_state.serialize(&mut account.data).unwrap();
+
+        let output = format_account(&account_id, &account, Some("budget")).unwrap();
+        assert_eq!(output, format!("{:#?}", budget_state));
+
+        // A hint that doesn't match the owner falls back to the raw account
+        let output = format_account(&account_id, &account, Some("vote")).unwrap();
+        let json: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["pubkey"], account_id.to_string());
+        assert_eq!(json["owner"], solana_budget_api::id().to_string());
+    }
+
+    #[test]
+    fn test_wallet_show_vote_account() {
+        let account_id = Keypair::new().pubkey();
+        let staker_id = Keypair::new().pubkey();
+        let vote_state = VoteState::new(&staker_id);
+        let mut account = Account::new(1, VoteState::max_size(), &solana_vote_api::id());
+        vote_state.serialize(&mut account.data).unwrap();
+
+        let output = format_account(&account_id, &account, Some("vote")).unwrap();
+        assert_eq!(output, format!("{:#?}", vote_state));
+
+        let output = format_account(&account_id, &account, None).unwrap();
+        let json: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["lamports"], 1);
+        assert_eq!(json["executable"], false);
+
+        let output = format_account(&account_id, &account, Some("budget")).unwrap();
+        let json: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["owner"], solana_vote_api::id().to_string());
     }
 
     #[test]
@@ -1462,6 +5244,48 @@ mod tests {
         assert!(process_command(&config).is_err());
     }
 
+    #[test]
+    fn test_wallet_replay_ledger() {
+        let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+        let ledger_path = tmp_file_path("test_wallet_replay_ledger");
+        fs::create_dir_all(&ledger_path).unwrap();
+        genesis_block.write(&ledger_path).unwrap();
+
+        let blockhash = genesis_block.hash();
+        let unfunded = Keypair::new();
+        let transactions = vec![
+            SystemTransaction::new_move(&mint_keypair, &Keypair::new().pubkey(), 10, blockhash, 0),
+            SystemTransaction::new_move(&unfunded, &Keypair::new().pubkey(), 10, blockhash, 0),
+            SystemTransaction::new_move(&mint_keypair, &Keypair::new().pubkey(), 20, blockhash, 0),
+        ];
+        let transactions_path = Path::new(&ledger_path).join("transactions.bin");
+        fs::write(
+            &transactions_path,
+            bincode::serialize(&transactions).unwrap(),
+        )
+        .unwrap();
+
+        let mut config = WalletConfig::default();
+        config.command =
+            WalletCommand::ReplayLedger(PathBuf::from(&ledger_path), transactions_path);
+        let output = process_command(&config).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Transaction 1 failed"));
+        assert_eq!(lines[2], "Transaction count: 2");
+
+        // Replaying the same transactions from the same genesis lands on the same bank hash
+        assert_eq!(process_command(&config).unwrap(), output);
+
+        config.command = WalletCommand::ReplayLedger(
+            PathBuf::from(&ledger_path),
+            PathBuf::from("bad/file/location.bin"),
+        );
+        assert!(process_command(&config).is_err());
+
+        fs::remove_dir_all(&ledger_path).unwrap();
+    }
+
     fn tmp_file_path(name: &str) -> String {
         use std::env;
         let out_dir = env::var("OUT_DIR").unwrap_or_else(|_| "target".to_string());
@@ -1,14 +1,15 @@
 use serde_json::{json, Value};
-use solana::fullnode::new_fullnode_for_tests;
-use solana_client::rpc_client::RpcClient;
 use solana_client::rpc_request::RpcRequest;
-use solana_drone::drone::run_local_drone;
+use solana_runtime::bank::Bank;
 use solana_sdk::bpf_loader;
+use solana_sdk::genesis_block::GenesisBlock;
+use solana_sdk::signature::KeypairUtil;
+use solana_wallet::local_rpc_client_request::new_local_rpc_client;
 use solana_wallet::wallet::{process_command, WalletCommand, WalletConfig};
-use std::fs::{remove_dir_all, File};
+use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
-use std::sync::mpsc::channel;
+use std::sync::Arc;
 
 #[test]
 fn test_wallet_deploy_program() {
@@ -20,19 +21,15 @@ fn test_wallet_deploy_program() {
     pathbuf.push("noop");
     pathbuf.set_extension("so");
 
-    let (server, leader_data, alice, ledger_path) = new_fullnode_for_tests();
+    let (genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+    let bank = Arc::new(Bank::new(&genesis_block));
+    let mint_keypair = Arc::new(mint_keypair);
 
-    let (sender, receiver) = channel();
-    run_local_drone(alice, sender);
-    let drone_addr = receiver.recv().unwrap();
-
-    let rpc_client = RpcClient::new_socket(leader_data.rpc);
+    let rpc_client = new_local_rpc_client(bank.clone(), mint_keypair.clone());
 
     let mut config = WalletConfig::default();
-    config.drone_port = drone_addr.port();
-    config.rpc_port = leader_data.rpc.port();
-    config.command = WalletCommand::Airdrop(50);
-    process_command(&config).unwrap();
+    config.rpc_client = Some(new_local_rpc_client(bank, mint_keypair));
+    rpc_client.request_airdrop(&config.id.pubkey(), 50).unwrap();
 
     config.command = WalletCommand::Deploy(pathbuf.to_str().unwrap().to_string());
 
@@ -74,7 +71,4 @@ fn test_wallet_deploy_program() {
         account_info_obj.get("data").unwrap().as_array().unwrap(),
         &elf
     );
-
-    server.close().unwrap();
-    remove_dir_all(ledger_path).unwrap();
 }
@@ -1,45 +1,50 @@
 use chrono::prelude::*;
 use serde_json::Value;
 use solana_client::rpc_client::RpcClient;
-use solana_drone::drone::run_local_drone;
+use solana_runtime::bank::Bank;
+use solana_sdk::genesis_block::GenesisBlock;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil};
-use solana_wallet::wallet::{
-    process_command, request_and_confirm_airdrop, WalletCommand, WalletConfig,
-};
-use std::fs::remove_dir_all;
-use std::sync::mpsc::channel;
-
-#[cfg(test)]
-use solana::fullnode::new_fullnode_for_tests;
+use solana_wallet::local_rpc_client_request::new_local_rpc_client;
+use solana_wallet::wallet::{process_command, WalletCommand, WalletConfig};
+use std::sync::Arc;
 
 fn check_balance(expected_balance: u64, client: &RpcClient, pubkey: &Pubkey) {
     let balance = client.retry_get_balance(pubkey, 1).unwrap().unwrap();
     assert_eq!(balance, expected_balance);
 }
 
+/// Return a bank that runs the budget program, and the keypair of its mint
+fn new_budget_bank() -> (Arc<Bank>, Arc<Keypair>) {
+    let (mut genesis_block, mint_keypair) = GenesisBlock::new(10_000);
+    genesis_block
+        .native_programs
+        .push(("solana_budget_program".to_string(), solana_budget_api::id()));
+    (Arc::new(Bank::new(&genesis_block)), Arc::new(mint_keypair))
+}
+
 #[test]
 fn test_wallet_timestamp_tx() {
-    let (server, leader_data, alice, ledger_path) = new_fullnode_for_tests();
+    let (bank, mint_keypair) = new_budget_bank();
     let bob_pubkey = Keypair::new().pubkey();
 
-    let (sender, receiver) = channel();
-    run_local_drone(alice, sender);
-    let drone_addr = receiver.recv().unwrap();
-
-    let rpc_client = RpcClient::new_socket(leader_data.rpc);
+    let rpc_client = new_local_rpc_client(bank.clone(), mint_keypair.clone());
 
     let mut config_payer = WalletConfig::default();
-    config_payer.drone_port = drone_addr.port();
-    config_payer.rpc_port = leader_data.rpc.port();
+    config_payer.rpc_client = Some(new_local_rpc_client(bank.clone(), mint_keypair.clone()));
 
     let mut config_witness = WalletConfig::default();
-    config_witness.drone_port = drone_addr.port();
-    config_witness.rpc_port = leader_data.rpc.port();
+    config_witness.rpc_client = Some(new_local_rpc_client(bank, mint_keypair));
 
     assert_ne!(config_payer.id.pubkey(), config_witness.id.pubkey());
 
-    request_and_confirm_airdrop(&rpc_client, &drone_addr, &config_payer.id.pubkey(), 50).unwrap();
+    rpc_client
+        .request_airdrop(&config_payer.id.pubkey(), 50)
+        .unwrap();
+    // Fund the witness so it doesn't need a drone to pay for its transaction
+    rpc_client
+        .request_airdrop(&config_witness.id.pubkey(), 1)
+        .unwrap();
     check_balance(50, &rpc_client, &config_payer.id.pubkey());
 
     // Make transaction (from config_payer to bob_pubkey) requiring timestamp from config_witness
@@ -52,6 +57,11 @@ fn test_wallet_timestamp_tx() {
         Some(config_witness.id.pubkey()),
         None,
         None,
+        None,
+        None,
+        true,
+        false,
+        None,
     );
     let sig_response = process_command(&config_payer);
 
@@ -67,39 +77,36 @@ fn test_wallet_timestamp_tx() {
     check_balance(0, &rpc_client, &bob_pubkey); // recipient balance
 
     // Sign transaction by config_witness
-    config_witness.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt);
+    config_witness.command = WalletCommand::TimeElapsed(bob_pubkey, process_id, dt, false);
     process_command(&config_witness).unwrap();
 
     check_balance(40, &rpc_client, &config_payer.id.pubkey()); // config_payer balance
     check_balance(0, &rpc_client, &process_id); // contract balance
     check_balance(10, &rpc_client, &bob_pubkey); // recipient balance
-
-    server.close().unwrap();
-    remove_dir_all(ledger_path).unwrap();
 }
 
 #[test]
 fn test_wallet_witness_tx() {
-    let (server, leader_data, alice, ledger_path) = new_fullnode_for_tests();
+    let (bank, mint_keypair) = new_budget_bank();
     let bob_pubkey = Keypair::new().pubkey();
 
-    let (sender, receiver) = channel();
-    run_local_drone(alice, sender);
-    let drone_addr = receiver.recv().unwrap();
-
-    let rpc_client = RpcClient::new_socket(leader_data.rpc);
+    let rpc_client = new_local_rpc_client(bank.clone(), mint_keypair.clone());
 
     let mut config_payer = WalletConfig::default();
-    config_payer.drone_port = drone_addr.port();
-    config_payer.rpc_port = leader_data.rpc.port();
+    config_payer.rpc_client = Some(new_local_rpc_client(bank.clone(), mint_keypair.clone()));
 
     let mut config_witness = WalletConfig::default();
-    config_witness.drone_port = drone_addr.port();
-    config_witness.rpc_port = leader_data.rpc.port();
+    config_witness.rpc_client = Some(new_local_rpc_client(bank, mint_keypair));
 
     assert_ne!(config_payer.id.pubkey(), config_witness.id.pubkey());
 
-    request_and_confirm_airdrop(&rpc_client, &drone_addr, &config_payer.id.pubkey(), 50).unwrap();
+    rpc_client
+        .request_airdrop(&config_payer.id.pubkey(), 50)
+        .unwrap();
+    // Fund the witness so it doesn't need a drone to pay for its transaction
+    rpc_client
+        .request_airdrop(&config_witness.id.pubkey(), 1)
+        .unwrap();
 
     // Make transaction (from config_payer to bob_pubkey) requiring witness signature from config_witness
     config_payer.command = WalletCommand::Pay(
@@ -109,6 +116,11 @@ fn test_wallet_witness_tx() {
         None,
         Some(vec![config_witness.id.pubkey()]),
         None,
+        None,
+        None,
+        true,
+        false,
+        None,
     );
     let sig_response = process_command(&config_payer);
 
@@ -124,39 +136,36 @@ fn test_wallet_witness_tx() {
     check_balance(0, &rpc_client, &bob_pubkey); // recipient balance
 
     // Sign transaction by config_witness
-    config_witness.command = WalletCommand::Witness(bob_pubkey, process_id);
+    config_witness.command = WalletCommand::Witness(bob_pubkey, process_id, false);
     process_command(&config_witness).unwrap();
 
     check_balance(40, &rpc_client, &config_payer.id.pubkey()); // config_payer balance
     check_balance(0, &rpc_client, &process_id); // contract balance
     check_balance(10, &rpc_client, &bob_pubkey); // recipient balance
-
-    server.close().unwrap();
-    remove_dir_all(ledger_path).unwrap();
 }
 
 #[test]
 fn test_wallet_cancel_tx() {
-    let (server, leader_data, alice, ledger_path) = new_fullnode_for_tests();
+    let (bank, mint_keypair) = new_budget_bank();
     let bob_pubkey = Keypair::new().pubkey();
 
-    let (sender, receiver) = channel();
-    run_local_drone(alice, sender);
-    let drone_addr = receiver.recv().unwrap();
-
-    let rpc_client = RpcClient::new_socket(leader_data.rpc);
+    let rpc_client = new_local_rpc_client(bank.clone(), mint_keypair.clone());
 
     let mut config_payer = WalletConfig::default();
-    config_payer.drone_port = drone_addr.port();
-    config_payer.rpc_port = leader_data.rpc.port();
+    config_payer.rpc_client = Some(new_local_rpc_client(bank.clone(), mint_keypair.clone()));
 
     let mut config_witness = WalletConfig::default();
-    config_witness.drone_port = drone_addr.port();
-    config_witness.rpc_port = leader_data.rpc.port();
+    config_witness.rpc_client = Some(new_local_rpc_client(bank, mint_keypair));
 
     assert_ne!(config_payer.id.pubkey(), config_witness.id.pubkey());
 
-    request_and_confirm_airdrop(&rpc_client, &drone_addr, &config_payer.id.pubkey(), 50).unwrap();
+    rpc_client
+        .request_airdrop(&config_payer.id.pubkey(), 50)
+        .unwrap();
+    // Fund the witness so it doesn't need a drone to pay for its transaction
+    rpc_client
+        .request_airdrop(&config_witness.id.pubkey(), 1)
+        .unwrap();
 
     // Make transaction (from config_payer to bob_pubkey) requiring witness signature from config_witness
     config_payer.command = WalletCommand::Pay(
@@ -166,6 +175,11 @@ fn test_wallet_cancel_tx() {
         None,
         Some(vec![config_witness.id.pubkey()]),
         Some(config_payer.id.pubkey()),
+        None,
+        None,
+        true,
+        false,
+        None,
     );
     let sig_response = process_command(&config_payer).unwrap();
 
@@ -187,7 +201,4 @@ fn test_wallet_cancel_tx() {
     check_balance(50, &rpc_client, &config_payer.id.pubkey()); // config_payer balance
     check_balance(0, &rpc_client, &process_id); // contract balance
     check_balance(0, &rpc_client, &bob_pubkey); // recipient balance
-
-    server.close().unwrap();
-    remove_dir_all(ledger_path).unwrap();
 }